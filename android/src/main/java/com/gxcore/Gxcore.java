@@ -0,0 +1,31 @@
+package com.gxcore;
+
+/**
+ * The gxcore codec, producing what {@code /v1/encode/raw} does. Seeds come from a
+ * {@link SeedProvider} on each call and are wiped on both sides when the call returns.
+ */
+public final class Gxcore {
+    public static final int COMPRESSION_NONE = 0;
+    public static final int COMPRESSION_LZ4 = 2;
+    public static final int COMPRESSION_BROTLI = 3;
+    public static final int COMPRESSION_LZ4_FRAME = 4;
+
+    static {
+        System.loadLibrary("gxcore_ffi");
+    }
+
+    private Gxcore() {}
+
+    /**
+     * @throws IllegalArgumentException for an unknown compression
+     * @throws Exception whatever {@code seeds} throws
+     */
+    public static native byte[] encode(byte[] data, SeedProvider seeds, int compression) throws Exception;
+
+    /**
+     * @throws IllegalArgumentException when {@code encoded} is corrupt or was encoded with
+     *     another seed or compression
+     * @throws Exception whatever {@code seeds} throws
+     */
+    public static native byte[] decode(byte[] encoded, SeedProvider seeds, int compression) throws Exception;
+}
@@ -0,0 +1,76 @@
+package com.gxcore;
+
+import android.security.keystore.KeyGenParameterSpec;
+import android.security.keystore.KeyProperties;
+
+import java.io.IOException;
+import java.security.GeneralSecurityException;
+import java.security.KeyStore;
+import java.util.Arrays;
+
+import javax.crypto.Cipher;
+import javax.crypto.KeyGenerator;
+import javax.crypto.SecretKey;
+import javax.crypto.spec.GCMParameterSpec;
+
+/**
+ * A seed stored encrypted under an AES key that never leaves the Android Keystore, decrypted
+ * only when the codec asks for it. Persist {@link #iv()} and {@link #sealed()} and rebuild the
+ * provider from them with the same alias.
+ */
+public final class KeystoreSeedProvider implements SeedProvider {
+    private static final String KEYSTORE = "AndroidKeyStore";
+    private static final String TRANSFORMATION = "AES/GCM/NoPadding";
+    private static final int TAG_BITS = 128;
+
+    private final String alias;
+    private final byte[] iv;
+    private final byte[] sealed;
+
+    public KeystoreSeedProvider(String alias, byte[] iv, byte[] sealed) {
+        this.alias = alias;
+        this.iv = iv.clone();
+        this.sealed = sealed.clone();
+    }
+
+    /** Encrypt {@code seed} under the Keystore key {@code alias}, created if missing, then wipe it. */
+    public static KeystoreSeedProvider seal(String alias, byte[] seed) throws GeneralSecurityException, IOException {
+        try {
+            Cipher cipher = Cipher.getInstance(TRANSFORMATION);
+            cipher.init(Cipher.ENCRYPT_MODE, key(alias));
+            return new KeystoreSeedProvider(alias, cipher.getIV(), cipher.doFinal(seed));
+        } finally {
+            Arrays.fill(seed, (byte) 0);
+        }
+    }
+
+    public byte[] iv() {
+        return iv.clone();
+    }
+
+    public byte[] sealed() {
+        return sealed.clone();
+    }
+
+    @Override
+    public byte[] seed() throws GeneralSecurityException, IOException {
+        Cipher cipher = Cipher.getInstance(TRANSFORMATION);
+        cipher.init(Cipher.DECRYPT_MODE, key(alias), new GCMParameterSpec(TAG_BITS, iv));
+        return cipher.doFinal(sealed);
+    }
+
+    private static SecretKey key(String alias) throws GeneralSecurityException, IOException {
+        KeyStore keyStore = KeyStore.getInstance(KEYSTORE);
+        keyStore.load(null);
+        KeyStore.Entry entry = keyStore.getEntry(alias, null);
+        if (entry instanceof KeyStore.SecretKeyEntry) {
+            return ((KeyStore.SecretKeyEntry) entry).getSecretKey();
+        }
+        KeyGenerator generator = KeyGenerator.getInstance(KeyProperties.KEY_ALGORITHM_AES, KEYSTORE);
+        generator.init(new KeyGenParameterSpec.Builder(alias, KeyProperties.PURPOSE_ENCRYPT | KeyProperties.PURPOSE_DECRYPT)
+                .setBlockModes(KeyProperties.BLOCK_MODE_GCM)
+                .setEncryptionPaddings(KeyProperties.ENCRYPTION_PADDING_NONE)
+                .build());
+        return generator.generateKey();
+    }
+}
@@ -0,0 +1,9 @@
+package com.gxcore;
+
+/**
+ * Supplies the seed for one call. The library overwrites the returned array with zeros as soon
+ * as it has copied it, so return a fresh array rather than one kept in a field.
+ */
+public interface SeedProvider {
+    byte[] seed() throws Exception;
+}
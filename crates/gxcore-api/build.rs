@@ -0,0 +1,20 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    compile_service();
+}
+
+/// Generate the tonic service for the protobuf types of gxcore-core
+/// without requiring a system `protoc`
+#[cfg(feature = "grpc")]
+fn compile_service() {
+    let files = ["../gxcore-core/proto/gxcore/v1/gxcore.proto"];
+    println!("cargo:rerun-if-changed=../gxcore-core/proto");
+
+    let descriptors = protox::compile(files, ["../gxcore-core/proto"]).expect("Failed to parse protos");
+
+    tonic_build::configure()
+        .build_client(false)
+        .extern_path(".gxcore.v1", "::gxcore_core::proto::v1")
+        .compile_fds(descriptors)
+        .expect("Failed to generate gRPC service");
+}
@@ -0,0 +1,596 @@
+use axum::{
+    extract::{Json, Query},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Extension, Router,
+};
+use serde::{Deserialize, Serialize};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use utoipa::ToSchema;
+use gxcore_core::cache::TranslatorCache;
+use gxcore_core::{partial_verify, CompressionAlgorithm};
+use crate::audit::AuditLog;
+use crate::keyring::Keyring;
+use crate::tenants::Tenants;
+use crate::storage::{BlobStore, Storage};
+use cache::{Cached, EncodeCache};
+use keys::{decode_any, SeedSource};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+mod audit;
+pub mod auth;
+mod benchmark;
+mod binary;
+mod blobs;
+mod cache;
+mod capabilities;
+pub mod docs;
+pub mod extract;
+mod file;
+#[cfg(feature = "graphql")]
+mod graphql;
+mod health;
+mod inspect;
+mod ipfs;
+mod jobs;
+mod keys;
+mod limits;
+mod metrics;
+mod negotiate;
+mod objects;
+mod progress;
+mod quota;
+pub mod ratelimit;
+mod raw;
+mod replication;
+pub mod signing;
+mod stream;
+mod tenants;
+mod verify;
+mod ws;
+
+pub use auth::{AuthConfig, Authenticator, Principal, Scope};
+pub use binary::{Binary, TextEncoding};
+pub use cache::CacheConfig;
+pub use extract::{envelopes, GxBytes, GxCodec, GxJson};
+pub use jobs::WebhookConfig;
+pub use limits::BodyLimits;
+pub use negotiate::{Format, Negotiated};
+pub use quota::{Plan, QuotaConfig, QuotaLimits, Quotas};
+pub use ratelimit::{Limit, RateLimitConfig};
+pub use raw::{COMPRESSION_HEADER, KEY_ID_HEADER, SEED_HEADER};
+pub use signing::{RequestSigning, SigningConfig};
+
+#[derive(Deserialize, ToSchema)]
+struct EncodeRequest {
+    data: Binary,
+    /// Seed sent by the client, unless `key_id` names a server-held key
+    seed: Option<Binary>,
+    key_id: Option<String>,
+    /// `none`, `lz4`, `lz4frame` or `brotli`; the server default when omitted
+    #[serde(default)]
+    compression: Option<String>,
+    #[serde(default)]
+    encoding: TextEncoding,
+}
+
+#[derive(Serialize, ToSchema)]
+struct EncodeResponse {
+    encoded: Binary,
+    /// CID of the pinned result with `?store=ipfs`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cid: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct DecodeRequest {
+    encoded: Binary,
+    /// Seed sent by the client, unless `key_id` names a server-held key
+    seed: Option<Binary>,
+    key_id: Option<String>,
+    /// `none`, `lz4`, `lz4frame` or `brotli`; the server default when omitted
+    #[serde(default)]
+    compression: Option<String>,
+    #[serde(default)]
+    encoding: TextEncoding,
+}
+
+#[derive(Serialize, ToSchema)]
+struct DecodeResponse {
+    decoded: Binary,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct VerifyRequest {
+    encoded: Binary,
+    #[serde(default)]
+    encoding: TextEncoding,
+}
+
+#[derive(Serialize, ToSchema)]
+struct VerifyResponse {
+    valid: bool,
+}
+
+#[cfg(feature = "proto")]
+mod proto_codec {
+    use super::*;
+    use gxcore_core::proto::v1;
+
+    fn compression_name(compression: v1::Compression) -> String {
+        CompressionAlgorithm::from(compression).name().to_string()
+    }
+
+    impl From<v1::EncodeRequest> for EncodeRequest {
+        fn from(request: v1::EncodeRequest) -> Self {
+            let compression = compression_name(request.compression());
+            EncodeRequest {
+                data: request.data.into(),
+                seed: Some(request.seed.into()),
+                key_id: None,
+                compression: Some(compression),
+                encoding: TextEncoding::Bytes,
+            }
+        }
+    }
+
+    impl From<v1::DecodeRequest> for DecodeRequest {
+        fn from(request: v1::DecodeRequest) -> Self {
+            let compression = compression_name(request.compression());
+            DecodeRequest {
+                encoded: request.encoded.into(),
+                seed: Some(request.seed.into()),
+                key_id: None,
+                compression: Some(compression),
+                encoding: TextEncoding::Bytes,
+            }
+        }
+    }
+
+    impl From<v1::VerifyRequest> for VerifyRequest {
+        fn from(request: v1::VerifyRequest) -> Self {
+            VerifyRequest {
+                encoded: request.encoded.into(),
+                encoding: TextEncoding::Bytes,
+            }
+        }
+    }
+
+    impl From<EncodeResponse> for v1::EncodeResponse {
+        fn from(response: EncodeResponse) -> Self {
+            v1::EncodeResponse { encoded: response.encoded.into_bytes() }
+        }
+    }
+
+    impl From<DecodeResponse> for v1::DecodeResponse {
+        fn from(response: DecodeResponse) -> Self {
+            v1::DecodeResponse { decoded: response.decoded.into_bytes() }
+        }
+    }
+
+    impl From<VerifyResponse> for v1::VerifyResponse {
+        fn from(response: VerifyResponse) -> Self {
+            v1::VerifyResponse { valid: response.valid }
+        }
+    }
+}
+
+/// Compression names accepted by the API
+fn parse_compression(name: &str) -> Result<CompressionAlgorithm, StatusCode> {
+    match name {
+        "none" => Ok(CompressionAlgorithm::None),
+        "lz4" => Ok(CompressionAlgorithm::Lz4),
+        "brotli" => Ok(CompressionAlgorithm::Brotli),
+        "lz4frame" => Ok(CompressionAlgorithm::Lz4Frame),
+        _ => Err(StatusCode::BAD_REQUEST),
+    }
+}
+
+/// Compression used when a request does not name one
+#[derive(Clone, Copy)]
+struct DefaultCompression(CompressionAlgorithm);
+
+impl DefaultCompression {
+    fn resolve(self, name: Option<&str>) -> Result<CompressionAlgorithm, StatusCode> {
+        name.map_or(Ok(self.0), parse_compression)
+    }
+}
+
+/// Run codec work on the blocking pool so large payloads do not stall the async workers
+async fn offload<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> Result<T, StatusCode> {
+    tokio::task::spawn_blocking(work).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/encode",
+    tag = "codec",
+    params(ipfs::EncodeParams),
+    request_body(content((EncodeRequest = "application/json"), (EncodeRequest = "application/msgpack"))),
+    responses(
+        (status = 200, body = EncodeResponse),
+        (status = 304, description = "`If-None-Match` matches the cached result"),
+        (status = 400, description = "Unknown compression, store or malformed field"),
+        (status = 403, description = "Key is disabled"),
+        (status = 404, description = "Unknown key"),
+        (status = 412, description = "`If-None-Match: *` with caching enabled"),
+        (status = 415, description = "Unsupported content type"),
+        (status = 501, description = "IPFS store requested but unavailable"),
+        (status = 502, description = "IPFS node failed"),
+    )
+)]
+async fn encode_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(cache): Extension<Arc<EncodeCache>>,
+    Extension(ipfs): Extension<ipfs::IpfsNode>,
+    Query(params): Query<ipfs::EncodeParams>,
+    headers: HeaderMap,
+    Negotiated(format, payload): Negotiated<EncodeRequest>,
+) -> Result<Response, StatusCode> {
+    let compression = default.resolve(payload.compression.as_deref())?;
+    let publish = params.ipfs()?;
+
+    let data = payload.encoding.decode(payload.data)?;
+    let seed = payload.seed.map(|seed| payload.encoding.decode(seed)).transpose()?;
+    let seed = SeedSource::from_parts(seed, payload.key_id)?.encoding(&keyring).await?;
+    let data_len = data.len();
+    let variant = format!("{}+{:?}", format.content_type(), payload.encoding);
+    let (encoded, etag, hit) = match cache.encode(&headers, &variant, data, seed, compression).await? {
+        Cached::NotModified(etag) => return Ok(cache::not_modified(etag)),
+        Cached::Encoded { encoded, etag, hit } => (encoded, etag, hit),
+    };
+    metrics::record_compression(compression, data_len, encoded.len());
+    let cid = match publish {
+        true => Some(ipfs.add(encoded.clone()).await?),
+        false => None,
+    };
+    let encoded = payload.encoding.encode(encoded);
+    let mut response = Negotiated(format, EncodeResponse { encoded, cid }).into_response();
+    cache::annotate(&mut response, etag, hit);
+    Ok(response)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/decode",
+    tag = "codec",
+    request_body(content((DecodeRequest = "application/json"), (DecodeRequest = "application/msgpack"))),
+    responses(
+        (status = 200, body = DecodeResponse),
+        (status = 400, description = "Invalid input or wrong seed"),
+        (status = 404, description = "Unknown key"),
+        (status = 415, description = "Unsupported content type"),
+    )
+)]
+async fn decode_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(translators): Extension<Arc<TranslatorCache>>,
+    Negotiated(format, payload): Negotiated<DecodeRequest>,
+) -> Result<Negotiated<DecodeResponse>, StatusCode> {
+    let compression = default.resolve(payload.compression.as_deref())?;
+
+    let encoded = payload.encoding.decode(payload.encoded)?;
+    let seed = payload.seed.map(|seed| payload.encoding.decode(seed)).transpose()?;
+    let seeds = SeedSource::from_parts(seed, payload.key_id)?.decoding(&keyring).await?;
+    match offload(move || decode_any(&translators, &encoded, &seeds, compression)).await? {
+        Ok(decoded) => Ok(Negotiated(format, DecodeResponse { decoded: payload.encoding.encode(decoded) })),
+        Err(e) => {
+            metrics::record_error(e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/verify",
+    tag = "codec",
+    request_body = VerifyRequest,
+    responses((status = 200, body = VerifyResponse))
+)]
+async fn verify_handler(Json(payload): Json<VerifyRequest>) -> Result<Json<VerifyResponse>, StatusCode> {
+    let encoded = payload.encoding.decode(payload.encoded)?;
+    let valid = partial_verify(&encoded);
+    Ok(Json(VerifyResponse { valid }))
+}
+
+/// Settings of the HTTP API
+#[derive(Clone, Default)]
+pub struct ApiConfig {
+    /// Require bearer tokens with the route's scope when set
+    pub auth: Option<Arc<Authenticator>>,
+    /// Mount the admin routes without `auth`, which otherwise leaves them out
+    pub unauthenticated_admin: bool,
+    /// Per-client token buckets when set
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Daily and monthly quotas on encode and decode routes when set
+    pub quotas: Option<Arc<Quotas>>,
+    /// Require requests signed with an API key secret and a fresh nonce when set
+    pub signing: Option<Arc<RequestSigning>>,
+    /// Record encode, decode and key operations when set
+    pub audit: Option<Arc<AuditLog>>,
+    /// Give each tenant named by tokens its own keyring and blobs when set
+    pub tenants: Option<Arc<Tenants>>,
+    pub limits: BodyLimits,
+    pub cors: CorsConfig,
+    /// Compression for requests that do not name one
+    pub default_compression: CompressionAlgorithm,
+    /// Cancelled when the server starts draining
+    pub shutdown: CancellationToken,
+    /// Named seeds requests can reference by `key_id`
+    pub keyring: Arc<Keyring>,
+    pub cache: CacheConfig,
+    /// Signing of job callbacks
+    pub webhooks: WebhookConfig,
+    /// Object storage for `/objects` routes, which answer `501` without it
+    pub storage: Option<Storage>,
+    /// Content-addressed store for `/blobs`, which answer `501` without it
+    pub blobs: Option<BlobStore>,
+    /// Serve `/v1/replication` to replicas presenting this token when set
+    pub replication_token: Option<String>,
+    /// Node behind `?store=ipfs` and `/decode/cid`
+    #[cfg(feature = "ipfs")]
+    pub ipfs: Option<Arc<crate::ipfs::Ipfs>>,
+}
+
+/// Cross-origin policy
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Origins allowed to call the API, any origin when empty
+    pub allowed_origins: Vec<String>,
+}
+
+impl CorsConfig {
+    fn layer(&self) -> CorsLayer {
+        let origins = if self.allowed_origins.is_empty() {
+            AllowOrigin::from(Any)
+        } else {
+            let origins = self.allowed_origins.iter().filter_map(|origin| HeaderValue::from_str(origin).ok());
+            AllowOrigin::list(origins)
+        };
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods([Method::GET, Method::POST, Method::PUT])
+            .allow_headers(Any)
+    }
+}
+
+pub fn create_router() -> Router {
+    create_router_with(ApiConfig::default())
+}
+
+/// Require a valid token with `scope` on every route of `router` when auth is enabled.
+/// Without a scope the token only has to be valid, used by routes checking scopes themselves.
+fn guarded(config: &ApiConfig, router: Router, scope: Option<Scope>) -> Router {
+    match &config.auth {
+        Some(authenticator) => router.route_layer(middleware::from_fn_with_state(
+            (authenticator.clone(), scope),
+            auth::require_scope,
+        )),
+        None => router,
+    }
+}
+
+/// Count `router`'s requests and body bytes against the client's quota when quotas are enabled
+fn metered(config: &ApiConfig, router: Router) -> Router {
+    match &config.quotas {
+        Some(quotas) => router.route_layer(middleware::from_fn_with_state(quotas.clone(), quota::enforce)),
+        None => router,
+    }
+}
+
+/// Record `router`'s requests to the audit log when one is configured
+fn audited(config: &ApiConfig, router: Router) -> Router {
+    match &config.audit {
+        Some(log) => router.route_layer(middleware::from_fn_with_state(log.clone(), audit::record)),
+        None => router,
+    }
+}
+
+/// Resolve keys and blobs within the caller's tenant when tenants are enabled
+fn tenanted(config: &ApiConfig, router: Router) -> Router {
+    match &config.tenants {
+        Some(tenants) => router.route_layer(middleware::from_fn_with_state(tenants.clone(), tenants::scope_tenant)),
+        None => router,
+    }
+}
+
+/// Seeds whose translators the codec handlers share, so a busy key derives its alphabet once
+const TRANSLATOR_CACHE_SEEDS: usize = 256;
+
+/// Version also served without a prefix, for clients predating versioned routes
+const LEGACY_VERSION: &str = "v1";
+
+/// Routers of every API version, each mounted under `/{version}`
+fn versions(config: &ApiConfig) -> Vec<(&'static str, Router)> {
+    vec![("v1", v1_routes(config))]
+}
+
+fn v1_routes(config: &ApiConfig) -> Router {
+    let limits = config.limits;
+    let encode_routes = Router::new()
+        .route("/encode", limits::buffered(post(encode_handler), limits.structured))
+        .route("/encode/raw", limits::buffered(post(raw::encode_raw_handler), limits.raw))
+        .route("/encode/file", limits::buffered(post(file::encode_file_handler), limits.file))
+        .route("/encode/stream", limits::streamed(post(stream::encode_stream_handler), limits.stream))
+        .route("/jobs/encode", limits::buffered(post(jobs::submit_encode_handler), limits.job))
+        .route("/objects/encode", limits::buffered(post(objects::encode_object_handler), limits.structured))
+        .route("/blobs", limits::buffered(put(blobs::put_blob_handler), limits.raw))
+        .route("/blobs/:id/refs", post(blobs::retain_blob_handler).delete(blobs::release_blob_handler));
+    let decode_routes = Router::new()
+        .route("/decode", limits::buffered(post(decode_handler), limits.structured))
+        .route("/decode/raw", limits::buffered(post(raw::decode_raw_handler), limits.raw))
+        .route("/decode/stream", limits::streamed(post(stream::decode_stream_handler), limits.stream))
+        .route("/decode/cid", limits::buffered(post(ipfs::decode_cid_handler), limits.structured))
+        .route("/jobs/decode", limits::buffered(post(jobs::submit_decode_handler), limits.job))
+        .route("/objects/decode", limits::buffered(post(objects::decode_object_handler), limits.structured))
+        .route("/blobs/:id", get(blobs::get_blob_handler));
+    let verify_routes = Router::new()
+        .route("/verify", limits::buffered(post(verify_handler), limits.structured))
+        .route("/verify/full", limits::buffered(post(verify::verify_full_handler), limits.structured))
+        .route("/inspect", limits::buffered(post(inspect::inspect_handler), limits.raw));
+    let admin_routes = Router::new()
+        .route("/benchmark", post(benchmark::start_benchmark_handler))
+        .route("/benchmark/:id", get(benchmark::benchmark_status_handler))
+        .route("/admin/keys", get(keys::list_keys_handler).post(keys::add_key_handler))
+        .route("/admin/keys/:id/rotate", post(keys::rotate_key_handler))
+        .route("/admin/keys/:id/disable", post(keys::disable_key_handler))
+        .route("/admin/keys/:id/enable", post(keys::enable_key_handler))
+        .route("/admin/audit", get(audit::query_audit_handler))
+        .route("/admin/blobs/gc", get(blobs::gc_status_handler).post(blobs::run_gc_handler))
+        .route("/admin/tenants", get(tenants::list_tenants_handler).post(tenants::create_tenant_handler));
+    let session_routes = Router::new()
+        .route("/ws", get(ws::ws_handler))
+        .route("/progress/:id", get(progress::progress_handler))
+        .route("/jobs/:id", get(jobs::job_status_handler).delete(jobs::delete_job_handler))
+        .route("/jobs/:id/result", get(jobs::job_result_handler))
+        .route("/usage", get(quota::usage_handler));
+
+    // Metered, audited and scoped to a tenant inside authentication so all follow the token
+    let encode_routes = tenanted(config, audited(config, metered(config, encode_routes)));
+    let decode_routes = tenanted(config, audited(config, metered(config, decode_routes)));
+    let admin_routes = tenanted(config, audited(config, admin_routes));
+    let router = Router::new()
+        .merge(guarded(config, encode_routes, Some(Scope::Encode)))
+        .merge(guarded(config, decode_routes, Some(Scope::Decode)))
+        .merge(guarded(config, tenanted(config, verify_routes), Some(Scope::Verify)))
+        .merge(guarded(config, tenanted(config, session_routes), None));
+    // Admin routes read and replace seeds, so they are never open to anyone by default
+    match config.auth.is_some() || config.unauthenticated_admin {
+        true => router.merge(guarded(config, admin_routes, Some(Scope::Admin))),
+        false => router,
+    }
+}
+
+/// Point clients of unversioned routes at their successor
+async fn deprecated(mut response: Response) -> Response {
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    headers.insert(
+        header::LINK,
+        HeaderValue::from_static("</v1>; rel=\"successor-version\""),
+    );
+    response
+}
+
+pub fn create_router_with(config: ApiConfig) -> Router {
+    let metrics_routes = Router::new().route("/metrics", get(metrics::metrics_handler));
+    // Probes stay reachable without credentials
+    let probe_routes = Router::new()
+        .route("/healthz", get(health::healthz_handler))
+        .route("/readyz", get(health::readyz_handler))
+        .with_state(health::ProbeState {
+            auth_enabled: config.auth.is_some(),
+            keyring: config.keyring.clone(),
+            shutdown: config.shutdown.clone(),
+        });
+
+    let versions = versions(&config);
+    let capabilities = Arc::new(capabilities::Capabilities::new(
+        &config,
+        versions.iter().map(|(version, _)| *version).collect(),
+    ));
+
+    let mut router = Router::new();
+    for (version, mut routes) in versions {
+        if let Some(signing) = &config.signing {
+            routes = routes.route_layer(middleware::from_fn_with_state(signing.clone(), signing::require_signature));
+        }
+        // Public like the probes, so clients can negotiate before obtaining a token
+        let routes = routes.route(
+            "/capabilities",
+            get(capabilities::capabilities_handler).with_state(capabilities.clone()),
+        );
+        if version == LEGACY_VERSION {
+            router = router.merge(routes.clone().layer(middleware::map_response(deprecated)));
+        }
+        router = router.nest(&format!("/{version}"), routes);
+    }
+    // Authenticated by the shared token rather than client tokens or signatures
+    if let Some(token) = &config.replication_token {
+        let replication_routes = Router::new()
+            .route("/v1/replication/blobs", get(replication::list_blobs_handler))
+            .route("/v1/replication/blobs/*key", get(replication::fetch_blob_handler))
+            .route("/v1/replication/keys", get(replication::keys_handler))
+            .route_layer(middleware::from_fn_with_state(Arc::new(token.clone()), replication::require_token));
+        router = router.merge(replication_routes);
+    }
+    #[cfg(feature = "graphql")]
+    {
+        let graphql_routes = Router::new().route(
+            "/graphql",
+            limits::buffered(post(graphql::graphql_handler), config.limits.structured),
+        );
+        // Resolvers check scopes themselves, as one request can mix queries and mutations
+        let mut graphql_routes = guarded(&config, tenanted(&config, audited(&config, metered(&config, graphql_routes))), None);
+        if let Some(signing) = &config.signing {
+            graphql_routes = graphql_routes.route_layer(middleware::from_fn_with_state(signing.clone(), signing::require_signature));
+        }
+        router = router.merge(
+            graphql_routes
+                .layer(Extension(graphql::schema()))
+                .layer(Extension(capabilities.clone())),
+        );
+    }
+    let translators = Arc::new(TranslatorCache::new(TRANSLATOR_CACHE_SEEDS));
+    let mut router = router
+        .merge(metrics_routes)
+        .merge(probe_routes)
+        .merge(docs::routes())
+        .layer(Extension(DefaultCompression(config.default_compression)))
+        .layer(Extension(config.shutdown.clone()))
+        .layer(Extension(config.keyring.clone()))
+        .layer(Extension(Arc::new(benchmark::BenchmarkJobs::default())))
+        .layer(Extension(Arc::new(progress::ProgressRegistry::default())))
+        .layer(Extension(Arc::new(jobs::JobQueue::new(&config.webhooks))))
+        .layer(Extension(Arc::new(EncodeCache::new(&config.cache, translators.clone()))))
+        .layer(Extension(translators))
+        .layer(Extension(config.storage.clone()))
+        .layer(Extension(config.blobs.clone()))
+        .layer(Extension(config.quotas.clone()))
+        .layer(Extension(config.audit.clone()))
+        .layer(Extension(config.tenants.clone()))
+        .layer(Extension(ipfs::IpfsNode::new(&config)))
+        .layer(middleware::from_fn(metrics::track));
+    // Limited before authentication so invalid tokens are throttled too
+    if let Some(limits) = config.rate_limit.as_ref().filter(|limits| limits.enabled) {
+        router = router.layer(middleware::from_fn_with_state(
+            Arc::new(ratelimit::RateLimits::new(limits)),
+            ratelimit::rate_limit,
+        ));
+    }
+    router.layer(config.cors.layer())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn verify(path: &str) -> Response {
+        let request = Request::post(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"encoded":[]}"#))
+            .unwrap();
+        create_router().oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_versioned_routes() {
+        let response = verify("/v1/verify").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("deprecation"));
+
+        let legacy = verify("/verify").await;
+        assert_eq!(legacy.status(), StatusCode::OK);
+        assert_eq!(legacy.headers()["deprecation"], "true");
+    }
+}
@@ -0,0 +1,171 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use axum::{
+    body::HttpBody,
+    extract::{MatchedPath, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::Response,
+    Extension, Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use super::auth::Principal;
+use super::quota::client_of;
+use crate::audit::{AuditEvent, AuditLog, AuditQuery};
+
+tokio::task_local! {
+    /// Key id used by the request being audited
+    static KEY_ID: RefCell<Option<String>>;
+}
+
+/// Attribute the current request to `id`; a no-op outside audited routes
+pub(super) fn note_key(id: &str) {
+    let _ = KEY_ID.try_with(|key| *key.borrow_mut() = Some(id.to_string()));
+}
+
+/// Middleware recording each request to the audit log once answered
+pub(super) async fn record(State(log): State<Arc<AuditLog>>, req: Request, next: Next) -> Response {
+    let principal = req.extensions().get::<Principal>();
+    let actor = client_of(principal, req.headers());
+    let tenant = principal.and_then(|principal| principal.tenant.clone());
+    let path = req.extensions().get::<MatchedPath>().map_or("unmatched", |path| path.as_str());
+    let operation = format!("{} {path}", req.method());
+    let request_bytes = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+
+    let (response, key_id) = KEY_ID
+        .scope(RefCell::new(None), async {
+            let response = next.run(req).await;
+            (response, KEY_ID.with(|key| key.take()))
+        })
+        .await;
+    log.record(AuditEvent {
+        timestamp: AuditEvent::now(),
+        actor,
+        tenant,
+        operation,
+        key_id,
+        request_bytes,
+        response_bytes: response.body().size_hint().exact(),
+        status: response.status().as_u16(),
+    });
+    response
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(super) struct AuditParams {
+    actor: Option<String>,
+    /// Ignored for tenant admins, who only see their own tenant
+    tenant: Option<String>,
+    key_id: Option<String>,
+    /// Substring of the method and route, such as `decode`
+    operation: Option<String>,
+    /// Unix seconds
+    since: Option<u64>,
+    /// At most this many events, 100 by default
+    limit: Option<usize>,
+}
+
+/// Recent audit events, newest first; the configured sink holds the full record.
+/// Admins of a tenant see that tenant's events only.
+#[utoipa::path(
+    get,
+    path = "/v1/admin/audit",
+    tag = "admin",
+    params(AuditParams),
+    responses(
+        (status = 200, body = [AuditEvent]),
+        (status = 501, description = "Audit log not configured"),
+    )
+)]
+pub(super) async fn query_audit_handler(
+    Extension(log): Extension<Option<Arc<AuditLog>>>,
+    principal: Option<Extension<Principal>>,
+    Query(params): Query<AuditParams>,
+) -> Result<Json<Vec<AuditEvent>>, StatusCode> {
+    let log = log.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let query = AuditQuery {
+        actor: params.actor,
+        tenant: principal.and_then(|Extension(principal)| principal.tenant).or(params.tenant),
+        key_id: params.key_id,
+        operation: params.operation,
+        since: params.since,
+    };
+    Ok(Json(log.query(&query, params.limit.unwrap_or(100))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router_with, ApiConfig};
+    use crate::audit::AuditSink;
+    use async_trait::async_trait;
+    use axum::{body::Body, Router};
+    use std::sync::Mutex;
+    use tower::ServiceExt;
+
+    #[derive(Default)]
+    struct MemorySink(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl AuditSink for MemorySink {
+        async fn write(&self, event: &AuditEvent) -> Result<(), String> {
+            self.0.lock().unwrap().push(serde_json::to_string(event).unwrap());
+            Ok(())
+        }
+    }
+
+    async fn call(router: &Router, request: axum::http::Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_operations_audited_without_secrets() {
+        let sink = Arc::new(MemorySink::default());
+        let router = create_router_with(ApiConfig {
+            audit: Some(Arc::new(AuditLog::new(sink.clone(), 100))),
+            unauthenticated_admin: true,
+            ..Default::default()
+        });
+        let post = |path: &str, body: &str| {
+            axum::http::Request::post(path)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let seed = "c2VjcmV0LXNlZWQ=";
+        let (status, _) = call(&router, post("/v1/admin/keys", &format!(r#"{{"id":"billing","seed":"{seed}"}}"#))).await;
+        assert!(status.is_success());
+        call(&router, post("/v1/encode", r#"{"data":[1,2,3],"key_id":"billing"}"#)).await;
+        call(&router, post("/v1/decode", r#"{"encoded":[1],"seed":[9]}"#)).await;
+
+        let request = axum::http::Request::get("/v1/admin/audit?key_id=billing").body(Body::empty()).unwrap();
+        let (status, events) = call(&router, request).await;
+        assert_eq!(status, StatusCode::OK);
+        let operations: Vec<_> = events.as_array().unwrap().iter().map(|e| e["operation"].clone()).collect();
+        assert_eq!(operations, ["POST /v1/encode", "POST /v1/admin/keys"]);
+        assert_eq!(events[0]["actor"], "anonymous");
+        assert_eq!(events[0]["status"], 200);
+
+        let request = axum::http::Request::get("/v1/admin/audit?operation=decode").body(Body::empty()).unwrap();
+        let (_, events) = call(&router, request).await;
+        assert_eq!(events[0]["status"], 400);
+        assert!(events[0]["key_id"].is_null());
+
+        while sink.0.lock().unwrap().len() < 5 {
+            tokio::task::yield_now().await;
+        }
+        let written = sink.0.lock().unwrap().join("\n");
+        assert!(written.contains("billing") && !written.contains(seed));
+    }
+}
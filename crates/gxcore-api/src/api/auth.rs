@@ -0,0 +1,288 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use jsonwebtoken::{jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+/// JWT validation settings
+#[derive(Clone, Debug, Deserialize)]
+pub struct AuthConfig {
+    /// Required `iss` claim
+    pub issuer: String,
+    /// Required `aud` claim, if any
+    pub audience: Option<String>,
+    /// Shared secret for HS256 tokens
+    pub hs256_secret: Option<String>,
+    /// JWKS document fetched at startup
+    pub jwks_url: Option<String>,
+    /// JWKS document read from disk at startup
+    pub jwks_path: Option<String>,
+    /// Claim naming the caller's tenant, when tenants are enabled
+    #[serde(default = "default_tenant_claim")]
+    pub tenant_claim: String,
+}
+
+fn default_tenant_claim() -> String {
+    "tenant".to_string()
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig {
+            issuer: String::new(),
+            audience: None,
+            hs256_secret: None,
+            jwks_url: None,
+            jwks_path: None,
+            tenant_claim: default_tenant_claim(),
+        }
+    }
+}
+
+/// Capability granted by a token scope
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scope {
+    Encode,
+    Decode,
+    Verify,
+    /// Grants every other scope
+    Admin,
+}
+
+impl Scope {
+    pub fn from_claim(scope: &str) -> Option<Self> {
+        match scope {
+            "gx:encode" => Some(Scope::Encode),
+            "gx:decode" => Some(Scope::Decode),
+            "gx:verify" => Some(Scope::Verify),
+            "gx:admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// Authenticated caller, available to handlers as a request extension
+#[derive(Clone, Debug)]
+pub struct Principal {
+    pub subject: String,
+    pub scopes: HashSet<Scope>,
+    /// Tenant the caller belongs to, if the token names one
+    pub tenant: Option<String>,
+}
+
+impl Principal {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope) || self.scopes.contains(&Scope::Admin)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScopeClaim {
+    /// OAuth2 space-separated `scope`
+    Spaced(String),
+    /// Array form used by some providers in `scp`
+    List(Vec<String>),
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+    scope: Option<ScopeClaim>,
+    scp: Option<ScopeClaim>,
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Claims {
+    fn scopes(&self) -> HashSet<Scope> {
+        let mut scopes = HashSet::new();
+        for claim in [&self.scope, &self.scp].into_iter().flatten() {
+            let names: Vec<&str> = match claim {
+                ScopeClaim::Spaced(s) => s.split_whitespace().collect(),
+                ScopeClaim::List(list) => list.iter().map(String::as_str).collect(),
+            };
+            scopes.extend(names.into_iter().filter_map(Scope::from_claim));
+        }
+        scopes
+    }
+}
+
+struct VerificationKey {
+    kid: Option<String>,
+    key: DecodingKey,
+    validation: Validation,
+}
+
+/// Validates bearer tokens against the configured keys
+pub struct Authenticator {
+    keys: Vec<VerificationKey>,
+    tenant_claim: String,
+}
+
+impl Authenticator {
+    /// Build from configuration, fetching the JWKS if a URL is set
+    pub async fn load(config: &AuthConfig) -> Result<Self, String> {
+        let mut jwks = match &config.jwks_url {
+            Some(url) => Some(
+                reqwest::get(url)
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| format!("Failed to fetch JWKS: {e}"))?
+                    .json::<JwkSet>()
+                    .await
+                    .map_err(|e| format!("Invalid JWKS: {e}"))?,
+            ),
+            None => None,
+        };
+        if let Some(path) = &config.jwks_path {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read JWKS: {e}"))?;
+            jwks = Some(serde_json::from_str(&text).map_err(|e| format!("Invalid JWKS: {e}"))?);
+        }
+        Self::from_parts(config, jwks.as_ref())
+    }
+
+    pub fn from_parts(config: &AuthConfig, jwks: Option<&JwkSet>) -> Result<Self, String> {
+        let validation = |alg| {
+            let mut validation = Validation::new(alg);
+            validation.set_issuer(&[&config.issuer]);
+            match &config.audience {
+                Some(aud) => validation.set_audience(&[aud]),
+                None => validation.validate_aud = false,
+            }
+            validation
+        };
+
+        let mut keys = Vec::new();
+        if let Some(secret) = &config.hs256_secret {
+            keys.push(VerificationKey {
+                kid: None,
+                key: DecodingKey::from_secret(secret.as_bytes()),
+                validation: validation(Algorithm::HS256),
+            });
+        }
+        for jwk in jwks.map(|set| set.keys.as_slice()).unwrap_or_default() {
+            let key = DecodingKey::from_jwk(jwk).map_err(|e| format!("Unsupported JWK: {e}"))?;
+            let alg = jwk
+                .common
+                .key_algorithm
+                .and_then(|alg| alg.to_string().parse().ok())
+                .unwrap_or(Algorithm::RS256);
+            keys.push(VerificationKey {
+                kid: jwk.common.key_id.clone(),
+                key,
+                validation: validation(alg),
+            });
+        }
+
+        if keys.is_empty() {
+            return Err("Auth enabled without any verification key".to_string());
+        }
+        Ok(Authenticator {
+            keys,
+            tenant_claim: config.tenant_claim.clone(),
+        })
+    }
+
+    pub fn authenticate(&self, token: &str) -> Option<Principal> {
+        let header = jsonwebtoken::decode_header(token).ok()?;
+        self.keys
+            .iter()
+            .filter(|k| k.validation.algorithms.contains(&header.alg))
+            .filter(|k| header.kid.is_none() || k.kid.is_none() || k.kid == header.kid)
+            .find_map(|k| jsonwebtoken::decode::<Claims>(token, &k.key, &k.validation).ok())
+            .map(|data| Principal {
+                scopes: data.claims.scopes(),
+                tenant: data.claims.other.get(&self.tenant_claim).and_then(|v| v.as_str()).map(str::to_string),
+                subject: data.claims.sub,
+            })
+    }
+}
+
+fn unauthorized() -> Response {
+    (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")]).into_response()
+}
+
+/// Middleware rejecting requests without a valid token carrying `scope`, if any
+pub async fn require_scope(
+    State((auth, scope)): State<(Arc<Authenticator>, Option<Scope>)>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let Some(principal) = token.and_then(|token| auth.authenticate(token.trim())) else {
+        return unauthorized();
+    };
+    if scope.is_some_and(|scope| !principal.has_scope(scope)) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    req.extensions_mut().insert(principal);
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router_with, ApiConfig};
+    use axum::body::Body;
+    use axum::http::Request;
+    use jsonwebtoken::{EncodingKey, Header};
+    use tower::ServiceExt;
+
+    const SECRET: &str = "test-secret";
+
+    fn token(scope: &str) -> String {
+        let claims = serde_json::json!({
+            "sub": "svc",
+            "iss": "https://idp.test",
+            "exp": 4_000_000_000u64,
+            "scope": scope,
+        });
+        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(SECRET.as_bytes())).unwrap()
+    }
+
+    async fn status(token: Option<String>) -> StatusCode {
+        let config = AuthConfig {
+            issuer: "https://idp.test".to_string(),
+            hs256_secret: Some(SECRET.to_string()),
+            ..Default::default()
+        };
+        let auth = Authenticator::from_parts(&config, None).unwrap();
+        let router = create_router_with(ApiConfig {
+            auth: Some(Arc::new(auth)),
+            ..Default::default()
+        });
+
+        let mut request = Request::post("/verify").header(header::CONTENT_TYPE, "application/json");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let request = request.body(Body::from(r#"{"encoded":[]}"#)).unwrap();
+        router.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_scopes_enforced() {
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("garbage".to_string())).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some(token("gx:encode"))).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(Some(token("gx:encode gx:verify"))).await, StatusCode::OK);
+        assert_eq!(status(Some(token("gx:admin"))).await, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_scope_claim_forms() {
+        let claims: Claims = serde_json::from_str(r#"{"sub":"a","scp":["gx:decode","other"]}"#).unwrap();
+        assert_eq!(claims.scopes(), HashSet::from([Scope::Decode]));
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::{
+    extract::{Path, Query},
+    http::{header, HeaderName, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use utoipa::{IntoParams, ToSchema};
+use gxcore_core::bench::{compare, measure, to_rows, BenchmarkResult, Comparison, RowFormat};
+use gxcore_core::CompressionAlgorithm;
+
+use super::jobs::JobStatus;
+use super::progress::{ProgressRegistry, ProgressState, Tracker, PROGRESS_HEADER};
+use crate::keyring::generate_seed;
+
+/// Finished jobs kept for polling before the oldest are dropped
+const MAX_FINISHED_JOBS: usize = 32;
+
+/// Named baselines kept before new names are refused
+const MAX_BASELINES: usize = 32;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(super) struct BenchmarkParams {
+    /// Keep the results under this name for later runs to compare against
+    save_baseline: Option<String>,
+    /// Compare the results with this saved baseline
+    baseline: Option<String>,
+    /// Percent slower than the baseline that counts as a regression, 10 by default
+    threshold: Option<f64>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(super) struct StatusParams {
+    /// Return only the results, one per line, for loading into other tools
+    format: Option<RowFormat>,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub(super) struct BenchmarkResponse {
+    results: Vec<BenchmarkResult>,
+    total_time_ms: f64,
+    /// Change from the requested baseline, present when one was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comparison: Option<Vec<Comparison>>,
+    /// Operations more than the threshold slower than the baseline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    regressions: Option<usize>,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub(super) struct BenchmarkJob {
+    id: String,
+    status: JobStatus,
+    /// Present once completed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<BenchmarkResponse>,
+}
+
+/// Benchmark runs started through the API, at most one running at a time, and the results
+/// saved as baselines, which last until restart
+#[derive(Default)]
+pub(super) struct BenchmarkJobs {
+    jobs: Mutex<HashMap<String, (u64, BenchmarkJob)>>,
+    started: AtomicU64,
+    baselines: Mutex<HashMap<String, Vec<BenchmarkResult>>>,
+}
+
+impl BenchmarkJobs {
+    /// Register a running job, `None` while another one is still running
+    fn start(&self) -> Option<String> {
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs.values().any(|(_, job)| job.status == JobStatus::Running) {
+            return None;
+        }
+        if jobs.len() >= MAX_FINISHED_JOBS
+            && let Some(oldest) = jobs.iter().min_by_key(|(_, (order, _))| *order).map(|(id, _)| id.clone())
+        {
+            jobs.remove(&oldest);
+        }
+
+        let order = self.started.fetch_add(1, Ordering::Relaxed);
+        let id = hex::encode(&generate_seed()[..8]);
+        let job = BenchmarkJob {
+            id: id.clone(),
+            status: JobStatus::Running,
+            result: None,
+        };
+        jobs.insert(id.clone(), (order, job));
+        Some(id)
+    }
+
+    fn finish(&self, id: &str, status: JobStatus, result: Option<BenchmarkResponse>) {
+        if let Some((_, job)) = self.jobs.lock().unwrap().get_mut(id) {
+            job.status = status;
+            job.result = result;
+        }
+    }
+
+    fn get(&self, id: &str) -> Option<BenchmarkJob> {
+        self.jobs.lock().unwrap().get(id).map(|(_, job)| job.clone())
+    }
+
+    fn baseline(&self, name: &str) -> Option<Vec<BenchmarkResult>> {
+        self.baselines.lock().unwrap().get(name).cloned()
+    }
+
+    /// Whether `name` can be saved: replacing a baseline always can, adding one only below the limit
+    fn can_save(&self, name: &str) -> bool {
+        let baselines = self.baselines.lock().unwrap();
+        baselines.contains_key(name) || baselines.len() < MAX_BASELINES
+    }
+
+    fn save_baseline(&self, name: String, results: Vec<BenchmarkResult>) {
+        self.baselines.lock().unwrap().insert(name, results);
+    }
+}
+
+/// Time encode and decode over the built-in data sets, `None` if cancelled between runs.
+/// Each finished data set and compression pair advances `tracker`.
+fn run(shutdown: &CancellationToken, tracker: &Tracker) -> Option<BenchmarkResponse> {
+    let start_time = Instant::now();
+    let mut results = Vec::new();
+
+    // Test data sets
+    let small_data = b"Hello, Solana World!";
+    let medium_data = b"This is a medium-sized test data for benchmarking CypherSolBase encoding performance with different compression algorithms. We want to measure how the library performs with realistic data sizes that might be used in Solana programs.";
+    let large_data = include_bytes!("../lib.rs"); // Use source code as large test data
+
+    let seed = b"benchmark_secret_key";
+    let iterations = 1000;
+
+    // Benchmark configurations
+    let configs = vec![
+        ("small", small_data.as_slice(), iterations / 10), // Fewer iterations for small data
+        ("medium", medium_data.as_slice(), iterations / 5),
+        ("large", large_data.as_slice(), iterations / 20), // Even fewer for large data
+    ];
+
+    let compressions = vec![CompressionAlgorithm::None, CompressionAlgorithm::Lz4, CompressionAlgorithm::Brotli];
+
+    tracker.set_total((configs.len() * compressions.len()) as u64);
+    for (data_name, data, iters) in configs {
+        for compression in &compressions {
+            // Give up between runs rather than hold up a rolling deploy
+            if shutdown.is_cancelled() {
+                return None;
+            }
+            results.extend(measure(data_name, data, seed, *compression, iters));
+            tracker.advance(1);
+        }
+    }
+
+    let total_duration = start_time.elapsed();
+
+    Some(BenchmarkResponse {
+        results,
+        total_time_ms: total_duration.as_millis() as f64,
+        comparison: None,
+        regressions: None,
+    })
+}
+
+impl BenchmarkResponse {
+    /// Flag the results more than `threshold` percent slower than `baseline`
+    fn compare(&mut self, baseline: &[BenchmarkResult], threshold: f64) {
+        let comparison = compare(baseline, &self.results, threshold);
+        self.regressions = Some(comparison.iter().filter(|comparison| comparison.regressed).count());
+        self.comparison = Some(comparison);
+    }
+}
+
+/// Start a benchmark on the blocking pool; poll the returned `Location` for results,
+/// or follow the `x-gx-progress` event stream. Finished runs can be saved as a named baseline
+/// and compared with one, flagging regressions in the result.
+#[utoipa::path(
+    post,
+    path = "/v1/benchmark",
+    tag = "admin",
+    params(BenchmarkParams),
+    responses(
+        (status = 202, body = BenchmarkJob, headers(
+            ("location" = String, description = "Job to poll"),
+            ("x-gx-progress" = String, description = "Event stream counting finished runs"),
+        )),
+        (status = 400, description = "Invalid threshold or baseline name, or too many baselines"),
+        (status = 404, description = "No baseline by that name"),
+        (status = 409, description = "A benchmark is already running"),
+        (status = 503, description = "Server shutting down"),
+    )
+)]
+pub(super) async fn start_benchmark_handler(
+    Extension(shutdown): Extension<CancellationToken>,
+    Extension(jobs): Extension<Arc<BenchmarkJobs>>,
+    Extension(progress): Extension<Arc<ProgressRegistry>>,
+    Query(params): Query<BenchmarkParams>,
+) -> Result<Response, StatusCode> {
+    if shutdown.is_cancelled() {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    let threshold = params.threshold.unwrap_or(10.0);
+    if !threshold.is_finite() || threshold < 0.0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let baseline = match &params.baseline {
+        Some(name) => Some(jobs.baseline(name).ok_or(StatusCode::NOT_FOUND)?),
+        None => None,
+    };
+    if let Some(name) = &params.save_baseline
+        && (name.is_empty() || !jobs.can_save(name))
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let id = jobs.start().ok_or(StatusCode::CONFLICT)?;
+    let tracker = progress.track(Some(&id), None).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let job = jobs.get(&id);
+    let headers = [
+        (header::LOCATION, format!("/v1/benchmark/{id}")),
+        (HeaderName::from_static(PROGRESS_HEADER), tracker.location()),
+    ];
+
+    tokio::spawn(async move {
+        let work = tokio::task::spawn_blocking(move || {
+            let mut result = run(&shutdown, &tracker);
+            if let (Some(result), Some(baseline)) = (&mut result, &baseline) {
+                result.compare(baseline, threshold);
+            }
+            let state = if result.is_some() { ProgressState::Completed } else { ProgressState::Cancelled };
+            tracker.finish(state);
+            result
+        });
+        let (status, result) = match work.await {
+            Ok(Some(result)) => {
+                if let Some(name) = params.save_baseline {
+                    jobs.save_baseline(name, result.results.clone());
+                }
+                (JobStatus::Completed, Some(result))
+            }
+            Ok(None) => (JobStatus::Cancelled, None),
+            Err(_) => (JobStatus::Failed, None),
+        };
+        jobs.finish(&id, status, result);
+    });
+
+    Ok((StatusCode::ACCEPTED, headers, Json(job)).into_response())
+}
+
+/// The job, or with `format` its results alone once completed
+#[utoipa::path(
+    get,
+    path = "/v1/benchmark/{id}",
+    tag = "admin",
+    params(("id" = String, Path), StatusParams),
+    responses(
+        (status = 200, body = BenchmarkJob, description = "The job, or NDJSON or CSV results with `format`"),
+        (status = 404),
+        (status = 409, description = "`format` given before the job completed"),
+    )
+)]
+pub(super) async fn benchmark_status_handler(
+    Extension(jobs): Extension<Arc<BenchmarkJobs>>,
+    Path(id): Path<String>,
+    Query(params): Query<StatusParams>,
+) -> Result<Response, StatusCode> {
+    let job = jobs.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    let Some(format) = params.format else {
+        return Ok(Json(job).into_response());
+    };
+    let result = job.result.ok_or(StatusCode::CONFLICT)?;
+    Ok(([(header::CONTENT_TYPE, format.content_type())], to_rows(&result.results, format)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_running_job() {
+        let jobs = BenchmarkJobs::default();
+        let id = jobs.start().unwrap();
+        assert!(jobs.start().is_none());
+        jobs.finish(&id, JobStatus::Completed, None);
+        assert!(jobs.get(&id).is_some_and(|job| job.status == JobStatus::Completed));
+        assert!(jobs.start().is_some());
+    }
+
+    #[test]
+    fn test_cancelled_run() {
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+        let tracker = ProgressRegistry::default().track(None, None).unwrap();
+        assert!(run(&shutdown, &tracker).is_none());
+    }
+
+    #[test]
+    fn test_baseline_comparison() {
+        let jobs = BenchmarkJobs::default();
+        let result = |ns| BenchmarkResult {
+            operation: "encode_small".to_string(),
+            data_size: 20,
+            compression: "lz4".to_string(),
+            iterations: 10,
+            total_time_ms: 0.0,
+            avg_time_per_op_ns: ns,
+            throughput_mb_per_sec: 0.0,
+        };
+        assert!(jobs.baseline("main").is_none());
+        jobs.save_baseline("main".to_string(), vec![result(100.0)]);
+
+        let mut response = BenchmarkResponse {
+            results: vec![result(150.0)],
+            total_time_ms: 0.0,
+            comparison: None,
+            regressions: None,
+        };
+        response.compare(&jobs.baseline("main").unwrap(), 10.0);
+        assert_eq!(response.regressions, Some(1));
+        response.compare(&jobs.baseline("main").unwrap(), 60.0);
+        assert_eq!(response.regressions, Some(0));
+
+        for i in 1..MAX_BASELINES {
+            jobs.save_baseline(i.to_string(), Vec::new());
+        }
+        assert!(jobs.can_save("main"));
+        assert!(!jobs.can_save("another"));
+    }
+
+    #[tokio::test]
+    async fn test_status_as_rows() {
+        let jobs = Arc::new(BenchmarkJobs::default());
+        let id = jobs.start().unwrap();
+        let status = |format: Option<RowFormat>| {
+            benchmark_status_handler(Extension(jobs.clone()), Path(id.clone()), Query(StatusParams { format }))
+        };
+        assert_eq!(status(Some(RowFormat::Csv)).await.unwrap_err(), StatusCode::CONFLICT);
+
+        let results = measure("small", b"data", b"seed", CompressionAlgorithm::Lz4, 1).to_vec();
+        let response = BenchmarkResponse {
+            results,
+            total_time_ms: 0.0,
+            comparison: None,
+            regressions: None,
+        };
+        jobs.finish(&id, JobStatus::Completed, Some(response));
+        let response = status(Some(RowFormat::Ndjson)).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body.split(|&b| b == b'\n').filter(|line| !line.is_empty()).count(), 2);
+        let response = status(None).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+}
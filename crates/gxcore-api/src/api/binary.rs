@@ -0,0 +1,192 @@
+use std::fmt;
+
+use axum::http::StatusCode;
+use base64::{Engine as _, engine::general_purpose};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use utoipa::openapi::schema::{ArrayBuilder, ObjectBuilder, OneOfBuilder, Schema, Type};
+use utoipa::openapi::RefOr;
+use utoipa::{PartialSchema, ToSchema};
+
+/// Byte field sent either as raw bytes (JSON number array, MessagePack bin)
+/// or as a string in the request's `encoding`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Binary {
+    Bytes(Vec<u8>),
+    Text(String),
+}
+
+impl Binary {
+    /// Raw bytes, or the UTF-8 bytes of a text representation
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Binary::Bytes(bytes) => bytes,
+            Binary::Text(text) => text.into_bytes(),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Binary {
+    fn from(bytes: Vec<u8>) -> Self {
+        Binary::Bytes(bytes)
+    }
+}
+
+impl Serialize for Binary {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Binary::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            Binary::Text(text) => serializer.serialize_str(text),
+        }
+    }
+}
+
+impl PartialSchema for Binary {
+    fn schema() -> RefOr<Schema> {
+        let byte = ObjectBuilder::new().schema_type(Type::Integer).minimum(Some(0)).maximum(Some(255));
+        OneOfBuilder::new()
+            .item(ArrayBuilder::new().items(byte))
+            .item(ObjectBuilder::new().schema_type(Type::String))
+            .description(Some("Byte array, or a string in the request's `encoding`"))
+            .into()
+    }
+}
+
+impl ToSchema for Binary {}
+
+struct BinaryVisitor;
+
+impl<'de> Visitor<'de> for BinaryVisitor {
+    type Value = Binary;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a byte array or an encoded string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Binary, E> {
+        Ok(Binary::Text(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Binary, E> {
+        Ok(Binary::Text(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Binary, E> {
+        Ok(Binary::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Binary, E> {
+        Ok(Binary::Bytes(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Binary, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        Ok(Binary::Bytes(bytes))
+    }
+}
+
+impl<'de> Deserialize<'de> for Binary {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(BinaryVisitor)
+    }
+}
+
+/// How string byte fields are encoded in a request and its response
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TextEncoding {
+    /// Byte arrays, the original request shape
+    #[default]
+    Bytes,
+    Base64,
+    Hex,
+}
+
+impl TextEncoding {
+    /// Resolve a request field to bytes
+    pub fn decode(self, field: Binary) -> Result<Vec<u8>, StatusCode> {
+        match (self, field) {
+            (_, Binary::Bytes(bytes)) => Ok(bytes),
+            (TextEncoding::Bytes, Binary::Text(_)) => Err(StatusCode::BAD_REQUEST),
+            (TextEncoding::Base64, Binary::Text(text)) => general_purpose::STANDARD
+                .decode(text)
+                .map_err(|_| StatusCode::BAD_REQUEST),
+            (TextEncoding::Hex, Binary::Text(text)) => hex::decode(text).map_err(|_| StatusCode::BAD_REQUEST),
+        }
+    }
+
+    /// Represent response bytes in the request's encoding
+    pub fn encode(self, bytes: Vec<u8>) -> Binary {
+        match self {
+            TextEncoding::Bytes => Binary::Bytes(bytes),
+            TextEncoding::Base64 => Binary::Text(general_purpose::STANDARD.encode(bytes)),
+            TextEncoding::Hex => Binary::Text(hex::encode(bytes)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use axum::{
+        body::{to_bytes, Body},
+        http::{header, Request},
+    };
+    use tower::ServiceExt;
+
+    async fn post_json(path: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let request = Request::post(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = create_router().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_base64_roundtrip() {
+        let data = general_purpose::STANDARD.encode(b"string payload");
+        let (status, body) = post_json(
+            "/encode",
+            serde_json::json!({"data": data, "seed": "c2VlZA==", "compression": "lz4", "encoding": "base64"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let encoded = body["encoded"].as_str().unwrap().to_string();
+
+        let (status, body) = post_json(
+            "/decode",
+            serde_json::json!({"encoded": encoded, "seed": "c2VlZA==", "compression": "lz4", "encoding": "base64"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["decoded"], serde_json::json!(data));
+    }
+
+    #[tokio::test]
+    async fn test_hex_mixed_with_arrays() {
+        let (status, body) = post_json(
+            "/encode",
+            serde_json::json!({"data": hex::encode(b"hex"), "seed": [115, 101, 101, 100], "compression": "none", "encoding": "hex"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["encoded"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_string_requires_encoding() {
+        let (status, _) = post_json(
+            "/encode",
+            serde_json::json!({"data": "aGk=", "seed": [1], "compression": "none"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
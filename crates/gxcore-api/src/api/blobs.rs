@@ -0,0 +1,275 @@
+use std::time::Duration;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::Path,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::storage::{BlobMeta, BlobStore, GcReport};
+
+/// Seconds until a stored blob may be collected, overriding `blobs.default_ttl_secs`
+pub const TTL_HEADER: &str = "x-gx-ttl";
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct BlobResponse {
+    /// Hex BLAKE3 hash of the contents
+    id: String,
+    size: usize,
+}
+
+/// Store an encoded blob under its content id, so clients can pass the id around instead
+#[utoipa::path(
+    put,
+    path = "/v1/blobs",
+    tag = "storage",
+    params(("x-gx-ttl" = Option<u64>, Header, description = "Seconds until the blob may be collected once unreferenced")),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 201, body = BlobResponse, headers(("location" = String))),
+        (status = 400, description = "Empty body or invalid TTL"),
+        (status = 501, description = "No blob store configured"),
+        (status = 502, description = "Blob store failed"),
+    )
+)]
+pub(super) async fn put_blob_handler(
+    Extension(blobs): Extension<Option<BlobStore>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, StatusCode> {
+    let blobs = blobs.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    if body.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let size = body.len();
+    let id = match headers.get(TTL_HEADER) {
+        Some(ttl) => {
+            let ttl: u64 = ttl
+                .to_str()
+                .ok()
+                .and_then(|ttl| ttl.parse().ok())
+                .filter(|&ttl| ttl > 0)
+                .ok_or(StatusCode::BAD_REQUEST)?;
+            blobs.put_expiring(body, Some(Duration::from_secs(ttl))).await
+        }
+        None => blobs.put(body).await,
+    }
+    .map_err(|_| StatusCode::BAD_GATEWAY)?;
+    let location = format!("/v1/blobs/{id}");
+    Ok((StatusCode::CREATED, [(header::LOCATION, location)], Json(BlobResponse { id, size })).into_response())
+}
+
+/// Contents of a stored blob, which never change for a given id
+#[utoipa::path(
+    get,
+    path = "/v1/blobs/{id}",
+    tag = "storage",
+    params(("id" = String, Path, description = "Id returned by `PUT /v1/blobs`")),
+    responses(
+        (status = 200, content_type = "application/octet-stream", body = Vec<u8>),
+        (status = 404),
+        (status = 501, description = "No blob store configured"),
+        (status = 502, description = "Blob store failed"),
+    )
+)]
+pub(super) async fn get_blob_handler(
+    Extension(blobs): Extension<Option<BlobStore>>,
+    Path(id): Path<String>,
+) -> Result<Response, StatusCode> {
+    let blobs = blobs.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let contents = blobs
+        .get(&id)
+        .await
+        .map_err(|_| StatusCode::BAD_GATEWAY)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let headers = [
+        (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+        (header::ETAG, format!("\"{id}\"")),
+        (header::CACHE_CONTROL, "public, max-age=31536000, immutable".to_string()),
+    ];
+    Ok((headers, Body::from_stream(contents)).into_response())
+}
+
+/// Hold a reference to a blob, keeping it past its TTL until released
+#[utoipa::path(
+    post,
+    path = "/v1/blobs/{id}/refs",
+    tag = "storage",
+    params(("id" = String, Path, description = "Id returned by `PUT /v1/blobs`")),
+    responses(
+        (status = 200, body = BlobMeta),
+        (status = 404),
+        (status = 501, description = "No blob store configured"),
+        (status = 502, description = "Blob store failed"),
+    )
+)]
+pub(super) async fn retain_blob_handler(
+    Extension(blobs): Extension<Option<BlobStore>>,
+    Path(id): Path<String>,
+) -> Result<Json<BlobMeta>, StatusCode> {
+    let blobs = blobs.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let meta = blobs.retain(&id).await.map_err(|_| StatusCode::BAD_GATEWAY)?;
+    meta.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Release a reference taken with `POST /v1/blobs/{id}/refs`
+#[utoipa::path(
+    delete,
+    path = "/v1/blobs/{id}/refs",
+    tag = "storage",
+    params(("id" = String, Path, description = "Id returned by `PUT /v1/blobs`")),
+    responses(
+        (status = 200, body = BlobMeta),
+        (status = 404),
+        (status = 501, description = "No blob store configured"),
+        (status = 502, description = "Blob store failed"),
+    )
+)]
+pub(super) async fn release_blob_handler(
+    Extension(blobs): Extension<Option<BlobStore>>,
+    Path(id): Path<String>,
+) -> Result<Json<BlobMeta>, StatusCode> {
+    let blobs = blobs.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let meta = blobs.release(&id).await.map_err(|_| StatusCode::BAD_GATEWAY)?;
+    meta.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct GcStatus {
+    /// Last collection that removed blobs, by the background task or `POST /v1/admin/blobs/gc`
+    last: Option<GcReport>,
+    /// Dry run showing what a collection would remove now
+    pending: GcReport,
+}
+
+/// Expired blobs awaiting collection and the outcome of the last collection
+#[utoipa::path(
+    get,
+    path = "/v1/admin/blobs/gc",
+    tag = "admin",
+    responses((status = 200, body = GcStatus), (status = 501, description = "No blob store configured"), (status = 502))
+)]
+pub(super) async fn gc_status_handler(Extension(blobs): Extension<Option<BlobStore>>) -> Result<Json<GcStatus>, StatusCode> {
+    let blobs = blobs.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let pending = blobs.collect(true).await.map_err(|_| StatusCode::BAD_GATEWAY)?;
+    Ok(Json(GcStatus {
+        last: blobs.last_collection(),
+        pending,
+    }))
+}
+
+/// Collect expired, unreferenced blobs now rather than at the next scheduled run
+#[utoipa::path(
+    post,
+    path = "/v1/admin/blobs/gc",
+    tag = "admin",
+    responses((status = 200, body = GcReport), (status = 501, description = "No blob store configured"), (status = 502))
+)]
+pub(super) async fn run_gc_handler(Extension(blobs): Extension<Option<BlobStore>>) -> Result<Json<GcReport>, StatusCode> {
+    let blobs = blobs.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    blobs.collect(false).await.map(Json).map_err(|_| StatusCode::BAD_GATEWAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router_with, ApiConfig};
+    use crate::storage::{FsConfig, FsStore};
+    use axum::{body::to_bytes, http::Request};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_put_and_get_blob() {
+        let root = std::env::temp_dir().join(format!("gx-blobs-{}", std::process::id()));
+        let store = Arc::new(FsStore::new(FsConfig { root: root.clone() }));
+        let router = create_router_with(ApiConfig {
+            blobs: Some(BlobStore::new(store, "blobs")),
+            ..Default::default()
+        });
+
+        let put = Request::put("/v1/blobs").body(Body::from("encoded blob")).unwrap();
+        let response = router.clone().oneshot(put).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let id = blake3::hash(b"encoded blob").to_hex().to_string();
+        assert_eq!(response.headers()[header::LOCATION], format!("/v1/blobs/{id}"));
+
+        let get = Request::get(format!("/v1/blobs/{id}")).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(get).await.unwrap();
+        assert_eq!(response.headers()[header::ETAG], format!("\"{id}\""));
+        assert_eq!(to_bytes(response.into_body(), usize::MAX).await.unwrap(), "encoded blob");
+
+        let unknown = Request::get(format!("/v1/blobs/{}", "0".repeat(64))).body(Body::empty()).unwrap();
+        assert_eq!(router.clone().oneshot(unknown).await.unwrap().status(), StatusCode::NOT_FOUND);
+        let traversal = Request::get("/v1/blobs/..%2Fsecret").body(Body::empty()).unwrap();
+        assert_eq!(router.oneshot(traversal).await.unwrap().status(), StatusCode::NOT_FOUND);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    async fn json(router: &axum::Router, request: Request<Body>) -> serde_json::Value {
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_expired_blobs_are_collected_once_released() {
+        let root = std::env::temp_dir().join(format!("gx-blobs-gc-{}", std::process::id()));
+        let store = Arc::new(FsStore::new(FsConfig { root: root.clone() }));
+        let router = create_router_with(ApiConfig {
+            blobs: Some(BlobStore::new(store, "blobs")),
+            unauthenticated_admin: true,
+            ..Default::default()
+        });
+        let put = |body: &'static str, ttl: Option<&str>| {
+            let mut request = Request::put("/v1/blobs");
+            if let Some(ttl) = ttl {
+                request = request.header(TTL_HEADER, ttl);
+            }
+            request.body(Body::from(body)).unwrap()
+        };
+        let status = |request: Request<Body>| {
+            let router = router.clone();
+            async move { router.oneshot(request).await.unwrap().status() }
+        };
+        let id = |body: &str| blake3::hash(body.as_bytes()).to_hex().to_string();
+
+        assert_eq!(status(put("expiring", Some("2"))).await, StatusCode::CREATED);
+        assert_eq!(status(put("held", Some("2"))).await, StatusCode::CREATED);
+        assert_eq!(status(put("kept", None)).await, StatusCode::CREATED);
+        assert_eq!(status(put("kept", Some("2"))).await, StatusCode::CREATED);
+        assert_eq!(status(put("invalid", Some("0"))).await, StatusCode::BAD_REQUEST);
+        let held = json(&router, Request::post(format!("/v1/blobs/{}/refs", id("held"))).body(Body::empty()).unwrap()).await;
+        assert_eq!(held["refs"], 1);
+        let unknown = Request::post(format!("/v1/blobs/{}/refs", "0".repeat(64))).body(Body::empty()).unwrap();
+        assert_eq!(status(unknown).await, StatusCode::NOT_FOUND);
+
+        let gc_status = || Request::get("/v1/admin/blobs/gc").body(Body::empty()).unwrap();
+        let run_gc = || Request::post("/v1/admin/blobs/gc").body(Body::empty()).unwrap();
+        let before = json(&router, gc_status()).await;
+        assert_eq!(before["pending"]["collected"], 0);
+        assert!(before["last"].is_null());
+
+        // Expiry is in whole seconds, so two of them have surely passed
+        tokio::time::sleep(Duration::from_millis(2100)).await;
+        assert_eq!(json(&router, gc_status()).await["pending"]["collected"], 1);
+        let report = json(&router, run_gc()).await;
+        assert_eq!((report["scanned"].as_u64(), report["collected"].as_u64()), (Some(2), Some(1)));
+        assert_eq!(report["freed_bytes"], "expiring".len());
+        let get = |body| Request::get(format!("/v1/blobs/{}", id(body))).body(Body::empty()).unwrap();
+        assert_eq!(status(get("expiring")).await, StatusCode::NOT_FOUND);
+        assert_eq!(status(get("held")).await, StatusCode::OK);
+        assert_eq!(status(get("kept")).await, StatusCode::OK);
+
+        let released = Request::delete(format!("/v1/blobs/{}/refs", id("held"))).body(Body::empty()).unwrap();
+        assert_eq!(json(&router, released).await["refs"], 0);
+        assert_eq!(json(&router, run_gc()).await["collected"], 1);
+        assert_eq!(status(get("held")).await, StatusCode::NOT_FOUND);
+        assert_eq!(json(&router, gc_status()).await["last"]["collected"], 1);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
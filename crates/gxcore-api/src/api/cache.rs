@@ -0,0 +1,247 @@
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use lru::LruCache;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use gxcore_core::cache::TranslatorCache;
+use gxcore_core::CompressionAlgorithm;
+
+use super::offload;
+
+/// Response header telling whether the encode was served from the cache
+pub const CACHE_HEADER: &str = "x-gx-cache";
+
+/// In-memory cache of encode results, keyed by a hash of the inputs
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    /// Results kept before the least recently used is evicted
+    pub max_entries: usize,
+    /// Larger results are encoded every time
+    pub max_entry_bytes: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            enabled: false,
+            max_entries: 1024,
+            max_entry_bytes: 1024 * 1024,
+        }
+    }
+}
+
+type Key = [u8; 32];
+
+/// Encode results shared by `/encode` and `/encode/raw`, also answering `If-None-Match`
+pub(super) struct EncodeCache {
+    entries: Option<Mutex<LruCache<Key, Vec<u8>>>>,
+    max_entry_bytes: usize,
+    /// Translators shared with the decode handlers, used for every miss
+    translators: Arc<TranslatorCache>,
+}
+
+/// Output of [`EncodeCache::encode`]
+pub(super) enum Cached {
+    /// The client's `If-None-Match` already names this result
+    NotModified(HeaderValue),
+    Encoded {
+        encoded: Vec<u8>,
+        etag: Option<HeaderValue>,
+        hit: bool,
+    },
+}
+
+impl EncodeCache {
+    pub fn new(config: &CacheConfig, translators: Arc<TranslatorCache>) -> Self {
+        let entries = NonZeroUsize::new(config.max_entries)
+            .filter(|_| config.enabled)
+            .map(|capacity| Mutex::new(LruCache::new(capacity)));
+        EncodeCache {
+            entries,
+            max_entry_bytes: config.max_entry_bytes,
+            translators,
+        }
+    }
+
+    /// Encode `data`, reusing a cached result when enabled. `variant` distinguishes response
+    /// representations of the same result, such as JSON and MessagePack. Encodes are POSTs, so
+    /// `If-None-Match: *` fails with 412 as RFC 9110 §13.1.2 requires of methods other than
+    /// GET and HEAD, while a listed tag still revalidates with 304.
+    pub async fn encode(
+        &self,
+        headers: &HeaderMap,
+        variant: &str,
+        data: impl AsRef<[u8]> + Send + 'static,
+        seed: Vec<u8>,
+        compression: CompressionAlgorithm,
+    ) -> Result<Cached, StatusCode> {
+        let translators = self.translators.clone();
+        let Some(entries) = &self.entries else {
+            let encoded = offload(move || translators.encode(data.as_ref(), &seed, compression)).await?;
+            return Ok(Cached::Encoded {
+                encoded,
+                etag: None,
+                hit: false,
+            });
+        };
+
+        let key = key(data.as_ref(), &seed, compression);
+        let etag = etag(&key, variant);
+        if if_none_match(headers, "*") {
+            return Err(StatusCode::PRECONDITION_FAILED);
+        }
+        if if_none_match(headers, etag.to_str().unwrap_or_default()) {
+            return Ok(Cached::NotModified(etag));
+        }
+        if let Some(encoded) = entries.lock().unwrap().get(&key) {
+            return Ok(Cached::Encoded {
+                encoded: encoded.clone(),
+                etag: Some(etag),
+                hit: true,
+            });
+        }
+
+        let encoded = offload(move || translators.encode(data.as_ref(), &seed, compression)).await?;
+        if encoded.len() <= self.max_entry_bytes {
+            entries.lock().unwrap().put(key, encoded.clone());
+        }
+        Ok(Cached::Encoded {
+            encoded,
+            etag: Some(etag),
+            hit: false,
+        })
+    }
+}
+
+/// Length-prefixed hash of every input, so `(ab, c)` and `(a, bc)` differ
+fn key(data: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Key {
+    let mut hasher = Sha256::new();
+    for part in [seed, &[compression.id()], data] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+fn etag(key: &Key, variant: &str) -> HeaderValue {
+    let tag = Sha256::new().chain_update(key).chain_update(variant.as_bytes()).finalize();
+    HeaderValue::from_str(&format!("\"{}\"", hex::encode(&tag[..16]))).expect("Hex is a valid header value")
+}
+
+/// Whether `If-None-Match` lists `etag`, comparing weakly as RFC 9110 requires
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+pub(super) fn not_modified(etag: HeaderValue) -> Response {
+    (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response()
+}
+
+/// Add `ETag` and the cache status to a response built from a cached encode
+pub(super) fn annotate(response: &mut Response, etag: Option<HeaderValue>, hit: bool) {
+    if let Some(etag) = etag {
+        let headers = response.headers_mut();
+        headers.insert(header::ETAG, etag);
+        headers.insert(
+            HeaderName::from_static(CACHE_HEADER),
+            HeaderValue::from_static(if hit { "hit" } else { "miss" }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router_with, ApiConfig};
+    use axum::{body::Body, http::Request, Router};
+    use tower::ServiceExt;
+
+    fn encode_request(if_none_match: Option<&str>) -> Request<Body> {
+        let mut request = Request::post("/v1/encode")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"data":[1,2,3],"seed":[9],"compression":"lz4"}"#))
+            .unwrap();
+        if let Some(tag) = if_none_match {
+            request.headers_mut().insert(header::IF_NONE_MATCH, tag.parse().unwrap());
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_and_not_modified() {
+        let router: Router = create_router_with(ApiConfig {
+            cache: CacheConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let first = router.clone().oneshot(encode_request(None)).await.unwrap();
+        assert_eq!(first.headers()[CACHE_HEADER], "miss");
+        let etag = first.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        let second = router.clone().oneshot(encode_request(None)).await.unwrap();
+        assert_eq!(second.headers()[CACHE_HEADER], "hit");
+        assert_eq!(second.headers()[header::ETAG], etag.as_str());
+
+        let revalidated = router.clone().oneshot(encode_request(Some(&format!("W/{etag}")))).await.unwrap();
+        assert_eq!(revalidated.status(), StatusCode::NOT_MODIFIED);
+
+        let stale = router.clone().oneshot(encode_request(Some("\"other\""))).await.unwrap();
+        assert_eq!(stale.status(), StatusCode::OK);
+
+        let any = router.oneshot(encode_request(Some("\"other\", *"))).await.unwrap();
+        assert_eq!(any.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_shared_cache_under_concurrent_encodes() {
+        fn shareable<T: Send + Sync>() {}
+        shareable::<EncodeCache>();
+
+        // Both caches smaller than the inputs, so lookups race with evictions in each
+        let translators = Arc::new(TranslatorCache::new(2));
+        let config = CacheConfig { enabled: true, max_entries: 4, ..Default::default() };
+        let cache = Arc::new(EncodeCache::new(&config, translators));
+        let tasks: Vec<_> = (0..64u8)
+            .map(|i| {
+                let cache = cache.clone();
+                tokio::spawn(async move {
+                    let (data, seed) = (vec![i % 8; 100 + usize::from(i % 8)], vec![i % 3]);
+                    let expected = gxcore_core::encode(&data, &seed, CompressionAlgorithm::Lz4);
+                    let headers = HeaderMap::new();
+                    for _ in 0..20 {
+                        let result = cache.encode(&headers, "json", data.clone(), seed.clone(), CompressionAlgorithm::Lz4);
+                        let Cached::Encoded { encoded, .. } = result.await.unwrap() else { panic!("no If-None-Match was sent") };
+                        assert_eq!(encoded, expected);
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(cache.entries.as_ref().unwrap().lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_key_separates_inputs() {
+        let lz4 = CompressionAlgorithm::Lz4;
+        assert_ne!(key(b"ab", b"c", lz4), key(b"a", b"bc", lz4));
+        assert_ne!(key(b"a", b"b", lz4), key(b"a", b"b", CompressionAlgorithm::None));
+        assert_ne!(etag(&key(b"a", b"b", lz4), "json"), etag(&key(b"a", b"b", lz4), "msgpack"));
+    }
+}
@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+use gxcore_core::envelope::FORMAT_VERSION;
+use gxcore_core::CompressionAlgorithm;
+
+use super::{parse_compression, ApiConfig, BodyLimits};
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct CompressionSupport {
+    name: &'static str,
+    /// Identifier stored in envelope headers
+    id: u8,
+    /// Whether the API accepts the name
+    accepted: bool,
+    /// Whether data is actually compressed; placeholders pass it through unchanged
+    implemented: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct EnvelopeSupport {
+    format_version: u8,
+    serializations: Vec<&'static str>,
+    payload_formats: Vec<&'static str>,
+    integrity_modes: Vec<&'static str>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct CipherSuites {
+    /// Derivation of the alphabet from the seed
+    alphabet: &'static str,
+    /// Encryption of the keyring file
+    keyring: &'static str,
+    /// Suites offered when TLS is enabled, in preference order
+    tls: Vec<&'static str>,
+}
+
+/// What this server build supports, so clients negotiate instead of hard-coding names
+#[derive(Serialize, ToSchema)]
+pub(super) struct Capabilities {
+    version: &'static str,
+    api_versions: Vec<&'static str>,
+    compression: Vec<CompressionSupport>,
+    default_compression: &'static str,
+    checksums: Vec<&'static str>,
+    envelope: EnvelopeSupport,
+    cipher_suites: CipherSuites,
+    content_types: Vec<&'static str>,
+    text_encodings: Vec<&'static str>,
+    /// `seed` sent inline, or `key_id` naming a server-held key
+    seed_sources: Vec<&'static str>,
+    limits: BodyLimits,
+    auth: bool,
+    cache: bool,
+    /// Whether encode and decode routes count against quotas reported by `/usage`
+    quotas: bool,
+    /// Whether requests must carry `x-gx-timestamp`, `x-gx-nonce` and `x-gx-signature`
+    request_signing: bool,
+    /// Whether operations are recorded and queryable at `/admin/audit`
+    audit: bool,
+    /// Whether tokens' tenant claim selects a separate keyring and blob namespace
+    tenants: bool,
+    /// Whether `/objects` routes have a store to work on
+    object_storage: bool,
+    /// Whether `/blobs` has a store
+    blob_store: bool,
+    /// Whether replicas can pull blobs and key metadata from `/replication`
+    replication: bool,
+    /// Optional cargo features compiled in
+    features: Vec<&'static str>,
+}
+
+impl Capabilities {
+    pub fn new(config: &ApiConfig, api_versions: Vec<&'static str>) -> Self {
+        let algorithms = [
+            CompressionAlgorithm::None,
+            CompressionAlgorithm::Huffman,
+            CompressionAlgorithm::Lz4,
+            CompressionAlgorithm::Brotli,
+            CompressionAlgorithm::Lz4Frame,
+        ];
+        let compression = algorithms
+            .into_iter()
+            .map(|algorithm| CompressionSupport {
+                name: algorithm.name(),
+                id: algorithm.id(),
+                accepted: parse_compression(algorithm.name()).is_ok(),
+                implemented: algorithm.is_implemented(),
+            })
+            .collect();
+        let tls = rustls::crypto::ring::DEFAULT_CIPHER_SUITES
+            .iter()
+            .filter_map(|suite| suite.suite().as_str())
+            .collect();
+        let features = [
+            ("proto", cfg!(feature = "proto")),
+            ("grpc", cfg!(feature = "grpc")),
+            ("arrow", cfg!(feature = "arrow")),
+            ("sqlx", cfg!(feature = "sqlx")),
+            ("diesel", cfg!(feature = "diesel")),
+            ("ipfs", cfg!(feature = "ipfs")),
+            ("graphql", cfg!(feature = "graphql")),
+        ];
+
+        Capabilities {
+            version: env!("CARGO_PKG_VERSION"),
+            api_versions,
+            compression,
+            default_compression: config.default_compression.name(),
+            checksums: vec!["crc32"],
+            envelope: EnvelopeSupport {
+                format_version: FORMAT_VERSION,
+                serializations: vec!["binary", "cbor"],
+                payload_formats: vec!["raw", "bincode"],
+                integrity_modes: vec!["crc32", "hmac-sha256", "none"],
+            },
+            cipher_suites: CipherSuites {
+                alphabet: "sha256-permutation",
+                keyring: "chacha20-poly1305",
+                tls,
+            },
+            content_types: vec!["application/json", "application/msgpack", "application/octet-stream"],
+            text_encodings: vec!["bytes", "base64", "hex"],
+            seed_sources: vec!["seed", "key_id"],
+            limits: config.limits,
+            auth: config.auth.is_some(),
+            cache: config.cache.enabled,
+            quotas: config.quotas.is_some(),
+            request_signing: config.signing.is_some(),
+            audit: config.audit.is_some(),
+            tenants: config.tenants.is_some(),
+            object_storage: config.storage.is_some(),
+            blob_store: config.blobs.is_some(),
+            replication: config.replication_token.is_some(),
+            features: features.into_iter().filter(|(_, on)| *on).map(|(name, _)| name).collect(),
+        }
+    }
+}
+
+#[utoipa::path(get, path = "/v1/capabilities", tag = "health", responses((status = 200, body = Capabilities)))]
+pub(super) async fn capabilities_handler(State(capabilities): State<Arc<Capabilities>>) -> Response {
+    Json(&*capabilities).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::create_router;
+    use axum::{
+        body::{to_bytes, Body},
+        http::Request,
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_placeholders_reported() {
+        let request = Request::get("/v1/capabilities").body(Body::empty()).unwrap();
+        let response = create_router().oneshot(request).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let capabilities: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let compression = capabilities["compression"].as_array().unwrap();
+        let brotli = compression.iter().find(|c| c["name"] == "brotli").unwrap();
+        assert_eq!(brotli["accepted"], true);
+        assert_eq!(brotli["implemented"], false);
+        let huffman = compression.iter().find(|c| c["name"] == "huffman").unwrap();
+        assert_eq!(huffman["accepted"], false);
+        assert_eq!(capabilities["api_versions"], serde_json::json!(["v1"]));
+        assert!(capabilities["cipher_suites"]["tls"].as_array().is_some_and(|suites| !suites.is_empty()));
+    }
+}
@@ -0,0 +1,102 @@
+use axum::{response::Html, routing::get, Json, Router};
+use utoipa::OpenApi;
+
+use super::{audit, benchmark, blobs, capabilities, file, health, inspect, ipfs, jobs, keys, metrics, objects, progress, quota, raw, replication, stream, tenants, verify};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "gxcore API", description = "Seeded base64 encoding with optional compression"),
+    paths(
+        super::encode_handler,
+        super::decode_handler,
+        ipfs::decode_cid_handler,
+        super::verify_handler,
+        verify::verify_full_handler,
+        inspect::inspect_handler,
+        benchmark::start_benchmark_handler,
+        benchmark::benchmark_status_handler,
+        raw::encode_raw_handler,
+        raw::decode_raw_handler,
+        file::encode_file_handler,
+        stream::encode_stream_handler,
+        stream::decode_stream_handler,
+        progress::progress_handler,
+        jobs::submit_encode_handler,
+        jobs::submit_decode_handler,
+        jobs::job_status_handler,
+        jobs::job_result_handler,
+        jobs::delete_job_handler,
+        objects::encode_object_handler,
+        objects::decode_object_handler,
+        blobs::put_blob_handler,
+        blobs::get_blob_handler,
+        blobs::retain_blob_handler,
+        blobs::release_blob_handler,
+        blobs::gc_status_handler,
+        blobs::run_gc_handler,
+        health::healthz_handler,
+        health::readyz_handler,
+        capabilities::capabilities_handler,
+        metrics::metrics_handler,
+        keys::list_keys_handler,
+        keys::add_key_handler,
+        keys::rotate_key_handler,
+        keys::disable_key_handler,
+        keys::enable_key_handler,
+        quota::usage_handler,
+        audit::query_audit_handler,
+        tenants::list_tenants_handler,
+        tenants::create_tenant_handler,
+        replication::list_blobs_handler,
+        replication::fetch_blob_handler,
+        replication::keys_handler,
+    ),
+    tags(
+        (name = "codec", description = "JSON and MessagePack encoding"),
+        (name = "raw", description = "Binary bodies with options in headers or query"),
+        (name = "jobs", description = "Background encodes and decodes polled for results"),
+        (name = "storage", description = "Objects encoded and decoded in place in object storage"),
+        (name = "health", description = "Probes and metrics"),
+        (name = "replication", description = "Blobs and key metadata pulled by replica servers"),
+        (name = "admin"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Swagger UI loaded from the CDN, pointed at the generated document
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+  <title>gxcore API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>SwaggerUIBundle({ url: "/docs/openapi.json", dom_id: "#swagger-ui" });</script>
+</body>
+</html>
+"##;
+
+/// `/docs` and the OpenAPI document at `/docs/openapi.json`
+pub(super) fn routes() -> Router {
+    Router::new()
+        .route("/docs", get(|| async { Html(SWAGGER_UI) }))
+        .route("/docs/openapi.json", get(|| async { Json(ApiDoc::openapi()) }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_covers_routes() {
+        let spec = ApiDoc::openapi();
+        for path in ["/v1/encode", "/v1/decode/raw", "/v1/encode/file", "/readyz"] {
+            assert!(spec.paths.paths.contains_key(path), "{path} missing");
+        }
+        let schemas = spec.components.unwrap().schemas;
+        assert!(schemas.contains_key("EncodeRequest"));
+        assert!(schemas.contains_key("Binary"));
+    }
+}
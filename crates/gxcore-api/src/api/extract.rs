@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    body::{to_bytes, Body, Bytes},
+    extract::{FromRequest, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use gxcore_core::envelope::Envelope;
+use gxcore_core::CompressionAlgorithm;
+
+use super::keys::SeedSource;
+use crate::keyring::Keyring;
+
+/// Content type of sealed bodies
+pub const CONTENT_TYPE: &str = "application/gxcore-envelope";
+
+tokio::task_local! {
+    static CODEC: GxCodec;
+}
+
+/// Keyring and key `GxJson` and `GxBytes` work with, installed by the `envelopes` layer
+#[derive(Clone)]
+pub struct GxCodec {
+    keyring: Arc<Keyring>,
+    key_id: String,
+    compression: CompressionAlgorithm,
+}
+
+impl GxCodec {
+    /// Seal responses with `key_id`; requests open with the key named in their envelope
+    pub fn new(keyring: Arc<Keyring>, key_id: &str) -> Self {
+        GxCodec {
+            keyring,
+            key_id: key_id.to_string(),
+            compression: CompressionAlgorithm::None,
+        }
+    }
+
+    pub fn with_compression(mut self, compression: CompressionAlgorithm) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    async fn seal(&self, data: &[u8]) -> Result<Vec<u8>, StatusCode> {
+        let seed = SeedSource::Key(self.key_id.clone()).encoding(&self.keyring).await?;
+        let envelope = Envelope::seal(data, &seed, self.compression).with_key_id(&self.key_id);
+        envelope
+            .authenticate(&seed)
+            .and_then(|envelope| envelope.to_bytes())
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// Payload of a binary or CBOR envelope, trying every version of its key
+    async fn open(&self, body: &[u8]) -> Result<Vec<u8>, StatusCode> {
+        let envelope = Envelope::parse(body).map_err(|_| StatusCode::BAD_REQUEST)?;
+        let key_id = envelope.key_id().unwrap_or(&self.key_id).to_string();
+        let seeds = SeedSource::Key(key_id).decoding(&self.keyring).await?;
+        seeds
+            .iter()
+            .find_map(|seed| envelope.open(seed).ok())
+            .ok_or(StatusCode::BAD_REQUEST)
+    }
+}
+
+/// Middleware giving handlers below it `codec`, and sealing the `GxJson` and `GxBytes` they return
+pub async fn envelopes(State(codec): State<GxCodec>, req: Request, next: Next) -> Response {
+    let response = CODEC.scope(codec.clone(), next.run(req)).await;
+    if response.extensions().get::<Unsealed>().is_none() {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let Ok(data) = to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    match codec.seal(&data).await {
+        Ok(sealed) => {
+            parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(sealed))
+        }
+        Err(status) => status.into_response(),
+    }
+}
+
+/// Marks a response body the `envelopes` layer has to seal
+#[derive(Clone, Copy)]
+struct Unsealed;
+
+/// Refuse to answer in the clear when the `envelopes` layer is missing
+fn unsealed(body: Vec<u8>) -> Response {
+    if CODEC.try_with(|_| ()).is_err() {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "No gxcore envelopes layer").into_response();
+    }
+    let mut response = body.into_response();
+    response.extensions_mut().insert(Unsealed);
+    response
+}
+
+/// Payload of the sealed request body
+async fn opened<S: Send + Sync>(req: Request, state: &S) -> Result<Vec<u8>, Response> {
+    let codec = CODEC
+        .try_with(GxCodec::clone)
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "No gxcore envelopes layer").into_response())?;
+    let body = Bytes::from_request(req, state).await.map_err(IntoResponse::into_response)?;
+    codec.open(&body).await.map_err(IntoResponse::into_response)
+}
+
+/// JSON carried in a gxcore envelope, opened when extracted and sealed when returned
+pub struct GxJson<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for GxJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let data = opened(req, state).await?;
+        let value = serde_json::from_slice(&data).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()).into_response())?;
+        Ok(GxJson(value))
+    }
+}
+
+impl<T: Serialize> IntoResponse for GxJson<T> {
+    fn into_response(self) -> Response {
+        match serde_json::to_vec(&self.0) {
+            Ok(body) => unsealed(body),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+/// Bytes carried in a gxcore envelope, opened when extracted and sealed when returned
+pub struct GxBytes(pub Vec<u8>);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequest<S> for GxBytes {
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        opened(req, state).await.map(GxBytes)
+    }
+}
+
+impl IntoResponse for GxBytes {
+    fn into_response(self) -> Response {
+        unsealed(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::post, Router};
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Serialize, Deserialize)]
+    struct Greeting {
+        name: String,
+    }
+
+    async fn greet(GxJson(greeting): GxJson<Greeting>) -> GxJson<Greeting> {
+        GxJson(Greeting {
+            name: format!("hello {}", greeting.name),
+        })
+    }
+
+    async fn send(router: Router, body: Vec<u8>) -> Response {
+        router.oneshot(Request::post("/greet").body(Body::from(body)).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_envelopes_opened_and_sealed() {
+        let keyring = Arc::new(Keyring::in_memory());
+        keyring.add("svc", Some(b"old".to_vec())).unwrap();
+        keyring.rotate("svc").unwrap();
+        let router = Router::new().route("/greet", post(greet));
+        let sealed = router.clone().layer(middleware::from_fn_with_state(GxCodec::new(keyring.clone(), "svc"), envelopes));
+
+        // Sealed with a previous version of the key
+        let request = Envelope::seal(br#"{"name":"edge"}"#, b"old", CompressionAlgorithm::None).with_key_id("svc");
+        let response = send(sealed.clone(), request.to_bytes().unwrap()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], CONTENT_TYPE);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let envelope = Envelope::parse(&body).unwrap();
+        assert_eq!(envelope.key_id(), Some("svc"));
+        let seed = keyring.encoding_seed("svc").unwrap();
+        let greeting: Greeting = serde_json::from_slice(&envelope.open(&seed).unwrap()).unwrap();
+        assert_eq!(greeting.name, "hello edge");
+
+        let wrong = Envelope::seal(br#"{"name":"edge"}"#, b"other", CompressionAlgorithm::None).with_key_id("svc");
+        assert_eq!(send(sealed, wrong.to_bytes().unwrap()).await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(send(router, request.to_bytes().unwrap()).await.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
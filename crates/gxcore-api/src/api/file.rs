@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Multipart,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use base64::{Engine as _, engine::general_purpose};
+use utoipa::ToSchema;
+use gxcore_core::encode;
+
+use super::keys::SeedSource;
+use super::{offload, DefaultCompression};
+use crate::keyring::Keyring;
+
+/// Keep only characters that are safe inside a quoted `Content-Disposition` filename
+fn sanitize_filename(name: &str) -> String {
+    let name: String = name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or("")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        .collect();
+    if name.is_empty() { "file".to_string() } else { name }
+}
+
+/// Multipart form accepted by `/encode/file`, documented for the OpenAPI spec
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub(super) struct FileUpload {
+    #[schema(content_media_type = "application/octet-stream")]
+    file: Vec<u8>,
+    /// Base64 seed, unless `key_id` is set
+    seed: Option<String>,
+    /// Server-held key used instead of a seed
+    key_id: Option<String>,
+    /// Compression name, the server default when absent
+    compression: Option<String>,
+}
+
+/// Encode an uploaded `file` field with the `seed` (base64) and optional `compression` fields
+#[utoipa::path(
+    post,
+    path = "/v1/encode/file",
+    tag = "raw",
+    request_body(content = inline(FileUpload), content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Encoded file as a `.gx` attachment", content_type = "application/octet-stream", body = Vec<u8>),
+        (status = 400),
+    )
+)]
+pub(super) async fn encode_file_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    mut multipart: Multipart,
+) -> Result<Response, StatusCode> {
+    let mut file = None;
+    let mut seed = None;
+    let mut key_id = None;
+    let mut compression = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(|_| StatusCode::BAD_REQUEST)? {
+        match field.name() {
+            Some("file") => {
+                let name = sanitize_filename(field.file_name().unwrap_or("file"));
+                let bytes = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                file = Some((name, bytes));
+            }
+            Some("seed") => {
+                let text = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                let bytes = general_purpose::STANDARD
+                    .decode(text.trim())
+                    .map_err(|_| StatusCode::BAD_REQUEST)?;
+                seed = Some(bytes);
+            }
+            Some("key_id") => {
+                let text = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                key_id = Some(text.trim().to_string());
+            }
+            Some("compression") => {
+                let text = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                compression = Some(text.trim().to_string());
+            }
+            _ => {}
+        }
+    }
+
+    let (name, data) = file.ok_or(StatusCode::BAD_REQUEST)?;
+    let seed = SeedSource::from_parts(seed, key_id)?.encoding(&keyring).await?;
+    let compression = default.resolve(compression.as_deref())?;
+    let encoded = offload(move || encode(&data, &seed, compression)).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{name}.gx\"")),
+        ],
+        encoded,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use gxcore_core::CompressionAlgorithm;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_filename("report \"q3\".pdf"), "reportq3.pdf");
+        assert_eq!(sanitize_filename(""), "file");
+    }
+
+    #[tokio::test]
+    async fn test_encode_file() {
+        let body = "--XBOUNDARY\r\n\
+            Content-Disposition: form-data; name=\"file\"; filename=\"notes.txt\"\r\n\
+            Content-Type: text/plain\r\n\r\n\
+            file contents\r\n\
+            --XBOUNDARY\r\n\
+            Content-Disposition: form-data; name=\"seed\"\r\n\r\n\
+            c2VlZA==\r\n\
+            --XBOUNDARY--\r\n";
+        let request = Request::post("/encode/file")
+            .header(header::CONTENT_TYPE, "multipart/form-data; boundary=XBOUNDARY")
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = create_router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"notes.txt.gx\""
+        );
+        let encoded = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let decoded = gxcore_core::decode(&encoded, b"seed", CompressionAlgorithm::None).unwrap();
+        assert_eq!(decoded, b"file contents");
+    }
+}
@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use async_graphql::{Context, EmptySubscription, Error, Json as GqlJson, Object, Result, Schema};
+use axum::{Extension, Json};
+use base64::{Engine as _, engine::general_purpose};
+use gxcore_core::cache::TranslatorCache;
+use gxcore_core::inspect::inspect;
+
+use super::auth::Principal;
+use super::capabilities::Capabilities;
+use super::keys::{decode_any, SeedSource};
+use super::{metrics, offload, DefaultCompression, Scope};
+use crate::keyring::Keyring;
+
+pub(super) type GxSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+/// Schema with federation enabled, so a gateway can compose it with other services
+pub(super) fn schema() -> GxSchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription).enable_federation().finish()
+}
+
+/// Refuse resolvers the caller's token does not grant; every resolver is allowed without auth
+fn require(ctx: &Context<'_>, scope: Scope) -> Result<()> {
+    match ctx.data_opt::<Principal>() {
+        Some(principal) if !principal.has_scope(scope) => Err(Error::new("Forbidden")),
+        _ => Ok(()),
+    }
+}
+
+fn base64_arg(name: &str, value: &str) -> Result<Vec<u8>> {
+    general_purpose::STANDARD
+        .decode(value)
+        .map_err(|_| Error::new(format!("{name} must be base64")))
+}
+
+/// Seed from the `seed` or `keyId` argument, whichever is given
+fn seed_source(seed: Option<String>, key_id: Option<String>) -> Result<SeedSource> {
+    let seed = seed.map(|seed| base64_arg("seed", &seed)).transpose()?;
+    SeedSource::from_parts(seed, key_id).map_err(|_| Error::new("Exactly one of seed and keyId is required"))
+}
+
+fn compression(ctx: &Context<'_>, name: Option<String>) -> Result<gxcore_core::CompressionAlgorithm> {
+    ctx.data_unchecked::<DefaultCompression>()
+        .resolve(name.as_deref())
+        .map_err(|_| Error::new("Unknown compression"))
+}
+
+pub(super) struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// What this server supports, as served by `/v1/capabilities`
+    async fn capabilities(&self, ctx: &Context<'_>) -> Result<GqlJson<serde_json::Value>> {
+        let capabilities = ctx.data_unchecked::<Arc<Capabilities>>();
+        Ok(GqlJson(serde_json::to_value(&**capabilities)?))
+    }
+
+    /// Envelope header, metadata and sizes of a base64 blob, readable without its seed
+    async fn inspect(&self, ctx: &Context<'_>, blob: String) -> Result<GqlJson<serde_json::Value>> {
+        require(ctx, Scope::Verify)?;
+        let blob = base64_arg("blob", &blob)?;
+        Ok(GqlJson(serde_json::to_value(inspect(&blob))?))
+    }
+}
+
+pub(super) struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Encode base64 `data`, returning the encoded bytes as base64
+    async fn encode(
+        &self,
+        ctx: &Context<'_>,
+        data: String,
+        seed: Option<String>,
+        key_id: Option<String>,
+        compression: Option<String>,
+    ) -> Result<String> {
+        require(ctx, Scope::Encode)?;
+        let compression = self::compression(ctx, compression)?;
+        let data = base64_arg("data", &data)?;
+        let keyring = ctx.data_unchecked::<Arc<Keyring>>();
+        let seed = seed_source(seed, key_id)?
+            .encoding(keyring)
+            .await
+            .map_err(|status| Error::new(status.to_string()))?;
+        let data_len = data.len();
+        let translators = ctx.data_unchecked::<Arc<TranslatorCache>>().clone();
+        let encoded = offload(move || translators.encode(&data, &seed, compression))
+            .await
+            .map_err(|status| Error::new(status.to_string()))?;
+        metrics::record_compression(compression, data_len, encoded.len());
+        Ok(general_purpose::STANDARD.encode(encoded))
+    }
+
+    /// Decode base64 `encoded`, returning the original bytes as base64
+    async fn decode(
+        &self,
+        ctx: &Context<'_>,
+        encoded: String,
+        seed: Option<String>,
+        key_id: Option<String>,
+        compression: Option<String>,
+    ) -> Result<String> {
+        require(ctx, Scope::Decode)?;
+        let compression = self::compression(ctx, compression)?;
+        let encoded = base64_arg("encoded", &encoded)?;
+        let keyring = ctx.data_unchecked::<Arc<Keyring>>();
+        let seeds = seed_source(seed, key_id)?
+            .decoding(keyring)
+            .await
+            .map_err(|status| Error::new(status.to_string()))?;
+        let translators = ctx.data_unchecked::<Arc<TranslatorCache>>().clone();
+        let decoded = offload(move || decode_any(&translators, &encoded, &seeds, compression))
+            .await
+            .map_err(|status| Error::new(status.to_string()))?;
+        match decoded {
+            Ok(decoded) => Ok(general_purpose::STANDARD.encode(decoded)),
+            Err(e) => {
+                metrics::record_error(e);
+                Err(Error::new(e))
+            }
+        }
+    }
+}
+
+/// GraphQL endpoint over the caller's keyring, so tenants resolve their own keys
+pub(super) async fn graphql_handler(
+    Extension(schema): Extension<GxSchema>,
+    Extension(capabilities): Extension<Arc<Capabilities>>,
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(translators): Extension<Arc<TranslatorCache>>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let mut request = request.data(capabilities).data(default).data(keyring).data(translators);
+    if let Some(Extension(principal)) = principal {
+        request = request.data(principal);
+    }
+    Json(schema.execute(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::create_router;
+    use axum::{
+        body::{to_bytes, Body},
+        http::{header, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    async fn execute(query: &str) -> serde_json::Value {
+        let request = Request::post("/graphql")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::json!({ "query": query }).to_string()))
+            .unwrap();
+        let response = create_router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_encode_decode_roundtrip() {
+        // "hello" and "seed" in base64
+        let encoded = execute(r#"mutation { encode(data: "aGVsbG8=", seed: "c2VlZA==", compression: "lz4") }"#).await;
+        let encoded = encoded["data"]["encode"].as_str().unwrap();
+        let query = format!(r#"mutation {{ decode(encoded: "{encoded}", seed: "c2VlZA==", compression: "lz4") }}"#);
+        assert_eq!(execute(&query).await["data"]["decode"], "aGVsbG8=");
+
+        let inspected = execute(&format!(r#"{{ inspect(blob: "{encoded}") capabilities }}"#)).await;
+        assert_eq!(inspected["data"]["inspect"]["kind"], "bare");
+        assert!(inspected["data"]["capabilities"]["features"].as_array().unwrap().contains(&"graphql".into()));
+        assert!(execute("{ _service { sdl } }").await["data"]["_service"]["sdl"].as_str().unwrap().contains("inspect"));
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+use utoipa::ToSchema;
+use gxcore_core::{decode, encode, CompressionAlgorithm};
+
+use crate::keyring::Keyring;
+
+/// What readiness depends on
+#[derive(Clone)]
+pub(super) struct ProbeState {
+    pub auth_enabled: bool,
+    pub keyring: Arc<Keyring>,
+    pub shutdown: CancellationToken,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct Health {
+    status: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct Readiness {
+    status: &'static str,
+    checks: BTreeMap<&'static str, Check>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct Check {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<&'static str>,
+}
+
+impl Check {
+    fn pass(detail: Option<&'static str>) -> Self {
+        Check { ok: true, detail }
+    }
+
+    fn fail(detail: &'static str) -> Self {
+        Check {
+            ok: false,
+            detail: Some(detail),
+        }
+    }
+}
+
+/// Liveness: the process is up and serving requests
+#[utoipa::path(get, path = "/healthz", tag = "health", responses((status = 200, body = Health)))]
+pub(super) async fn healthz_handler() -> Json<Health> {
+    Json(Health {
+        status: "ok",
+        version: env!("CARGO_PKG_VERSION"),
+    })
+}
+
+/// Codec roundtrip through every compression the API accepts
+fn codec_check() -> Check {
+    let sample = b"gxcore readiness probe";
+    let ok = [CompressionAlgorithm::None, CompressionAlgorithm::Lz4, CompressionAlgorithm::Brotli]
+        .into_iter()
+        .all(|compression| {
+            decode(&encode(sample, b"probe", compression), b"probe", compression).is_ok_and(|d| d == sample)
+        });
+    if ok { Check::pass(None) } else { Check::fail("codec roundtrip failed") }
+}
+
+/// Readiness: configuration loaded and dependencies usable, `503` otherwise
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    responses((status = 200, body = Readiness), (status = 503, body = Readiness))
+)]
+pub(super) async fn readyz_handler(State(state): State<ProbeState>) -> (StatusCode, Json<Readiness>) {
+    let mut checks = BTreeMap::new();
+    // Failing readiness first takes the pod out of load balancing while requests drain
+    let draining = state.shutdown.is_cancelled();
+    checks.insert("shutdown", if draining { Check::fail("draining") } else { Check::pass(None) });
+    // The router only exists once its configuration was loaded and validated
+    checks.insert("config", Check::pass(None));
+    checks.insert("auth", Check::pass(Some(if state.auth_enabled { "enabled" } else { "disabled" })));
+    checks.insert("codec", codec_check());
+    let keys = if state.keyring.is_empty() { "empty" } else { "loaded" };
+    checks.insert("keyring", Check::pass(Some(keys)));
+
+    let ready = checks.values().all(|check| check.ok);
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let body = Readiness {
+        status: if ready { "ready" } else { "not_ready" },
+        checks,
+    };
+    (status, Json(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::create_router;
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    async fn get(path: &str) -> (StatusCode, serde_json::Value) {
+        let response = create_router()
+            .oneshot(Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_probes() {
+        let (status, body) = get("/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+
+        let (status, body) = get("/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ready");
+        assert_eq!(body["checks"]["codec"]["ok"], true);
+        assert_eq!(body["checks"]["auth"]["detail"], "disabled");
+    }
+}
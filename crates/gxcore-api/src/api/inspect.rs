@@ -0,0 +1,14 @@
+use axum::{body::Bytes, Json};
+use gxcore_core::inspect::{inspect, Inspection};
+
+/// Describe an envelope or encoded payload without the seed, for triaging failed decodes
+#[utoipa::path(
+    post,
+    path = "/v1/inspect",
+    tag = "codec",
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses((status = 200, body = Inspection))
+)]
+pub(super) async fn inspect_handler(body: Bytes) -> Json<Inspection> {
+    Json(inspect(&body))
+}
@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use axum::{http::StatusCode, Extension};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+#[cfg(feature = "ipfs")]
+use crate::ipfs::Ipfs;
+use gxcore_core::cache::TranslatorCache;
+
+use super::keys::{decode_any, SeedSource};
+use super::{metrics, offload, ApiConfig, Binary, DecodeResponse, DefaultCompression, Negotiated, TextEncoding};
+use crate::keyring::Keyring;
+
+/// Where `/encode` publishes its result besides the response
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(super) struct EncodeParams {
+    /// `ipfs` to pin the encoded bytes and return their CID
+    store: Option<String>,
+}
+
+impl EncodeParams {
+    /// Whether the result goes to IPFS, `400 Bad Request` for unknown stores
+    pub fn ipfs(&self) -> Result<bool, StatusCode> {
+        match self.store.as_deref() {
+            None => Ok(false),
+            Some("ipfs") => Ok(true),
+            Some(_) => Err(StatusCode::BAD_REQUEST),
+        }
+    }
+}
+
+/// IPFS node, present when built with the `ipfs` feature and configured
+#[derive(Clone, Default)]
+pub(super) struct IpfsNode {
+    #[cfg(feature = "ipfs")]
+    client: Option<Arc<Ipfs>>,
+}
+
+impl IpfsNode {
+    pub fn new(config: &ApiConfig) -> Self {
+        #[cfg(not(feature = "ipfs"))]
+        let _ = config;
+        IpfsNode {
+            #[cfg(feature = "ipfs")]
+            client: config.ipfs.clone(),
+        }
+    }
+
+    /// Pin `data`, returning its CID
+    pub async fn add(&self, data: Vec<u8>) -> Result<String, StatusCode> {
+        #[cfg(feature = "ipfs")]
+        if let Some(client) = &self.client {
+            return client.add(data).await.map_err(|_| StatusCode::BAD_GATEWAY);
+        }
+        let _ = data;
+        Err(StatusCode::NOT_IMPLEMENTED)
+    }
+
+    async fn cat(&self, cid: &str) -> Result<Vec<u8>, StatusCode> {
+        #[cfg(feature = "ipfs")]
+        if let Some(client) = &self.client {
+            if !crate::ipfs::valid_cid(cid) {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            return client.cat(cid).await.map_err(|_| StatusCode::BAD_GATEWAY);
+        }
+        let _ = cid;
+        Err(StatusCode::NOT_IMPLEMENTED)
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(super) struct DecodeCidRequest {
+    /// Blob pinned by `/encode?store=ipfs`
+    cid: String,
+    /// Seed sent by the client, unless `key_id` names a server-held key
+    seed: Option<Binary>,
+    key_id: Option<String>,
+    /// `none`, `lz4`, `lz4frame` or `brotli`; the server default when omitted
+    #[serde(default)]
+    compression: Option<String>,
+    #[serde(default)]
+    encoding: TextEncoding,
+}
+
+/// Fetch an encoded blob from IPFS and decode it
+#[utoipa::path(
+    post,
+    path = "/v1/decode/cid",
+    tag = "codec",
+    request_body(content((DecodeCidRequest = "application/json"), (DecodeCidRequest = "application/msgpack"))),
+    responses(
+        (status = 200, body = DecodeResponse),
+        (status = 400, description = "Invalid CID, input or wrong seed"),
+        (status = 404, description = "Unknown key"),
+        (status = 501, description = "Built without IPFS support or no node configured"),
+        (status = 502, description = "IPFS node failed or the blob is too large"),
+    )
+)]
+pub(super) async fn decode_cid_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(ipfs): Extension<IpfsNode>,
+    Extension(translators): Extension<Arc<TranslatorCache>>,
+    Negotiated(format, payload): Negotiated<DecodeCidRequest>,
+) -> Result<Negotiated<DecodeResponse>, StatusCode> {
+    let compression = default.resolve(payload.compression.as_deref())?;
+    let seed = payload.seed.map(|seed| payload.encoding.decode(seed)).transpose()?;
+    let seeds = SeedSource::from_parts(seed, payload.key_id)?.decoding(&keyring).await?;
+
+    let encoded = ipfs.cat(&payload.cid).await?;
+    match offload(move || decode_any(&translators, &encoded, &seeds, compression)).await? {
+        Ok(decoded) => Ok(Negotiated(format, DecodeResponse { decoded: payload.encoding.encode(decoded) })),
+        Err(e) => {
+            metrics::record_error(e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use axum::{body::Body, http::Request, Router};
+    use tower::ServiceExt;
+
+    async fn status(router: &Router, path: &str, body: serde_json::Value) -> StatusCode {
+        let request = Request::post(path)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_unconfigured_node() {
+        let router = create_router();
+        let encode = serde_json::json!({"data": [1], "seed": [1]});
+        assert_eq!(status(&router, "/v1/encode?store=ipfs", encode.clone()).await, StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(status(&router, "/v1/encode?store=tape", encode).await, StatusCode::BAD_REQUEST);
+        let decode = serde_json::json!({"cid": "bafkreid", "seed": [1]});
+        assert_eq!(status(&router, "/v1/decode/cid", decode).await, StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[cfg(feature = "ipfs")]
+    #[tokio::test]
+    async fn test_pin_and_decode_by_cid() {
+        use crate::api::create_router_with;
+        use crate::ipfs::IpfsConfig;
+        use axum::{body::{to_bytes, Bytes}, extract::{Multipart, Query, State}, routing::post, Json};
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+
+        // Kubo RPC stand-in keeping added blobs under fake CIDs
+        type Blobs = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+        async fn add(State(blobs): State<Blobs>, mut form: Multipart) -> Json<serde_json::Value> {
+            let data = form.next_field().await.unwrap().unwrap().bytes().await.unwrap();
+            let cid = format!("bafkrei{}", blobs.lock().unwrap().len());
+            blobs.lock().unwrap().insert(cid.clone(), data.to_vec());
+            Json(serde_json::json!({"Name": cid, "Hash": cid, "Size": data.len().to_string()}))
+        }
+        async fn cat(State(blobs): State<Blobs>, Query(query): Query<HashMap<String, String>>) -> Bytes {
+            Bytes::from(blobs.lock().unwrap()[&query["arg"]].clone())
+        }
+        let node = Router::new()
+            .route("/api/v0/add", post(add))
+            .route("/api/v0/cat", post(cat))
+            .with_state(Blobs::default());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, node).await });
+
+        let router = create_router_with(ApiConfig {
+            ipfs: Some(Arc::new(Ipfs::new(IpfsConfig { api_url, ..Default::default() }))),
+            ..Default::default()
+        });
+        let request = Request::post("/v1/encode?store=ipfs")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"data":[7,8,9],"seed":[1],"compression":"lz4"}"#))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let cid = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["cid"].clone();
+        assert_eq!(cid, "bafkrei0");
+
+        let request = Request::post("/v1/decode/cid")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::json!({"cid": cid, "seed": [1], "compression": "lz4"}).to_string()))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap()["decoded"], serde_json::json!([7, 8, 9]));
+    }
+}
@@ -0,0 +1,776 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use utoipa::{IntoParams, ToSchema};
+use gxcore_core::cache::TranslatorCache;
+
+use super::keys::decode_any;
+use super::raw::{raw_options, RawHeaders, RawParams};
+use super::{metrics, DefaultCompression};
+use crate::keyring::{generate_seed, Keyring};
+
+/// Header naming a URL the result is POSTed to once the job completes
+pub const SINK_HEADER: &str = "x-gx-sink";
+/// Header naming a URL notified with the job's status once it finishes
+pub const CALLBACK_HEADER: &str = "x-gx-callback";
+/// Header of callback notifications: `sha256=` and the hex HMAC of the body under the webhook secret
+pub const SIGNATURE_HEADER: &str = "x-gx-signature";
+
+/// Jobs kept before the oldest finished ones are dropped with their results. When none has
+/// finished, new jobs are refused rather than let the unfinished ones grow without bound.
+const MAX_JOBS: usize = 64;
+/// Seconds a client refused for a full queue is told to wait
+const BUSY_RETRY_AFTER: u64 = 5;
+
+/// Signing of job callbacks, and where sinks and callbacks may point
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// HMAC-SHA256 key shared with receivers; callbacks are refused when absent
+    pub secret: Option<String>,
+    /// Deliveries tried before giving up, doubling the delay from one second
+    pub attempts: u32,
+    /// Hosts (`hooks.example.com`) or URL prefixes (`https://hooks.example.com/gx/`) sinks and
+    /// callbacks may name; both are refused when empty
+    pub allowed_targets: Vec<String>,
+    /// Let listed targets be loopback, private or link-local addresses, which are refused
+    /// otherwise even when a listed host resolves to one
+    pub allow_private_targets: bool,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            secret: None,
+            attempts: 3,
+            allowed_targets: Vec::new(),
+            allow_private_targets: false,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Whether a sink or callback may be delivered to `url`
+    fn permits(&self, url: &Url) -> bool {
+        let listed = self.allowed_targets.iter().any(|target| match Url::parse(target) {
+            Ok(prefix) => {
+                prefix.scheme() == url.scheme()
+                    && prefix.host_str() == url.host_str()
+                    && prefix.port_or_known_default() == url.port_or_known_default()
+                    && url.path().starts_with(prefix.path())
+            }
+            Err(_) => url.host_str().is_some_and(|host| host.eq_ignore_ascii_case(target)),
+        });
+        // Names are checked once resolved, by `PublicResolver`
+        let private = url
+            .host_str()
+            .and_then(|host| host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().ok())
+            .is_some_and(|ip| !is_public(ip));
+        listed && (self.allow_private_targets || !private)
+    }
+}
+
+/// Whether `ip` is reachable from the internet rather than loopback, private, link-local, shared
+/// or unspecified, where deliveries could reach the server's own network or cloud metadata
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || (a == 100 && b & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback() || ip.is_unspecified() || first & 0xfe00 == 0xfc00 || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Resolver of sink and callback hosts keeping only public addresses, so a listed name pointed
+/// at an internal address later is not followed there
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Lifecycle of a background job
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum JobStatus {
+    /// Waiting for a worker
+    Queued,
+    Running,
+    Completed,
+    /// Stopped because the server started draining
+    Cancelled,
+    Failed,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum JobOperation {
+    Encode,
+    Decode,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub(super) struct JobInfo {
+    id: String,
+    operation: JobOperation,
+    status: JobStatus,
+    input_size: usize,
+    output_size: Option<usize>,
+    /// Why the job failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sink: Option<String>,
+    /// Why delivering to the sink failed; the result can still be fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sink_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callback: Option<String>,
+    /// Why notifying the callback failed after every attempt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    callback_error: Option<String>,
+    created_at: u64,
+    finished_at: Option<u64>,
+}
+
+/// Body POSTed to the callback URL
+#[derive(Serialize)]
+struct JobNotification<'a> {
+    #[serde(flatten)]
+    job: &'a JobInfo,
+    /// Path of the output, set once the job completed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<String>,
+}
+
+/// Where a finished job's output and status are pushed
+#[derive(Default)]
+struct Delivery {
+    sink: Option<String>,
+    callback: Option<String>,
+}
+
+struct Job {
+    order: u64,
+    info: JobInfo,
+    result: Option<Bytes>,
+}
+
+/// Encode and decode jobs, run on the blocking pool a few at a time
+pub(super) struct JobQueue {
+    jobs: Mutex<HashMap<String, Job>>,
+    submitted: AtomicU64,
+    workers: Semaphore,
+    client: reqwest::Client,
+    webhooks: WebhookConfig,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Client for deliveries, which never follows redirects as those could lead past `permits`
+fn client(webhooks: &WebhookConfig) -> reqwest::Client {
+    let builder = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+    let builder = match webhooks.allow_private_targets {
+        true => builder,
+        false => builder.dns_resolver(Arc::new(PublicResolver)),
+    };
+    builder.build().expect("HTTP client builds")
+}
+
+impl JobQueue {
+    pub fn new(webhooks: &WebhookConfig) -> Self {
+        let workers = std::thread::available_parallelism().map_or(2, |n| n.get());
+        JobQueue {
+            jobs: Mutex::new(HashMap::new()),
+            submitted: AtomicU64::new(0),
+            workers: Semaphore::new(workers),
+            client: client(webhooks),
+            webhooks: webhooks.clone(),
+        }
+    }
+
+    /// Record a new queued job, `None` when every slot holds an unfinished one
+    fn submit(&self, operation: JobOperation, input_size: usize, delivery: Delivery) -> Option<JobInfo> {
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs.len() >= MAX_JOBS {
+            let finished = jobs
+                .iter()
+                .filter(|(_, job)| !matches!(job.info.status, JobStatus::Queued | JobStatus::Running))
+                .min_by_key(|(_, job)| job.order)
+                .map(|(id, _)| id.clone())?;
+            jobs.remove(&finished);
+        }
+        let info = JobInfo {
+            id: hex::encode(&generate_seed()[..8]),
+            operation,
+            status: JobStatus::Queued,
+            input_size,
+            output_size: None,
+            error: None,
+            sink: delivery.sink,
+            sink_error: None,
+            callback: delivery.callback,
+            callback_error: None,
+            created_at: now(),
+            finished_at: None,
+        };
+        let job = Job {
+            order: self.submitted.fetch_add(1, Ordering::Relaxed),
+            info: info.clone(),
+            result: None,
+        };
+        jobs.insert(info.id.clone(), job);
+        Some(info)
+    }
+
+    fn update(&self, id: &str, change: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(id) {
+            change(job);
+        }
+    }
+
+    /// Wait for a worker, run the codec, then deliver the result to the sink and notify the callback
+    async fn run(self: Arc<Self>, id: String, work: impl FnOnce() -> Result<Vec<u8>, &'static str> + Send + 'static) {
+        let Ok(_permit) = self.workers.acquire().await else {
+            return;
+        };
+        self.update(&id, |job| job.info.status = JobStatus::Running);
+
+        let outcome = match tokio::task::spawn_blocking(work).await {
+            Ok(outcome) => outcome.map_err(str::to_string),
+            Err(_) => Err("Job panicked".to_string()),
+        };
+        let mut sink = None;
+        let mut finished = None;
+        self.update(&id, |job| {
+            job.info.finished_at = Some(now());
+            match outcome {
+                Ok(output) => {
+                    job.info.status = JobStatus::Completed;
+                    job.info.output_size = Some(output.len());
+                    let output = Bytes::from(output);
+                    sink = job.info.sink.clone().map(|url| (url, output.clone()));
+                    job.result = Some(output);
+                }
+                Err(e) => {
+                    job.info.status = JobStatus::Failed;
+                    job.info.error = Some(e);
+                }
+            }
+            finished = Some(job.info.clone());
+        });
+
+        if let Some((url, output)) = sink {
+            let delivered = self
+                .client
+                .post(url)
+                .header(header::CONTENT_TYPE, "application/octet-stream")
+                .header("x-gx-job-id", &id)
+                .body(output)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+            if let Err(e) = delivered {
+                self.update(&id, |job| job.info.sink_error = Some(e.to_string()));
+            }
+        }
+        if let Some(info) = finished
+            && info.callback.is_some()
+        {
+            self.notify(info).await;
+        }
+    }
+
+    /// POST the signed status to the callback, retrying with backoff
+    async fn notify(&self, info: JobInfo) {
+        let (Some(url), Some(secret)) = (&info.callback, &self.webhooks.secret) else {
+            return;
+        };
+        let notification = JobNotification {
+            job: &info,
+            result: (info.status == JobStatus::Completed).then(|| format!("/v1/jobs/{}/result", info.id)),
+        };
+        let body = serde_json::to_vec(&notification).expect("Notification serializes to JSON");
+        let signature = sign(secret, &body);
+
+        let mut delay = Duration::from_secs(1);
+        let mut error = None;
+        for attempt in 1..=self.webhooks.attempts.max(1) {
+            if attempt > 1 {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            let delivered = self
+                .client
+                .post(url)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .body(body.clone())
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+            match delivered {
+                Ok(_) => return,
+                Err(e) => error = Some(e.to_string()),
+            }
+        }
+        self.update(&info.id, |job| job.info.callback_error = error);
+    }
+
+    /// Mark unfinished jobs cancelled once the server drains
+    fn cancel_pending(&self) {
+        for job in self.jobs.lock().unwrap().values_mut() {
+            if matches!(job.info.status, JobStatus::Queued | JobStatus::Running) {
+                job.info.status = JobStatus::Cancelled;
+            }
+        }
+    }
+}
+
+/// URL from the header `name`, which must be http or https and a target `webhooks` permits
+fn url_header(headers: &HeaderMap, name: &str, webhooks: &WebhookConfig) -> Result<Option<String>, StatusCode> {
+    let Some(value) = headers.get(name) else {
+        return Ok(None);
+    };
+    let url = value.to_str().map_err(|_| StatusCode::BAD_REQUEST)?;
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => match webhooks.permits(&parsed) {
+            true => Ok(Some(url.to_string())),
+            false => Err(StatusCode::FORBIDDEN),
+        },
+        _ => Err(StatusCode::BAD_REQUEST),
+    }
+}
+
+/// Sink and callback from the request; callbacks need a webhook secret to be signed with
+fn delivery(headers: &HeaderMap, queue: &JobQueue) -> Result<Delivery, StatusCode> {
+    let callback = url_header(headers, CALLBACK_HEADER, &queue.webhooks)?;
+    if callback.is_some() && queue.webhooks.secret.is_none() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(Delivery {
+        sink: url_header(headers, SINK_HEADER, &queue.webhooks)?,
+        callback,
+    })
+}
+
+/// Delivery headers, documented for the OpenAPI spec
+#[derive(IntoParams)]
+#[into_params(parameter_in = Header)]
+#[allow(dead_code)]
+pub(super) struct DeliveryHeaders {
+    /// URL receiving the result as `application/octet-stream` once the job completes, which
+    /// must be in `webhooks.allowed_targets`
+    #[param(rename = "x-gx-sink")]
+    sink: Option<String>,
+    /// URL receiving the job status and result path as JSON once the job finishes,
+    /// signed in `x-gx-signature`
+    #[param(rename = "x-gx-callback")]
+    callback: Option<String>,
+}
+
+fn busy() -> Response {
+    (StatusCode::SERVICE_UNAVAILABLE, [(header::RETRY_AFTER, BUSY_RETRY_AFTER.to_string())], "Job queue full").into_response()
+}
+
+fn accepted(info: JobInfo) -> Response {
+    let location = format!("/v1/jobs/{}", info.id);
+    (StatusCode::ACCEPTED, [(header::LOCATION, location)], Json(info)).into_response()
+}
+
+fn spawn(
+    queue: Arc<JobQueue>,
+    shutdown: CancellationToken,
+    info: &JobInfo,
+    work: impl FnOnce() -> Result<Vec<u8>, &'static str> + Send + 'static,
+) {
+    let id = info.id.clone();
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = queue.clone().run(id, work) => {}
+            _ = shutdown.cancelled() => queue.cancel_pending(),
+        }
+    });
+}
+
+/// Queue an encode of the raw body; poll the returned `Location` for status
+#[utoipa::path(
+    post,
+    path = "/v1/jobs/encode",
+    tag = "jobs",
+    params(RawParams, RawHeaders, DeliveryHeaders),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 202, body = JobInfo, headers(("location" = String))),
+        (status = 400, description = "Invalid option, or a callback without a webhook secret configured"),
+        (status = 403, description = "Sink or callback not in `webhooks.allowed_targets`, or a private address"),
+        (status = 503, description = "Shutting down, or every job slot holds an unfinished job", headers(("retry-after" = u64))),
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn submit_encode_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(queue): Extension<Arc<JobQueue>>,
+    Extension(shutdown): Extension<CancellationToken>,
+    Extension(translators): Extension<Arc<TranslatorCache>>,
+    Query(params): Query<RawParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, StatusCode> {
+    if shutdown.is_cancelled() {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    let (seed, compression) = raw_options(&headers, params, default)?;
+    let seed = seed.encoding(&keyring).await?;
+    let Some(info) = queue.submit(JobOperation::Encode, body.len(), delivery(&headers, &queue)?) else {
+        return Ok(busy());
+    };
+    spawn(queue, shutdown, &info, move || {
+        let encoded = translators.encode(&body, &seed, compression);
+        metrics::record_compression(compression, body.len(), encoded.len());
+        Ok(encoded)
+    });
+    Ok(accepted(info))
+}
+
+/// Queue a decode of the raw body; poll the returned `Location` for status
+#[utoipa::path(
+    post,
+    path = "/v1/jobs/decode",
+    tag = "jobs",
+    params(RawParams, RawHeaders, DeliveryHeaders),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 202, body = JobInfo, headers(("location" = String))),
+        (status = 400, description = "Invalid option, or a callback without a webhook secret configured"),
+        (status = 403, description = "Sink or callback not in `webhooks.allowed_targets`, or a private address"),
+        (status = 503, description = "Shutting down, or every job slot holds an unfinished job", headers(("retry-after" = u64))),
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn submit_decode_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(queue): Extension<Arc<JobQueue>>,
+    Extension(shutdown): Extension<CancellationToken>,
+    Extension(translators): Extension<Arc<TranslatorCache>>,
+    Query(params): Query<RawParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, StatusCode> {
+    if shutdown.is_cancelled() {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    let (seed, compression) = raw_options(&headers, params, default)?;
+    let seeds = seed.decoding(&keyring).await?;
+    let Some(info) = queue.submit(JobOperation::Decode, body.len(), delivery(&headers, &queue)?) else {
+        return Ok(busy());
+    };
+    spawn(queue, shutdown, &info, move || {
+        decode_any(&translators, &body, &seeds, compression).inspect_err(|e| metrics::record_error(e))
+    });
+    Ok(accepted(info))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path)),
+    responses((status = 200, body = JobInfo), (status = 404))
+)]
+pub(super) async fn job_status_handler(
+    Extension(queue): Extension<Arc<JobQueue>>,
+    Path(id): Path<String>,
+) -> Result<Json<JobInfo>, StatusCode> {
+    let jobs = queue.jobs.lock().unwrap();
+    jobs.get(&id).map(|job| Json(job.info.clone())).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Output of a completed job
+#[utoipa::path(
+    get,
+    path = "/v1/jobs/{id}/result",
+    tag = "jobs",
+    params(("id" = String, Path)),
+    responses(
+        (status = 200, content_type = "application/octet-stream", body = Vec<u8>),
+        (status = 404),
+        (status = 409, description = "Job has not completed"),
+    )
+)]
+pub(super) async fn job_result_handler(
+    Extension(queue): Extension<Arc<JobQueue>>,
+    Path(id): Path<String>,
+) -> Result<Response, StatusCode> {
+    let jobs = queue.jobs.lock().unwrap();
+    let job = jobs.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    let result = job.result.clone().ok_or(StatusCode::CONFLICT)?;
+    Ok(([(header::CONTENT_TYPE, "application/octet-stream")], result).into_response())
+}
+
+/// Forget a finished job and its result
+#[utoipa::path(
+    delete,
+    path = "/v1/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path)),
+    responses((status = 204), (status = 404), (status = 409, description = "Job is still running"))
+)]
+pub(super) async fn delete_job_handler(
+    Extension(queue): Extension<Arc<JobQueue>>,
+    Path(id): Path<String>,
+) -> StatusCode {
+    let mut jobs = queue.jobs.lock().unwrap();
+    match jobs.get(&id).map(|job| job.info.status) {
+        None => StatusCode::NOT_FOUND,
+        Some(JobStatus::Queued | JobStatus::Running) => StatusCode::CONFLICT,
+        Some(_) => {
+            jobs.remove(&id);
+            StatusCode::NO_CONTENT
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router, create_router_with, ApiConfig};
+    use gxcore_core::CompressionAlgorithm;
+    use crate::api::raw::SEED_HEADER;
+    use axum::{body::{to_bytes, Body}, http::Request, routing::post, Router};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tower::ServiceExt;
+
+    async fn send(router: &Router, request: Request<Body>) -> (StatusCode, Bytes) {
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        (status, to_bytes(response.into_body(), usize::MAX).await.unwrap())
+    }
+
+    /// Webhook settings letting deliveries reach the test receiver at `url`
+    fn local_target(url: &str) -> WebhookConfig {
+        WebhookConfig {
+            allowed_targets: vec![url.to_string()],
+            allow_private_targets: true,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_roundtrip_with_sink() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let sink = Router::new().route(
+            "/results",
+            post(|body: Bytes| async move {
+                tx.send(body).await.unwrap();
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let sink_url = format!("http://{}/results", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, sink).await });
+
+        let router = create_router_with(ApiConfig {
+            webhooks: local_target(&sink_url),
+            ..Default::default()
+        });
+        let request = Request::post("/v1/jobs/encode?compression=lz4")
+            .header(SEED_HEADER, "c2VlZA==")
+            .header(SINK_HEADER, &sink_url)
+            .body(Body::from("large payload"))
+            .unwrap();
+        let (status, body) = send(&router, request).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let id = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let pushed = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        let (status, result) = send(&router, Request::get(format!("/v1/jobs/{id}/result")).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(result, pushed);
+        assert_eq!(gxcore_core::decode(&result, b"seed", CompressionAlgorithm::Lz4).unwrap(), b"large payload");
+
+        let delete = Request::delete(format!("/v1/jobs/{id}")).body(Body::empty()).unwrap();
+        assert_eq!(send(&router, delete).await.0, StatusCode::NO_CONTENT);
+        let status = Request::get(format!("/v1/jobs/{id}")).body(Body::empty()).unwrap();
+        assert_eq!(send(&router, status).await.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_signed_callback() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let receiver = Router::new().route(
+            "/hooks",
+            post(|headers: HeaderMap, body: Bytes| async move {
+                tx.send((headers[SIGNATURE_HEADER].to_str().unwrap().to_string(), body)).await.unwrap();
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let callback_url = format!("http://{}/hooks", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, receiver).await });
+
+        let submit = |router: Router| {
+            let request = Request::post("/v1/jobs/encode")
+                .header(SEED_HEADER, "c2VlZA==")
+                .header(CALLBACK_HEADER, &callback_url)
+                .body(Body::from("batch"))
+                .unwrap();
+            async move { send(&router, request).await }
+        };
+        assert_eq!(submit(create_router()).await.0, StatusCode::FORBIDDEN);
+        let unsigned = create_router_with(ApiConfig {
+            webhooks: local_target(&callback_url),
+            ..Default::default()
+        });
+        assert_eq!(submit(unsigned).await.0, StatusCode::BAD_REQUEST);
+
+        let router = create_router_with(ApiConfig {
+            webhooks: WebhookConfig {
+                secret: Some("hook-secret".to_string()),
+                ..local_target(&callback_url)
+            },
+            ..Default::default()
+        });
+        let (status, body) = submit(router).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let id = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["id"].clone();
+
+        let (signature, body) = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(signature, sign("hook-secret", &body));
+        let notification: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(notification["id"], id);
+        assert_eq!(notification["status"], "completed");
+        assert_eq!(notification["result"], format!("/v1/jobs/{}/result", id.as_str().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_http_sink() {
+        let request = Request::post("/v1/jobs/encode")
+            .header(SEED_HEADER, "c2VlZA==")
+            .header(SINK_HEADER, "file:///etc/passwd")
+            .body(Body::from("data"))
+            .unwrap();
+        assert_eq!(send(&create_router(), request).await.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unlisted_and_private_targets() {
+        let router = create_router_with(ApiConfig {
+            webhooks: WebhookConfig {
+                allowed_targets: vec![
+                    "hooks.example.com".to_string(),
+                    "https://results.example.com/gx/".to_string(),
+                    "169.254.169.254".to_string(),
+                    "http://[::1]/".to_string(),
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let sink = |url: &str| {
+            Request::post("/v1/jobs/encode")
+                .header(SEED_HEADER, "c2VlZA==")
+                .header(SINK_HEADER, url)
+                .body(Body::from("data"))
+                .unwrap()
+        };
+        for url in [
+            "https://elsewhere.example.com/",
+            "https://results.example.com/other",
+            "http://results.example.com/gx/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://[::1]/",
+        ] {
+            assert_eq!(send(&router, sink(url)).await.0, StatusCode::FORBIDDEN, "{url}");
+        }
+        for url in ["https://HOOKS.example.com/anything", "https://results.example.com/gx/out"] {
+            assert_eq!(send(&router, sink(url)).await.0, StatusCode::ACCEPTED, "{url}");
+        }
+    }
+
+    #[test]
+    fn test_public_addresses() {
+        let private = [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0",
+            "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1",
+        ];
+        for ip in private {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["93.184.216.34", "2606:4700::1111", "::ffff:8.8.8.8"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_full_queue_refuses_until_a_job_finishes() {
+        let queue = JobQueue::new(&WebhookConfig::default());
+        let submit = || queue.submit(JobOperation::Encode, 1, Delivery::default());
+        let first = submit().unwrap();
+        for _ in 1..MAX_JOBS {
+            submit().unwrap();
+        }
+        assert!(submit().is_none());
+        assert_eq!(queue.jobs.lock().unwrap().len(), MAX_JOBS);
+
+        queue.update(&first.id, |job| job.info.status = JobStatus::Completed);
+        assert!(submit().is_some());
+        assert!(!queue.jobs.lock().unwrap().contains_key(&first.id));
+        assert_eq!(queue.jobs.lock().unwrap().len(), MAX_JOBS);
+    }
+}
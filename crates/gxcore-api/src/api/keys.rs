@@ -0,0 +1,265 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use gxcore_core::cache::TranslatorCache;
+use gxcore_core::CompressionAlgorithm;
+
+use super::audit::note_key;
+use crate::keyring::{KeyInfo, Keyring};
+
+/// Seed sent with the request, or held by the server under a key id
+pub(super) enum SeedSource {
+    Inline(Vec<u8>),
+    Key(String),
+}
+
+impl SeedSource {
+    /// Exactly one of `seed` and `key_id` must be given
+    pub fn from_parts(seed: Option<Vec<u8>>, key_id: Option<String>) -> Result<Self, StatusCode> {
+        match (seed, key_id) {
+            (Some(seed), None) => Ok(SeedSource::Inline(seed)),
+            (None, Some(id)) => Ok(SeedSource::Key(id)),
+            _ => Err(StatusCode::BAD_REQUEST),
+        }
+    }
+
+    /// Attribute the request to the key in the audit log
+    fn note(&self) {
+        if let SeedSource::Key(id) = self {
+            note_key(id);
+        }
+    }
+
+    pub async fn encoding(&self, keyring: &Keyring) -> Result<Vec<u8>, StatusCode> {
+        self.note();
+        match self {
+            SeedSource::Inline(seed) => Ok(seed.clone()),
+            SeedSource::Key(id) => match external_seed(keyring, id).await? {
+                Some(seed) => Ok(seed),
+                None => keyring.encoding_seed(id).map_err(key_status),
+            },
+        }
+    }
+
+    /// Candidate seeds, newest first for rotated keys
+    pub async fn decoding(&self, keyring: &Keyring) -> Result<Vec<Vec<u8>>, StatusCode> {
+        self.note();
+        match self {
+            SeedSource::Inline(seed) => Ok(vec![seed.clone()]),
+            SeedSource::Key(id) => match external_seed(keyring, id).await? {
+                Some(seed) => Ok(vec![seed]),
+                None => keyring.decoding_seeds(id).map_err(key_status),
+            },
+        }
+    }
+}
+
+/// Seed from the configured provider, `502 Bad Gateway` if it cannot be reached
+async fn external_seed(keyring: &Keyring, id: &str) -> Result<Option<Vec<u8>>, StatusCode> {
+    let Some(external) = keyring.external() else {
+        return Ok(None);
+    };
+    external.seed(id).await.transpose().map_err(|_| StatusCode::BAD_GATEWAY)
+}
+
+fn key_status(error: &'static str) -> StatusCode {
+    match error {
+        "Unknown key" => StatusCode::NOT_FOUND,
+        "Key is disabled" => StatusCode::FORBIDDEN,
+        "Key already exists" => StatusCode::CONFLICT,
+        "Invalid key id" | "Empty seed" => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Decode with the first seed whose checksum matches, reporting the last error otherwise
+pub(super) fn decode_any(
+    translators: &TranslatorCache,
+    encoded: &[u8],
+    seeds: &[Vec<u8>],
+    compression: CompressionAlgorithm,
+) -> Result<Vec<u8>, &'static str> {
+    let mut last = Err("No seed");
+    for seed in seeds {
+        last = translators.decode(encoded, seed, compression);
+        if last.is_ok() {
+            break;
+        }
+    }
+    last
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(super) struct AddKeyRequest {
+    id: String,
+    /// Base64 seed, random when omitted
+    seed: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct KeyResponse {
+    id: String,
+    /// Number of seed versions; encodes use the latest
+    version: usize,
+    disabled: bool,
+    created_at: u64,
+    rotated_at: Option<u64>,
+}
+
+impl From<KeyInfo> for KeyResponse {
+    fn from(info: KeyInfo) -> Self {
+        KeyResponse {
+            id: info.id,
+            version: info.version,
+            disabled: info.disabled,
+            created_at: info.created_at,
+            rotated_at: info.rotated_at,
+        }
+    }
+}
+
+type Keys = Extension<Arc<Keyring>>;
+
+#[utoipa::path(get, path = "/v1/admin/keys", tag = "admin", responses((status = 200, body = [KeyResponse])))]
+pub(super) async fn list_keys_handler(Extension(keyring): Keys) -> Json<Vec<KeyResponse>> {
+    Json(keyring.list().into_iter().map(KeyResponse::from).collect())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/keys",
+    tag = "admin",
+    request_body = AddKeyRequest,
+    responses((status = 201, body = KeyResponse), (status = 400), (status = 409, description = "Key already exists"))
+)]
+pub(super) async fn add_key_handler(
+    Extension(keyring): Keys,
+    Json(request): Json<AddKeyRequest>,
+) -> Result<(StatusCode, Json<KeyResponse>), StatusCode> {
+    use base64::{Engine as _, engine::general_purpose};
+
+    let seed = match request.seed {
+        Some(seed) => Some(general_purpose::STANDARD.decode(seed).map_err(|_| StatusCode::BAD_REQUEST)?),
+        None => None,
+    };
+    note_key(&request.id);
+    let info = keyring.add(&request.id, seed).map_err(key_status)?;
+    Ok((StatusCode::CREATED, Json(info.into())))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/keys/{id}/rotate",
+    tag = "admin",
+    params(("id" = String, Path)),
+    responses((status = 200, body = KeyResponse), (status = 404))
+)]
+pub(super) async fn rotate_key_handler(
+    Extension(keyring): Keys,
+    Path(id): Path<String>,
+) -> Result<Json<KeyResponse>, StatusCode> {
+    note_key(&id);
+    keyring.rotate(&id).map(|info| Json(info.into())).map_err(key_status)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/keys/{id}/disable",
+    tag = "admin",
+    params(("id" = String, Path)),
+    responses((status = 200, body = KeyResponse), (status = 404))
+)]
+pub(super) async fn disable_key_handler(
+    Extension(keyring): Keys,
+    Path(id): Path<String>,
+) -> Result<Json<KeyResponse>, StatusCode> {
+    note_key(&id);
+    keyring.set_disabled(&id, true).map(|info| Json(info.into())).map_err(key_status)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/keys/{id}/enable",
+    tag = "admin",
+    params(("id" = String, Path)),
+    responses((status = 200, body = KeyResponse), (status = 404))
+)]
+pub(super) async fn enable_key_handler(
+    Extension(keyring): Keys,
+    Path(id): Path<String>,
+) -> Result<Json<KeyResponse>, StatusCode> {
+    note_key(&id);
+    keyring.set_disabled(&id, false).map(|info| Json(info.into())).map_err(key_status)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{create_router_with, ApiConfig};
+    use crate::keyring::Keyring;
+    use axum::{
+        body::{to_bytes, Body},
+        http::{header, Request, StatusCode},
+        Router,
+    };
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    async fn post(router: &Router, path: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let request = Request::post(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_key_id_roundtrip_across_rotation() {
+        let keyring = Arc::new(Keyring::in_memory());
+        // Without auth, admin routes exist only when opted into
+        let closed = create_router_with(ApiConfig {
+            keyring: keyring.clone(),
+            ..Default::default()
+        });
+        let (status, _) = post(&closed, "/v1/admin/keys", serde_json::json!({"id": "web"})).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let router = create_router_with(ApiConfig {
+            keyring: keyring.clone(),
+            unauthenticated_admin: true,
+            ..Default::default()
+        });
+        let (status, _) = post(&router, "/v1/admin/keys", serde_json::json!({"id": "web"})).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let encode = serde_json::json!({"data": [1, 2, 3], "key_id": "web", "compression": "none"});
+        let (status, body) = post(&router, "/v1/encode", encode.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        let encoded = body["encoded"].clone();
+
+        let (status, body) = post(&router, "/v1/admin/keys/web/rotate", serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["version"], 2);
+
+        // Data from the previous version still decodes
+        let decode = serde_json::json!({"encoded": encoded, "key_id": "web", "compression": "none"});
+        let (status, body) = post(&router, "/v1/decode", decode).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["decoded"], serde_json::json!([1, 2, 3]));
+
+        post(&router, "/v1/admin/keys/web/disable", serde_json::json!({})).await;
+        let (status, _) = post(&router, "/v1/encode", encode).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let both = serde_json::json!({"data": [1], "seed": [1], "key_id": "web", "compression": "none"});
+        assert_eq!(post(&router, "/v1/encode", both).await.0, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,85 @@
+use axum::{extract::DefaultBodyLimit, routing::MethodRouter};
+use serde::{Deserialize, Serialize};
+use tower_http::limit::RequestBodyLimitLayer;
+
+const MIB: usize = 1024 * 1024;
+
+/// Maximum request body size in bytes per route class
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(default)]
+pub struct BodyLimits {
+    /// JSON and MessagePack requests, deserialized in memory
+    pub structured: usize,
+    /// `/encode/raw` and `/decode/raw`
+    pub raw: usize,
+    /// Multipart uploads to `/encode/file`
+    pub file: usize,
+    /// Raw bodies submitted to `/jobs`, held in memory until the job is dropped
+    pub job: usize,
+    /// Streaming routes, which never buffer the whole body; chunked uploads
+    /// past the limit are cut off mid-response
+    pub stream: usize,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        BodyLimits {
+            structured: 4 * MIB,
+            raw: 16 * MIB,
+            file: 64 * MIB,
+            job: 256 * MIB,
+            stream: 4096 * MIB,
+        }
+    }
+}
+
+/// Cap bodies read through buffering extractors, rejected with `413` before deserialization
+pub(super) fn buffered(route: MethodRouter, limit: usize) -> MethodRouter {
+    route.layer(DefaultBodyLimit::max(limit))
+}
+
+/// Cap bodies consumed as a stream, which bypass `DefaultBodyLimit`
+pub(super) fn streamed(route: MethodRouter, limit: usize) -> MethodRouter {
+    route.layer(RequestBodyLimitLayer::new(limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router_with, ApiConfig};
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    async fn status(path: &str, content_type: &str, body: Vec<u8>) -> StatusCode {
+        let limits = BodyLimits {
+            structured: 1024,
+            stream: 1024,
+            ..Default::default()
+        };
+        let router = create_router_with(ApiConfig {
+            limits,
+            ..Default::default()
+        });
+        let request = Request::post(path)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(Body::from(body))
+            .unwrap();
+        router.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_oversized_bodies_rejected() {
+        let small = br#"{"encoded":[1,2,3]}"#.to_vec();
+        let large = format!(r#"{{"encoded":[{}1]}}"#, "1,".repeat(1024)).into_bytes();
+        assert_eq!(status("/verify", "application/json", small).await, StatusCode::OK);
+        assert_eq!(status("/verify", "application/json", large).await, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(
+            status("/encode/stream?seed=c2VlZA==", "application/octet-stream", vec![0; 2048]).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+}
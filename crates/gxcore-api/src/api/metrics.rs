@@ -0,0 +1,169 @@
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use axum::{
+    body::HttpBody,
+    extract::{MatchedPath, Request},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prometheus::{
+    exponential_buckets, Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use gxcore_core::CompressionAlgorithm;
+
+/// Collectors exported on `/metrics`
+struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    latency: HistogramVec,
+    request_bytes: HistogramVec,
+    response_bytes: HistogramVec,
+    compression_ratio: HistogramVec,
+    errors: IntCounterVec,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(|| {
+    let size_buckets = exponential_buckets(64.0, 4.0, 12).unwrap();
+    let requests = IntCounterVec::new(
+        Opts::new("gx_http_requests_total", "HTTP requests by route and status"),
+        &["route", "method", "status"],
+    )
+    .unwrap();
+    let latency = HistogramVec::new(
+        HistogramOpts::new("gx_http_request_duration_seconds", "HTTP request latency by route"),
+        &["route", "method"],
+    )
+    .unwrap();
+    let request_bytes = HistogramVec::new(
+        HistogramOpts::new("gx_http_request_size_bytes", "Request body size by route").buckets(size_buckets.clone()),
+        &["route"],
+    )
+    .unwrap();
+    let response_bytes = HistogramVec::new(
+        HistogramOpts::new("gx_http_response_size_bytes", "Response body size by route").buckets(size_buckets),
+        &["route"],
+    )
+    .unwrap();
+    let compression_ratio = HistogramVec::new(
+        HistogramOpts::new("gx_compression_ratio", "Encoded size over input size by compression")
+            .buckets(vec![0.1, 0.25, 0.5, 0.75, 1.0, 1.25, 1.5, 2.0, 4.0]),
+        &["compression"],
+    )
+    .unwrap();
+    let errors = IntCounterVec::new(Opts::new("gx_errors_total", "Failed requests by error type"), &["kind"]).unwrap();
+
+    let registry = Registry::new();
+    registry.register(Box::new(requests.clone())).unwrap();
+    registry.register(Box::new(latency.clone())).unwrap();
+    registry.register(Box::new(request_bytes.clone())).unwrap();
+    registry.register(Box::new(response_bytes.clone())).unwrap();
+    registry.register(Box::new(compression_ratio.clone())).unwrap();
+    registry.register(Box::new(errors.clone())).unwrap();
+    Metrics {
+        registry,
+        requests,
+        latency,
+        request_bytes,
+        response_bytes,
+        compression_ratio,
+        errors,
+    }
+});
+
+/// Record the output/input size ratio of an encode
+pub(super) fn record_compression(compression: CompressionAlgorithm, input: usize, output: usize) {
+    if input > 0 {
+        METRICS
+            .compression_ratio
+            .with_label_values(&[compression.name()])
+            .observe(output as f64 / input as f64);
+    }
+}
+
+/// Count a codec failure, labelled by its error message
+pub(super) fn record_error(kind: &str) {
+    METRICS.errors.with_label_values(&[kind]).inc();
+}
+
+/// Middleware recording request counts, latency, sizes and error statuses
+pub async fn track(req: Request, next: Next) -> Response {
+    // Matched templates keep label cardinality bounded, unlike raw paths
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", |path| path.as_str())
+        .to_string();
+    let method = req.method().to_string();
+    let request_size = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<f64>().ok());
+
+    let start = Instant::now();
+    let response = next.run(req).await;
+    let status = response.status();
+
+    let metrics = &*METRICS;
+    metrics
+        .latency
+        .with_label_values(&[&route, &method])
+        .observe(start.elapsed().as_secs_f64());
+    metrics
+        .requests
+        .with_label_values(&[&route, &method, status.as_str()])
+        .inc();
+    if let Some(size) = request_size {
+        metrics.request_bytes.with_label_values(&[&route]).observe(size);
+    }
+    // Streamed bodies have no exact size up front
+    if let Some(size) = response.body().size_hint().exact() {
+        metrics.response_bytes.with_label_values(&[&route]).observe(size as f64);
+    }
+    if status.is_client_error() || status.is_server_error() {
+        let kind = status.canonical_reason().unwrap_or("Unknown");
+        metrics.errors.with_label_values(&[kind]).inc();
+    }
+    response
+}
+
+/// Prometheus text exposition of every collector
+#[utoipa::path(get, path = "/metrics", tag = "health", responses((status = 200, content_type = "text/plain", body = String)))]
+pub(super) async fn metrics_handler() -> Response {
+    let mut buffer = Vec::new();
+    if TextEncoder::new().encode(&METRICS.registry.gather(), &mut buffer).is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    ([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], buffer).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::create_router;
+    use axum::{
+        body::{to_bytes, Body},
+        http::{header, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_metrics_exported() {
+        let request = Request::post("/encode")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"data":[1,2,3,4],"seed":[1],"compression":"lz4"}"#))
+            .unwrap();
+        let response = create_router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = create_router()
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains(r#"gx_http_requests_total{method="POST",route="/encode",status="200"}"#));
+        assert!(text.contains(r#"gx_compression_ratio_count{compression="lz4"}"#));
+    }
+}
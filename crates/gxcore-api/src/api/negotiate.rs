@@ -0,0 +1,174 @@
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+const MSGPACK: &str = "application/msgpack";
+const MSGPACK_LEGACY: &str = "application/x-msgpack";
+
+/// Body serialization negotiated from `Content-Type` and `Accept`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Msgpack,
+}
+
+impl Format {
+    fn from_content_type(headers: &HeaderMap) -> Option<Self> {
+        let Some(value) = headers.get(header::CONTENT_TYPE) else {
+            return Some(Format::Json);
+        };
+        let mime = value.to_str().ok()?.split(';').next()?.trim();
+        match mime {
+            "application/json" => Some(Format::Json),
+            MSGPACK | MSGPACK_LEGACY => Some(Format::Msgpack),
+            _ => None,
+        }
+    }
+
+    /// Response format from `Accept`, falling back to the request format
+    fn from_accept(headers: &HeaderMap, fallback: Self) -> Self {
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("");
+        if accept.contains(MSGPACK) || accept.contains(MSGPACK_LEGACY) {
+            Format::Msgpack
+        } else if accept.contains("application/json") {
+            Format::Json
+        } else {
+            fallback
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Msgpack => MSGPACK,
+        }
+    }
+}
+
+/// Body extracted as JSON or MessagePack, carrying the format to answer with
+pub struct Negotiated<T>(pub Format, pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Negotiated<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let request_format = Format::from_content_type(req.headers())
+            .ok_or_else(|| StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response())?;
+        let response_format = Format::from_accept(req.headers(), request_format);
+
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let value = match request_format {
+            Format::Json => serde_json::from_slice(&body).map_err(|e| e.to_string()),
+            Format::Msgpack => rmp_serde::from_slice(&body).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| (StatusCode::BAD_REQUEST, e).into_response())?;
+
+        Ok(Negotiated(response_format, value))
+    }
+}
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        let Negotiated(format, value) = self;
+        let body = match format {
+            Format::Json => serde_json::to_vec(&value).map_err(|e| e.to_string()),
+            Format::Msgpack => rmp_serde::to_vec_named(&value).map_err(|e| e.to_string()),
+        };
+        match body {
+            Ok(body) => (
+                [(header::CONTENT_TYPE, HeaderValue::from_static(format.content_type()))],
+                body,
+            )
+                .into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::create_router;
+    use axum::{
+        body::{to_bytes, Body},
+        http::{header, Request, StatusCode},
+    };
+    use serde::{Deserialize, Serialize};
+    use tower::ServiceExt;
+
+    #[derive(Serialize)]
+    struct Encode<'a> {
+        #[serde(with = "serde_bytes")]
+        data: &'a [u8],
+        #[serde(with = "serde_bytes")]
+        seed: &'a [u8],
+        compression: &'a str,
+    }
+
+    #[derive(Deserialize)]
+    struct Encoded {
+        #[serde(with = "serde_bytes")]
+        encoded: Vec<u8>,
+    }
+
+    #[tokio::test]
+    async fn test_msgpack_encode() {
+        let body = rmp_serde::to_vec_named(&Encode {
+            data: b"msgpack data",
+            seed: b"seed",
+            compression: "none",
+        })
+        .unwrap();
+        let request = Request::post("/encode")
+            .header(header::CONTENT_TYPE, "application/msgpack")
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = create_router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/msgpack");
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let encoded: Encoded = rmp_serde::from_slice(&body).unwrap();
+        let decoded = gxcore_core::decode(&encoded.encoded, b"seed", gxcore_core::CompressionAlgorithm::None);
+        assert_eq!(decoded.unwrap(), b"msgpack data");
+    }
+
+    #[tokio::test]
+    async fn test_json_accepts_msgpack_response() {
+        let request = Request::post("/encode")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT, "application/x-msgpack")
+            .body(Body::from(r#"{"data":[1,2,3],"seed":[4],"compression":"lz4"}"#))
+            .unwrap();
+
+        let response = create_router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/msgpack");
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_content_type() {
+        let request = Request::post("/encode")
+            .header(header::CONTENT_TYPE, "text/csv")
+            .body(Body::from("1,2,3"))
+            .unwrap();
+
+        let response = create_router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
@@ -0,0 +1,256 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use axum::{body::Bytes, http::StatusCode, Extension, Json};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use gxcore_core::stream::{StreamDecoder, StreamEncoder};
+
+use super::keys::SeedSource;
+use super::stream::Incremental;
+use super::{metrics, Binary, DefaultCompression, TextEncoding};
+use crate::keyring::Keyring;
+use crate::storage::{ByteStream, Storage};
+
+#[derive(Clone, Deserialize, Serialize, ToSchema)]
+pub(super) struct ObjectRef {
+    bucket: String,
+    key: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(super) struct ObjectRequest {
+    source: ObjectRef,
+    /// Overwritten with the result
+    destination: ObjectRef,
+    /// Seed sent by the client, unless `key_id` names a server-held key
+    seed: Option<Binary>,
+    key_id: Option<String>,
+    /// `none`, `lz4`, `lz4frame` or `brotli`; the server default when omitted
+    #[serde(default)]
+    compression: Option<String>,
+    /// Encoding of `seed`
+    #[serde(default)]
+    encoding: TextEncoding,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct ObjectResponse {
+    source: ObjectRef,
+    destination: ObjectRef,
+    input_size: u64,
+    output_size: u64,
+}
+
+/// Bytes read from the source, and the codec error that stopped the transfer if any
+#[derive(Default)]
+struct Transfer {
+    read: AtomicU64,
+    failed: OnceLock<&'static str>,
+}
+
+/// Run `codec` over the source as the destination pulls it
+fn transform<C: Incremental>(input: ByteStream, codec: C, transfer: Arc<Transfer>) -> ByteStream {
+    let chunks = stream::unfold((input, Some(codec)), move |(mut input, codec)| {
+        let transfer = transfer.clone();
+        async move {
+            let mut codec = codec?;
+            let (out, codec) = match input.next().await {
+                Some(Ok(chunk)) => {
+                    transfer.read.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                    let out = codec.update(&chunk);
+                    let next = out.is_ok().then_some(codec);
+                    (out, next)
+                }
+                Some(Err(e)) => return Some((Err(e), (input, None))),
+                None => (codec.finish(), None),
+            };
+            let out = out.map(Bytes::from).map_err(|e| {
+                let _ = transfer.failed.set(e);
+                e.to_string()
+            });
+            Some((out, (input, codec)))
+        }
+    });
+    Box::pin(chunks)
+}
+
+/// Stream the source object through the codec into the destination
+async fn process<C: Incremental>(storage: &Storage, request: &ObjectRequest, codec: C) -> Result<Json<ObjectResponse>, StatusCode> {
+    let (source, destination) = (&request.source, &request.destination);
+    if !storage.allows(&source.bucket) || !storage.allows(&destination.bucket) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let store = storage.store();
+    let input = store
+        .get(&source.bucket, &source.key)
+        .await
+        .map_err(|_| StatusCode::BAD_GATEWAY)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let transfer = Arc::new(Transfer::default());
+    let written = store
+        .put(&destination.bucket, &destination.key, transform(input, codec, transfer.clone()))
+        .await;
+    let output_size = written.map_err(|_| match transfer.failed.get() {
+        Some(e) => {
+            metrics::record_error(e);
+            StatusCode::BAD_REQUEST
+        }
+        None => StatusCode::BAD_GATEWAY,
+    })?;
+    Ok(Json(ObjectResponse {
+        source: source.clone(),
+        destination: destination.clone(),
+        input_size: transfer.read.load(Ordering::Relaxed),
+        output_size,
+    }))
+}
+
+/// Encode an object into another with the framing of `/encode/stream`, returning only sizes
+#[utoipa::path(
+    post,
+    path = "/v1/objects/encode",
+    tag = "storage",
+    request_body = ObjectRequest,
+    responses(
+        (status = 200, body = ObjectResponse),
+        (status = 403, description = "Bucket not allowed, or key disabled"),
+        (status = 404, description = "Unknown key or source object"),
+        (status = 501, description = "No object storage configured"),
+        (status = 502, description = "Object storage failed"),
+    )
+)]
+pub(super) async fn encode_object_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(storage): Extension<Option<Storage>>,
+    Json(request): Json<ObjectRequest>,
+) -> Result<Json<ObjectResponse>, StatusCode> {
+    let storage = storage.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let compression = default.resolve(request.compression.as_deref())?;
+    let seed = request.seed.clone().map(|seed| request.encoding.decode(seed)).transpose()?;
+    let seed = SeedSource::from_parts(seed, request.key_id.clone())?.encoding(&keyring).await?;
+    process(&storage, &request, StreamEncoder::new(&seed, compression)).await
+}
+
+/// Decode an object written by `/objects/encode` or `/encode/stream` into another
+#[utoipa::path(
+    post,
+    path = "/v1/objects/decode",
+    tag = "storage",
+    request_body = ObjectRequest,
+    responses(
+        (status = 200, body = ObjectResponse),
+        (status = 400, description = "Invalid input or wrong seed"),
+        (status = 403, description = "Bucket not allowed"),
+        (status = 404, description = "Unknown key or source object"),
+        (status = 501, description = "No object storage configured"),
+        (status = 502, description = "Object storage failed"),
+    )
+)]
+pub(super) async fn decode_object_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(storage): Extension<Option<Storage>>,
+    Json(request): Json<ObjectRequest>,
+) -> Result<Json<ObjectResponse>, StatusCode> {
+    let storage = storage.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let compression = default.resolve(request.compression.as_deref())?;
+    let seed = request.seed.clone().map(|seed| request.encoding.decode(seed)).transpose()?;
+    let seeds = SeedSource::from_parts(seed, request.key_id.clone())?.decoding(&keyring).await?;
+    let decoder = StreamDecoder::new(&seeds[0], compression).with_fallback_seeds(seeds[1..].to_vec());
+    process(&storage, &request, decoder).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router_with, ApiConfig};
+    use crate::storage::ObjectStore;
+    use async_trait::async_trait;
+    use axum::{body::Body, http::Request, Router};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use tower::ServiceExt;
+
+    #[derive(Default)]
+    struct MemoryStore(Mutex<HashMap<(String, String), Vec<u8>>>);
+
+    #[async_trait]
+    impl ObjectStore for MemoryStore {
+        async fn get(&self, bucket: &str, key: &str) -> Result<Option<ByteStream>, String> {
+            let object = self.0.lock().unwrap().get(&(bucket.to_string(), key.to_string())).cloned();
+            Ok(object.map(|object| {
+                let chunks: Vec<_> = object.chunks(3).map(|chunk| Ok(Bytes::copy_from_slice(chunk))).collect();
+                Box::pin(stream::iter(chunks)) as ByteStream
+            }))
+        }
+
+        async fn put(&self, bucket: &str, key: &str, mut body: ByteStream) -> Result<u64, String> {
+            let mut object = Vec::new();
+            while let Some(chunk) = body.next().await {
+                object.extend_from_slice(&chunk?);
+            }
+            let len = object.len() as u64;
+            self.0.lock().unwrap().insert((bucket.to_string(), key.to_string()), object);
+            Ok(len)
+        }
+    }
+
+    async fn call(router: &Router, path: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let request = Request::post(path)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_object_roundtrip() {
+        let store = Arc::new(MemoryStore::default());
+        store.0.lock().unwrap().insert(("in".into(), "a.bin".into()), b"object payload".to_vec());
+        let router = create_router_with(ApiConfig {
+            storage: Some(Storage::new(store.clone(), vec!["in".into(), "out".into()])),
+            ..Default::default()
+        });
+
+        let encode = serde_json::json!({
+            "source": {"bucket": "in", "key": "a.bin"},
+            "destination": {"bucket": "out", "key": "a.gx"},
+            "seed": [1, 2, 3],
+            "compression": "lz4",
+        });
+        let (status, body) = call(&router, "/v1/objects/encode", encode).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["input_size"], 14);
+
+        let decode = serde_json::json!({
+            "source": {"bucket": "out", "key": "a.gx"},
+            "destination": {"bucket": "out", "key": "a.bin"},
+            "seed": [1, 2, 3],
+            "compression": "lz4",
+        });
+        assert_eq!(call(&router, "/v1/objects/decode", decode).await.0, StatusCode::OK);
+        let decoded = store.0.lock().unwrap()[&("out".to_string(), "a.bin".to_string())].clone();
+        assert_eq!(decoded, b"object payload");
+
+        let wrong_seed = serde_json::json!({
+            "source": {"bucket": "out", "key": "a.gx"},
+            "destination": {"bucket": "out", "key": "b.bin"},
+            "seed": [9],
+            "compression": "lz4",
+        });
+        assert_eq!(call(&router, "/v1/objects/decode", wrong_seed).await.0, StatusCode::BAD_REQUEST);
+        let forbidden = serde_json::json!({
+            "source": {"bucket": "private", "key": "a.bin"},
+            "destination": {"bucket": "out", "key": "c.gx"},
+            "seed": [1],
+        });
+        assert_eq!(call(&router, "/v1/objects/encode", forbidden).await.0, StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use futures_util::{stream, Stream};
+use serde::Serialize;
+use tokio::sync::watch;
+use utoipa::ToSchema;
+
+use crate::keyring::generate_seed;
+
+/// Request header choosing the progress id, so clients can subscribe before uploading
+pub const PROGRESS_ID_HEADER: &str = "x-gx-progress-id";
+/// Response header with the path of the operation's event stream
+pub const PROGRESS_HEADER: &str = "x-gx-progress";
+
+/// Operations tracked before finished ones are evicted
+const MAX_TRACKED: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum ProgressState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Payload of every event; `done` and `total` count bytes for streams and runs for benchmarks
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub(super) struct Progress {
+    done: u64,
+    /// Unknown for streams sent without `Content-Length`
+    total: Option<u64>,
+    state: ProgressState,
+}
+
+struct Entry {
+    order: u64,
+    sender: Arc<watch::Sender<Progress>>,
+    /// Whether an operation reports to this entry, as opposed to an early subscriber
+    claimed: bool,
+}
+
+/// Progress of long-running operations, keyed by id
+#[derive(Default)]
+pub(super) struct ProgressRegistry {
+    entries: Mutex<HashMap<String, Entry>>,
+    inserted: AtomicU64,
+}
+
+fn valid_id(id: &str) -> bool {
+    (1..=64).contains(&id.len()) && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+}
+
+impl ProgressRegistry {
+    fn entry<'a>(&self, entries: &'a mut HashMap<String, Entry>, id: &str) -> &'a mut Entry {
+        if !entries.contains_key(id) && entries.len() >= MAX_TRACKED {
+            let finished = entries
+                .iter()
+                .filter(|(_, entry)| entry.sender.borrow().state != ProgressState::Running)
+                .min_by_key(|(_, entry)| entry.order)
+                .map(|(id, _)| id.clone());
+            if let Some(finished) = finished {
+                entries.remove(&finished);
+            }
+        }
+        entries.entry(id.to_string()).or_insert_with(|| Entry {
+            order: self.inserted.fetch_add(1, Ordering::Relaxed),
+            sender: Arc::new(watch::Sender::new(Progress {
+                done: 0,
+                total: None,
+                state: ProgressState::Running,
+            })),
+            claimed: false,
+        })
+    }
+
+    /// Start reporting under `id`, or a random id; `None` if the id is invalid or taken
+    pub fn track(&self, id: Option<&str>, total: Option<u64>) -> Option<Tracker> {
+        let id = match id {
+            Some(id) if valid_id(id) => id.to_string(),
+            Some(_) => return None,
+            None => hex::encode(&generate_seed()[..8]),
+        };
+        let mut entries = self.entries.lock().unwrap();
+        let entry = self.entry(&mut entries, &id);
+        if entry.claimed {
+            return None;
+        }
+        entry.claimed = true;
+        entry.sender.send_modify(|progress| progress.total = total);
+        Some(Tracker {
+            id,
+            sender: entry.sender.clone(),
+        })
+    }
+
+    /// Track the operation whose id the client chose in [`PROGRESS_ID_HEADER`], if any
+    pub fn track_request(&self, headers: &HeaderMap, total: Option<u64>) -> Result<Tracker, StatusCode> {
+        let id = match headers.get(PROGRESS_ID_HEADER) {
+            Some(value) => Some(value.to_str().map_err(|_| StatusCode::BAD_REQUEST)?),
+            None => None,
+        };
+        self.track(id, total).ok_or(if id.is_some_and(valid_id) {
+            StatusCode::CONFLICT
+        } else {
+            StatusCode::BAD_REQUEST
+        })
+    }
+
+    /// Updates for `id`, waiting for the operation to start if it has not yet
+    fn subscribe(&self, id: &str) -> Option<watch::Receiver<Progress>> {
+        if !valid_id(id) {
+            return None;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        Some(self.entry(&mut entries, id).sender.subscribe())
+    }
+}
+
+/// Handle an operation reports through; dropping it while running marks the operation failed
+pub(super) struct Tracker {
+    id: String,
+    sender: Arc<watch::Sender<Progress>>,
+}
+
+impl Tracker {
+    /// Path of the event stream
+    pub fn location(&self) -> String {
+        format!("/v1/progress/{}", self.id)
+    }
+
+    pub fn set_total(&self, total: u64) {
+        self.sender.send_modify(|progress| progress.total = Some(total));
+    }
+
+    pub fn advance(&self, amount: u64) {
+        self.sender.send_modify(|progress| progress.done += amount);
+    }
+
+    pub fn finish(&self, state: ProgressState) {
+        self.sender.send_modify(|progress| progress.state = state);
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        self.sender.send_if_modified(|progress| {
+            let running = progress.state == ProgressState::Running;
+            if running {
+                progress.state = ProgressState::Failed;
+            }
+            running
+        });
+    }
+}
+
+fn event(progress: &Progress) -> Event {
+    let name = match progress.state {
+        ProgressState::Running => "progress",
+        ProgressState::Completed => "complete",
+        ProgressState::Failed => "failed",
+        ProgressState::Cancelled => "cancelled",
+    };
+    Event::default()
+        .event(name)
+        .json_data(progress)
+        .expect("Progress serializes to JSON")
+}
+
+/// Stream `progress` events until a final `complete`, `failed` or `cancelled` event
+#[utoipa::path(
+    get,
+    path = "/v1/progress/{id}",
+    tag = "raw",
+    params(("id" = String, Path, description = "From the `x-gx-progress` response header, or chosen in `x-gx-progress-id`")),
+    responses((status = 200, content_type = "text/event-stream", body = Progress), (status = 404))
+)]
+pub(super) async fn progress_handler(
+    Extension(registry): Extension<Arc<ProgressRegistry>>,
+    Path(id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    let receiver = registry.subscribe(&id).ok_or(StatusCode::NOT_FOUND)?;
+    let events = stream::unfold((Some(receiver), true), |(receiver, first)| async move {
+        let mut receiver = receiver?;
+        if !first && receiver.changed().await.is_err() {
+            return None;
+        }
+        let progress = receiver.borrow_and_update().clone();
+        let next = (progress.state == ProgressState::Running).then_some(receiver);
+        Some((Ok(event(&progress)), (next, false)))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use crate::api::raw::SEED_HEADER;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_tracker_lifecycle() {
+        let registry = ProgressRegistry::default();
+        let early = registry.subscribe("upload-1").unwrap();
+        let tracker = registry.track(Some("upload-1"), Some(10)).unwrap();
+        assert!(registry.track(Some("upload-1"), None).is_none());
+        assert!(registry.track(Some("bad id"), None).is_none());
+
+        tracker.advance(4);
+        assert_eq!(early.borrow().done, 4);
+        assert_eq!(early.borrow().total, Some(10));
+        drop(tracker);
+        assert_eq!(early.borrow().state, ProgressState::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_stream_progress_events() {
+        let router = create_router();
+        let request = Request::post("/v1/encode/stream")
+            .header(SEED_HEADER, "c2VlZA==")
+            .header(PROGRESS_ID_HEADER, "upload-1")
+            .header("content-length", "5")
+            .body(Body::from("hello"))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[PROGRESS_HEADER], "/v1/progress/upload-1");
+        to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        let events = router
+            .oneshot(Request::get("/v1/progress/upload-1").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = to_bytes(events.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("event: complete"), "{body}");
+        assert!(body.contains(r#""done":5,"total":5"#), "{body}");
+    }
+}
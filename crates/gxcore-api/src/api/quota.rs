@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use axum::{
+    body::Body,
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::auth::{Principal, Scope};
+use super::ratelimit::credential_key;
+use crate::sigv4::civil_date;
+
+/// Client of requests without credentials
+const ANONYMOUS: &str = "anonymous";
+
+/// Caps within one window, unlimited when absent
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, ToSchema)]
+pub struct QuotaLimits {
+    pub requests: Option<u64>,
+    /// Request body bytes sent to encode and decode routes
+    pub bytes: Option<u64>,
+}
+
+/// Limits per UTC day and calendar month
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Plan {
+    pub daily: QuotaLimits,
+    pub monthly: QuotaLimits,
+}
+
+/// Usage accounting on encode and decode routes, per token subject or API key
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct QuotaConfig {
+    pub enabled: bool,
+    pub default: Plan,
+    /// Plans replacing the default for particular clients, keyed as reported by `/usage`
+    pub clients: HashMap<String, Plan>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, ToSchema)]
+pub(super) struct Counters {
+    requests: u64,
+    bytes: u64,
+}
+
+impl Counters {
+    fn exceeds(&self, limits: QuotaLimits) -> bool {
+        limits.requests.is_some_and(|limit| self.requests >= limit) || limits.bytes.is_some_and(|limit| self.bytes >= limit)
+    }
+}
+
+/// Counters of the current day and month, reset as either rolls over
+#[derive(Default)]
+struct Usage {
+    day: i64,
+    month: i64,
+    daily: Counters,
+    monthly: Counters,
+}
+
+/// UTC day since the epoch, month since year 0, and time left in each
+struct Period {
+    day: i64,
+    month: i64,
+    day_left: Duration,
+    month_left: Duration,
+}
+
+impl Period {
+    fn at(now: SystemTime) -> Self {
+        let secs = now.duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let day = (secs / 86_400) as i64;
+        let (year, month, date) = civil_date(day);
+        let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let month_days = match month {
+            2 if leap => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        };
+        let day_left = 86_400 - secs % 86_400;
+        Period {
+            day,
+            month: year * 12 + month - 1,
+            day_left: Duration::from_secs(day_left),
+            month_left: Duration::from_secs(day_left + (month_days - date) as u64 * 86_400),
+        }
+    }
+}
+
+impl Usage {
+    fn roll(&mut self, period: &Period) {
+        if self.day != period.day {
+            self.day = period.day;
+            self.daily = Counters::default();
+        }
+        if self.month != period.month {
+            self.month = period.month;
+            self.monthly = Counters::default();
+        }
+    }
+}
+
+/// Usage of every client since startup, enforced against their plans
+pub struct Quotas {
+    config: QuotaConfig,
+    usage: Mutex<HashMap<String, Usage>>,
+}
+
+impl Quotas {
+    pub fn new(config: QuotaConfig) -> Self {
+        Quotas {
+            config,
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn plan(&self, client: &str) -> Plan {
+        self.config.clients.get(client).copied().unwrap_or(self.config.default)
+    }
+
+    /// Count a request by `client`, or return how long until its exhausted quota resets.
+    /// Bytes are counted as bodies are read, so the request using up a byte quota completes.
+    pub fn admit(&self, client: &str, now: SystemTime) -> Result<(), Duration> {
+        let plan = self.plan(client);
+        let period = Period::at(now);
+        let mut usage = self.usage.lock().unwrap();
+        let usage = usage.entry(client.to_string()).or_default();
+        usage.roll(&period);
+        if usage.monthly.exceeds(plan.monthly) {
+            return Err(period.month_left);
+        }
+        if usage.daily.exceeds(plan.daily) {
+            return Err(period.day_left);
+        }
+        usage.daily.requests += 1;
+        usage.monthly.requests += 1;
+        Ok(())
+    }
+
+    pub fn record_bytes(&self, client: &str, bytes: u64, now: SystemTime) {
+        let period = Period::at(now);
+        let mut usage = self.usage.lock().unwrap();
+        let usage = usage.entry(client.to_string()).or_default();
+        usage.roll(&period);
+        usage.daily.bytes += bytes;
+        usage.monthly.bytes += bytes;
+    }
+
+    fn report(&self, client: &str, now: SystemTime) -> UsageResponse {
+        let plan = self.plan(client);
+        let period = Period::at(now);
+        let mut usage = self.usage.lock().unwrap();
+        let (daily, monthly) = match usage.get_mut(client) {
+            Some(usage) => {
+                usage.roll(&period);
+                (usage.daily, usage.monthly)
+            }
+            None => Default::default(),
+        };
+        UsageResponse {
+            client: client.to_string(),
+            daily: Window {
+                used: daily,
+                limits: plan.daily,
+                resets_in: period.day_left.as_secs(),
+            },
+            monthly: Window {
+                used: monthly,
+                limits: plan.monthly,
+                resets_in: period.month_left.as_secs(),
+            },
+        }
+    }
+}
+
+/// `tenant:` and the tenant id for tenant members, the token subject for other
+/// authenticated callers, else the hashed API key
+pub(super) fn client_of(principal: Option<&Principal>, headers: &HeaderMap) -> String {
+    match principal {
+        Some(Principal { tenant: Some(tenant), .. }) => format!("tenant:{tenant}"),
+        Some(principal) => principal.subject.clone(),
+        None => credential_key(headers).unwrap_or_else(|| ANONYMOUS.to_string()),
+    }
+}
+
+/// Middleware answering `429 Too Many Requests` with `Retry-After` once a client's quota is used up
+pub(super) async fn enforce(State(quotas): State<Arc<Quotas>>, req: Request, next: Next) -> Response {
+    let client = client_of(req.extensions().get::<Principal>(), req.headers());
+    if let Err(wait) = quotas.admit(&client, SystemTime::now()) {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, wait.as_secs().max(1).to_string())],
+        )
+            .into_response();
+    }
+
+    // Counted per chunk, since streaming routes read their body after responding
+    let (parts, body) = req.into_parts();
+    let counted = body.into_data_stream().inspect(move |chunk| {
+        if let Ok(chunk) = chunk {
+            quotas.record_bytes(&client, chunk.len() as u64, SystemTime::now());
+        }
+    });
+    next.run(Request::from_parts(parts, Body::from_stream(counted))).await
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct Window {
+    used: Counters,
+    limits: QuotaLimits,
+    /// Seconds until the counters reset
+    resets_in: u64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct UsageResponse {
+    client: String,
+    daily: Window,
+    monthly: Window,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(super) struct UsageParams {
+    /// Another client's usage, for admins
+    client: Option<String>,
+}
+
+/// Usage and limits of the caller, or of `client` for admins
+#[utoipa::path(
+    get,
+    path = "/v1/usage",
+    tag = "admin",
+    params(UsageParams),
+    responses(
+        (status = 200, body = UsageResponse),
+        (status = 403, description = "Another client's usage requested without the admin scope"),
+        (status = 501, description = "Quotas disabled"),
+    )
+)]
+pub(super) async fn usage_handler(
+    Extension(quotas): Extension<Option<Arc<Quotas>>>,
+    principal: Option<Extension<Principal>>,
+    Query(params): Query<UsageParams>,
+    headers: HeaderMap,
+) -> Result<Json<UsageResponse>, StatusCode> {
+    let quotas = quotas.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let principal = principal.map(|Extension(principal)| principal);
+    let own = client_of(principal.as_ref(), &headers);
+    let client = match params.client {
+        Some(client) if client != own => {
+            // Without auth every caller may see every client, as with the admin routes
+            if principal.is_some_and(|principal| !principal.has_scope(Scope::Admin)) {
+                return Err(StatusCode::FORBIDDEN);
+            }
+            client
+        }
+        _ => own,
+    };
+    Ok(Json(quotas.report(&client, SystemTime::now())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router_with, ApiConfig};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_windows_reset() {
+        let quotas = Quotas::new(QuotaConfig {
+            enabled: true,
+            default: Plan {
+                daily: QuotaLimits {
+                    requests: Some(2),
+                    bytes: None,
+                },
+                monthly: QuotaLimits {
+                    requests: None,
+                    bytes: Some(100),
+                },
+            },
+            ..Default::default()
+        });
+        // 2024-02-28T12:00:00Z
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_121_600);
+        assert!(quotas.admit("a", now).is_ok());
+        assert!(quotas.admit("a", now).is_ok());
+        assert_eq!(quotas.admit("a", now), Err(Duration::from_secs(12 * 3_600)));
+        assert!(quotas.admit("b", now).is_ok());
+
+        // A new day, but the leap month's byte quota stays spent until March
+        let tomorrow = now + Duration::from_secs(86_400);
+        assert!(quotas.admit("a", tomorrow).is_ok());
+        quotas.record_bytes("a", 100, tomorrow);
+        assert_eq!(quotas.admit("a", tomorrow), Err(Duration::from_secs(12 * 3_600)));
+        assert!(quotas.admit("a", tomorrow + Duration::from_secs(12 * 3_600)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_quota_enforced_and_reported() {
+        let config = QuotaConfig {
+            enabled: true,
+            default: Plan {
+                daily: QuotaLimits {
+                    requests: Some(1),
+                    bytes: None,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let router = create_router_with(ApiConfig {
+            quotas: Some(Arc::new(Quotas::new(config))),
+            ..Default::default()
+        });
+        let encode = || {
+            Request::post("/v1/encode")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header("x-api-key", "team-a")
+                .body(Body::from(r#"{"data":[1,2,3],"seed":[1]}"#))
+                .unwrap()
+        };
+        assert_eq!(router.clone().oneshot(encode()).await.unwrap().status(), StatusCode::OK);
+        let response = router.clone().oneshot(encode()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+        let request = Request::get("/v1/usage").header("x-api-key", "team-a").body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let usage: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(usage["client"].as_str().unwrap().starts_with("key:"));
+        assert_eq!(usage["daily"]["used"], serde_json::json!({"requests": 1, "bytes": 27}));
+        assert_eq!(usage["daily"]["limits"]["requests"], 1);
+    }
+}
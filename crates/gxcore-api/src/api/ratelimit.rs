@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// Header identifying API key clients
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Buckets kept before idle ones are evicted
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Token bucket parameters
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct Limit {
+    /// Sustained requests per second
+    pub per_second: f64,
+    /// Requests allowed in a burst
+    pub burst: u32,
+}
+
+/// Rate limits applied per client
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub default: Limit,
+    /// Stricter limit for the CPU-heavy benchmark endpoint
+    pub benchmark: Limit,
+    /// Identify clients by the first `X-Forwarded-For` address when behind a proxy
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            default: Limit {
+                per_second: 50.0,
+                burst: 100,
+            },
+            benchmark: Limit {
+                per_second: 1.0 / 60.0,
+                burst: 2,
+            },
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets keyed by client
+pub struct RateLimiter {
+    limit: Limit,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: Limit) -> Self {
+        RateLimiter {
+            limit,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `client`, or return how long until one is available
+    pub fn check(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let burst = f64::from(self.limit.burst.max(1));
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(client) {
+            // Full buckets carry no state worth keeping
+            let limit = self.limit;
+            buckets.retain(|_, b| b.tokens + now.duration_since(b.updated).as_secs_f64() * limit.per_second < burst);
+        }
+
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.limit.per_second).min(burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = (1.0 - bucket.tokens) / self.limit.per_second;
+            Err(Duration::from_secs_f64(wait))
+        }
+    }
+}
+
+/// `key:` and the hash of the API key or bearer token, so credentials are not kept in memory verbatim
+pub(super) fn credential_key(headers: &HeaderMap) -> Option<String> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let credential = header(API_KEY_HEADER).or_else(|| {
+        header(header::AUTHORIZATION.as_str()).and_then(|value| value.strip_prefix("Bearer "))
+    })?;
+    Some(format!("key:{}", hex::encode(Sha256::digest(credential.as_bytes()))))
+}
+
+/// Limiters for each route class
+pub struct RateLimits {
+    default: RateLimiter,
+    benchmark: RateLimiter,
+    trust_forwarded_for: bool,
+}
+
+impl RateLimits {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        RateLimits {
+            default: RateLimiter::new(config.default),
+            benchmark: RateLimiter::new(config.benchmark),
+            trust_forwarded_for: config.trust_forwarded_for,
+        }
+    }
+
+    fn client_key(&self, headers: &HeaderMap, peer: Option<SocketAddr>) -> String {
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+        if let Some(key) = credential_key(headers) {
+            return key;
+        }
+
+        let forwarded = self
+            .trust_forwarded_for
+            .then(|| header("x-forwarded-for"))
+            .flatten()
+            .and_then(|value| value.split(',').next())
+            .map(|ip| ip.trim().to_string());
+        match forwarded.or_else(|| peer.map(|addr| addr.ip().to_string())) {
+            Some(ip) => format!("ip:{ip}"),
+            None => "unknown".to_string(),
+        }
+    }
+}
+
+/// Middleware answering `429 Too Many Requests` with `Retry-After` once a client's bucket is empty
+pub async fn rate_limit(State(limits): State<Arc<RateLimits>>, req: Request, next: Next) -> Response {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| *addr);
+    let client = limits.client_key(req.headers(), peer);
+    let limiter = if req.uri().path().ends_with("/benchmark") {
+        &limits.benchmark
+    } else {
+        &limits.default
+    };
+
+    if let Err(wait) = limiter.check(&client, Instant::now()) {
+        let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after.to_string())],
+        )
+            .into_response();
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router_with, ApiConfig};
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::new(Limit {
+            per_second: 2.0,
+            burst: 2,
+        });
+        let start = Instant::now();
+        assert!(limiter.check("a", start).is_ok());
+        assert!(limiter.check("a", start).is_ok());
+        assert_eq!(limiter.check("a", start), Err(Duration::from_millis(500)));
+        // Other clients have their own bucket
+        assert!(limiter.check("b", start).is_ok());
+        assert!(limiter.check("a", start + Duration::from_millis(500)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_retry_after() {
+        let limits = RateLimitConfig {
+            default: Limit {
+                per_second: 0.1,
+                burst: 1,
+            },
+            ..Default::default()
+        };
+        let router = create_router_with(ApiConfig {
+            rate_limit: Some(limits),
+            ..Default::default()
+        });
+        let request = |key: &str| {
+            axum::http::Request::post("/verify")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(API_KEY_HEADER, key)
+                .body(Body::from(r#"{"encoded":[]}"#))
+                .unwrap()
+        };
+
+        let first = router.clone().oneshot(request("team-a")).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let limited = router.clone().oneshot(request("team-a")).await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "10");
+        let other = router.oneshot(request("team-b")).await.unwrap();
+        assert_eq!(other.status(), StatusCode::OK);
+    }
+}
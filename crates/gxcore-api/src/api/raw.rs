@@ -0,0 +1,258 @@
+use axum::{
+    body::Bytes,
+    extract::Query,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use base64::{Engine as _, engine::general_purpose};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use gxcore_core::cache::TranslatorCache;
+use gxcore_core::CompressionAlgorithm;
+
+use std::sync::Arc;
+
+use super::keys::{decode_any, SeedSource};
+use super::cache::{self, Cached, EncodeCache};
+use super::{metrics, offload, DefaultCompression};
+use crate::keyring::Keyring;
+
+/// Header carrying the base64 seed for raw endpoints
+pub const SEED_HEADER: &str = "x-gx-seed";
+/// Header naming a server-held key instead of sending the seed
+pub const KEY_ID_HEADER: &str = "x-gx-key-id";
+/// Header carrying the compression name for raw endpoints
+pub const COMPRESSION_HEADER: &str = "x-gx-compression";
+
+const TEXT_PLAIN: &str = "text/plain; charset=utf-8";
+
+/// Query fallback for the option headers
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(super) struct RawParams {
+    /// Base64 seed, when `x-gx-seed` is absent
+    seed: Option<String>,
+    /// Server-held key, when `x-gx-key-id` is absent
+    key_id: Option<String>,
+    /// Compression name, when `x-gx-compression` is absent
+    compression: Option<String>,
+}
+
+/// Option headers, documented for the OpenAPI spec
+#[derive(IntoParams)]
+#[into_params(parameter_in = Header)]
+#[allow(dead_code)]
+pub(super) struct RawHeaders {
+    /// Base64 seed
+    #[param(rename = "x-gx-seed")]
+    seed: Option<String>,
+    /// Server-held key used instead of a seed
+    #[param(rename = "x-gx-key-id")]
+    key_id: Option<String>,
+    /// Compression name, the server default when absent
+    #[param(rename = "x-gx-compression")]
+    compression: Option<String>,
+}
+
+/// Options from headers, falling back to query parameters
+pub(super) fn raw_options(
+    headers: &HeaderMap,
+    params: RawParams,
+    default: DefaultCompression,
+) -> Result<(SeedSource, CompressionAlgorithm), StatusCode> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+
+    let seed = match header(SEED_HEADER).or(params.seed) {
+        Some(seed) => Some(general_purpose::STANDARD.decode(seed).map_err(|_| StatusCode::BAD_REQUEST)?),
+        None => None,
+    };
+    let seed = SeedSource::from_parts(seed, header(KEY_ID_HEADER).or(params.key_id))?;
+
+    let compression = default.resolve(header(COMPRESSION_HEADER).or(params.compression).as_deref())?;
+    Ok((seed, compression))
+}
+
+fn octet_stream(body: Vec<u8>) -> Response {
+    ([(header::CONTENT_TYPE, "application/octet-stream")], body).into_response()
+}
+
+fn text_plain(body: Vec<u8>) -> Response {
+    ([(header::CONTENT_TYPE, TEXT_PLAIN)], body).into_response()
+}
+
+/// Whether the `name` header, `Content-Type` or `Accept`, asks for `text/plain`
+fn wants_text(headers: &HeaderMap, name: header::HeaderName) -> bool {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("text/plain"))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/encode/raw",
+    tag = "raw",
+    description = "A `text/plain` body must be UTF-8 and is answered as `text/plain`",
+    params(RawParams, RawHeaders),
+    request_body(content((Vec<u8> = "application/octet-stream"), (String = "text/plain"))),
+    responses(
+        (status = 200, content((Vec<u8> = "application/octet-stream"), (String = "text/plain"))),
+        (status = 304, description = "`If-None-Match` matches the cached result"),
+        (status = 400),
+        (status = 412, description = "`If-None-Match: *` with caching enabled"),
+    )
+)]
+pub(super) async fn encode_raw_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(cache): Extension<Arc<EncodeCache>>,
+    Query(params): Query<RawParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, StatusCode> {
+    let (seed, compression) = raw_options(&headers, params, default)?;
+    let text = wants_text(&headers, header::CONTENT_TYPE);
+    if text && std::str::from_utf8(&body).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let seed = seed.encoding(&keyring).await?;
+    let body_len = body.len();
+    let (encoded, etag, hit) = match cache.encode(&headers, "raw", body, seed, compression).await? {
+        Cached::NotModified(etag) => return Ok(cache::not_modified(etag)),
+        Cached::Encoded { encoded, etag, hit } => (encoded, etag, hit),
+    };
+    metrics::record_compression(compression, body_len, encoded.len());
+    // Encoded payloads are ASCII, so any caller may read them as text
+    let mut response = match text || wants_text(&headers, header::ACCEPT) {
+        true => text_plain(encoded),
+        false => octet_stream(encoded),
+    };
+    cache::annotate(&mut response, etag, hit);
+    Ok(response)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/decode/raw",
+    tag = "raw",
+    description = "With `Accept: text/plain` the decoded payload must be UTF-8",
+    params(RawParams, RawHeaders),
+    request_body(content((Vec<u8> = "application/octet-stream"), (String = "text/plain"))),
+    responses(
+        (status = 200, content((Vec<u8> = "application/octet-stream"), (String = "text/plain"))),
+        (status = 400),
+    )
+)]
+pub(super) async fn decode_raw_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(translators): Extension<Arc<TranslatorCache>>,
+    Query(params): Query<RawParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, StatusCode> {
+    let (seed, compression) = raw_options(&headers, params, default)?;
+    let seeds = seed.decoding(&keyring).await?;
+    let text = wants_text(&headers, header::ACCEPT);
+    let decoded = offload(move || {
+        let decoded = decode_any(&translators, &body, &seeds, compression)?;
+        match text && std::str::from_utf8(&decoded).is_err() {
+            true => Err("Invalid UTF-8"),
+            false => Ok(decoded),
+        }
+    })
+    .await?
+    .map_err(|e| {
+        metrics::record_error(e);
+        StatusCode::BAD_REQUEST
+    })?;
+    match text {
+        true => Ok(text_plain(decoded)),
+        false => Ok(octet_stream(decoded)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn post_raw(request: Request<Body>) -> (StatusCode, Vec<u8>) {
+        let response = create_router().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, body.to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_raw_roundtrip() {
+        let request = Request::post("/encode/raw?compression=lz4")
+            .header(SEED_HEADER, "c2VlZA==")
+            .body(Body::from(vec![0u8, 1, 2, 255, 254]))
+            .unwrap();
+        let (status, encoded) = post_raw(request).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let request = Request::post("/decode/raw")
+            .header(SEED_HEADER, "c2VlZA==")
+            .header(COMPRESSION_HEADER, "lz4")
+            .body(Body::from(encoded))
+            .unwrap();
+        let (status, decoded) = post_raw(request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(decoded, vec![0u8, 1, 2, 255, 254]);
+    }
+
+    #[tokio::test]
+    async fn test_text_roundtrip() {
+        let request = Request::post("/encode/raw")
+            .header(SEED_HEADER, "c2VlZA==")
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(Body::from("Grüße"))
+            .unwrap();
+        let response = create_router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], TEXT_PLAIN);
+        let encoded = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        let request = Request::post("/decode/raw")
+            .header(SEED_HEADER, "c2VlZA==")
+            .header(header::ACCEPT, "text/plain")
+            .body(Body::from(encoded))
+            .unwrap();
+        let (status, decoded) = post_raw(request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(decoded, "Grüße".as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_text_rejects_binary() {
+        let request = Request::post("/encode/raw")
+            .header(SEED_HEADER, "c2VlZA==")
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(Body::from(vec![0xffu8, 0xfe]))
+            .unwrap();
+        let (status, _) = post_raw(request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let encoded = gxcore_core::encode(&[0xff, 0xfe], b"seed", CompressionAlgorithm::None);
+        let request = Request::post("/decode/raw")
+            .header(SEED_HEADER, "c2VlZA==")
+            .header(header::ACCEPT, "text/plain")
+            .body(Body::from(encoded))
+            .unwrap();
+        let (status, _) = post_raw(request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_raw_requires_seed() {
+        let request = Request::post("/encode/raw").body(Body::from("data")).unwrap();
+        let (status, _) = post_raw(request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use utoipa::IntoParams;
+
+use super::keys::KeyResponse;
+use crate::keyring::Keyring;
+use crate::replication::TOKEN_HEADER;
+use crate::storage::BlobStore;
+
+/// Largest listing page
+const MAX_LIMIT: usize = 1000;
+
+/// Middleware refusing requests without the shared replication token
+pub(super) async fn require_token(State(token): State<Arc<String>>, req: Request, next: Next) -> Response {
+    let sent = req.headers().get(TOKEN_HEADER).map(|value| value.as_bytes()).unwrap_or_default();
+    // Digests compared so the comparison time reveals nothing about the token
+    if Sha256::digest(sent) != Sha256::digest(token.as_bytes()) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    next.run(req).await
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(super) struct ListParams {
+    /// Last key of the previous page
+    after: Option<String>,
+    /// At most 1000, 100 by default
+    limit: Option<usize>,
+}
+
+/// Blob keys in byte order, every tenant's included
+#[utoipa::path(
+    get,
+    path = "/v1/replication/blobs",
+    tag = "replication",
+    params(ListParams),
+    responses(
+        (status = 200, body = [String]),
+        (status = 401, description = "Missing or wrong replication token"),
+        (status = 501, description = "No blob store configured"),
+        (status = 502, description = "Blob store failed or cannot list"),
+    )
+)]
+pub(super) async fn list_blobs_handler(
+    Extension(blobs): Extension<Option<BlobStore>>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Vec<String>>, StatusCode> {
+    let blobs = blobs.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let limit = params.limit.unwrap_or(100).clamp(1, MAX_LIMIT);
+    let keys = blobs.keys(params.after.as_deref(), limit).await.map_err(|_| StatusCode::BAD_GATEWAY)?;
+    Ok(Json(keys))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(super) struct FetchParams {
+    /// Bytes the replica already holds, skipped to resume an interrupted transfer
+    #[serde(default)]
+    offset: u64,
+}
+
+/// Contents of a blob from `offset`; replicas check the whole against the id in the key
+#[utoipa::path(
+    get,
+    path = "/v1/replication/blobs/{key}",
+    tag = "replication",
+    params(("key" = String, Path, description = "Key from the listing"), FetchParams),
+    responses(
+        (status = 200, content_type = "application/octet-stream", body = Vec<u8>),
+        (status = 401, description = "Missing or wrong replication token"),
+        (status = 404),
+        (status = 501, description = "No blob store configured"),
+        (status = 502, description = "Blob store failed"),
+    )
+)]
+pub(super) async fn fetch_blob_handler(
+    Extension(blobs): Extension<Option<BlobStore>>,
+    Path(key): Path<String>,
+    Query(params): Query<FetchParams>,
+) -> Result<Response, StatusCode> {
+    let blobs = blobs.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    let contents = blobs
+        .get_key(&key)
+        .await
+        .map_err(|_| StatusCode::BAD_GATEWAY)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    // Drop the first `offset` bytes, splitting the chunk they end in
+    let remaining = contents.scan(params.offset, |skip, chunk| {
+        let chunk = chunk.map(|chunk| {
+            let cut = (*skip).min(chunk.len() as u64) as usize;
+            *skip -= cut as u64;
+            chunk.slice(cut..)
+        });
+        std::future::ready(Some(chunk))
+    });
+    let remaining = remaining.filter(|chunk| std::future::ready(!matches!(chunk, Ok(chunk) if chunk.is_empty())));
+    let body = Body::from_stream(remaining);
+    Ok(([(header::CONTENT_TYPE, "application/octet-stream")], body).into_response())
+}
+
+/// Key metadata, never seeds; replicas mirror whether each key is disabled
+#[utoipa::path(
+    get,
+    path = "/v1/replication/keys",
+    tag = "replication",
+    responses(
+        (status = 200, body = [KeyResponse]),
+        (status = 401, description = "Missing or wrong replication token"),
+    )
+)]
+pub(super) async fn keys_handler(Extension(keyring): Extension<Arc<Keyring>>) -> Json<Vec<KeyResponse>> {
+    Json(keyring.list().into_iter().map(KeyResponse::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{create_router_with, ApiConfig};
+    use crate::keyring::Keyring;
+    use crate::replication::{ReplicationConfig, Replicator, SyncReport, UpstreamConfig};
+    use crate::storage::{BlobStore, FsConfig, FsStore};
+    use bytes::Bytes;
+    use futures_util::StreamExt;
+    use std::sync::Arc;
+
+    fn blob_store(root: &std::path::Path) -> BlobStore {
+        BlobStore::new(Arc::new(FsStore::new(FsConfig { root: root.to_path_buf() })), "blobs")
+    }
+
+    #[tokio::test]
+    async fn test_replica_catches_up_and_resumes() {
+        let dir = std::env::temp_dir().join(format!("gx-replication-{}", std::process::id()));
+        let upstream_blobs = blob_store(&dir.join("upstream"));
+        let keyring = Arc::new(Keyring::in_memory());
+        keyring.add("billing", None).unwrap();
+        keyring.set_disabled("billing", true).unwrap();
+        let router = create_router_with(ApiConfig {
+            blobs: Some(upstream_blobs.clone()),
+            keyring,
+            replication_token: Some("shared".to_string()),
+            ..Default::default()
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let first = upstream_blobs.put(Bytes::from_static(b"first blob")).await.unwrap();
+        let second = upstream_blobs.scoped("team-a").put(Bytes::from_static(b"tenant blob")).await.unwrap();
+        let replica_blobs = blob_store(&dir.join("replica"));
+        let replica_keyring = Arc::new(Keyring::in_memory());
+        replica_keyring.add("billing", None).unwrap();
+        let upstream = UpstreamConfig {
+            url: url.clone(),
+            interval_secs: 1,
+            state_dir: dir.join("state"),
+            batch: 1,
+        };
+        let config = ReplicationConfig {
+            token: "shared".to_string(),
+            upstream: None,
+        };
+        let replicator = Replicator::new(&config, upstream.clone(), replica_blobs.clone(), replica_keyring.clone());
+
+        // An interrupted transfer left the first bytes behind
+        std::fs::create_dir_all(dir.join("state/partial")).unwrap();
+        std::fs::write(dir.join("state/partial").join(&first), b"first").unwrap();
+        assert_eq!(replicator.sync_once().await.unwrap(), SyncReport { blobs: 2, keys: 1 });
+        let mut copied = replica_blobs.get(&first).await.unwrap().unwrap();
+        assert_eq!(copied.next().await.unwrap().unwrap(), "first blob");
+        assert!(replica_blobs.scoped("team-a").get(&second).await.unwrap().is_some());
+        assert_eq!(replica_keyring.encoding_seed("billing"), Err("Key is disabled"));
+        // Nothing new, nothing copied
+        assert_eq!(replicator.sync_once().await.unwrap(), SyncReport { blobs: 0, keys: 1 });
+
+        let wrong = ReplicationConfig {
+            token: "guess".to_string(),
+            upstream: None,
+        };
+        let intruder = Replicator::new(&wrong, upstream, replica_blobs, replica_keyring);
+        assert!(intruder.sync_once().await.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{OriginalUri, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use super::ratelimit::API_KEY_HEADER;
+
+/// Unix seconds at which the request was signed
+pub const TIMESTAMP_HEADER: &str = "x-gx-timestamp";
+/// Unique per request within the replay window
+pub const NONCE_HEADER: &str = "x-gx-nonce";
+/// `sha256=<hex HMAC>` of the canonical request, as for job callbacks
+pub const REQUEST_SIGNATURE_HEADER: &str = "x-gx-signature";
+
+const MIB: usize = 1024 * 1024;
+
+/// Nonces remembered before new requests are refused rather than risking a replay
+const MAX_TRACKED_NONCES: usize = 100_000;
+
+/// Request signing with per API key secrets, refusing replayed requests
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SigningConfig {
+    pub enabled: bool,
+    /// Secrets by the API key clients send in `x-api-key`, best set through `GX_SIGNING__KEYS__<key>`
+    pub keys: HashMap<String, String>,
+    /// Allowed clock skew, and how long nonces are remembered
+    pub window_secs: u64,
+    /// Largest body buffered to check its signature; streaming routes are capped by it too
+    pub max_body: usize,
+}
+
+impl Default for SigningConfig {
+    fn default() -> Self {
+        SigningConfig {
+            enabled: false,
+            keys: HashMap::new(),
+            window_secs: 300,
+            max_body: 16 * MIB,
+        }
+    }
+}
+
+/// Signed string: method, path and query, timestamp, nonce and the hex SHA-256 of the body, one per line
+pub fn canonical_request(method: &str, path_and_query: &str, timestamp: u64, nonce: &str, body: &[u8]) -> String {
+    format!(
+        "{method}\n{path_and_query}\n{timestamp}\n{nonce}\n{}",
+        hex::encode(Sha256::digest(body))
+    )
+}
+
+/// `x-gx-signature` value for `canonical` signed with `secret`
+pub fn sign_request(secret: &str, canonical: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(canonical.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Checks signatures and remembers nonces until their timestamp leaves the window
+pub struct RequestSigning {
+    config: SigningConfig,
+    nonces: Mutex<HashMap<(String, String), SystemTime>>,
+}
+
+impl RequestSigning {
+    pub fn new(config: SigningConfig) -> Self {
+        RequestSigning {
+            config,
+            nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Verify the signature in `headers` over the request, then claim its nonce
+    fn verify(&self, headers: &HeaderMap, method: &str, path_and_query: &str, body: &[u8], now: SystemTime) -> Result<(), StatusCode> {
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok()).ok_or(StatusCode::UNAUTHORIZED);
+        let secret = self.config.keys.get(header(API_KEY_HEADER)?).ok_or(StatusCode::UNAUTHORIZED)?;
+        let timestamp: u64 = header(TIMESTAMP_HEADER)?.parse().map_err(|_| StatusCode::UNAUTHORIZED)?;
+        let nonce = header(NONCE_HEADER)?;
+        let signature = header(REQUEST_SIGNATURE_HEADER)?
+            .strip_prefix("sha256=")
+            .and_then(|signature| hex::decode(signature).ok())
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+
+        let window = Duration::from_secs(self.config.window_secs);
+        let signed_at = SystemTime::UNIX_EPOCH + Duration::from_secs(timestamp);
+        let skew = now.duration_since(signed_at).unwrap_or_else(|e| e.duration());
+        if skew > window || nonce.is_empty() {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+        mac.update(canonical_request(method, path_and_query, timestamp, nonce, body).as_bytes());
+        mac.verify_slice(&signature).map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+        // Claimed only once the signature holds, so forged requests cannot burn nonces
+        let mut nonces = self.nonces.lock().unwrap();
+        if nonces.len() >= MAX_TRACKED_NONCES {
+            nonces.retain(|_, expires| *expires > now);
+            if nonces.len() >= MAX_TRACKED_NONCES {
+                return Err(StatusCode::SERVICE_UNAVAILABLE);
+            }
+        }
+        let key = (header(API_KEY_HEADER)?.to_string(), nonce.to_string());
+        match nonces.get(&key) {
+            Some(expires) if *expires > now => Err(StatusCode::UNAUTHORIZED),
+            _ => {
+                nonces.insert(key, signed_at + window);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Middleware refusing unsigned, stale or replayed requests with `401 Unauthorized`
+pub(super) async fn require_signature(State(signing): State<Arc<RequestSigning>>, req: Request, next: Next) -> Response {
+    let (parts, body) = req.into_parts();
+    // Nested routers see their own path, the client signed the full one
+    let uri = parts.extensions.get::<OriginalUri>().map_or(&parts.uri, |OriginalUri(uri)| uri);
+    let path_and_query = uri.path_and_query().map_or(uri.path(), |pq| pq.as_str()).to_string();
+    let body = match to_bytes(body, signing.config.max_body).await {
+        Ok(body) => body,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    if let Err(status) = signing.verify(&parts.headers, parts.method.as_str(), &path_and_query, &body, SystemTime::now()) {
+        return status.into_response();
+    }
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router_with, ApiConfig};
+    use axum::http::header;
+    use tower::ServiceExt;
+
+    fn signing() -> Arc<RequestSigning> {
+        Arc::new(RequestSigning::new(SigningConfig {
+            enabled: true,
+            keys: HashMap::from([("team-a".to_string(), "s3cret".to_string())]),
+            ..Default::default()
+        }))
+    }
+
+    fn request(timestamp: u64, nonce: &str, secret: &str) -> Request {
+        let body = r#"{"encoded":[]}"#;
+        let canonical = canonical_request("POST", "/v1/verify", timestamp, nonce, body.as_bytes());
+        Request::post("/v1/verify")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(API_KEY_HEADER, "team-a")
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(NONCE_HEADER, nonce)
+            .header(REQUEST_SIGNATURE_HEADER, sign_request(secret, &canonical))
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_signed_requests_not_replayable() {
+        let router = create_router_with(ApiConfig {
+            signing: Some(signing()),
+            ..Default::default()
+        });
+        let send = |request: Request| router.clone().oneshot(request);
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+
+        assert_eq!(send(request(now, "n1", "s3cret")).await.unwrap().status(), StatusCode::OK);
+        assert_eq!(send(request(now, "n1", "s3cret")).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send(request(now, "n2", "wrong")).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send(request(now - 600, "n3", "s3cret")).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        let unsigned = Request::post("/v1/verify").body(Body::from(r#"{"encoded":[]}"#)).unwrap();
+        assert_eq!(send(unsigned).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        // Probes and discovery stay unsigned
+        let probe = Request::get("/v1/capabilities").body(Body::empty()).unwrap();
+        assert_eq!(send(probe).await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_nonces_expire_with_window() {
+        let signing = signing();
+        let now = SystemTime::now();
+        let timestamp = now.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        let canonical = canonical_request("GET", "/v1/jobs/1", timestamp, "n", b"");
+        let mut headers = HeaderMap::new();
+        headers.insert(API_KEY_HEADER, "team-a".parse().unwrap());
+        headers.insert(TIMESTAMP_HEADER, timestamp.to_string().parse().unwrap());
+        headers.insert(NONCE_HEADER, "n".parse().unwrap());
+        headers.insert(REQUEST_SIGNATURE_HEADER, sign_request("s3cret", &canonical).parse().unwrap());
+
+        assert_eq!(signing.verify(&headers, "GET", "/v1/jobs/1", b"", now), Ok(()));
+        assert!(signing.verify(&headers, "GET", "/v1/jobs/1", b"", now).is_err());
+        // A different path breaks the signature
+        assert!(signing.verify(&headers, "GET", "/v1/jobs/2", b"", now).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use std::io;
+use std::sync::Arc;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::Query,
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use futures_util::{stream, Stream, StreamExt};
+use tokio::sync::mpsc;
+use gxcore_core::stream::{StreamDecoder, StreamEncoder};
+
+use super::progress::{ProgressRegistry, ProgressState, Tracker, PROGRESS_HEADER};
+use super::raw::{raw_options, RawHeaders, RawParams};
+use super::DefaultCompression;
+use crate::keyring::Keyring;
+
+/// Frames buffered between the codec task and the response body
+const CHANNEL_CAPACITY: usize = 4;
+
+type Chunk = Result<Bytes, io::Error>;
+
+/// Incremental codec driven by the streaming endpoints
+pub(super) trait Incremental: Send + 'static {
+    fn update(&mut self, chunk: &[u8]) -> Result<Vec<u8>, &'static str>;
+    fn finish(self) -> Result<Vec<u8>, &'static str>;
+}
+
+impl Incremental for StreamEncoder {
+    fn update(&mut self, chunk: &[u8]) -> Result<Vec<u8>, &'static str> {
+        Ok(StreamEncoder::update(self, chunk))
+    }
+
+    fn finish(self) -> Result<Vec<u8>, &'static str> {
+        Ok(StreamEncoder::finish(self))
+    }
+}
+
+impl Incremental for StreamDecoder {
+    fn update(&mut self, chunk: &[u8]) -> Result<Vec<u8>, &'static str> {
+        StreamDecoder::update(self, chunk)
+    }
+
+    fn finish(self) -> Result<Vec<u8>, &'static str> {
+        StreamDecoder::finish(self)
+    }
+}
+
+/// Run the codec over each request chunk in a task, forwarding output through a bounded
+/// channel so memory stays flat whatever the payload size. Input bytes are reported to `tracker`.
+fn pipe<C: Incremental>(body: Body, mut codec: C, tracker: Tracker) -> Response {
+    let (tx, rx) = mpsc::channel::<Chunk>(CHANNEL_CAPACITY);
+    let location = tracker.location();
+    let mut input = body.into_data_stream();
+
+    tokio::spawn(async move {
+        while let Some(chunk) = input.next().await {
+            let out = chunk.map_err(io::Error::other).and_then(|chunk| {
+                tracker.advance(chunk.len() as u64);
+                codec.update(&chunk).map_err(io::Error::other)
+            });
+            match out {
+                Ok(out) if out.is_empty() => {}
+                Ok(out) => {
+                    if tx.send(Ok(out.into())).await.is_err() {
+                        return;
+                    }
+                }
+                // Errors after the status line abort the response body
+                Err(e) => {
+                    tracker.finish(ProgressState::Failed);
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            }
+        }
+        let last = codec.finish();
+        tracker.finish(if last.is_ok() { ProgressState::Completed } else { ProgressState::Failed });
+        let last = match last {
+            Ok(out) if out.is_empty() => return,
+            Ok(out) => Ok(out.into()),
+            Err(e) => Err(io::Error::other(e)),
+        };
+        let _ = tx.send(last).await;
+    });
+
+    let output = receiver_stream(rx);
+    let headers = [
+        (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+        (HeaderName::from_static(PROGRESS_HEADER), location),
+    ];
+    (headers, Body::from_stream(output)).into_response()
+}
+
+/// Expected input size, for progress totals
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers.get(header::CONTENT_LENGTH)?.to_str().ok()?.parse().ok()
+}
+
+fn receiver_stream(rx: mpsc::Receiver<Chunk>) -> impl Stream<Item = Chunk> {
+    stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|item| (item, rx)) })
+}
+
+/// Encode a request body of any size into newline-delimited frames
+#[utoipa::path(
+    post,
+    path = "/v1/encode/stream",
+    tag = "raw",
+    params(RawParams, RawHeaders),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses((
+        status = 200,
+        description = "Newline-delimited frames",
+        content_type = "application/octet-stream",
+        body = Vec<u8>,
+        headers(("x-gx-progress" = String, description = "Event stream reporting input bytes consumed"))
+    ))
+)]
+pub(super) async fn encode_stream_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(progress): Extension<Arc<ProgressRegistry>>,
+    Query(params): Query<RawParams>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, StatusCode> {
+    let (seed, compression) = raw_options(&headers, params, default)?;
+    let encoder = StreamEncoder::new(&seed.encoding(&keyring).await?, compression);
+    let tracker = progress.track_request(&headers, content_length(&headers))?;
+    Ok(pipe(body, encoder, tracker))
+}
+
+/// Decode a stream of frames produced by the streaming encoder
+#[utoipa::path(
+    post,
+    path = "/v1/decode/stream",
+    tag = "raw",
+    params(RawParams, RawHeaders),
+    request_body(content = Vec<u8>, description = "Newline-delimited frames", content_type = "application/octet-stream"),
+    responses((
+        status = 200,
+        content_type = "application/octet-stream",
+        body = Vec<u8>,
+        headers(("x-gx-progress" = String, description = "Event stream reporting input bytes consumed"))
+    ))
+)]
+pub(super) async fn decode_stream_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(progress): Extension<Arc<ProgressRegistry>>,
+    Query(params): Query<RawParams>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, StatusCode> {
+    let (seed, compression) = raw_options(&headers, params, default)?;
+    let seeds = seed.decoding(&keyring).await?;
+    let decoder = StreamDecoder::new(&seeds[0], compression).with_fallback_seeds(seeds[1..].to_vec());
+    let tracker = progress.track_request(&headers, content_length(&headers))?;
+    Ok(pipe(body, decoder, tracker))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::create_router;
+    use crate::api::raw::SEED_HEADER;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_stream_roundtrip() {
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i % 256) as u8).collect();
+        let request = Request::post("/encode/stream?compression=lz4")
+            .header(SEED_HEADER, "c2VlZA==")
+            .body(Body::from(data.clone()))
+            .unwrap();
+        let response = create_router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let encoded = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        let request = Request::post("/decode/stream?compression=lz4")
+            .header(SEED_HEADER, "c2VlZA==")
+            .body(Body::from(encoded))
+            .unwrap();
+        let response = create_router().oneshot(request).await.unwrap();
+        let decoded = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(decoded.to_vec(), data);
+    }
+}
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::auth::Principal;
+use crate::storage::BlobStore;
+use crate::tenants::{TenantInfo, Tenants};
+
+/// Middleware swapping the keyring and blob store for the caller's tenant; unknown tenants get `403 Forbidden`
+pub(super) async fn scope_tenant(State(tenants): State<Arc<Tenants>>, mut req: Request, next: Next) -> Response {
+    let Some(tenant) = req.extensions().get::<Principal>().and_then(|principal| principal.tenant.clone()) else {
+        return next.run(req).await;
+    };
+    let Some(keyring) = tenants.keyring(&tenant) else {
+        return StatusCode::FORBIDDEN.into_response();
+    };
+    let blobs = req
+        .extensions()
+        .get::<Option<BlobStore>>()
+        .and_then(|blobs| blobs.as_ref().map(|blobs| blobs.scoped(&tenant)));
+    let extensions = req.extensions_mut();
+    extensions.insert(keyring);
+    extensions.insert(blobs);
+    next.run(req).await
+}
+
+/// Tenant management is reserved to admins outside any tenant
+fn require_operator(principal: Option<Extension<Principal>>) -> Result<(), StatusCode> {
+    match principal {
+        Some(Extension(principal)) if principal.tenant.is_some() => Err(StatusCode::FORBIDDEN),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub(super) struct CreateTenantRequest {
+    /// Value of the tenant claim in the tenant's tokens
+    id: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct TenantResponse {
+    id: String,
+    created_at: u64,
+}
+
+impl From<TenantInfo> for TenantResponse {
+    fn from(info: TenantInfo) -> Self {
+        TenantResponse {
+            id: info.id,
+            created_at: info.created_at,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/tenants",
+    tag = "admin",
+    responses(
+        (status = 200, body = [TenantResponse]),
+        (status = 403, description = "Caller belongs to a tenant"),
+        (status = 501, description = "Tenants disabled"),
+    )
+)]
+pub(super) async fn list_tenants_handler(
+    Extension(tenants): Extension<Option<Arc<Tenants>>>,
+    principal: Option<Extension<Principal>>,
+) -> Result<Json<Vec<TenantResponse>>, StatusCode> {
+    require_operator(principal)?;
+    let tenants = tenants.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    Ok(Json(tenants.list().into_iter().map(TenantResponse::from).collect()))
+}
+
+/// Create a tenant with an empty keyring
+#[utoipa::path(
+    post,
+    path = "/v1/admin/tenants",
+    tag = "admin",
+    request_body = CreateTenantRequest,
+    responses(
+        (status = 201, body = TenantResponse),
+        (status = 400, description = "Invalid tenant id"),
+        (status = 403, description = "Caller belongs to a tenant"),
+        (status = 409, description = "Tenant already exists"),
+        (status = 501, description = "Tenants disabled"),
+    )
+)]
+pub(super) async fn create_tenant_handler(
+    Extension(tenants): Extension<Option<Arc<Tenants>>>,
+    principal: Option<Extension<Principal>>,
+    Json(request): Json<CreateTenantRequest>,
+) -> Result<(StatusCode, Json<TenantResponse>), StatusCode> {
+    require_operator(principal)?;
+    let tenants = tenants.ok_or(StatusCode::NOT_IMPLEMENTED)?;
+    match tenants.create(&request.id) {
+        Ok(info) => Ok((StatusCode::CREATED, Json(info.into()))),
+        Err("Invalid tenant id") => Err(StatusCode::BAD_REQUEST),
+        Err("Tenant already exists") => Err(StatusCode::CONFLICT),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{create_router_with, ApiConfig, AuthConfig, Authenticator};
+    use axum::{body::Body, http::header, Router};
+    use jsonwebtoken::{EncodingKey, Header};
+    use tower::ServiceExt;
+
+    const SECRET: &str = "test-secret";
+
+    fn token(tenant: Option<&str>) -> String {
+        let mut claims = serde_json::json!({
+            "sub": "svc",
+            "iss": "https://idp.test",
+            "exp": 4_000_000_000u64,
+            "scope": "gx:admin",
+        });
+        if let Some(tenant) = tenant {
+            claims["tenant"] = tenant.into();
+        }
+        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(SECRET.as_bytes())).unwrap()
+    }
+
+    async fn post(router: &Router, path: &str, tenant: Option<&str>, body: serde_json::Value) -> StatusCode {
+        let request = Request::post(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token(tenant)))
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_keys_partitioned_by_tenant() {
+        let config = AuthConfig {
+            issuer: "https://idp.test".to_string(),
+            hs256_secret: Some(SECRET.to_string()),
+            ..Default::default()
+        };
+        let router = create_router_with(ApiConfig {
+            auth: Some(Arc::new(Authenticator::from_parts(&config, None).unwrap())),
+            tenants: Some(Arc::new(Tenants::in_memory())),
+            ..Default::default()
+        });
+        let tenant = |id: &str| serde_json::json!({ "id": id });
+        assert_eq!(post(&router, "/v1/admin/tenants", None, tenant("a")).await, StatusCode::CREATED);
+        assert_eq!(post(&router, "/v1/admin/tenants", None, tenant("b")).await, StatusCode::CREATED);
+        assert_eq!(post(&router, "/v1/admin/tenants", Some("a"), tenant("c")).await, StatusCode::FORBIDDEN);
+
+        let key = serde_json::json!({"id": "billing"});
+        assert_eq!(post(&router, "/v1/admin/keys", Some("a"), key.clone()).await, StatusCode::CREATED);
+        // The same id is free in another tenant, and unknown tenants are refused
+        assert_eq!(post(&router, "/v1/admin/keys", Some("b"), key.clone()).await, StatusCode::CREATED);
+        assert_eq!(post(&router, "/v1/admin/keys", Some("z"), key).await, StatusCode::FORBIDDEN);
+
+        let encode = serde_json::json!({"data": [1], "key_id": "billing"});
+        assert_eq!(post(&router, "/v1/encode", Some("a"), encode.clone()).await, StatusCode::OK);
+        assert_eq!(post(&router, "/v1/encode", None, encode).await, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use axum::{http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use gxcore_core::{decode, CompressionAlgorithm};
+
+use super::keys::SeedSource;
+use super::{offload, Binary, DefaultCompression, TextEncoding};
+use crate::keyring::Keyring;
+
+#[derive(Deserialize, ToSchema)]
+pub(super) struct VerifyFullRequest {
+    encoded: Binary,
+    /// Seed sent by the client, unless `key_id` names a server-held key
+    seed: Option<Binary>,
+    key_id: Option<String>,
+    /// `none`, `lz4`, `lz4frame` or `brotli`; the server default when omitted
+    #[serde(default)]
+    compression: Option<String>,
+    #[serde(default)]
+    encoding: TextEncoding,
+}
+
+/// Decode step that rejected the input, in the order they run
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum Stage {
+    /// Mapping through the seed's alphabet and base64 decoding
+    Alphabet,
+    /// CRC32 of the decoded bytes; a wrong seed usually fails here
+    Checksum,
+    Decompression,
+}
+
+impl Stage {
+    fn of(error: &str) -> Stage {
+        match error {
+            "Checksum mismatch" => Stage::Checksum,
+            "Invalid character" | "Invalid base64" | "Data too short" => Stage::Alphabet,
+            _ => Stage::Decompression,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub(super) struct VerifyFullResponse {
+    /// Whether a decode with the same inputs would succeed
+    valid: bool,
+    failed_stage: Option<Stage>,
+    error: Option<&'static str>,
+}
+
+/// Run a full decode and discard the result; with several key versions the failure that
+/// got furthest is reported
+fn verify(encoded: &[u8], seeds: &[Vec<u8>], compression: CompressionAlgorithm) -> VerifyFullResponse {
+    let mut furthest: Option<(Stage, &'static str)> = None;
+    for seed in seeds {
+        match decode(encoded, seed, compression) {
+            Ok(_) => {
+                return VerifyFullResponse {
+                    valid: true,
+                    failed_stage: None,
+                    error: None,
+                };
+            }
+            Err(e) => {
+                let stage = Stage::of(e);
+                if furthest.is_none_or(|(reached, _)| stage > reached) {
+                    furthest = Some((stage, e));
+                }
+            }
+        }
+    }
+    VerifyFullResponse {
+        valid: false,
+        failed_stage: furthest.map(|(stage, _)| stage),
+        error: furthest.map(|(_, e)| e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/verify/full",
+    tag = "codec",
+    request_body = VerifyFullRequest,
+    responses(
+        (status = 200, body = VerifyFullResponse),
+        (status = 400, description = "Unknown compression or malformed field"),
+        (status = 404, description = "Unknown key"),
+    )
+)]
+pub(super) async fn verify_full_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Json(payload): Json<VerifyFullRequest>,
+) -> Result<Json<VerifyFullResponse>, StatusCode> {
+    let compression = default.resolve(payload.compression.as_deref())?;
+
+    let encoded = payload.encoding.decode(payload.encoded)?;
+    let seed = payload.seed.map(|seed| payload.encoding.decode(seed)).transpose()?;
+    let seeds = SeedSource::from_parts(seed, payload.key_id)?.decoding(&keyring).await?;
+    Ok(Json(offload(move || verify(&encoded, &seeds, compression)).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gxcore_core::encode;
+
+    #[test]
+    fn test_failed_stages() {
+        let lz4 = CompressionAlgorithm::Lz4;
+        let encoded = encode(b"payload", b"seed", lz4);
+        assert!(verify(&encoded, &[b"seed".to_vec()], lz4).valid);
+
+        let report = verify(&encoded, &[b"other".to_vec()], lz4);
+        assert_eq!(report.failed_stage, Some(Stage::Checksum));
+        let report = verify(b"!!!!", &[b"seed".to_vec()], lz4);
+        assert_eq!(report.failed_stage, Some(Stage::Alphabet));
+
+        // Valid checksum over bytes that are not LZ4 blocks
+        let plain = encode(b"not compressed", b"seed", CompressionAlgorithm::None);
+        let report = verify(&plain, &[b"other".to_vec(), b"seed".to_vec()], lz4);
+        assert_eq!(report.failed_stage, Some(Stage::Decompression));
+        assert_eq!(report.error, Some("Decompression LZ4 failed"));
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    response::Response,
+    Extension,
+};
+use base64::{Engine as _, engine::general_purpose};
+use serde::Deserialize;
+use gxcore_core::cache::TranslatorCache;
+use gxcore_core::CompressionAlgorithm;
+
+use super::auth::{Principal, Scope};
+use super::keys::{decode_any, SeedSource};
+use super::{offload, DefaultCompression};
+use crate::keyring::Keyring;
+
+/// Session settings sent by the client as a text message before binary frames
+#[derive(Deserialize)]
+struct SessionConfig {
+    op: Operation,
+    /// Base64 seed, unless `key_id` is set
+    seed: Option<String>,
+    key_id: Option<String>,
+    compression: Option<String>,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Operation {
+    Encode,
+    Decode,
+}
+
+impl Operation {
+    fn scope(self) -> Scope {
+        match self {
+            Operation::Encode => Scope::Encode,
+            Operation::Decode => Scope::Decode,
+        }
+    }
+}
+
+struct Session {
+    op: Operation,
+    /// Encoding seed, or decoding candidates newest first
+    seeds: Vec<Vec<u8>>,
+    compression: CompressionAlgorithm,
+}
+
+impl Session {
+    async fn configure(
+        text: &str,
+        principal: Option<&Principal>,
+        default: DefaultCompression,
+        keyring: &Keyring,
+    ) -> Result<Self, &'static str> {
+        let config: SessionConfig = serde_json::from_str(text).map_err(|_| "Invalid session config")?;
+        if principal.is_some_and(|p| !p.has_scope(config.op.scope())) {
+            return Err("Missing scope for operation");
+        }
+        let seed = match config.seed {
+            Some(seed) => Some(general_purpose::STANDARD.decode(seed).map_err(|_| "Invalid base64 seed")?),
+            None => None,
+        };
+        let source = SeedSource::from_parts(seed, config.key_id).map_err(|_| "Send either seed or key_id")?;
+        let seeds = match config.op {
+            Operation::Encode => source.encoding(keyring).await.map(|seed| vec![seed]),
+            Operation::Decode => source.decoding(keyring).await,
+        }
+        .map_err(|_| "Key unavailable")?;
+        let compression = default
+            .resolve(config.compression.as_deref())
+            .map_err(|_| "Unsupported compression")?;
+        Ok(Session {
+            op: config.op,
+            seeds,
+            compression,
+        })
+    }
+
+    fn process(&self, translators: &TranslatorCache, frame: &[u8]) -> Result<Vec<u8>, &'static str> {
+        match self.op {
+            Operation::Encode => Ok(translators.encode(frame, &self.seeds[0], self.compression)),
+            Operation::Decode => decode_any(translators, frame, &self.seeds, self.compression),
+        }
+    }
+}
+
+fn error_message(error: &str) -> Message {
+    Message::Text(serde_json::json!({ "error": error }).to_string())
+}
+
+/// Upgrade to a session where each binary frame is answered with its encoded or decoded form.
+/// A text message (re)configures the session: `{"op": "encode", "seed": "<base64>", "compression": "lz4"}`,
+/// with `"key_id"` in place of `"seed"` for server-held keys.
+pub(super) async fn ws_handler(
+    Extension(default): Extension<DefaultCompression>,
+    Extension(keyring): Extension<Arc<Keyring>>,
+    Extension(translators): Extension<Arc<TranslatorCache>>,
+    principal: Option<Extension<Principal>>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let principal = principal.map(|Extension(principal)| principal);
+    upgrade.on_upgrade(move |socket| run_session(socket, principal, default, keyring, translators))
+}
+
+async fn run_session(
+    mut socket: WebSocket,
+    principal: Option<Principal>,
+    default: DefaultCompression,
+    keyring: Arc<Keyring>,
+    translators: Arc<TranslatorCache>,
+) {
+    let mut session: Option<Arc<Session>> = None;
+
+    while let Some(Ok(message)) = socket.recv().await {
+        let reply = match message {
+            Message::Text(text) => match Session::configure(&text, principal.as_ref(), default, &keyring).await {
+                Ok(configured) => {
+                    session = Some(Arc::new(configured));
+                    Message::Text(r#"{"status":"ready"}"#.to_string())
+                }
+                Err(e) => error_message(e),
+            },
+            Message::Binary(frame) => match session.clone() {
+                Some(session) => {
+                    let translators = translators.clone();
+                    match offload(move || session.process(&translators, &frame)).await {
+                        Ok(Ok(out)) => Message::Binary(out),
+                        Ok(Err(e)) => error_message(e),
+                        Err(_) => error_message("Internal error"),
+                    }
+                }
+                None => error_message("Session not configured"),
+            },
+            Message::Close(_) => break,
+            // Pings are answered by axum
+            Message::Ping(_) | Message::Pong(_) => continue,
+        };
+        if socket.send(reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::create_router;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+    #[tokio::test]
+    async fn test_ws_session() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, create_router()).await.unwrap() });
+
+        let (mut socket, _) = connect_async(format!("ws://{addr}/ws")).await.unwrap();
+
+        socket.send(Message::Binary(b"early".to_vec())).await.unwrap();
+        let reply = socket.next().await.unwrap().unwrap();
+        assert!(reply.to_text().unwrap().contains("not configured"));
+
+        let config = r#"{"op":"encode","seed":"c2VlZA==","compression":"lz4"}"#;
+        socket.send(Message::Text(config.to_string())).await.unwrap();
+        assert!(socket.next().await.unwrap().unwrap().to_text().unwrap().contains("ready"));
+
+        socket.send(Message::Binary(b"log line".to_vec())).await.unwrap();
+        let encoded = socket.next().await.unwrap().unwrap().into_data();
+        let decoded = gxcore_core::decode(&encoded, b"seed", gxcore_core::CompressionAlgorithm::Lz4).unwrap();
+        assert_eq!(decoded, b"log line");
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use utoipa::ToSchema;
+
+/// One operation: who did what with which key, never payloads or seeds
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct AuditEvent {
+    /// Unix seconds
+    pub timestamp: u64,
+    /// Token subject, `key:` and the hashed API key, or `anonymous`
+    pub actor: String,
+    pub tenant: Option<String>,
+    /// Method and route template, such as `POST /v1/encode`
+    pub operation: String,
+    pub key_id: Option<String>,
+    /// From `Content-Length`, absent for chunked bodies
+    pub request_bytes: Option<u64>,
+    /// Absent for streamed responses
+    pub response_bytes: Option<u64>,
+    pub status: u16,
+}
+
+impl AuditEvent {
+    pub fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    }
+}
+
+/// Append-only destination of audit events
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn write(&self, event: &AuditEvent) -> Result<(), String>;
+}
+
+/// JSON lines appended to a file
+pub struct FileSink {
+    file: tokio::sync::Mutex<tokio::fs::File>,
+}
+
+impl FileSink {
+    pub async fn open(path: &PathBuf) -> Result<Self, String> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|e| format!("Cannot open audit log {}: {e}", path.display()))?;
+        Ok(FileSink {
+            file: tokio::sync::Mutex::new(file),
+        })
+    }
+}
+
+#[async_trait]
+impl AuditSink for FileSink {
+    async fn write(&self, event: &AuditEvent) -> Result<(), String> {
+        let mut line = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+        line.push(b'\n');
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await.map_err(|e| format!("Cannot write audit log: {e}"))?;
+        file.sync_data().await.map_err(|e| format!("Cannot write audit log: {e}"))
+    }
+}
+
+/// RFC 5424 messages over UDP with the event as JSON message
+pub struct SyslogSink {
+    socket: tokio::net::UdpSocket,
+    address: SocketAddr,
+}
+
+impl SyslogSink {
+    pub async fn connect(address: SocketAddr) -> Result<Self, String> {
+        let local = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = tokio::net::UdpSocket::bind(local)
+            .await
+            .map_err(|e| format!("Cannot open syslog socket: {e}"))?;
+        Ok(SyslogSink { socket, address })
+    }
+}
+
+#[async_trait]
+impl AuditSink for SyslogSink {
+    async fn write(&self, event: &AuditEvent) -> Result<(), String> {
+        let json = serde_json::to_string(event).map_err(|e| e.to_string())?;
+        // authpriv.info; the timestamp travels in the message
+        let message = format!("<86>1 - - gxcore - audit - {json}");
+        self.socket
+            .send_to(message.as_bytes(), self.address)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Cannot send to syslog: {e}"))
+    }
+}
+
+/// Each event POSTed as JSON
+pub struct HttpSink {
+    url: String,
+    bearer_token: Option<String>,
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl AuditSink for HttpSink {
+    async fn write(&self, event: &AuditEvent) -> Result<(), String> {
+        let mut request = self.client.post(&self.url).json(event);
+        if let Some(token) = &self.bearer_token {
+            request = request.bearer_auth(token);
+        }
+        request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map(|_| ())
+            .map_err(|e| format!("Cannot deliver audit event: {e}"))
+    }
+}
+
+/// Sink selection, tagged by `kind`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditSinkConfig {
+    File { path: PathBuf },
+    Syslog { address: SocketAddr },
+    Http { url: String, bearer_token: Option<String> },
+}
+
+fn default_retain() -> usize {
+    10_000
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AuditConfig {
+    #[serde(flatten)]
+    pub sink: AuditSinkConfig,
+    /// Recent events kept in memory for the admin query endpoint
+    #[serde(default = "default_retain")]
+    pub retain: usize,
+}
+
+impl AuditConfig {
+    /// Open the sink and start writing to it; must run inside the runtime
+    pub async fn build(&self) -> Result<AuditLog, String> {
+        let sink: Arc<dyn AuditSink> = match &self.sink {
+            AuditSinkConfig::File { path } => Arc::new(FileSink::open(path).await?),
+            AuditSinkConfig::Syslog { address } => Arc::new(SyslogSink::connect(*address).await?),
+            AuditSinkConfig::Http { url, bearer_token } => Arc::new(HttpSink {
+                url: url.clone(),
+                bearer_token: bearer_token.clone(),
+                client: reqwest::Client::new(),
+            }),
+        };
+        Ok(AuditLog::new(sink, self.retain))
+    }
+}
+
+/// Filters of `AuditLog::query`, each ignored when absent
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub tenant: Option<String>,
+    pub key_id: Option<String>,
+    /// Substring of the operation, such as `decode`
+    pub operation: Option<String>,
+    /// Unix seconds, inclusive
+    pub since: Option<u64>,
+}
+
+impl AuditQuery {
+    fn matches(&self, event: &AuditEvent) -> bool {
+        self.actor.as_ref().is_none_or(|actor| *actor == event.actor)
+            && self.tenant.as_ref().is_none_or(|tenant| event.tenant.as_ref() == Some(tenant))
+            && self.key_id.as_ref().is_none_or(|id| event.key_id.as_ref() == Some(id))
+            && self.operation.as_ref().is_none_or(|op| event.operation.contains(op.as_str()))
+            && self.since.is_none_or(|since| event.timestamp >= since)
+    }
+}
+
+/// Events written in order by a background task, so requests never wait on the sink
+pub struct AuditLog {
+    sender: mpsc::UnboundedSender<AuditEvent>,
+    recent: Mutex<VecDeque<AuditEvent>>,
+    retain: usize,
+}
+
+impl AuditLog {
+    pub fn new(sink: Arc<dyn AuditSink>, retain: usize) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<AuditEvent>();
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                if let Err(e) = sink.write(&event).await {
+                    eprintln!("Audit event lost: {e}");
+                }
+            }
+        });
+        AuditLog {
+            sender,
+            recent: Mutex::new(VecDeque::new()),
+            retain,
+        }
+    }
+
+    pub fn record(&self, event: AuditEvent) {
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() >= self.retain {
+            recent.pop_front();
+        }
+        recent.push_back(event.clone());
+        let _ = self.sender.send(event);
+    }
+
+    /// Retained events matching `query`, newest first
+    pub fn query(&self, query: &AuditQuery, limit: usize) -> Vec<AuditEvent> {
+        let recent = self.recent.lock().unwrap();
+        recent.iter().rev().filter(|event| query.matches(event)).take(limit).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(actor: &str, key_id: Option<&str>) -> AuditEvent {
+        AuditEvent {
+            timestamp: 1,
+            actor: actor.to_string(),
+            tenant: None,
+            operation: "POST /v1/encode".to_string(),
+            key_id: key_id.map(str::to_string),
+            request_bytes: Some(3),
+            response_bytes: None,
+            status: 200,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_sink_appends() {
+        let path = std::env::temp_dir().join(format!("gx-audit-{}.log", std::process::id()));
+        let config = AuditConfig {
+            sink: AuditSinkConfig::File { path: path.clone() },
+            retain: 2,
+        };
+        let log = config.build().await.unwrap();
+        log.record(event("a", Some("billing")));
+        log.record(event("b", None));
+        log.record(event("a", None));
+
+        // Only the latest two are retained for queries
+        let query = AuditQuery {
+            actor: Some("a".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(log.query(&query, 10).len(), 1);
+
+        let mut lines = 0;
+        for _ in 0..100 {
+            lines = std::fs::read_to_string(&path).unwrap_or_default().lines().count();
+            if lines == 3 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(lines, 3);
+        std::fs::remove_file(path).unwrap();
+    }
+}
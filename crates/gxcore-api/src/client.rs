@@ -0,0 +1,132 @@
+use axum::http::{self, Extensions, HeaderValue};
+use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH};
+use reqwest::{Request, Response, ResponseBuilderExt};
+use reqwest_middleware::{Middleware, Next, Result};
+use gxcore_core::envelope::Envelope;
+use gxcore_core::CompressionAlgorithm;
+
+/// `Content-Encoding` of bodies sealed in a gxcore envelope
+pub const CONTENT_ENCODING_GXCORE: &str = "gxcore";
+
+fn failure(message: String) -> reqwest_middleware::Error {
+    reqwest_middleware::Error::middleware(std::io::Error::other(message))
+}
+
+/// `reqwest-middleware` layer sealing request bodies and opening responses sent with `Content-Encoding: gxcore`
+pub struct GxMiddleware {
+    seed: Vec<u8>,
+    key_id: Option<String>,
+    compression: CompressionAlgorithm,
+}
+
+impl GxMiddleware {
+    pub fn new(seed: &[u8]) -> Self {
+        GxMiddleware {
+            seed: seed.to_vec(),
+            key_id: None,
+            compression: CompressionAlgorithm::None,
+        }
+    }
+
+    /// Name the server-held key matching `seed` in sealed envelopes
+    pub fn with_key_id(mut self, key_id: &str) -> Self {
+        self.key_id = Some(key_id.to_string());
+        self
+    }
+
+    pub fn with_compression(mut self, compression: CompressionAlgorithm) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    fn seal(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut envelope = Envelope::seal(data, &self.seed, self.compression);
+        if let Some(key_id) = &self.key_id {
+            envelope = envelope.with_key_id(key_id);
+        }
+        envelope
+            .authenticate(&self.seed)
+            .and_then(|envelope| envelope.to_bytes())
+            .map_err(|e| failure(e.to_string()))
+    }
+
+    /// Replace a sealed response body with its payload
+    async fn open(&self, response: Response) -> Result<Response> {
+        let sealed = response
+            .headers()
+            .get(CONTENT_ENCODING)
+            .is_some_and(|value| value == CONTENT_ENCODING_GXCORE);
+        if !sealed {
+            return Ok(response);
+        }
+        let mut builder = http::Response::builder()
+            .status(response.status())
+            .version(response.version())
+            .url(response.url().clone());
+        for (name, value) in response.headers() {
+            if name != CONTENT_ENCODING && name != CONTENT_LENGTH {
+                builder = builder.header(name, value);
+            }
+        }
+        let body = response.bytes().await?;
+        let envelope = Envelope::parse(&body).map_err(|e| failure(e.to_string()))?;
+        let data = envelope.open(&self.seed).map_err(|e| failure(e.to_string()))?;
+        let response = builder.body(data).map_err(|e| failure(e.to_string()))?;
+        Ok(Response::from(response))
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for GxMiddleware {
+    async fn handle(&self, mut req: Request, extensions: &mut Extensions, next: Next<'_>) -> Result<Response> {
+        if let Some(body) = req.body() {
+            // Streams cannot be sealed without buffering them, and are never sent in the clear
+            let data = body
+                .as_bytes()
+                .ok_or_else(|| failure("Streamed bodies cannot be sealed".to_string()))?;
+            let sealed = self.seal(data)?;
+            let headers = req.headers_mut();
+            headers.insert(CONTENT_ENCODING, HeaderValue::from_static(CONTENT_ENCODING_GXCORE));
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(sealed.len()));
+            *req.body_mut() = Some(sealed.into());
+        }
+        req.headers_mut()
+            .insert(ACCEPT_ENCODING, HeaderValue::from_static(CONTENT_ENCODING_GXCORE));
+        let response = next.run(req, extensions).await?;
+        self.open(response).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, http::HeaderMap, routing::post, Router};
+
+    const SEED: &[u8] = b"client-seed";
+
+    /// Echo the opened request, sealed again with `Content-Encoding: gxcore`
+    async fn echo(headers: HeaderMap, body: Bytes) -> ([(&'static str, &'static str); 1], Vec<u8>) {
+        assert_eq!(headers[CONTENT_ENCODING], CONTENT_ENCODING_GXCORE);
+        let envelope = Envelope::parse(&body).unwrap();
+        assert_eq!(envelope.key_id(), Some("svc"));
+        let mut data = envelope.open(SEED).unwrap();
+        data.extend_from_slice(b" back");
+        let sealed = Envelope::seal(&data, SEED, CompressionAlgorithm::None).to_bytes().unwrap();
+        ([("content-encoding", CONTENT_ENCODING_GXCORE)], sealed)
+    }
+
+    #[tokio::test]
+    async fn test_bodies_sealed_both_ways() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/echo", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, Router::new().route("/echo", post(echo))).await });
+
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+            .with(GxMiddleware::new(SEED).with_key_id("svc").with_compression(CompressionAlgorithm::Lz4))
+            .build();
+        let response = client.post(&url).body("ping").send().await.unwrap();
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(response.url().as_str(), url);
+        assert_eq!(response.text().await.unwrap(), "ping back");
+    }
+}
@@ -0,0 +1,365 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::HeaderValue;
+use base64::{Engine as _, engine::general_purpose};
+use figment::{
+    providers::{Env, Format, Toml},
+    Figment,
+};
+use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+use gxcore_core::CompressionAlgorithm;
+
+use crate::api::{
+    ApiConfig, AuthConfig, Authenticator, BodyLimits, CacheConfig, CorsConfig, QuotaConfig, Quotas, RateLimitConfig, RequestSigning,
+    SigningConfig, WebhookConfig,
+};
+use crate::audit::AuditConfig;
+use crate::keyring::Keyring;
+use crate::replication::{ReplicationConfig, Replicator};
+use crate::tenants::{Tenants, TenantsConfig};
+use crate::seeds::{ExternalSeeds, SeedProviderConfig};
+use crate::storage::{BlobCollector, BlobStoreConfig, StorageConfig};
+
+/// Prefix of environment overrides, with `__` separating nested keys (`GX_AUTH__ISSUER`)
+pub const ENV_PREFIX: &str = "GX_";
+
+/// Protocols served by the binary
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServeMode {
+    #[default]
+    Http,
+    Grpc,
+    Both,
+}
+
+impl ServeMode {
+    pub fn http(self) -> bool {
+        self != ServeMode::Grpc
+    }
+
+    pub fn grpc(self) -> bool {
+        self != ServeMode::Http
+    }
+}
+
+/// Certificate and private key for HTTPS
+#[derive(Clone, Debug, Deserialize)]
+pub struct TlsConfig {
+    /// PEM certificate chain
+    pub cert_path: PathBuf,
+    /// PEM private key
+    pub key_path: PathBuf,
+    /// Plain HTTP listener redirecting to HTTPS, if any
+    pub redirect_bind: Option<SocketAddr>,
+}
+
+/// Server settings, read from an optional TOML file then `GX_` environment variables
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub bind: SocketAddr,
+    pub mode: ServeMode,
+    pub grpc_bind: SocketAddr,
+    pub tls: Option<TlsConfig>,
+    pub limits: BodyLimits,
+    pub rate_limit: RateLimitConfig,
+    /// Daily and monthly usage limits per client, reported by `/v1/usage`
+    pub quota: QuotaConfig,
+    /// Nonce and timestamp signatures refusing replayed requests
+    pub signing: SigningConfig,
+    /// Compression for requests that do not name one
+    pub default_compression: CompressionAlgorithm,
+    pub cors: CorsConfig,
+    /// Encode result cache with `ETag` revalidation
+    pub cache: CacheConfig,
+    /// Secret signing job callbacks, best set through `GX_WEBHOOKS__SECRET`
+    pub webhooks: WebhookConfig,
+    /// Bearer token validation, disabled when absent
+    pub auth: Option<AuthConfig>,
+    /// Serve the admin routes without `auth`, to anyone reaching the port; for local development
+    pub unauthenticated_admin: bool,
+    /// Keyring file of named seeds, created on first change; in memory when absent
+    pub keyring: Option<PathBuf>,
+    /// Base64 32-byte key encrypting the keyring file, best set through `GX_KEYRING_KEY`
+    pub keyring_key: Option<String>,
+    /// KMS or Vault holding seeds referenced by key id, so they never appear in config
+    pub seed_provider: Option<SeedProviderConfig>,
+    /// Tenants named by the `auth.tenant_claim` claim, each with its own keyring and blobs
+    pub tenants: Option<TenantsConfig>,
+    /// Append-only record of encode, decode and key operations
+    pub audit: Option<AuditConfig>,
+    /// Object storage served through `/v1/objects`
+    pub storage: Option<StorageConfig>,
+    /// Content-addressed blobs served through `/v1/blobs`
+    pub blobs: Option<BlobStoreConfig>,
+    /// Serve blobs and key metadata to replicas, or pull them from an upstream server
+    pub replication: Option<ReplicationConfig>,
+    /// Kubo node pinning blobs for `?store=ipfs`
+    #[cfg(feature = "ipfs")]
+    pub ipfs: Option<crate::ipfs::IpfsConfig>,
+    /// Time given to in-flight requests after SIGTERM before connections are dropped
+    pub shutdown_timeout_secs: u64,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            bind: SocketAddr::from(([0, 0, 0, 0], 3000)),
+            mode: ServeMode::default(),
+            grpc_bind: SocketAddr::from(([0, 0, 0, 0], 50051)),
+            tls: None,
+            limits: BodyLimits::default(),
+            rate_limit: RateLimitConfig::default(),
+            quota: QuotaConfig::default(),
+            signing: SigningConfig::default(),
+            default_compression: CompressionAlgorithm::None,
+            cors: CorsConfig::default(),
+            cache: CacheConfig::default(),
+            webhooks: WebhookConfig::default(),
+            auth: None,
+            unauthenticated_admin: false,
+            keyring: None,
+            keyring_key: None,
+            seed_provider: None,
+            tenants: None,
+            audit: None,
+            storage: None,
+            blobs: None,
+            replication: None,
+            #[cfg(feature = "ipfs")]
+            ipfs: None,
+            shutdown_timeout_secs: 30,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Sources in increasing priority: defaults, the file at `path`, the environment
+    pub fn figment(path: Option<&Path>) -> Figment {
+        let mut figment = Figment::new();
+        if let Some(path) = path {
+            figment = figment.merge(Toml::file_exact(path));
+        }
+        figment.merge(Env::prefixed(ENV_PREFIX).split("__"))
+    }
+
+    /// Load and validate, failing on the first invalid setting
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let config: AppConfig = Self::figment(path).extract().map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.default_compression == CompressionAlgorithm::Huffman {
+            return Err("default_compression must be none, lz4 or brotli".to_string());
+        }
+        let limits = &self.limits;
+        if [limits.structured, limits.raw, limits.file, limits.job, limits.stream].contains(&0) {
+            return Err("Body limits must be positive".to_string());
+        }
+        for limit in [self.rate_limit.default, self.rate_limit.benchmark] {
+            if limit.per_second <= 0.0 || limit.burst == 0 {
+                return Err("Rate limits must be positive".to_string());
+            }
+        }
+        for origin in &self.cors.allowed_origins {
+            let scheme = origin.starts_with("http://") || origin.starts_with("https://");
+            if !scheme || HeaderValue::from_str(origin).is_err() {
+                return Err(format!("Invalid CORS origin: {origin}"));
+            }
+        }
+        if let Some(tls) = &self.tls {
+            for path in [&tls.cert_path, &tls.key_path] {
+                if !path.is_file() {
+                    return Err(format!("TLS file not found: {}", path.display()));
+                }
+            }
+            if tls.redirect_bind == Some(self.bind) {
+                return Err("tls.redirect_bind must differ from bind".to_string());
+            }
+        }
+        if self.webhooks.secret.as_ref().is_some_and(String::is_empty) || self.webhooks.attempts == 0 {
+            return Err("webhooks.secret must not be empty and webhooks.attempts must be positive".to_string());
+        }
+        let signing = &self.signing;
+        if signing.enabled && (signing.keys.is_empty() || signing.keys.values().any(String::is_empty)) {
+            return Err("signing needs keys with non-empty secrets".to_string());
+        }
+        if signing.window_secs == 0 || signing.max_body == 0 {
+            return Err("signing.window_secs and signing.max_body must be positive".to_string());
+        }
+        if let Some(auth) = &self.auth {
+            if auth.issuer.is_empty() {
+                return Err("auth.issuer is required".to_string());
+            }
+            if auth.hs256_secret.is_none() && auth.jwks_url.is_none() && auth.jwks_path.is_none() {
+                return Err("auth needs hs256_secret, jwks_url or jwks_path".to_string());
+            }
+        }
+        if let Some(dir) = self.keyring.as_ref().and_then(|path| path.parent())
+            && !dir.as_os_str().is_empty()
+            && !dir.is_dir()
+        {
+            return Err(format!("Keyring directory not found: {}", dir.display()));
+        }
+        self.keyring_master_key()?;
+        if self.audit.as_ref().is_some_and(|audit| audit.retain == 0) {
+            return Err("audit.retain must be positive".to_string());
+        }
+        if self.blobs.as_ref().is_some_and(|blobs| blobs.default_ttl_secs == Some(0)) {
+            return Err("blobs.default_ttl_secs must be positive".to_string());
+        }
+        if let Some(replication) = &self.replication {
+            if replication.token.is_empty() {
+                return Err("replication.token must not be empty".to_string());
+            }
+            if replication.upstream.is_some() && self.blobs.is_none() {
+                return Err("replication.upstream needs [blobs] to copy into".to_string());
+            }
+        }
+        if let Some(provider) = &self.seed_provider
+            && provider.keys.is_empty()
+        {
+            return Err("seed_provider.keys is empty".to_string());
+        }
+        Ok(())
+    }
+
+    fn keyring_master_key(&self) -> Result<Option<[u8; 32]>, String> {
+        let Some(key) = &self.keyring_key else {
+            return Ok(None);
+        };
+        let bytes = general_purpose::STANDARD
+            .decode(key)
+            .map_err(|_| "keyring_key must be base64".to_string())?;
+        bytes
+            .try_into()
+            .map(Some)
+            .map_err(|_| "keyring_key must be 32 bytes".to_string())
+    }
+
+    /// HTTP API settings, fetching JWKS documents if auth is enabled
+    pub async fn api_config(&self, shutdown: CancellationToken) -> Result<ApiConfig, String> {
+        let auth = match &self.auth {
+            Some(auth) => Some(Arc::new(Authenticator::load(auth).await?)),
+            None => None,
+        };
+        let mut keyring = match &self.keyring {
+            Some(path) => Keyring::open(path, self.keyring_master_key()?)?,
+            None => Keyring::in_memory(),
+        };
+        if let Some(provider) = &self.seed_provider {
+            let external = ExternalSeeds::from_config(provider)?;
+            external.preload().await?;
+            keyring = keyring.with_external(external);
+        }
+        Ok(ApiConfig {
+            auth,
+            unauthenticated_admin: self.unauthenticated_admin,
+            rate_limit: Some(self.rate_limit.clone()),
+            quotas: self.quota.enabled.then(|| Arc::new(Quotas::new(self.quota.clone()))),
+            signing: self.signing.enabled.then(|| Arc::new(RequestSigning::new(self.signing.clone()))),
+            limits: self.limits,
+            cors: self.cors.clone(),
+            default_compression: self.default_compression,
+            shutdown,
+            keyring: Arc::new(keyring),
+            cache: self.cache,
+            webhooks: self.webhooks.clone(),
+            tenants: match &self.tenants {
+                Some(TenantsConfig { dir: Some(dir) }) => Some(Arc::new(Tenants::open(dir, self.keyring_master_key()?)?)),
+                Some(TenantsConfig { dir: None }) => Some(Arc::new(Tenants::in_memory())),
+                None => None,
+            },
+            audit: match &self.audit {
+                Some(audit) => Some(Arc::new(audit.build().await?)),
+                None => None,
+            },
+            storage: self.storage.as_ref().map(StorageConfig::build).transpose()?,
+            blobs: self.blobs.as_ref().map(BlobStoreConfig::build).transpose()?,
+            replication_token: self.replication.as_ref().map(|replication| replication.token.clone()),
+            #[cfg(feature = "ipfs")]
+            ipfs: self.ipfs.clone().map(|config| Arc::new(crate::ipfs::Ipfs::new(config))),
+        })
+    }
+
+    /// Replicator pulling into `api`'s blob store and keyring when an upstream is configured
+    pub fn replicator(&self, api: &ApiConfig) -> Option<Replicator> {
+        let replication = self.replication.as_ref()?;
+        let upstream = replication.upstream.clone()?;
+        let blobs = api.blobs.clone()?;
+        Some(Replicator::new(replication, upstream, blobs, api.keyring.clone()))
+    }
+
+    /// Collector of `api`'s expired blobs, unless blobs are not configured or collection is off
+    pub fn blob_collector(&self, api: &ApiConfig) -> Option<BlobCollector> {
+        let interval = self.blobs.as_ref()?.gc_interval_secs;
+        let blobs = api.blobs.clone()?;
+        (interval > 0).then(|| BlobCollector::new(blobs, Duration::from_secs(interval)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Result<AppConfig, String> {
+        let config: AppConfig = Figment::new()
+            .merge(Toml::string(toml))
+            .extract()
+            .map_err(|e| e.to_string())?;
+        config.validate().map(|_| config)
+    }
+
+    #[test]
+    fn test_file_overrides_defaults() {
+        let config = parse(
+            r#"
+            bind = "127.0.0.1:8080"
+            default_compression = "lz4"
+
+            [limits]
+            structured = 1024
+
+            [rate_limit]
+            enabled = false
+
+            [cors]
+            allowed_origins = ["https://gxcore.io"]
+
+            [seed_provider]
+            kind = "gcp_kms"
+            key_name = "projects/gx/locations/global/keyRings/gx/cryptoKeys/seeds"
+            fetch = "request"
+            keys = { billing = "Y2lwaGVydGV4dA==" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.bind.port(), 8080);
+        assert_eq!(config.default_compression, CompressionAlgorithm::Lz4);
+        assert_eq!(config.limits.structured, 1024);
+        assert_eq!(config.limits.raw, BodyLimits::default().raw);
+        assert!(!config.rate_limit.enabled);
+        assert_eq!(config.mode, ServeMode::Http);
+        let provider = config.seed_provider.unwrap();
+        assert!(matches!(provider.provider, crate::seeds::ProviderConfig::GcpKms(_)));
+        assert_eq!(provider.keys["billing"], "Y2lwaGVydGV4dA==");
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(parse(r#"default_compression = "huffman""#).is_err());
+        assert!(parse("[cors]\nallowed_origins = [\"gxcore.io\"]").is_err());
+        assert!(parse("[auth]\nissuer = \"https://idp\"").is_err());
+        assert!(parse(r#"keyring = "/nonexistent/keys.json""#).is_err());
+        assert!(parse(r#"keyring_key = "c2hvcnQ=""#).is_err());
+        assert!(parse("[seed_provider]\nkind = \"vault\"\naddress = \"https://vault\"\nkeys = {}").is_err());
+        assert!(parse("[blobs]\nkind = \"fs\"\nroot = \"/tmp\"\ndefault_ttl_secs = 0").is_err());
+    }
+}
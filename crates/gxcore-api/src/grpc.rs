@@ -0,0 +1,117 @@
+use std::net::SocketAddr;
+
+use tonic::{Request, Response, Status};
+use gxcore_core::envelope::FORMAT_VERSION;
+use gxcore_core::proto::v1;
+use gxcore_core::{decode, encode, partial_verify, CompressionAlgorithm};
+
+use service::gxcore_server::{Gxcore, GxcoreServer};
+
+/// Service generated from `gxcore.proto` over the message types of `gxcore_core::proto`
+mod service {
+    include!(concat!(env!("OUT_DIR"), "/gxcore.v1.rs"));
+}
+
+/// gRPC implementation of the encode/decode/verify operations
+#[derive(Default)]
+pub struct GxcoreService;
+
+#[tonic::async_trait]
+impl Gxcore for GxcoreService {
+    async fn encode(&self, request: Request<v1::EncodeRequest>) -> Result<Response<v1::EncodeResponse>, Status> {
+        let request = request.into_inner();
+        let compression = CompressionAlgorithm::from(request.compression());
+        let encoded = encode(&request.data, &request.seed, compression);
+        Ok(Response::new(v1::EncodeResponse { encoded }))
+    }
+
+    async fn decode(&self, request: Request<v1::DecodeRequest>) -> Result<Response<v1::DecodeResponse>, Status> {
+        let request = request.into_inner();
+        let compression = CompressionAlgorithm::from(request.compression());
+        let decoded = decode(&request.encoded, &request.seed, compression).map_err(Status::invalid_argument)?;
+        Ok(Response::new(v1::DecodeResponse { decoded }))
+    }
+
+    async fn verify(&self, request: Request<v1::VerifyRequest>) -> Result<Response<v1::VerifyResponse>, Status> {
+        let valid = partial_verify(&request.into_inner().encoded);
+        Ok(Response::new(v1::VerifyResponse { valid }))
+    }
+
+    async fn capabilities(
+        &self,
+        _request: Request<v1::CapabilitiesRequest>,
+    ) -> Result<Response<v1::CapabilitiesResponse>, Status> {
+        let compressions = [v1::Compression::None, v1::Compression::Lz4, v1::Compression::Brotli]
+            .into_iter()
+            .map(|c| c as i32)
+            .collect();
+        Ok(Response::new(v1::CapabilitiesResponse {
+            compressions,
+            format_version: FORMAT_VERSION as u32,
+        }))
+    }
+}
+
+/// Serve the gRPC service until the process stops
+pub async fn serve(addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(GxcoreServer::new(GxcoreService))
+        .serve(addr)
+        .await
+}
+
+/// Serve until `shutdown` resolves, then let in-flight calls finish
+pub async fn serve_with_shutdown(
+    addr: SocketAddr,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(GxcoreServer::new(GxcoreService))
+        .serve_with_shutdown(addr, shutdown)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_grpc_roundtrip() {
+        let service = GxcoreService;
+        let encoded = service
+            .encode(Request::new(v1::EncodeRequest {
+                data: b"grpc data".to_vec(),
+                seed: b"seed".to_vec(),
+                compression: v1::Compression::Lz4 as i32,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .encoded;
+
+        let decoded = service
+            .decode(Request::new(v1::DecodeRequest {
+                encoded,
+                seed: b"seed".to_vec(),
+                compression: v1::Compression::Lz4 as i32,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .decoded;
+        assert_eq!(decoded, b"grpc data");
+    }
+
+    #[tokio::test]
+    async fn test_grpc_decode_error() {
+        let status = GxcoreService
+            .decode(Request::new(v1::DecodeRequest {
+                encoded: b"!!".to_vec(),
+                seed: b"seed".to_vec(),
+                compression: v1::Compression::None as i32,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,98 @@
+use serde::Deserialize;
+
+const MIB: usize = 1024 * 1024;
+
+/// Kubo node used to pin encoded blobs and fetch them by CID
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct IpfsConfig {
+    /// RPC API address, never exposed publicly since it grants admin access to the node
+    pub api_url: String,
+    /// Keep added blobs from being garbage collected
+    pub pin: bool,
+    /// Larger blobs are refused when fetched by CID
+    pub max_fetch_bytes: usize,
+}
+
+impl Default for IpfsConfig {
+    fn default() -> Self {
+        IpfsConfig {
+            api_url: "http://127.0.0.1:5001".to_string(),
+            pin: true,
+            max_fetch_bytes: 16 * MIB,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AddResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+/// Client of the Kubo RPC API
+pub struct Ipfs {
+    config: IpfsConfig,
+    client: reqwest::Client,
+}
+
+/// CIDs are base32 or base58 strings, so anything else is rejected before reaching the node
+pub fn valid_cid(cid: &str) -> bool {
+    (2..=128).contains(&cid.len()) && cid.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+impl Ipfs {
+    pub fn new(config: IpfsConfig) -> Self {
+        Ipfs {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn endpoint(&self, command: &str) -> String {
+        format!("{}/api/v0/{command}", self.config.api_url.trim_end_matches('/'))
+    }
+
+    /// Add a blob as a raw CIDv1 block, returning its CID
+    pub async fn add(&self, data: Vec<u8>) -> Result<String, String> {
+        let form = reqwest::multipart::Form::new().part("file", reqwest::multipart::Part::bytes(data));
+        let pin = if self.config.pin { "true" } else { "false" };
+        let response = self
+            .client
+            .post(self.endpoint("add"))
+            .query(&[("pin", pin), ("cid-version", "1"), ("raw-leaves", "true")])
+            .multipart(form)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("IPFS add failed: {e}"))?
+            .json::<AddResponse>()
+            .await
+            .map_err(|e| format!("Invalid IPFS add response: {e}"))?;
+        Ok(response.hash)
+    }
+
+    /// Contents of `cid`, `Err` if larger than `max_fetch_bytes`
+    pub async fn cat(&self, cid: &str) -> Result<Vec<u8>, String> {
+        if !valid_cid(cid) {
+            return Err("Invalid CID".to_string());
+        }
+        let limit = self.config.max_fetch_bytes;
+        let length = (limit + 1).to_string();
+        let data = self
+            .client
+            .post(self.endpoint("cat"))
+            .query(&[("arg", cid), ("length", &length)])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("IPFS cat failed: {e}"))?
+            .bytes()
+            .await
+            .map_err(|e| format!("IPFS cat failed: {e}"))?;
+        if data.len() > limit {
+            return Err("IPFS object too large".to_string());
+        }
+        Ok(data.to_vec())
+    }
+}
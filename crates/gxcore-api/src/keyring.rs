@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{Engine as _, engine::general_purpose};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chacha20poly1305::aead::rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::seeds::ExternalSeeds;
+
+/// Prefix of keyring files encrypted with a master key
+const ENCRYPTED_MAGIC: &[u8; 5] = b"GXKR1";
+const NONCE_LEN: usize = 12;
+/// Bytes of generated seeds
+pub const SEED_LEN: usize = 32;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+mod base64_seeds {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(seeds: &[Vec<u8>], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(seeds.iter().map(|seed| general_purpose::STANDARD.encode(seed)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Vec<u8>>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|seed| general_purpose::STANDARD.decode(seed).map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct KeyRecord {
+    /// Every version of the seed, newest last
+    #[serde(with = "base64_seeds")]
+    seeds: Vec<Vec<u8>>,
+    #[serde(default)]
+    disabled: bool,
+    created_at: u64,
+    rotated_at: Option<u64>,
+}
+
+/// Public view of a key, without its seeds
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyInfo {
+    pub id: String,
+    pub version: usize,
+    pub disabled: bool,
+    pub created_at: u64,
+    pub rotated_at: Option<u64>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct KeyFile {
+    keys: BTreeMap<String, KeyRecord>,
+}
+
+/// Named seeds held by the server, so clients reference a `key_id` instead of sending seeds.
+/// Rotation adds a seed version: new data uses the latest, older versions still decode.
+/// Disabled keys can no longer encode.
+#[derive(Default)]
+pub struct Keyring {
+    keys: RwLock<BTreeMap<String, KeyRecord>>,
+    path: Option<PathBuf>,
+    master_key: Option<[u8; 32]>,
+    /// Seeds held by a KMS or Vault, never written to the keyring file
+    external: Option<ExternalSeeds>,
+}
+
+impl Keyring {
+    /// Keyring that is lost on restart
+    pub fn in_memory() -> Self {
+        Keyring::default()
+    }
+
+    /// Load the keyring at `path`, created on first change if missing.
+    /// With a master key the file is encrypted with ChaCha20-Poly1305.
+    pub fn open(path: &Path, master_key: Option<[u8; 32]>) -> Result<Self, &'static str> {
+        let keys = match fs::read(path) {
+            Ok(bytes) => {
+                let json = match &master_key {
+                    Some(key) => decrypt(&bytes, key)?,
+                    None if bytes.starts_with(ENCRYPTED_MAGIC) => return Err("Keyring is encrypted"),
+                    None => bytes,
+                };
+                serde_json::from_slice::<KeyFile>(&json)
+                    .map_err(|_| "Invalid keyring file")?
+                    .keys
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(_) => return Err("Failed to read keyring"),
+        };
+        Ok(Keyring {
+            keys: RwLock::new(keys),
+            path: Some(path.to_path_buf()),
+            master_key,
+            external: None,
+        })
+    }
+
+    /// Also serve the key ids of an external seed provider
+    pub fn with_external(mut self, external: ExternalSeeds) -> Self {
+        self.external = Some(external);
+        self
+    }
+
+    pub fn external(&self) -> Option<&ExternalSeeds> {
+        self.external.as_ref()
+    }
+
+    fn persist(&self, keys: &BTreeMap<String, KeyRecord>) -> Result<(), &'static str> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = KeyFile { keys: keys.clone() };
+        let json = serde_json::to_vec_pretty(&file).map_err(|_| "Failed to serialize keyring")?;
+        let bytes = match &self.master_key {
+            Some(key) => encrypt(&json, key),
+            None => json,
+        };
+        // Write then rename so a crash never leaves a truncated keyring
+        let tmp = path.with_extension("tmp");
+        write_private(&tmp, &bytes).map_err(|_| "Failed to write keyring")?;
+        fs::rename(&tmp, path).map_err(|_| "Failed to write keyring")
+    }
+
+    /// Apply `change` to a copy of the keys and keep it only once persisted, so a failed write
+    /// leaves the keys served matching the file
+    fn update<T>(
+        &self,
+        change: impl FnOnce(&mut BTreeMap<String, KeyRecord>) -> Result<T, &'static str>,
+    ) -> Result<T, &'static str> {
+        let mut keys = self.keys.write().unwrap();
+        let mut updated = keys.clone();
+        let result = change(&mut updated)?;
+        self.persist(&updated)?;
+        *keys = updated;
+        Ok(result)
+    }
+
+    fn info(id: &str, record: &KeyRecord) -> KeyInfo {
+        KeyInfo {
+            id: id.to_string(),
+            version: record.seeds.len(),
+            disabled: record.disabled,
+            created_at: record.created_at,
+            rotated_at: record.rotated_at,
+        }
+    }
+
+    pub fn list(&self) -> Vec<KeyInfo> {
+        let keys = self.keys.read().unwrap();
+        keys.iter().map(|(id, record)| Self::info(id, record)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add a key with `seed`, or a random one
+    pub fn add(&self, id: &str, seed: Option<Vec<u8>>) -> Result<KeyInfo, &'static str> {
+        if !valid_id(id) {
+            return Err("Invalid key id");
+        }
+        if seed.as_ref().is_some_and(Vec::is_empty) {
+            return Err("Empty seed");
+        }
+        self.update(|keys| {
+            if keys.contains_key(id) || self.external.as_ref().is_some_and(|external| external.contains(id)) {
+                return Err("Key already exists");
+            }
+            let record = KeyRecord {
+                seeds: vec![seed.unwrap_or_else(generate_seed)],
+                disabled: false,
+                created_at: now(),
+                rotated_at: None,
+            };
+            let info = Self::info(id, &record);
+            keys.insert(id.to_string(), record);
+            Ok(info)
+        })
+    }
+
+    /// Add a new random seed version used for all further encodes
+    pub fn rotate(&self, id: &str) -> Result<KeyInfo, &'static str> {
+        self.update(|keys| {
+            let record = keys.get_mut(id).ok_or("Unknown key")?;
+            record.seeds.push(generate_seed());
+            record.rotated_at = Some(now());
+            Ok(Self::info(id, record))
+        })
+    }
+
+    pub fn set_disabled(&self, id: &str, disabled: bool) -> Result<KeyInfo, &'static str> {
+        self.update(|keys| {
+            let record = keys.get_mut(id).ok_or("Unknown key")?;
+            record.disabled = disabled;
+            Ok(Self::info(id, record))
+        })
+    }
+
+    /// Latest seed of an enabled key
+    pub fn encoding_seed(&self, id: &str) -> Result<Vec<u8>, &'static str> {
+        let keys = self.keys.read().unwrap();
+        let record = keys.get(id).ok_or("Unknown key")?;
+        if record.disabled {
+            return Err("Key is disabled");
+        }
+        Ok(record.seeds.last().cloned().unwrap_or_default())
+    }
+
+    /// Every seed version of a key, newest first
+    pub fn decoding_seeds(&self, id: &str) -> Result<Vec<Vec<u8>>, &'static str> {
+        let keys = self.keys.read().unwrap();
+        let record = keys.get(id).ok_or("Unknown key")?;
+        Ok(record.seeds.iter().rev().cloned().collect())
+    }
+}
+
+/// Ids of keys and tenants: ASCII letters, digits, `-`, `_` and `.`
+pub fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Write `bytes` to a new file at `path` readable only by its owner, as it may hold plaintext seeds
+fn write_private(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    // A file left by an earlier attempt keeps its permissions when reopened
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Random seed from the operating system
+pub fn generate_seed() -> Vec<u8> {
+    let mut seed = vec![0u8; SEED_LEN];
+    OsRng.fill_bytes(&mut seed);
+    seed
+}
+
+fn encrypt(plaintext: &[u8], key: &[u8; 32]) -> Vec<u8> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, plaintext).expect("Keyring encryption failed");
+    [ENCRYPTED_MAGIC.as_slice(), nonce.as_slice(), &ciphertext].concat()
+}
+
+fn decrypt(bytes: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, &'static str> {
+    let body = bytes.strip_prefix(ENCRYPTED_MAGIC).ok_or("Keyring is not encrypted")?;
+    if body.len() < NONCE_LEN {
+        return Err("Invalid keyring file");
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Wrong keyring key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate_and_disable() {
+        let keyring = Keyring::in_memory();
+        keyring.add("billing", Some(b"v1".to_vec())).unwrap();
+        assert_eq!(keyring.add("billing", None), Err("Key already exists"));
+
+        let info = keyring.rotate("billing").unwrap();
+        assert_eq!(info.version, 2);
+        let seeds = keyring.decoding_seeds("billing").unwrap();
+        assert_eq!(seeds[1], b"v1");
+        assert_eq!(keyring.encoding_seed("billing").unwrap(), seeds[0]);
+
+        keyring.set_disabled("billing", true).unwrap();
+        assert_eq!(keyring.encoding_seed("billing"), Err("Key is disabled"));
+        assert!(keyring.decoding_seeds("billing").is_ok());
+        assert_eq!(keyring.add("empty", Some(Vec::new())), Err("Empty seed"));
+    }
+
+    #[test]
+    fn test_failed_write_keeps_keys_unchanged() {
+        let dir = std::env::temp_dir().join(format!("gx-keyring-dir-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("keys.json");
+        let keyring = Keyring::open(&path, None).unwrap();
+        keyring.add("app", Some(b"seed".to_vec())).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        // The temporary file cannot be created while a directory holds its name
+        fs::create_dir_all(path.with_extension("tmp").join("blocker")).unwrap();
+        assert_eq!(keyring.rotate("app"), Err("Failed to write keyring"));
+        assert_eq!(keyring.add("other", None), Err("Failed to write keyring"));
+        assert_eq!(keyring.decoding_seeds("app").unwrap().len(), 1);
+        assert_eq!(keyring.encoding_seed("other"), Err("Unknown key"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_file() {
+        let path = std::env::temp_dir().join(format!("gx-keyring-{}.json", std::process::id()));
+        let master = [7u8; 32];
+        Keyring::open(&path, Some(master)).unwrap().add("app", Some(b"seed".to_vec())).unwrap();
+
+        assert!(fs::read(&path).unwrap().starts_with(ENCRYPTED_MAGIC));
+        assert_eq!(Keyring::open(&path, None).err(), Some("Keyring is encrypted"));
+        assert_eq!(Keyring::open(&path, Some([8u8; 32])).err(), Some("Wrong keyring key"));
+        let reopened = Keyring::open(&path, Some(master)).unwrap();
+        assert_eq!(reopened.encoding_seed("app").unwrap(), b"seed");
+        fs::remove_file(path).unwrap();
+    }
+}
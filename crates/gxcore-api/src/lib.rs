@@ -0,0 +1,22 @@
+pub mod api;
+pub mod audit;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "server-bin")]
+pub mod config;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "ipfs")]
+pub mod ipfs;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod keyring;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod replication;
+pub mod seeds;
+#[cfg(feature = "server-bin")]
+pub mod server;
+mod sigv4;
+pub mod storage;
+pub mod tenants;
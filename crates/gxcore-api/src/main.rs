@@ -0,0 +1,68 @@
+use gxcore_api::api::create_router_with;
+use gxcore_api::config::AppConfig;
+use gxcore_api::server::{serve_http, shutdown_signal};
+use std::env;
+use std::path::PathBuf;
+use tokio_util::sync::CancellationToken;
+
+/// Config file from `--config <path>`, falling back to `GX_CONFIG`
+fn config_path() -> Option<PathBuf> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return Some(args.next().expect("--config requires a path").into());
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.into());
+        }
+    }
+    env::var_os("GX_CONFIG").map(PathBuf::from)
+}
+
+#[tokio::main]
+async fn main() {
+    let config = match AppConfig::load(config_path().as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid configuration: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    #[cfg(not(feature = "grpc"))]
+    assert!(!config.mode.grpc(), "mode {:?} requires the grpc feature", config.mode);
+
+    let shutdown = CancellationToken::new();
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown_signal().await;
+            println!("Shutting down, draining in-flight requests");
+            shutdown.cancel();
+        }
+    });
+
+    #[cfg(feature = "grpc")]
+    let grpc = config.mode.grpc().then(|| {
+        println!("gRPC running on {}", config.grpc_bind);
+        let shutdown = shutdown.clone().cancelled_owned();
+        tokio::spawn(gxcore_api::grpc::serve_with_shutdown(config.grpc_bind, shutdown))
+    });
+
+    if config.mode.http() {
+        let api = config.api_config(shutdown.clone()).await.expect("Invalid auth configuration");
+        if let Some(replicator) = config.replicator(&api) {
+            tokio::spawn(replicator.run(shutdown.clone()));
+        }
+        if let Some(collector) = config.blob_collector(&api) {
+            tokio::spawn(collector.run(shutdown.clone()));
+        }
+        let app = create_router_with(api);
+        serve_http(&config, app, shutdown).await.expect("HTTP server failed");
+    }
+
+    #[cfg(feature = "grpc")]
+    if let Some(grpc) = grpc {
+        grpc.await.unwrap().unwrap();
+    }
+}
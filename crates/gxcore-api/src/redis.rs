@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use ::redis::aio::ConnectionManager;
+use ::redis::AsyncCommands;
+
+use gxcore_core::envelope::Envelope;
+use gxcore_core::CompressionAlgorithm;
+
+/// Redis values encoded with a seed before they are stored and decoded when read back.
+/// Keys are stored as given, so they must not carry anything sensitive themselves.
+#[derive(Clone)]
+pub struct GxRedis {
+    connection: ConnectionManager,
+    seed: Vec<u8>,
+    compression: CompressionAlgorithm,
+    /// Expiry of values set without one
+    ttl: Option<Duration>,
+}
+
+impl GxRedis {
+    pub fn new(connection: ConnectionManager, seed: &[u8]) -> Self {
+        GxRedis {
+            connection,
+            seed: seed.to_vec(),
+            compression: CompressionAlgorithm::None,
+            ttl: None,
+        }
+    }
+
+    /// Connect to `url`, such as `redis://127.0.0.1:6379`, reconnecting when the connection drops
+    pub async fn connect(url: &str, seed: &[u8]) -> Result<Self, String> {
+        let client = ::redis::Client::open(url).map_err(|e| format!("Invalid Redis URL: {e}"))?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(|e| format!("Cannot connect to Redis: {e}"))?;
+        Ok(GxRedis::new(connection, seed))
+    }
+
+    pub fn with_compression(mut self, compression: CompressionAlgorithm) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Expire values after `ttl` unless `set_with_ttl` gives another
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub async fn set(&self, key: &str, value: &[u8]) -> Result<(), String> {
+        match self.ttl {
+            Some(ttl) => self.set_with_ttl(key, value, ttl).await,
+            None => {
+                let sealed = seal(value, &self.seed, self.compression)?;
+                self.connection.clone().set(key, sealed).await.map_err(redis_error)
+            }
+        }
+    }
+
+    /// Store `value` expiring after `ttl`, rounded up to whole milliseconds
+    pub async fn set_with_ttl(&self, key: &str, value: &[u8], ttl: Duration) -> Result<(), String> {
+        let sealed = seal(value, &self.seed, self.compression)?;
+        let millis = ttl.as_nanos().div_ceil(1_000_000).max(1) as u64;
+        self.connection.clone().pset_ex(key, sealed, millis).await.map_err(redis_error)
+    }
+
+    /// Decoded value of `key`, `None` if absent or expired
+    pub async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let sealed: Option<Vec<u8>> = self.connection.clone().get(key).await.map_err(redis_error)?;
+        sealed.map(|sealed| open(&sealed, &self.seed)).transpose()
+    }
+
+    /// Remove `key`, returning whether it existed
+    pub async fn delete(&self, key: &str) -> Result<bool, String> {
+        let removed: u64 = self.connection.clone().del(key).await.map_err(redis_error)?;
+        Ok(removed > 0)
+    }
+}
+
+fn redis_error(e: ::redis::RedisError) -> String {
+    format!("Redis request failed: {e}")
+}
+
+/// Stored form of a value: an envelope, so the compression travels with it
+fn seal(value: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Result<Vec<u8>, String> {
+    Envelope::seal(value, seed, compression)
+        .to_bytes()
+        .map_err(|e| format!("Cannot encode Redis value: {e}"))
+}
+
+fn open(sealed: &[u8], seed: &[u8]) -> Result<Vec<u8>, String> {
+    Envelope::from_bytes(sealed)
+        .and_then(|envelope| envelope.open(seed))
+        .map_err(|e| format!("Cannot decode Redis value: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_stored_encoded() {
+        let sealed = seal(b"alice@example.com", b"seed", CompressionAlgorithm::Lz4).unwrap();
+        assert!(!sealed.windows(5).any(|window| window == b"alice"));
+        assert_eq!(open(&sealed, b"seed").unwrap(), b"alice@example.com");
+        assert!(open(b"plain value", b"seed").is_err());
+    }
+}
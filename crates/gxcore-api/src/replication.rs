@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+use crate::keyring::{KeyInfo, Keyring};
+use crate::storage::BlobStore;
+
+/// Shared secret sent by replicas and checked by the upstream server
+pub const TOKEN_HEADER: &str = "x-gx-replication-token";
+
+const CURSOR_FILE: &str = "cursor";
+const KEYS_FILE: &str = "keys.json";
+const PARTIAL_DIR: &str = "partial";
+
+fn default_interval() -> u64 {
+    300
+}
+
+fn default_batch() -> usize {
+    100
+}
+
+/// Server a replica pulls blobs and key metadata from
+#[derive(Clone, Debug, Deserialize)]
+pub struct UpstreamConfig {
+    pub url: String,
+    #[serde(default = "default_interval")]
+    pub interval_secs: u64,
+    /// Cursor, key metadata and partial downloads, kept so restarts resume where they stopped
+    pub state_dir: PathBuf,
+    /// Blob keys requested per listing
+    #[serde(default = "default_batch")]
+    pub batch: usize,
+}
+
+/// Blob and key metadata exchange between servers; seeds are never sent
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReplicationConfig {
+    /// Secret both sides share, required to serve `/v1/replication`
+    pub token: String,
+    /// Pull from this server, making this one a replica
+    pub upstream: Option<UpstreamConfig>,
+}
+
+/// What one sync pass copied
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub blobs: usize,
+    pub keys: usize,
+}
+
+/// Pulls new blobs in key order and mirrors key metadata from the upstream server
+pub struct Replicator {
+    upstream: UpstreamConfig,
+    token: String,
+    blobs: BlobStore,
+    keyring: Arc<Keyring>,
+    client: reqwest::Client,
+}
+
+impl Replicator {
+    pub fn new(config: &ReplicationConfig, upstream: UpstreamConfig, blobs: BlobStore, keyring: Arc<Keyring>) -> Self {
+        Replicator {
+            upstream,
+            token: config.token.clone(),
+            blobs,
+            keyring,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/v1/replication/{path}", self.upstream.url.trim_end_matches('/'))
+    }
+
+    async fn get(&self, path: &str, query: &[(&str, String)]) -> Result<reqwest::Response, String> {
+        self.client
+            .get(self.endpoint(path))
+            .query(query)
+            .header(TOKEN_HEADER, &self.token)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Replication request failed: {e}"))
+    }
+
+    /// Sync every few `interval_secs` until `shutdown`
+    pub async fn run(self, shutdown: CancellationToken) {
+        let interval = Duration::from_secs(self.upstream.interval_secs.max(1));
+        loop {
+            if let Err(e) = self.sync_once().await {
+                eprintln!("Replication failed: {e}");
+            }
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    }
+
+    pub async fn sync_once(&self) -> Result<SyncReport, String> {
+        tokio::fs::create_dir_all(self.upstream.state_dir.join(PARTIAL_DIR))
+            .await
+            .map_err(|e| format!("Cannot create replication state: {e}"))?;
+        let keys = self.sync_keys().await?;
+        let blobs = self.sync_blobs().await?;
+        Ok(SyncReport { blobs, keys })
+    }
+
+    /// Record upstream key metadata and mirror which keys are disabled
+    async fn sync_keys(&self) -> Result<usize, String> {
+        let upstream: Vec<KeyInfo> = self
+            .get("keys", &[])
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid key metadata: {e}"))?;
+        let local: HashMap<String, KeyInfo> = self.keyring.list().into_iter().map(|info| (info.id.clone(), info)).collect();
+        for info in &upstream {
+            if local.get(&info.id).is_some_and(|local| local.disabled != info.disabled) {
+                self.keyring.set_disabled(&info.id, info.disabled)?;
+            }
+        }
+        let json = serde_json::to_vec_pretty(&upstream).map_err(|e| e.to_string())?;
+        tokio::fs::write(self.upstream.state_dir.join(KEYS_FILE), json)
+            .await
+            .map_err(|e| format!("Cannot write key metadata: {e}"))?;
+        Ok(upstream.len())
+    }
+
+    /// Copy blobs listed after the saved cursor, advancing it after each one
+    async fn sync_blobs(&self) -> Result<usize, String> {
+        let cursor_path = self.upstream.state_dir.join(CURSOR_FILE);
+        let mut cursor = tokio::fs::read_to_string(&cursor_path).await.ok().filter(|c| !c.is_empty());
+        let mut copied = 0;
+        loop {
+            let mut query = vec![("limit", self.upstream.batch.to_string())];
+            if let Some(cursor) = &cursor {
+                query.push(("after", cursor.clone()));
+            }
+            let keys: Vec<String> = self
+                .get("blobs", &query)
+                .await?
+                .json()
+                .await
+                .map_err(|e| format!("Invalid blob listing: {e}"))?;
+            for key in &keys {
+                self.fetch(key).await?;
+                tokio::fs::write(&cursor_path, key)
+                    .await
+                    .map_err(|e| format!("Cannot save replication cursor: {e}"))?;
+                cursor = Some(key.clone());
+                copied += 1;
+            }
+            if keys.len() < self.upstream.batch {
+                return Ok(copied);
+            }
+        }
+    }
+
+    /// Download `key`, continuing a partial download, then store it once its hash checks out
+    async fn fetch(&self, key: &str) -> Result<(), String> {
+        let partial = self.upstream.state_dir.join(PARTIAL_DIR).join(key.replace('/', "_"));
+        let offset = tokio::fs::metadata(&partial).await.map_or(0, |meta| meta.len());
+        let response = self.get(&format!("blobs/{key}"), &[("offset", offset.to_string())]).await?;
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&partial)
+            .await
+            .map_err(|e| format!("Cannot write {}: {e}", partial.display()))?;
+        let mut chunks = response.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(|e| format!("Replication download failed: {e}"))?;
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| format!("Cannot write {}: {e}", partial.display()))?;
+        }
+        file.flush().await.map_err(|e| format!("Cannot write {}: {e}", partial.display()))?;
+
+        let data = tokio::fs::read(&partial)
+            .await
+            .map_err(|e| format!("Cannot read {}: {e}", partial.display()))?;
+        let stored = self.blobs.put_key(key, Bytes::from(data)).await;
+        // Dropped either way: stored, or corrupt and downloaded again by the next pass
+        let _ = tokio::fs::remove_file(&partial).await;
+        stored
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+mod aws;
+mod gcp;
+mod vault;
+
+pub use aws::AwsKms;
+pub use gcp::GcpKms;
+pub use vault::Vault;
+
+/// Source of seeds kept outside the server, unwrapped or fetched on demand
+#[async_trait]
+pub trait SeedProvider: Send + Sync {
+    /// Fetch or unwrap the seed stored under `reference`
+    async fn fetch(&self, reference: &str) -> Result<Vec<u8>, String>;
+}
+
+/// When provider seeds are retrieved
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FetchMode {
+    /// Once at startup, then kept in memory
+    #[default]
+    Startup,
+    /// On every request, so revocation takes effect immediately
+    Request,
+}
+
+/// Provider selection, tagged by `kind`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProviderConfig {
+    AwsKms(aws::AwsKmsConfig),
+    GcpKms(gcp::GcpKmsConfig),
+    Vault(vault::VaultConfig),
+}
+
+/// Externally held seeds, exposed to clients under key ids
+#[derive(Clone, Debug, Deserialize)]
+pub struct SeedProviderConfig {
+    #[serde(flatten)]
+    pub provider: ProviderConfig,
+    #[serde(default)]
+    pub fetch: FetchMode,
+    /// Key id to provider reference: a base64 ciphertext for KMS, a secret path for Vault
+    pub keys: BTreeMap<String, String>,
+}
+
+impl SeedProviderConfig {
+    pub fn build(&self) -> Result<Arc<dyn SeedProvider>, String> {
+        Ok(match &self.provider {
+            ProviderConfig::AwsKms(config) => Arc::new(AwsKms::new(config.clone())?),
+            ProviderConfig::GcpKms(config) => Arc::new(GcpKms::new(config.clone())),
+            ProviderConfig::Vault(config) => Arc::new(Vault::new(config.clone())?),
+        })
+    }
+}
+
+/// Key ids served by a provider, cached in memory in startup mode
+pub struct ExternalSeeds {
+    provider: Arc<dyn SeedProvider>,
+    references: BTreeMap<String, String>,
+    mode: FetchMode,
+    cache: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl ExternalSeeds {
+    pub fn new(provider: Arc<dyn SeedProvider>, references: BTreeMap<String, String>, mode: FetchMode) -> Self {
+        ExternalSeeds {
+            provider,
+            references,
+            mode,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &SeedProviderConfig) -> Result<Self, String> {
+        Ok(Self::new(config.build()?, config.keys.clone(), config.fetch))
+    }
+
+    pub fn contains(&self, key_id: &str) -> bool {
+        self.references.contains_key(key_id)
+    }
+
+    /// Fetch every seed up front in startup mode, failing on the first unavailable one
+    pub async fn preload(&self) -> Result<(), String> {
+        if self.mode != FetchMode::Startup {
+            return Ok(());
+        }
+        for (id, reference) in &self.references {
+            let seed = self
+                .provider
+                .fetch(reference)
+                .await
+                .map_err(|e| format!("Seed {id}: {e}"))?;
+            self.cache.write().unwrap().insert(id.clone(), seed);
+        }
+        Ok(())
+    }
+
+    /// Seed of `key_id`, `None` if this provider does not serve it
+    pub async fn seed(&self, key_id: &str) -> Option<Result<Vec<u8>, String>> {
+        let reference = self.references.get(key_id)?;
+        if let Some(seed) = self.cache.read().unwrap().get(key_id) {
+            return Some(Ok(seed.clone()));
+        }
+        let seed = self.provider.fetch(reference).await;
+        if self.mode == FetchMode::Startup
+            && let Ok(seed) = &seed
+        {
+            self.cache.write().unwrap().insert(key_id.to_string(), seed.clone());
+        }
+        Some(seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counting(AtomicUsize);
+
+    #[async_trait]
+    impl SeedProvider for Counting {
+        async fn fetch(&self, reference: &str) -> Result<Vec<u8>, String> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(reference.as_bytes().to_vec())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_modes() {
+        let references = BTreeMap::from([("app".to_string(), "secret".to_string())]);
+        for (mode, expected_fetches) in [(FetchMode::Startup, 1), (FetchMode::Request, 2)] {
+            let provider = Arc::new(Counting(AtomicUsize::new(0)));
+            let seeds = ExternalSeeds::new(provider.clone(), references.clone(), mode);
+            seeds.preload().await.unwrap();
+            assert_eq!(seeds.seed("app").await.unwrap().unwrap(), b"secret");
+            assert_eq!(seeds.seed("app").await.unwrap().unwrap(), b"secret");
+            assert!(seeds.seed("other").await.is_none());
+            assert_eq!(provider.0.load(Ordering::SeqCst), expected_fetches);
+        }
+    }
+
+    #[test]
+    fn test_config_selects_provider() {
+        let config: SeedProviderConfig = serde_json::from_value(serde_json::json!({
+            "kind": "vault",
+            "address": "https://vault.internal:8200",
+            "token": "s.token",
+            "fetch": "request",
+            "keys": {"billing": "gxcore/billing"},
+        }))
+        .unwrap();
+        assert!(matches!(config.provider, ProviderConfig::Vault(_)));
+        assert_eq!(config.fetch, FetchMode::Request);
+        assert!(config.build().is_ok());
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose};
+use serde::Deserialize;
+
+use super::SeedProvider;
+use crate::sigv4::{self, amz_date, sign, Credentials};
+
+/// AWS KMS settings; credentials come from the standard `AWS_*` environment variables
+#[derive(Clone, Debug, Deserialize)]
+pub struct AwsKmsConfig {
+    /// Region, read from `AWS_REGION` when unset
+    pub region: Option<String>,
+    /// Endpoint override, for LocalStack and VPC endpoints
+    pub endpoint: Option<String>,
+}
+
+/// Unwraps seeds encrypted with `kms:Encrypt`; references are base64 ciphertext blobs
+pub struct AwsKms {
+    region: String,
+    endpoint: String,
+    credentials: Credentials,
+    client: reqwest::Client,
+}
+
+impl AwsKms {
+    pub fn new(config: AwsKmsConfig) -> Result<Self, String> {
+        let region = config.region.or_else(sigv4::env_region).ok_or("AWS region not set")?;
+        let credentials = Credentials::from_env()?;
+        let endpoint = config
+            .endpoint
+            .unwrap_or_else(|| format!("https://kms.{region}.amazonaws.com"));
+        Ok(AwsKms {
+            region,
+            endpoint,
+            credentials,
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct DecryptResponse {
+    #[serde(rename = "Plaintext")]
+    plaintext: String,
+}
+
+#[async_trait]
+impl SeedProvider for AwsKms {
+    async fn fetch(&self, reference: &str) -> Result<Vec<u8>, String> {
+        let url = reqwest::Url::parse(&self.endpoint).map_err(|e| format!("Invalid KMS endpoint: {e}"))?;
+        let host = sigv4::host(&url).ok_or("Invalid KMS endpoint")?;
+        let body = serde_json::json!({ "CiphertextBlob": reference }).to_string();
+        let amz_date = amz_date(SystemTime::now());
+
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", host),
+            ("x-amz-date", amz_date.clone()),
+            ("x-amz-target", "TrentService.Decrypt".to_string()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+            headers.sort_by_key(|(name, _)| *name);
+        }
+        let authorization = sign(
+            &self.credentials,
+            &self.region,
+            "kms",
+            &amz_date,
+            "POST",
+            "/",
+            "",
+            &headers,
+            &sigv4::payload_hash(body.as_bytes()),
+        );
+
+        let mut request = self.client.post(url).header("authorization", authorization);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("AWS KMS request failed: {e}"))?
+            .json::<DecryptResponse>()
+            .await
+            .map_err(|e| format!("Invalid AWS KMS response: {e}"))?;
+        general_purpose::STANDARD
+            .decode(response.plaintext)
+            .map_err(|_| "Invalid AWS KMS plaintext".to_string())
+    }
+}
@@ -0,0 +1,119 @@
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose};
+use serde::Deserialize;
+
+use super::SeedProvider;
+
+const DEFAULT_ENDPOINT: &str = "https://cloudkms.googleapis.com";
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+/// Google Cloud KMS settings
+#[derive(Clone, Debug, Deserialize)]
+pub struct GcpKmsConfig {
+    /// `projects/{project}/locations/{location}/keyRings/{ring}/cryptoKeys/{key}`
+    pub key_name: String,
+    /// OAuth access token, read from `GCP_ACCESS_TOKEN` or the metadata server when unset
+    pub access_token: Option<String>,
+    /// API endpoint override, for emulators and private endpoints
+    pub endpoint: Option<String>,
+}
+
+/// Unwraps seeds encrypted under a Cloud KMS key; references are base64 ciphertexts
+pub struct GcpKms {
+    config: GcpKmsConfig,
+    client: reqwest::Client,
+}
+
+impl GcpKms {
+    pub fn new(config: GcpKmsConfig) -> Self {
+        GcpKms {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn access_token(&self) -> Result<String, String> {
+        if let Some(token) = &self.config.access_token {
+            return Ok(token.clone());
+        }
+        if let Ok(token) = std::env::var("GCP_ACCESS_TOKEN") {
+            return Ok(token);
+        }
+
+        #[derive(Deserialize)]
+        struct MetadataToken {
+            access_token: String,
+        }
+        // Tokens expire, so the metadata server is asked on every call
+        self.client
+            .get(METADATA_TOKEN_URL)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Failed to get GCP access token: {e}"))?
+            .json::<MetadataToken>()
+            .await
+            .map(|token| token.access_token)
+            .map_err(|e| format!("Invalid GCP token response: {e}"))
+    }
+}
+
+#[derive(Deserialize)]
+struct DecryptResponse {
+    plaintext: String,
+}
+
+#[async_trait]
+impl SeedProvider for GcpKms {
+    async fn fetch(&self, reference: &str) -> Result<Vec<u8>, String> {
+        let endpoint = self.config.endpoint.as_deref().unwrap_or(DEFAULT_ENDPOINT);
+        let url = format!("{}/v1/{}:decrypt", endpoint.trim_end_matches('/'), self.config.key_name);
+        let response = self
+            .client
+            .post(url)
+            .bearer_auth(self.access_token().await?)
+            .json(&serde_json::json!({ "ciphertext": reference }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("GCP KMS request failed: {e}"))?
+            .json::<DecryptResponse>()
+            .await
+            .map_err(|e| format!("Invalid GCP KMS response: {e}"))?;
+        general_purpose::STANDARD
+            .decode(response.plaintext)
+            .map_err(|_| "Invalid GCP KMS plaintext".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Path, http::HeaderMap, routing::post, Json, Router};
+
+    #[tokio::test]
+    async fn test_decrypt_request() {
+        const KEY: &str = "projects/p/locations/global/keyRings/gx/cryptoKeys/seeds";
+        let app = Router::new().route(
+            "/v1/*name",
+            post(|Path(name): Path<String>, headers: HeaderMap, Json(body): Json<serde_json::Value>| async move {
+                assert_eq!(name, format!("{KEY}:decrypt"));
+                assert_eq!(headers["authorization"], "Bearer ya29.test");
+                assert_eq!(body["ciphertext"], "d3JhcHBlZA==");
+                Json(serde_json::json!({"plaintext": "c2VlZA=="}))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let kms = GcpKms::new(GcpKmsConfig {
+            key_name: KEY.to_string(),
+            access_token: Some("ya29.test".to_string()),
+            endpoint: Some(endpoint),
+        });
+        assert_eq!(kms.fetch("d3JhcHBlZA==").await.unwrap(), b"seed");
+    }
+}
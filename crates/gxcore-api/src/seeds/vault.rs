@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose};
+use serde::Deserialize;
+
+use super::SeedProvider;
+
+fn default_mount() -> String {
+    "secret".to_string()
+}
+
+fn default_field() -> String {
+    "seed".to_string()
+}
+
+/// HashiCorp Vault KV version 2 settings
+#[derive(Clone, Debug, Deserialize)]
+pub struct VaultConfig {
+    /// Server address, e.g. `https://vault.internal:8200`
+    pub address: String,
+    /// Token, read from `VAULT_TOKEN` when unset
+    pub token: Option<String>,
+    #[serde(default = "default_mount")]
+    pub mount: String,
+    /// Secret field holding the base64 seed
+    #[serde(default = "default_field")]
+    pub field: String,
+}
+
+/// Reads seeds stored as base64 fields of KV v2 secrets; references are secret paths
+pub struct Vault {
+    config: VaultConfig,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl Vault {
+    pub fn new(config: VaultConfig) -> Result<Self, String> {
+        let token = match &config.token {
+            Some(token) => token.clone(),
+            None => std::env::var("VAULT_TOKEN").map_err(|_| "Vault token not set".to_string())?,
+        };
+        Ok(Vault {
+            config,
+            token,
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct SecretResponse {
+    data: SecretData,
+}
+
+#[derive(Deserialize)]
+struct SecretData {
+    data: serde_json::Map<String, serde_json::Value>,
+}
+
+#[async_trait]
+impl SeedProvider for Vault {
+    async fn fetch(&self, reference: &str) -> Result<Vec<u8>, String> {
+        let url = format!(
+            "{}/v1/{}/data/{}",
+            self.config.address.trim_end_matches('/'),
+            self.config.mount,
+            reference.trim_start_matches('/')
+        );
+        let secret = self
+            .client
+            .get(url)
+            .header("X-Vault-Token", &self.token)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Vault request failed: {e}"))?
+            .json::<SecretResponse>()
+            .await
+            .map_err(|e| format!("Invalid Vault response: {e}"))?;
+        let seed = secret
+            .data
+            .data
+            .get(&self.config.field)
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| format!("Secret has no `{}` field", self.config.field))?;
+        general_purpose::STANDARD
+            .decode(seed)
+            .map_err(|_| "Seed is not valid base64".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Path, http::{HeaderMap, StatusCode}, routing::get, Json, Router};
+
+    #[tokio::test]
+    async fn test_reads_kv2_secret() {
+        let app = Router::new().route(
+            "/v1/secret/data/*path",
+            get(|Path(path): Path<String>, headers: HeaderMap| async move {
+                if path != "gxcore/billing" || headers["x-vault-token"] != "s.test" {
+                    return Err(StatusCode::NOT_FOUND);
+                }
+                Ok(Json(serde_json::json!({"data": {"data": {"seed": "c2VlZA=="}, "metadata": {}}})))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let vault = Vault::new(VaultConfig {
+            address,
+            token: Some("s.test".to_string()),
+            mount: default_mount(),
+            field: default_field(),
+        })
+        .unwrap();
+        assert_eq!(vault.fetch("gxcore/billing").await.unwrap(), b"seed");
+        assert!(vault.fetch("gxcore/missing").await.is_err());
+    }
+}
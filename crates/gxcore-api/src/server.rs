@@ -0,0 +1,138 @@
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::{
+    extract::Host,
+    http::Uri,
+    response::Redirect,
+    Router,
+};
+use axum_server::{tls_rustls::RustlsConfig, Handle};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::AppConfig;
+
+/// Resolve on SIGINT or SIGTERM
+pub async fn shutdown_signal() {
+    let interrupt = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                term.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {}
+        _ = terminate => {}
+    }
+}
+
+/// Serve `app` on the configured address, over HTTPS when TLS is configured, until
+/// `shutdown` is cancelled. In-flight requests then get `shutdown_timeout_secs` to finish.
+pub async fn serve_http(config: &AppConfig, app: Router, shutdown: CancellationToken) -> io::Result<()> {
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    let deadline = Duration::from_secs(config.shutdown_timeout_secs);
+    match &config.tls {
+        Some(tls) => {
+            let rustls = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path).await?;
+            if let Some(redirect) = tls.redirect_bind {
+                tokio::spawn(serve_redirect(redirect, config.bind.port(), shutdown.clone()));
+            }
+            let handle = Handle::new();
+            tokio::spawn({
+                let handle = handle.clone();
+                async move {
+                    shutdown.cancelled().await;
+                    handle.graceful_shutdown(Some(deadline));
+                }
+            });
+            println!("API running on https://{}", config.bind);
+            axum_server::bind_rustls(config.bind, rustls).handle(handle).serve(service).await
+        }
+        None => {
+            let listener = TcpListener::bind(config.bind).await?;
+            println!("API running on http://{}", config.bind);
+            let server = axum::serve(listener, service).with_graceful_shutdown(shutdown.clone().cancelled_owned());
+            tokio::select! {
+                result = server => result,
+                _ = async {
+                    shutdown.cancelled().await;
+                    tokio::time::sleep(deadline).await;
+                } => {
+                    eprintln!("Shutdown deadline reached, dropping remaining connections");
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// HTTPS URL of the same resource on `https_port`
+fn https_location(host: &str, uri: &Uri, https_port: u16) -> String {
+    // Strip any port, keeping bracketed IPv6 hosts intact
+    let hostname = match host.rfind(':') {
+        Some(i) if !host[i..].contains(']') => &host[..i],
+        _ => host,
+    };
+    let path = uri.path_and_query().map_or("/", |p| p.as_str());
+    if https_port == 443 {
+        format!("https://{hostname}{path}")
+    } else {
+        format!("https://{hostname}:{https_port}{path}")
+    }
+}
+
+/// Plain HTTP listener answering every request with a permanent redirect to HTTPS
+async fn serve_redirect(addr: SocketAddr, https_port: u16, shutdown: CancellationToken) -> io::Result<()> {
+    let app = Router::new().fallback(move |Host(host): Host, uri: Uri| async move {
+        Redirect::permanent(&https_location(&host, &uri, https_port))
+    });
+    let listener = TcpListener::bind(addr).await?;
+    println!("Redirecting http://{} to HTTPS", addr);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown.cancelled_owned())
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::create_router;
+
+    #[test]
+    fn test_https_location() {
+        let uri: Uri = "/encode?compression=lz4".parse().unwrap();
+        assert_eq!(https_location("gxcore.io", &uri, 443), "https://gxcore.io/encode?compression=lz4");
+        assert_eq!(https_location("gxcore.io:80", &uri, 8443), "https://gxcore.io:8443/encode?compression=lz4");
+        assert_eq!(https_location("[::1]:8080", &Uri::from_static("/"), 443), "https://[::1]/");
+        assert_eq!(https_location("[::1]", &Uri::from_static("/"), 443), "https://[::1]/");
+    }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown() {
+        let config = AppConfig {
+            bind: SocketAddr::from(([127, 0, 0, 1], 0)),
+            shutdown_timeout_secs: 1,
+            ..Default::default()
+        };
+        let shutdown = CancellationToken::new();
+        let server = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { serve_http(&config, create_router(), shutdown).await }
+        });
+        shutdown.cancel();
+        let result = tokio::time::timeout(Duration::from_secs(5), server).await;
+        assert!(result.unwrap().unwrap().is_ok());
+    }
+}
@@ -0,0 +1,424 @@
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use utoipa::ToSchema;
+
+mod fs;
+mod s3;
+
+pub use fs::{FsConfig, FsStore};
+pub use s3::{S3Config, S3Store};
+
+/// Object contents, read or written in chunks so size is not bounded by memory
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, String>> + Send>>;
+
+/// Bucket and key addressed objects, such as S3
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    /// Contents of the object, `None` if it does not exist
+    async fn get(&self, bucket: &str, key: &str) -> Result<Option<ByteStream>, String>;
+
+    /// Write the object, replacing any previous version; returns the bytes written
+    async fn put(&self, bucket: &str, key: &str, body: ByteStream) -> Result<u64, String>;
+
+    /// Up to `limit` keys starting with `prefix`, in byte order, after `after` if given
+    async fn list(&self, bucket: &str, prefix: &str, after: Option<&str>, limit: usize) -> Result<Vec<String>, String> {
+        let _ = (bucket, prefix, after, limit);
+        Err("Listing not supported by this store".to_string())
+    }
+
+    /// Remove the object; removing one that does not exist succeeds
+    async fn delete(&self, bucket: &str, key: &str) -> Result<(), String> {
+        let _ = (bucket, key);
+        Err("Deletion not supported by this store".to_string())
+    }
+}
+
+/// Backend selection, tagged by `kind`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StoreConfig {
+    Fs(FsConfig),
+    S3(S3Config),
+}
+
+impl StoreConfig {
+    pub fn build(&self) -> Result<Arc<dyn ObjectStore>, String> {
+        Ok(match self {
+            StoreConfig::Fs(config) => Arc::new(FsStore::new(config.clone())),
+            StoreConfig::S3(config) => Arc::new(S3Store::from_config(config.clone())?),
+        })
+    }
+}
+
+/// Object storage the API may read from and write to
+#[derive(Clone, Debug, Deserialize)]
+pub struct StorageConfig {
+    #[serde(flatten)]
+    pub store: StoreConfig,
+    /// Buckets requests may name, any bucket the credentials reach when empty
+    #[serde(default)]
+    pub buckets: Vec<String>,
+}
+
+impl StorageConfig {
+    pub fn build(&self) -> Result<Storage, String> {
+        Ok(Storage::new(self.store.build()?, self.buckets.clone()))
+    }
+}
+
+/// An object store restricted to the configured buckets
+#[derive(Clone)]
+pub struct Storage {
+    store: Arc<dyn ObjectStore>,
+    buckets: Vec<String>,
+}
+
+impl Storage {
+    pub fn new(store: Arc<dyn ObjectStore>, buckets: Vec<String>) -> Self {
+        Storage { store, buckets }
+    }
+
+    pub fn allows(&self, bucket: &str) -> bool {
+        self.buckets.is_empty() || self.buckets.iter().any(|allowed| allowed == bucket)
+    }
+
+    pub fn store(&self) -> &dyn ObjectStore {
+        self.store.as_ref()
+    }
+}
+
+fn default_blob_bucket() -> String {
+    "blobs".to_string()
+}
+
+fn default_gc_interval() -> u64 {
+    3600
+}
+
+/// Where content-addressed blobs are kept
+#[derive(Clone, Debug, Deserialize)]
+pub struct BlobStoreConfig {
+    #[serde(flatten)]
+    pub store: StoreConfig,
+    #[serde(default = "default_blob_bucket")]
+    pub bucket: String,
+    /// Lifetime of blobs stored without a TTL of their own, forever when unset
+    #[serde(default)]
+    pub default_ttl_secs: Option<u64>,
+    /// Seconds between collections of expired blobs, never when 0
+    #[serde(default = "default_gc_interval")]
+    pub gc_interval_secs: u64,
+}
+
+impl BlobStoreConfig {
+    pub fn build(&self) -> Result<BlobStore, String> {
+        let blobs = BlobStore::new(self.store.build()?, &self.bucket);
+        Ok(blobs.with_default_ttl(self.default_ttl_secs.map(Duration::from_secs)))
+    }
+}
+
+/// Suffix of the object beside a blob holding its `BlobMeta`
+const META_SUFFIX: &str = ".meta";
+/// Keys listed at once while collecting, the most one S3 page holds
+const GC_PAGE: usize = 1000;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Expiry and references of a blob, kept only for blobs stored with a TTL or referenced; others
+/// never expire
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct BlobMeta {
+    pub size: u64,
+    /// Unix time after which the blob is collected once unreferenced, never when unset
+    pub expires_at: Option<u64>,
+    /// References held by clients, each keeping the blob past its expiry until released
+    pub refs: u64,
+}
+
+impl BlobMeta {
+    fn collectable(&self, now: u64) -> bool {
+        self.refs == 0 && self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// What one collection found, or removed unless it was a dry run
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct GcReport {
+    /// Unix time the collection started
+    pub started_at: u64,
+    pub dry_run: bool,
+    /// Blobs with an expiry or references looked at
+    pub scanned: usize,
+    pub collected: usize,
+    pub freed_bytes: u64,
+}
+
+/// Blobs stored under the hex BLAKE3 hash of their contents
+#[derive(Clone)]
+pub struct BlobStore {
+    store: Arc<dyn ObjectStore>,
+    bucket: String,
+    /// Key prefix of a tenant's blobs, empty otherwise
+    prefix: String,
+    default_ttl: Option<Duration>,
+    /// Held while a blob's metadata is read and rewritten, so puts, references and collection
+    /// do not undo each other
+    meta_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Report of the last collection that removed blobs
+    last_collection: Arc<Mutex<Option<GcReport>>>,
+}
+
+/// Hex BLAKE3 ids are 64 lowercase characters
+fn valid_blob_id(id: &str) -> bool {
+    id.len() == 64 && id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Id of a blob key, which is the id itself or `tenant/id`
+fn blob_key_id(key: &str) -> Option<&str> {
+    let (tenant, id) = key.rsplit_once('/').unwrap_or(("", key));
+    let tenant_ok = tenant.is_empty() || (crate::keyring::valid_id(tenant) && !tenant.starts_with('.'));
+    (tenant_ok && valid_blob_id(id)).then_some(id)
+}
+
+impl BlobStore {
+    pub fn new(store: Arc<dyn ObjectStore>, bucket: &str) -> Self {
+        BlobStore {
+            store,
+            bucket: bucket.to_string(),
+            prefix: String::new(),
+            default_ttl: None,
+            meta_lock: Arc::default(),
+            last_collection: Arc::default(),
+        }
+    }
+
+    /// Expire blobs stored without a TTL of their own after `ttl`
+    pub fn with_default_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// The same store with blobs kept under `tenant/`, out of reach of other tenants
+    pub fn scoped(&self, tenant: &str) -> Self {
+        BlobStore {
+            prefix: format!("{tenant}/"),
+            ..self.clone()
+        }
+    }
+
+    /// Store `data` with the default TTL, returning its id
+    pub async fn put(&self, data: Bytes) -> Result<String, String> {
+        self.put_expiring(data, self.default_ttl).await
+    }
+
+    /// Store `data`, returning its id, to expire after `ttl` or never. Storing the same contents
+    /// again rewrites the same object and keeps the later expiry; blobs stored without one keep
+    /// never expiring.
+    pub async fn put_expiring(&self, data: Bytes, ttl: Option<Duration>) -> Result<String, String> {
+        let id = blake3::hash(&data).to_hex().to_string();
+        let key = format!("{}{id}", self.prefix);
+        let size = data.len() as u64;
+        let expires_at = ttl.map(|ttl| now().saturating_add(ttl.as_secs()));
+
+        let _guard = self.meta_lock.lock().await;
+        let meta = match self.read_meta(&key).await? {
+            Some(meta) => Some(BlobMeta {
+                expires_at: meta.expires_at.zip(expires_at).map(|(old, new)| old.max(new)),
+                ..meta
+            }),
+            None if expires_at.is_some() && self.store.get(&self.bucket, &key).await?.is_none() => Some(BlobMeta {
+                size,
+                expires_at,
+                refs: 0,
+            }),
+            None => None,
+        };
+        let body: ByteStream = Box::pin(futures_util::stream::once(async { Ok(data) }));
+        self.store.put(&self.bucket, &key, body).await?;
+        if let Some(meta) = meta {
+            self.write_meta(&key, &meta).await?;
+        }
+        Ok(id)
+    }
+
+    /// Contents of `id`, `None` if unknown or not a valid id
+    pub async fn get(&self, id: &str) -> Result<Option<ByteStream>, String> {
+        if !valid_blob_id(id) {
+            return Ok(None);
+        }
+        self.store.get(&self.bucket, &format!("{}{id}", self.prefix)).await
+    }
+
+    /// Keys of up to `limit` blobs after `after`, every tenant's included; ids, or `tenant/id`
+    pub async fn keys(&self, after: Option<&str>, limit: usize) -> Result<Vec<String>, String> {
+        let keys = self.store.list(&self.bucket, &self.prefix, after, limit).await?;
+        Ok(keys.into_iter().filter(|key| blob_key_id(key).is_some()).collect())
+    }
+
+    /// Contents under a key from `keys`, `None` if unknown or not a blob key
+    pub async fn get_key(&self, key: &str) -> Result<Option<ByteStream>, String> {
+        if blob_key_id(key).is_none() {
+            return Ok(None);
+        }
+        self.store.get(&self.bucket, key).await
+    }
+
+    /// Store a blob copied from another server under its key, refusing contents not matching its id
+    pub async fn put_key(&self, key: &str, data: Bytes) -> Result<(), String> {
+        let id = blob_key_id(key).ok_or("Invalid blob key")?;
+        if blake3::hash(&data).to_hex().as_str() != id {
+            return Err(format!("Blob {key} does not match its hash"));
+        }
+        let body: ByteStream = Box::pin(futures_util::stream::once(async { Ok(data) }));
+        self.store.put(&self.bucket, key, body).await.map(|_| ())
+    }
+
+    async fn read_meta(&self, key: &str) -> Result<Option<BlobMeta>, String> {
+        let Some(mut body) = self.store.get(&self.bucket, &format!("{key}{META_SUFFIX}")).await? else {
+            return Ok(None);
+        };
+        let mut json = Vec::new();
+        while let Some(chunk) = body.next().await {
+            json.extend_from_slice(&chunk?);
+        }
+        serde_json::from_slice(&json)
+            .map(Some)
+            .map_err(|e| format!("Invalid metadata of blob {key}: {e}"))
+    }
+
+    async fn write_meta(&self, key: &str, meta: &BlobMeta) -> Result<(), String> {
+        let json = Bytes::from(serde_json::to_vec(meta).expect("metadata serializes"));
+        let body: ByteStream = Box::pin(futures_util::stream::once(async { Ok(json) }));
+        self.store.put(&self.bucket, &format!("{key}{META_SUFFIX}"), body).await.map(|_| ())
+    }
+
+    /// Expiry and references of `id`, `None` if unknown; blobs that never expire report as such
+    pub async fn meta(&self, id: &str) -> Result<Option<BlobMeta>, String> {
+        if !valid_blob_id(id) {
+            return Ok(None);
+        }
+        let key = format!("{}{id}", self.prefix);
+        if let Some(meta) = self.read_meta(&key).await? {
+            return Ok(Some(meta));
+        }
+        let Some(mut body) = self.store.get(&self.bucket, &key).await? else {
+            return Ok(None);
+        };
+        let mut size = 0;
+        while let Some(chunk) = body.next().await {
+            size += chunk?.len() as u64;
+        }
+        Ok(Some(BlobMeta {
+            size,
+            expires_at: None,
+            refs: 0,
+        }))
+    }
+
+    /// Add a reference to `id`, keeping it past its expiry until released; `None` if unknown
+    pub async fn retain(&self, id: &str) -> Result<Option<BlobMeta>, String> {
+        self.update_refs(id, |refs| refs + 1).await
+    }
+
+    /// Drop a reference to `id`, letting it be collected once none are left and it has expired.
+    /// Releasing a blob nobody references changes nothing.
+    pub async fn release(&self, id: &str) -> Result<Option<BlobMeta>, String> {
+        self.update_refs(id, |refs| refs.saturating_sub(1)).await
+    }
+
+    async fn update_refs(&self, id: &str, update: impl FnOnce(u64) -> u64) -> Result<Option<BlobMeta>, String> {
+        let _guard = self.meta_lock.lock().await;
+        let Some(mut meta) = self.meta(id).await? else {
+            return Ok(None);
+        };
+        meta.refs = update(meta.refs);
+        self.write_meta(&format!("{}{id}", self.prefix), &meta).await?;
+        Ok(Some(meta))
+    }
+
+    /// Remove blobs past their expiry that nobody references, or with `dry_run` only report them.
+    /// A tenant's store collects only that tenant's blobs.
+    pub async fn collect(&self, dry_run: bool) -> Result<GcReport, String> {
+        let now = now();
+        let mut report = GcReport {
+            started_at: now,
+            dry_run,
+            ..GcReport::default()
+        };
+        let mut after = None;
+        loop {
+            let keys = self.store.list(&self.bucket, &self.prefix, after.as_deref(), GC_PAGE).await?;
+            for key in &keys {
+                let Some(blob) = key.strip_suffix(META_SUFFIX).filter(|blob| blob_key_id(blob).is_some()) else {
+                    continue;
+                };
+                report.scanned += 1;
+                let _guard = self.meta_lock.lock().await;
+                let Some(meta) = self.read_meta(blob).await?.filter(|meta| meta.collectable(now)) else {
+                    continue;
+                };
+                if !dry_run {
+                    // The blob goes first, so a failure leaves metadata for the next run to retry
+                    self.store.delete(&self.bucket, blob).await?;
+                    self.store.delete(&self.bucket, key).await?;
+                }
+                report.collected += 1;
+                report.freed_bytes += meta.size;
+            }
+            if keys.len() < GC_PAGE {
+                break;
+            }
+            after = keys.last().cloned();
+        }
+        if !dry_run {
+            *self.last_collection.lock().unwrap() = Some(report.clone());
+        }
+        Ok(report)
+    }
+
+    /// Report of the last collection that was not a dry run
+    pub fn last_collection(&self) -> Option<GcReport> {
+        self.last_collection.lock().unwrap().clone()
+    }
+}
+
+/// Collects expired blobs in the background
+pub struct BlobCollector {
+    blobs: BlobStore,
+    interval: Duration,
+}
+
+impl BlobCollector {
+    pub fn new(blobs: BlobStore, interval: Duration) -> Self {
+        BlobCollector { blobs, interval }
+    }
+
+    /// Collect every interval until `shutdown`
+    pub async fn run(self, shutdown: CancellationToken) {
+        loop {
+            match self.blobs.collect(false).await {
+                Ok(report) if report.collected > 0 => {
+                    println!("Collected {} expired blobs, {} bytes", report.collected, report.freed_bytes);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Blob collection failed: {e}"),
+            }
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = tokio::time::sleep(self.interval) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::path::{Component, Path, PathBuf};
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+
+use super::{ByteStream, ObjectStore};
+use crate::keyring::generate_seed;
+
+/// Objects kept as files under `root/bucket/key`
+#[derive(Clone, Debug, Deserialize)]
+pub struct FsConfig {
+    pub root: PathBuf,
+}
+
+pub struct FsStore {
+    root: PathBuf,
+}
+
+/// Whether `name` stays below the directory it is joined to
+fn contained(name: &str) -> bool {
+    !name.is_empty() && Path::new(name).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+impl FsStore {
+    pub fn new(config: FsConfig) -> Self {
+        FsStore { root: config.root }
+    }
+
+    fn path(&self, bucket: &str, key: &str) -> Result<PathBuf, String> {
+        if !contained(bucket) || !contained(key) {
+            return Err("Invalid object path".to_string());
+        }
+        Ok(self.root.join(bucket).join(key))
+    }
+}
+
+#[async_trait]
+impl ObjectStore for FsStore {
+    async fn get(&self, bucket: &str, key: &str) -> Result<Option<ByteStream>, String> {
+        let file = match tokio::fs::File::open(self.path(bucket, key)?).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Cannot open object: {e}")),
+        };
+        let chunks = ReaderStream::new(file).map(|chunk| chunk.map_err(|e| format!("Cannot read object: {e}")));
+        Ok(Some(Box::pin(chunks)))
+    }
+
+    /// Written to a temporary file renamed into place, so readers never see partial objects
+    async fn put(&self, bucket: &str, key: &str, mut body: ByteStream) -> Result<u64, String> {
+        let path = self.path(bucket, key)?;
+        let dir = path.parent().ok_or("Invalid object path")?;
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| format!("Cannot create {}: {e}", dir.display()))?;
+        let temp = dir.join(format!(".{}.tmp", hex::encode(&generate_seed()[..8])));
+
+        let written = async {
+            let mut file = tokio::fs::File::create(&temp).await.map_err(|e| format!("Cannot write object: {e}"))?;
+            let mut written = 0;
+            while let Some(chunk) = body.next().await {
+                let chunk = chunk?;
+                file.write_all(&chunk).await.map_err(|e| format!("Cannot write object: {e}"))?;
+                written += chunk.len() as u64;
+            }
+            file.sync_all().await.map_err(|e| format!("Cannot write object: {e}"))?;
+            tokio::fs::rename(&temp, &path)
+                .await
+                .map_err(|e| format!("Cannot write object: {e}"))?;
+            Ok(written)
+        }
+        .await;
+        if written.is_err() {
+            let _ = tokio::fs::remove_file(&temp).await;
+        }
+        written
+    }
+
+    async fn list(&self, bucket: &str, prefix: &str, after: Option<&str>, limit: usize) -> Result<Vec<String>, String> {
+        if !contained(bucket) {
+            return Err("Invalid object path".to_string());
+        }
+        let dir = self.root.join(bucket);
+        let mut keys = tokio::task::spawn_blocking(move || walk(&dir))
+            .await
+            .map_err(|e| format!("Cannot list objects: {e}"))??;
+        keys.retain(|key| key.starts_with(prefix) && after.is_none_or(|after| key.as_str() > after));
+        keys.sort();
+        keys.truncate(limit);
+        Ok(keys)
+    }
+
+    async fn delete(&self, bucket: &str, key: &str) -> Result<(), String> {
+        match tokio::fs::remove_file(self.path(bucket, key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Cannot delete object: {e}")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Keys of every object below `dir`, skipping temporary files
+fn walk(dir: &Path) -> Result<Vec<String>, String> {
+    let mut keys = Vec::new();
+    let mut pending = vec![(dir.to_path_buf(), String::new())];
+    while let Some((dir, base)) = pending.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Cannot list objects: {e}")),
+        };
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Cannot list objects: {e}"))?;
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if name.starts_with('.') {
+                continue;
+            }
+            let key = format!("{base}{name}");
+            if entry.file_type().map_err(|e| format!("Cannot list objects: {e}"))?.is_dir() {
+                pending.push((entry.path(), format!("{key}/")));
+            } else {
+                keys.push(key);
+            }
+        }
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures_util::stream;
+
+    #[tokio::test]
+    async fn test_roundtrip_and_containment() {
+        let root = std::env::temp_dir().join(format!("gx-fs-store-{}", std::process::id()));
+        let store = FsStore::new(FsConfig { root: root.clone() });
+        let body: ByteStream = Box::pin(stream::iter([Ok(Bytes::from_static(b"ab")), Ok(Bytes::from_static(b"c"))]));
+        assert_eq!(store.put("bucket", "dir/object", body).await.unwrap(), 3);
+
+        let mut read = store.get("bucket", "dir/object").await.unwrap().unwrap();
+        let mut contents = Vec::new();
+        while let Some(chunk) = read.next().await {
+            contents.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(contents, b"abc");
+        assert!(store.get("bucket", "missing").await.unwrap().is_none());
+        assert!(store.get("bucket", "../bucket/dir/object").await.is_err());
+        assert!(store.get("/etc", "passwd").await.is_err());
+
+        let body: ByteStream = Box::pin(stream::iter([Ok(Bytes::from_static(b"d"))]));
+        store.put("bucket", "a", body).await.unwrap();
+        assert_eq!(store.list("bucket", "", None, 10).await.unwrap(), ["a", "dir/object"]);
+        assert_eq!(store.list("bucket", "", Some("a"), 10).await.unwrap(), ["dir/object"]);
+        assert_eq!(store.list("bucket", "dir/", None, 10).await.unwrap(), ["dir/object"]);
+
+        store.delete("bucket", "a").await.unwrap();
+        store.delete("bucket", "a").await.unwrap();
+        assert_eq!(store.list("bucket", "", None, 10).await.unwrap(), ["dir/object"]);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
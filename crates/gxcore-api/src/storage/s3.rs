@@ -0,0 +1,377 @@
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use reqwest::{Method, StatusCode};
+use serde::Deserialize;
+
+use super::{ByteStream, ObjectStore};
+use crate::sigv4::{self, amz_date, payload_hash, sign, Credentials};
+
+const MIB: usize = 1024 * 1024;
+
+/// S3 or a compatible service; credentials come from the standard `AWS_*` environment variables
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct S3Config {
+    /// Region, read from `AWS_REGION` when unset
+    pub region: Option<String>,
+    /// Endpoint override, for MinIO, LocalStack and VPC endpoints; objects use path-style URLs
+    pub endpoint: Option<String>,
+    /// Objects larger than this are uploaded in parts of this size, at least 5 MiB on AWS
+    pub part_size: usize,
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        S3Config {
+            region: None,
+            endpoint: None,
+            part_size: 8 * MIB,
+        }
+    }
+}
+
+/// Reads objects as streams and writes them with multipart uploads, holding one part in memory
+pub struct S3Store {
+    region: String,
+    endpoint: reqwest::Url,
+    credentials: Credentials,
+    part_size: usize,
+    client: reqwest::Client,
+}
+
+/// Percent-encode everything but unreserved characters, and `/` when `keep_slash`
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') || (keep_slash && b == b'/') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+fn object_path(bucket: &str, key: &str) -> String {
+    format!("/{}/{}", uri_encode(bucket, false), uri_encode(key, true))
+}
+
+/// Text of the first `<tag>` element, enough for the few fields read from S3 responses
+fn xml_field<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{tag}>"))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{tag}>"))?;
+    Some(&xml[start..end])
+}
+
+/// Text of every `<tag>` element, with XML escapes undone
+fn xml_fields(xml: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{tag}>"), format!("</{tag}>"));
+    let mut fields = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        let text = rest[..end]
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&");
+        fields.push(text);
+        rest = &rest[end..];
+    }
+    fields
+}
+
+impl S3Store {
+    pub(crate) fn new(config: S3Config, credentials: Credentials) -> Result<Self, String> {
+        let region = config.region.or_else(sigv4::env_region).ok_or("AWS region not set")?;
+        let endpoint = config
+            .endpoint
+            .unwrap_or_else(|| format!("https://s3.{region}.amazonaws.com"));
+        let endpoint = reqwest::Url::parse(&endpoint).map_err(|e| format!("Invalid S3 endpoint: {e}"))?;
+        Ok(S3Store {
+            region,
+            endpoint,
+            credentials,
+            part_size: config.part_size.max(1),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    pub fn from_config(config: S3Config) -> Result<Self, String> {
+        Self::new(config, Credentials::from_env()?)
+    }
+
+    /// Send a signed request; `query` must be canonical, its keys sorted and values encoded
+    async fn request(&self, method: Method, path: &str, query: &str, body: Vec<u8>) -> Result<reqwest::Response, String> {
+        let host = sigv4::host(&self.endpoint).ok_or("Invalid S3 endpoint")?;
+        let amz_date = amz_date(SystemTime::now());
+        let content_hash = payload_hash(&body);
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", content_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let authorization = sign(
+            &self.credentials,
+            &self.region,
+            "s3",
+            &amz_date,
+            method.as_str(),
+            path,
+            query,
+            &headers,
+            &content_hash,
+        );
+
+        let mut url = self.endpoint.clone();
+        url.set_path(path);
+        url.set_query(Some(query).filter(|query| !query.is_empty()));
+        let mut request = self.client.request(method, url).header("authorization", authorization);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+        request.body(body).send().await.map_err(|e| format!("S3 request failed: {e}"))
+    }
+
+    async fn checked(&self, method: Method, path: &str, query: &str, body: Vec<u8>) -> Result<reqwest::Response, String> {
+        self.request(method, path, query, body)
+            .await?
+            .error_for_status()
+            .map_err(|e| format!("S3 request failed: {e}"))
+    }
+
+    async fn create_upload(&self, path: &str) -> Result<String, String> {
+        let response = self.checked(Method::POST, path, "uploads=", Vec::new()).await?;
+        let xml = response.text().await.map_err(|e| format!("Invalid S3 response: {e}"))?;
+        xml_field(&xml, "UploadId")
+            .map(str::to_string)
+            .ok_or_else(|| "S3 response has no UploadId".to_string())
+    }
+
+    /// Upload one part, returning its ETag
+    async fn upload_part(&self, path: &str, upload_id: &str, number: usize, part: Vec<u8>) -> Result<String, String> {
+        let query = format!("partNumber={number}&uploadId={}", uri_encode(upload_id, false));
+        let response = self.checked(Method::PUT, path, &query, part).await?;
+        response
+            .headers()
+            .get("etag")
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| "S3 part upload returned no ETag".to_string())
+    }
+
+    async fn upload_parts(&self, path: &str, upload_id: &str, mut part: Vec<u8>, mut body: ByteStream) -> Result<u64, String> {
+        let mut etags = Vec::new();
+        let mut total = 0;
+        loop {
+            let next = body.next().await.transpose()?;
+            if let Some(chunk) = &next {
+                part.extend_from_slice(chunk);
+            }
+            let last = next.is_none();
+            if part.len() >= self.part_size || (last && !part.is_empty()) {
+                total += part.len() as u64;
+                let number = etags.len() + 1;
+                etags.push(self.upload_part(path, upload_id, number, std::mem::take(&mut part)).await?);
+            }
+            if last {
+                break;
+            }
+        }
+
+        let parts: String = etags
+            .iter()
+            .enumerate()
+            .map(|(i, etag)| format!("<Part><PartNumber>{}</PartNumber><ETag>{etag}</ETag></Part>", i + 1))
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{parts}</CompleteMultipartUpload>");
+        let query = format!("uploadId={}", uri_encode(upload_id, false));
+        let response = self.checked(Method::POST, path, &query, body.into_bytes()).await?;
+        // Completion can fail after the status line was sent as 200
+        let xml = response.text().await.map_err(|e| format!("Invalid S3 response: {e}"))?;
+        if let Some(code) = xml_field(&xml, "Code") {
+            return Err(format!("S3 multipart upload failed: {code}"));
+        }
+        Ok(total)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for S3Store {
+    async fn get(&self, bucket: &str, key: &str) -> Result<Option<ByteStream>, String> {
+        let response = self.request(Method::GET, &object_path(bucket, key), "", Vec::new()).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response.error_for_status().map_err(|e| format!("S3 request failed: {e}"))?;
+        let chunks = response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(|e| format!("S3 read failed: {e}")));
+        Ok(Some(Box::pin(chunks)))
+    }
+
+    async fn put(&self, bucket: &str, key: &str, mut body: ByteStream) -> Result<u64, String> {
+        let path = object_path(bucket, key);
+        let mut first = Vec::new();
+        while first.len() < self.part_size {
+            match body.next().await {
+                Some(chunk) => first.extend_from_slice(&chunk?),
+                None => {
+                    let len = first.len() as u64;
+                    self.checked(Method::PUT, &path, "", first).await?;
+                    return Ok(len);
+                }
+            }
+        }
+
+        let upload_id = self.create_upload(&path).await?;
+        let uploaded = self.upload_parts(&path, &upload_id, first, body).await;
+        if uploaded.is_err() {
+            // Parts of abandoned uploads are billed until aborted
+            let query = format!("uploadId={}", uri_encode(&upload_id, false));
+            let _ = self.request(Method::DELETE, &path, &query, Vec::new()).await;
+        }
+        uploaded
+    }
+
+    /// One ListObjectsV2 page, so `limit` is capped at 1000 keys
+    async fn list(&self, bucket: &str, prefix: &str, after: Option<&str>, limit: usize) -> Result<Vec<String>, String> {
+        let mut query = format!("list-type=2&max-keys={}&prefix={}", limit.min(1000), uri_encode(prefix, false));
+        if let Some(after) = after {
+            query.push_str(&format!("&start-after={}", uri_encode(after, false)));
+        }
+        let path = format!("/{}", uri_encode(bucket, false));
+        let response = self.checked(Method::GET, &path, &query, Vec::new()).await?;
+        let xml = response.text().await.map_err(|e| format!("Invalid S3 response: {e}"))?;
+        Ok(xml_fields(&xml, "Key"))
+    }
+
+    /// S3 answers `204 No Content` whether or not the object existed
+    async fn delete(&self, bucket: &str, key: &str) -> Result<(), String> {
+        self.checked(Method::DELETE, &object_path(bucket, key), "", Vec::new()).await.map(drop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, extract::State, http::{HeaderMap, Uri}, routing::any, Router};
+    use futures_util::stream;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Objects and in-progress parts by path
+    #[derive(Clone, Default)]
+    struct MockS3 {
+        objects: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+        parts: Arc<Mutex<Vec<Vec<u8>>>>,
+    }
+
+    async fn handle(State(s3): State<MockS3>, method: axum::http::Method, uri: Uri, headers: HeaderMap, body: Bytes) -> (axum::http::StatusCode, HeaderMap, String) {
+        assert!(headers["authorization"].to_str().unwrap().starts_with("AWS4-HMAC-SHA256"));
+        let path = uri.path().to_string();
+        let query = uri.query().unwrap_or_default();
+        let mut response = HeaderMap::new();
+        let mut objects = s3.objects.lock().unwrap();
+        let body = match (method.as_str(), query) {
+            ("GET", "list-type=2&max-keys=10&prefix=dir%2F") => {
+                let mut keys: Vec<_> = objects.keys().filter_map(|key| key.strip_prefix("/bucket/dir/")).collect();
+                keys.sort();
+                let keys: String = keys.iter().map(|key| format!("<Contents><Key>dir/{key}</Key></Contents>")).collect();
+                format!("<ListBucketResult>{keys}</ListBucketResult>")
+            }
+            ("GET", _) => match objects.get(&path) {
+                Some(object) => String::from_utf8(object.clone()).unwrap(),
+                None => return (axum::http::StatusCode::NOT_FOUND, response, String::new()),
+            },
+            ("PUT", "") => {
+                objects.insert(path, body.to_vec());
+                String::new()
+            }
+            ("POST", "uploads=") => "<InitiateMultipartUploadResult><UploadId>up/1</UploadId></InitiateMultipartUploadResult>".to_string(),
+            ("PUT", _) => {
+                let mut parts = s3.parts.lock().unwrap();
+                parts.push(body.to_vec());
+                response.insert("etag", format!("\"{}\"", parts.len()).parse().unwrap());
+                String::new()
+            }
+            ("POST", _) => {
+                assert_eq!(query, "uploadId=up%2F1");
+                objects.insert(path, s3.parts.lock().unwrap().concat());
+                "<CompleteMultipartUploadResult></CompleteMultipartUploadResult>".to_string()
+            }
+            ("DELETE", "") => {
+                objects.remove(&path);
+                return (axum::http::StatusCode::NO_CONTENT, response, String::new());
+            }
+            _ => unreachable!(),
+        };
+        (axum::http::StatusCode::OK, response, body)
+    }
+
+    async fn store(part_size: usize) -> (S3Store, MockS3) {
+        let s3 = MockS3::default();
+        let router = Router::new().fallback(any(handle)).with_state(s3.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        let config = S3Config {
+            region: Some("us-east-1".to_string()),
+            endpoint: Some(endpoint),
+            part_size,
+        };
+        let credentials = Credentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: None,
+        };
+        (S3Store::new(config, credentials).unwrap(), s3)
+    }
+
+    fn chunks(parts: &'static [&'static str]) -> ByteStream {
+        Box::pin(stream::iter(parts.iter().map(|part| Ok(Bytes::from_static(part.as_bytes())))))
+    }
+
+    async fn read(store: &S3Store, key: &str) -> Option<Vec<u8>> {
+        let mut body = store.get("bucket", key).await.unwrap()?;
+        let mut out = Vec::new();
+        while let Some(chunk) = body.next().await {
+            out.extend_from_slice(&chunk.unwrap());
+        }
+        Some(out)
+    }
+
+    #[tokio::test]
+    async fn test_single_and_multipart_put() {
+        let (store, s3) = store(4).await;
+        assert_eq!(store.put("bucket", "small key", chunks(&["abc"])).await.unwrap(), 3);
+        assert_eq!(read(&store, "small key").await.unwrap(), b"abc");
+
+        let written = store.put("bucket", "dir/large", chunks(&["abcdef", "gh", "ij"])).await.unwrap();
+        assert_eq!(written, 10);
+        assert_eq!(s3.parts.lock().unwrap().len(), 2);
+        assert_eq!(read(&store, "dir/large").await.unwrap(), b"abcdefghij");
+        assert!(read(&store, "missing").await.is_none());
+        assert_eq!(store.list("bucket", "dir/", None, 10).await.unwrap(), ["dir/large"]);
+
+        store.delete("bucket", "dir/large").await.unwrap();
+        assert!(read(&store, "dir/large").await.is_none());
+    }
+
+    #[test]
+    fn test_paths_and_xml() {
+        assert_eq!(object_path("bucket", "a b/c+d"), "/bucket/a%20b/c%2Bd");
+        assert_eq!(xml_field("<R><UploadId>x</UploadId></R>", "UploadId"), Some("x"));
+        assert_eq!(xml_field("<R></R>", "Code"), None);
+        assert_eq!(xml_fields("<R><Key>a&amp;b</Key><Key>c</Key></R>", "Key"), ["a&b", "c"]);
+    }
+}
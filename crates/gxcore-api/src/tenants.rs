@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::keyring::{valid_id, Keyring};
+
+/// Marker file of a tenant directory
+const TENANT_FILE: &str = "tenant.json";
+const KEYRING_FILE: &str = "keyring.json";
+
+/// Tenant registry, persisted as one directory per tenant when `dir` is set
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TenantsConfig {
+    pub dir: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantInfo {
+    pub id: String,
+    pub created_at: u64,
+}
+
+struct Tenant {
+    info: TenantInfo,
+    keyring: Arc<Keyring>,
+}
+
+/// Tenants with their own keyring, so one tenant's key ids never resolve another's seeds
+#[derive(Default)]
+pub struct Tenants {
+    dir: Option<PathBuf>,
+    master_key: Option<[u8; 32]>,
+    tenants: RwLock<BTreeMap<String, Tenant>>,
+}
+
+/// Tenant ids name directories, so dot-only ids such as `..` are refused
+fn valid_tenant_id(id: &str) -> bool {
+    valid_id(id) && !id.starts_with('.')
+}
+
+impl Tenants {
+    /// Registry that is lost on restart
+    pub fn in_memory() -> Self {
+        Tenants::default()
+    }
+
+    /// Load every tenant below `dir`; their keyrings are encrypted with `master_key` if given
+    pub fn open(dir: &Path, master_key: Option<[u8; 32]>) -> Result<Self, String> {
+        let mut tenants = BTreeMap::new();
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries.collect::<Result<Vec<_>, _>>().map_err(|e| format!("Cannot read {}: {e}", dir.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("Cannot read {}: {e}", dir.display())),
+        };
+        for entry in entries {
+            let path = entry.path().join(TENANT_FILE);
+            let Ok(json) = fs::read(&path) else {
+                continue;
+            };
+            let info: TenantInfo =
+                serde_json::from_slice(&json).map_err(|e| format!("Invalid {}: {e}", path.display()))?;
+            let keyring = Keyring::open(&entry.path().join(KEYRING_FILE), master_key)
+                .map_err(|e| format!("Tenant {}: {e}", info.id))?;
+            tenants.insert(
+                info.id.clone(),
+                Tenant {
+                    info,
+                    keyring: Arc::new(keyring),
+                },
+            );
+        }
+        Ok(Tenants {
+            dir: Some(dir.to_path_buf()),
+            master_key,
+            tenants: RwLock::new(tenants),
+        })
+    }
+
+    pub fn list(&self) -> Vec<TenantInfo> {
+        self.tenants.read().unwrap().values().map(|tenant| tenant.info.clone()).collect()
+    }
+
+    pub fn create(&self, id: &str) -> Result<TenantInfo, &'static str> {
+        if !valid_tenant_id(id) {
+            return Err("Invalid tenant id");
+        }
+        let mut tenants = self.tenants.write().unwrap();
+        if tenants.contains_key(id) {
+            return Err("Tenant already exists");
+        }
+        let info = TenantInfo {
+            id: id.to_string(),
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        };
+        let keyring = match &self.dir {
+            Some(dir) => {
+                let dir = dir.join(id);
+                fs::create_dir_all(&dir).map_err(|_| "Failed to create tenant")?;
+                let json = serde_json::to_vec_pretty(&info).map_err(|_| "Failed to create tenant")?;
+                fs::write(dir.join(TENANT_FILE), json).map_err(|_| "Failed to create tenant")?;
+                Keyring::open(&dir.join(KEYRING_FILE), self.master_key)?
+            }
+            None => Keyring::in_memory(),
+        };
+        tenants.insert(
+            id.to_string(),
+            Tenant {
+                info: info.clone(),
+                keyring: Arc::new(keyring),
+            },
+        );
+        Ok(info)
+    }
+
+    /// Keyring of `id`, `None` for unknown tenants
+    pub fn keyring(&self, id: &str) -> Option<Arc<Keyring>> {
+        self.tenants.read().unwrap().get(id).map(|tenant| tenant.keyring.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenants_persisted_with_own_keyrings() {
+        let dir = std::env::temp_dir().join(format!("gx-tenants-{}", std::process::id()));
+        let tenants = Tenants::open(&dir, None).unwrap();
+        tenants.create("team-a").unwrap();
+        tenants.create("team-b").unwrap();
+        assert_eq!(tenants.create("team-a"), Err("Tenant already exists"));
+        assert_eq!(tenants.create(".."), Err("Invalid tenant id"));
+        tenants.keyring("team-a").unwrap().add("billing", Some(b"a".to_vec())).unwrap();
+        assert_eq!(tenants.keyring("team-b").unwrap().encoding_seed("billing"), Err("Unknown key"));
+
+        let reopened = Tenants::open(&dir, None).unwrap();
+        assert_eq!(reopened.list().len(), 2);
+        assert_eq!(reopened.keyring("team-a").unwrap().encoding_seed("billing").unwrap(), b"a");
+        assert!(reopened.keyring("team-c").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,48 @@
+use base64::{Engine as _, engine::general_purpose};
+
+const BEGIN: &str = "-----BEGIN GXCORE ENVELOPE-----";
+const END: &str = "-----END GXCORE ENVELOPE-----";
+const LINE_LEN: usize = 64;
+
+/// Envelope as base64 lines between markers, safe for email, chat and terminals
+pub fn armor(sealed: &[u8]) -> Vec<u8> {
+    let encoded = general_purpose::STANDARD.encode(sealed);
+    let mut out = format!("{BEGIN}\n");
+    for line in encoded.as_bytes().chunks(LINE_LEN) {
+        out.push_str(std::str::from_utf8(line).unwrap());
+        out.push('\n');
+    }
+    out.push_str(END);
+    out.push('\n');
+    out.into_bytes()
+}
+
+/// Envelope bytes of armored input; other input is returned unchanged
+pub fn unarmor(input: &[u8]) -> Result<Vec<u8>, String> {
+    let Some(text) = std::str::from_utf8(input).ok().map(str::trim).filter(|text| text.starts_with(BEGIN)) else {
+        return Ok(input.to_vec());
+    };
+    let body = text
+        .strip_prefix(BEGIN)
+        .and_then(|text| text.strip_suffix(END))
+        .ok_or("armor has no end marker")?;
+    let encoded: String = body.split_whitespace().collect();
+    general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|_| "armor is not valid base64".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_armor_roundtrip() {
+        let sealed: Vec<u8> = (0..=255).collect();
+        let armored = armor(&sealed);
+        assert!(armored.split(|&b| b == b'\n').all(|line| line.len() <= LINE_LEN.max(END.len())));
+        assert_eq!(unarmor(&armored).unwrap(), sealed);
+        assert_eq!(unarmor(b"GXE\x01raw").unwrap(), b"GXE\x01raw");
+        assert!(unarmor(BEGIN.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,379 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use clap::Args;
+use gxcore_core::bench::{compare, measure, sample_data, to_rows, BenchmarkResult, Comparison, RowFormat};
+use gxcore_core::CompressionAlgorithm;
+use serde::Deserialize;
+
+use crate::io::print_json;
+use crate::parse_compression;
+
+mod report;
+
+/// Seed used for every run, as in the HTTP benchmark
+const SEED: &[u8] = b"benchmark_secret_key";
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Bytes of sample data per run, with an optional `K`, `M` or `G` suffix; comma-separated
+    /// sizes are each run and named by their byte count, as in `encode_sample_1024`
+    #[arg(long = "size", default_value = "64K", value_delimiter = ',', value_parser = parse_size)]
+    sizes: Vec<usize>,
+    /// Comma-separated compressions to compare
+    #[arg(long, default_value = "none,lz4,brotli", value_delimiter = ',', value_parser = parse_compression)]
+    algos: Vec<CompressionAlgorithm>,
+    /// Encodes and decodes timed per compression
+    #[arg(long, default_value_t = 100)]
+    iterations: usize,
+    /// Save the results as baseline NAME for later runs to compare against
+    #[arg(long, value_name = "NAME")]
+    save_baseline: Option<String>,
+    /// Compare with baseline NAME, or a file of `--json` output, failing on regressions
+    #[arg(long, value_name = "NAME")]
+    baseline: Option<String>,
+    /// Compare the baseline with saved run NAME instead of running, e.g. one from another machine
+    #[arg(long, value_name = "NAME", requires = "baseline", conflicts_with = "save_baseline")]
+    candidate: Option<String>,
+    /// Percent slower than the baseline that counts as a regression
+    #[arg(long, default_value_t = 10.0, value_parser = parse_threshold)]
+    threshold: f64,
+    /// Where named baselines are kept, `$XDG_DATA_HOME/gxcore/baselines` by default
+    #[arg(long, value_name = "DIR")]
+    baseline_dir: Option<PathBuf>,
+    /// Also write the results to this file
+    #[arg(long, value_name = "PATH")]
+    export: Option<PathBuf>,
+    /// `json`, `ndjson` or `csv`; by default from the `--export` extension, JSON when unknown
+    #[arg(long, value_name = "FORMAT", value_parser = parse_export_format, requires = "export")]
+    export_format: Option<ExportFormat>,
+    /// Write `report.html` and `report.md` into DIR, with tables by compression and any comparison
+    #[arg(long, value_name = "DIR")]
+    report: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy)]
+enum ExportFormat {
+    /// The `--json` output, which also serves as a baseline
+    Json,
+    Rows(RowFormat),
+}
+
+fn parse_export_format(name: &str) -> Result<ExportFormat, String> {
+    match name {
+        "json" => Ok(ExportFormat::Json),
+        "ndjson" => Ok(ExportFormat::Rows(RowFormat::Ndjson)),
+        "csv" => Ok(ExportFormat::Rows(RowFormat::Csv)),
+        _ => Err(format!("unknown export format {name}")),
+    }
+}
+
+impl ExportFormat {
+    /// Format implied by the extension of `path`, JSON when unknown
+    fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => ExportFormat::Rows(RowFormat::Csv),
+            Some("ndjson" | "jsonl") => ExportFormat::Rows(RowFormat::Ndjson),
+            _ => ExportFormat::Json,
+        }
+    }
+}
+
+fn export(path: &Path, format: ExportFormat, results: &[BenchmarkResult], output: &serde_json::Value) -> Result<(), String> {
+    let data = match format {
+        ExportFormat::Rows(format) => to_rows(results, format).into_bytes(),
+        ExportFormat::Json => serde_json::to_vec_pretty(output).map_err(|e| e.to_string())?,
+    };
+    std::fs::write(path, data).map_err(|e| format!("cannot write {}: {e}", path.display()))
+}
+
+/// Results as `--json` prints them; `total_time_ms` and other fields are ignored when reading
+#[derive(Deserialize)]
+struct Saved {
+    results: Vec<BenchmarkResult>,
+}
+
+fn parse_threshold(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|threshold: &f64| threshold.is_finite() && *threshold >= 0.0)
+        .ok_or_else(|| format!("invalid threshold {value}"))
+}
+
+/// `$XDG_DATA_HOME/gxcore/baselines`, falling back to `~/.local/share`
+fn default_baseline_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))?;
+    Some(base.join("gxcore").join("baselines"))
+}
+
+/// File of baseline `name` in `dir`. Names containing a path separator or ending in `.json` are
+/// paths themselves, so output saved by hand compares too.
+fn baseline_path(dir: Option<&Path>, name: &str) -> Result<PathBuf, String> {
+    if name.contains(std::path::is_separator) || name.ends_with(".json") {
+        return Ok(PathBuf::from(name));
+    }
+    if name.is_empty() || name.starts_with('.') {
+        return Err(format!("invalid baseline name {name:?}"));
+    }
+    let dir = dir
+        .map(Path::to_path_buf)
+        .or_else(default_baseline_dir)
+        .ok_or("no baseline directory; pass --baseline-dir")?;
+    Ok(dir.join(format!("{name}.json")))
+}
+
+fn load_baseline(path: &Path) -> Result<Vec<BenchmarkResult>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("cannot read baseline {}: {e}", path.display()))?;
+    let saved: Saved = serde_json::from_slice(&data).map_err(|e| format!("{} is not a benchmark result: {e}", path.display()))?;
+    Ok(saved.results)
+}
+
+fn save_baseline(path: &Path, output: &serde_json::Value) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {e}", dir.display()))?;
+    }
+    let data = serde_json::to_vec_pretty(output).map_err(|e| e.to_string())?;
+    std::fs::write(path, data).map_err(|e| format!("cannot write baseline {}: {e}", path.display()))
+}
+
+/// Byte count such as `4096`, `64K` or `1M`, in powers of 1024
+fn parse_size(value: &str) -> Result<usize, String> {
+    let (digits, unit) = match value.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((at, _)) => value.split_at(at),
+        None => (value, ""),
+    };
+    let shift = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" => 10,
+        "M" | "MB" => 20,
+        "G" | "GB" => 30,
+        _ => return Err(format!("unknown size unit {unit}")),
+    };
+    let count: usize = digits.parse().map_err(|_| format!("invalid size {value}"))?;
+    count.checked_mul(1 << shift).filter(|&size| size > 0).ok_or_else(|| format!("invalid size {value}"))
+}
+
+fn print_table(results: &[BenchmarkResult]) {
+    println!("{:<14} {:<12} {:>12} {:>10} {:>14} {:>12}", "operation", "compression", "bytes", "iterations", "avg/op (us)", "MB/s");
+    for result in results {
+        println!(
+            "{:<14} {:<12} {:>12} {:>10} {:>14.1} {:>12.1}",
+            result.operation,
+            result.compression,
+            result.data_size,
+            result.iterations,
+            result.avg_time_per_op_ns / 1000.0,
+            result.throughput_mb_per_sec,
+        );
+    }
+}
+
+fn print_comparison(comparisons: &[Comparison]) {
+    println!("{:<14} {:<12} {:>14} {:>14} {:>10}", "operation", "compression", "baseline (us)", "current (us)", "change");
+    for comparison in comparisons {
+        println!(
+            "{:<14} {:<12} {:>14.1} {:>14.1} {:>+9.1}%{}",
+            comparison.operation,
+            comparison.compression,
+            comparison.baseline_ns / 1000.0,
+            comparison.current_ns / 1000.0,
+            comparison.change_percent,
+            if comparison.regressed { "  REGRESSED" } else { "" },
+        );
+    }
+}
+
+fn results(args: &BenchArgs) -> Vec<BenchmarkResult> {
+    args.sizes
+        .iter()
+        .flat_map(|&size| {
+            let data = sample_data(size);
+            // A single size keeps the names of earlier runs, so their baselines still compare
+            let name = match args.sizes.len() {
+                1 => "sample".to_string(),
+                _ => format!("sample_{size}"),
+            };
+            args.algos
+                .iter()
+                .flat_map(move |&compression| measure(&name, &data, SEED, compression, args.iterations))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Print a table, or with `--json` the results shaped like the HTTP benchmark's. With
+/// `--baseline`, also the change from it, failing when any operation regressed.
+pub fn run(args: &BenchArgs, json: bool) -> Result<(), String> {
+    let dir = args.baseline_dir.as_deref();
+    // Resolve paths and read the baseline before spending time on the run
+    let baseline = args
+        .baseline
+        .as_deref()
+        .map(|name| baseline_path(dir, name).and_then(|path| load_baseline(&path)))
+        .transpose()?;
+    let save = args.save_baseline.as_deref().map(|name| baseline_path(dir, name)).transpose()?;
+    let candidate = args
+        .candidate
+        .as_deref()
+        .map(|name| baseline_path(dir, name).and_then(|path| load_baseline(&path)))
+        .transpose()?;
+
+    let start_time = Instant::now();
+    let results = candidate.unwrap_or_else(|| results(args));
+    let mut output = serde_json::json!({
+        "results": results,
+        "total_time_ms": start_time.elapsed().as_millis() as f64,
+    });
+    if let Some(path) = &save {
+        save_baseline(path, &output)?;
+    }
+    let comparisons = baseline.map(|baseline| compare(&baseline, &results, args.threshold));
+    if let Some(comparisons) = &comparisons {
+        output["comparison"] = serde_json::json!(comparisons);
+    }
+    if let Some(path) = &args.export {
+        let format = args.export_format.unwrap_or_else(|| ExportFormat::of(path));
+        export(path, format, &results, &output)?;
+    }
+    if let Some(dir) = &args.report {
+        let report = report::Report {
+            label: args.candidate.as_deref().unwrap_or("this run"),
+            results: &results,
+            baseline: args.baseline.as_deref().zip(comparisons.as_deref()),
+            threshold: args.threshold,
+        };
+        report::write(dir, &report)?;
+    }
+
+    if json {
+        print_json(&output)?;
+    } else {
+        print_table(&results);
+        if let Some(comparisons) = &comparisons {
+            println!();
+            print_comparison(comparisons);
+        }
+    }
+    match comparisons.iter().flatten().filter(|comparison| comparison.regressed).count() {
+        0 => Ok(()),
+        regressed => Err(format!("{regressed} operation(s) regressed more than {}%", args.threshold)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cli, Command};
+    use clap::Parser;
+
+    #[test]
+    fn test_sizes_and_algos() {
+        assert_eq!(parse_size("1M"), Ok(1 << 20));
+        assert_eq!(parse_size("64k"), Ok(64 << 10));
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("1T").is_err());
+
+        let cli = Cli::try_parse_from(["gxcore", "bench", "--size", "2K", "--algos", "lz4,brotli", "--iterations", "2"]).unwrap();
+        let Command::Bench(args) = cli.command else { panic!("not bench") };
+        let results = results(&args);
+        let compressions: Vec<_> = results.iter().map(|result| result.compression.as_str()).collect();
+        assert_eq!(compressions, ["lz4", "lz4", "brotli", "brotli"]);
+        assert_eq!(results[0].data_size, 2048);
+
+        assert!(Cli::try_parse_from(["gxcore", "bench", "--algos", "lz4,zstd"]).is_err());
+    }
+
+    #[test]
+    fn test_baselines() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-bench-{}", std::process::id()));
+        let dir_arg = dir.to_str().unwrap();
+        let bench = |extra: &[&str]| {
+            let mut argv = vec!["gxcore", "bench", "--size", "1K", "--algos", "none", "--iterations", "2", "--baseline-dir", dir_arg];
+            argv.extend(extra);
+            crate::run(Cli::try_parse_from(argv).unwrap())
+        };
+
+        assert!(bench(&["--baseline", "main"]).unwrap_err().starts_with("cannot read baseline"));
+        bench(&["--save-baseline", "main"]).unwrap();
+        assert_eq!(load_baseline(&dir.join("main.json")).unwrap().len(), 2);
+
+        // A baseline a thousand times faster than any real run makes this one regress
+        let mut saved: serde_json::Value = serde_json::from_slice(&std::fs::read(dir.join("main.json")).unwrap()).unwrap();
+        for result in saved["results"].as_array_mut().unwrap() {
+            result["avg_time_per_op_ns"] = serde_json::json!(result["avg_time_per_op_ns"].as_f64().unwrap() / 1000.0);
+        }
+        let fast = dir.join("fast.json");
+        std::fs::write(&fast, saved.to_string()).unwrap();
+        let error = bench(&["--baseline", fast.to_str().unwrap()]).unwrap_err();
+        assert_eq!(error, "2 operation(s) regressed more than 10%");
+        bench(&["--baseline", fast.to_str().unwrap(), "--threshold", "1e9"]).unwrap();
+
+        assert!(baseline_path(Some(&dir), "../main").is_ok_and(|path| path == Path::new("../main")));
+        assert!(baseline_path(Some(&dir), ".hidden").is_err());
+        assert!(Cli::try_parse_from(["gxcore", "bench", "--threshold", "-1"]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-bench-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let bench = |path: &Path, extra: &[&str]| {
+            let mut argv = vec!["gxcore", "bench", "--size", "1K", "--algos", "none,lz4", "--iterations", "1", "--export", path.to_str().unwrap()];
+            argv.extend(extra);
+            crate::run(Cli::try_parse_from(argv).unwrap()).unwrap();
+            std::fs::read_to_string(path).unwrap()
+        };
+
+        let csv = bench(&dir.join("runs.csv"), &[]);
+        assert_eq!(csv.lines().count(), 5);
+        assert!(csv.lines().nth(3).unwrap().starts_with("encode_sample,1024,lz4,1,"));
+        assert_eq!(bench(&dir.join("runs.jsonl"), &[]).lines().count(), 4);
+        assert_eq!(bench(&dir.join("runs.txt"), &["--export-format", "ndjson"]).lines().count(), 4);
+        // JSON exports read back as baselines
+        let json = dir.join("runs.json");
+        bench(&json, &[]);
+        assert_eq!(load_baseline(&json).unwrap().len(), 4);
+
+        assert!(Cli::try_parse_from(["gxcore", "bench", "--export-format", "csv"]).is_err());
+        assert!(Cli::try_parse_from(["gxcore", "bench", "--export", "x", "--export-format", "xml"]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_report() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-bench-report-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        let bench = |extra: &[&str]| {
+            let mut argv = vec!["gxcore", "bench", "--size", "1K,2K", "--algos", "none,lz4", "--iterations", "1"];
+            argv.extend(extra);
+            crate::run(Cli::try_parse_from(argv).unwrap())
+        };
+
+        bench(&["--export", &path("main.json"), "--report", &path("first")]).unwrap();
+        let markdown = std::fs::read_to_string(dir.join("first/report.md")).unwrap();
+        assert!(markdown.contains("1 KiB, 2 KiB of sample data"));
+        assert!(markdown.contains("| operation | none | lz4 |"));
+        assert!(markdown.contains("| decode_sample_2048 |"));
+        assert!(!markdown.contains("against"));
+        assert!(std::fs::read_to_string(dir.join("first/report.html")).unwrap().starts_with("<!DOCTYPE html>"));
+
+        // Two saved runs side by side, without running again
+        std::fs::copy(dir.join("main.json"), dir.join("branch.json")).unwrap();
+        bench(&["--baseline", &path("main.json"), "--candidate", &path("branch.json"), "--report", &path("saved")]).unwrap();
+        let markdown = std::fs::read_to_string(dir.join("saved/report.md")).unwrap();
+        assert!(markdown.contains(&format!("## {} against {}", path("branch.json"), path("main.json"))));
+        assert!(markdown.contains("| encode_sample_1024 | lz4 |"));
+        assert!(markdown.contains("+0.0%"));
+
+        assert!(Cli::try_parse_from(["gxcore", "bench", "--candidate", "x"]).is_err());
+        assert!(Cli::try_parse_from(["gxcore", "bench", "--baseline", "a", "--candidate", "b", "--save-baseline", "c"]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
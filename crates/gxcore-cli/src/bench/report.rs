@@ -0,0 +1,251 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use gxcore_core::bench::{BenchmarkResult, Comparison};
+
+/// Results to render, with the baseline they were compared with, if any
+pub struct Report<'a> {
+    /// `this run`, or the name of the saved run standing in for it
+    pub label: &'a str,
+    pub results: &'a [BenchmarkResult],
+    pub baseline: Option<(&'a str, &'a [Comparison])>,
+    pub threshold: f64,
+}
+
+/// How a cell stands out from its row or column
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Plain,
+    /// Fastest in its row, or faster than the baseline by more than the threshold
+    Better,
+    /// Regressed from the baseline
+    Worse,
+}
+
+/// Table rendered alike to Markdown and HTML; the first column is a label, the rest numbers
+struct Table {
+    title: String,
+    headers: Vec<String>,
+    rows: Vec<Vec<(String, Mark)>>,
+}
+
+/// Values in first-seen order
+fn distinct<'a>(values: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut seen = Vec::new();
+    for value in values {
+        if !seen.contains(&value) {
+            seen.push(value);
+        }
+    }
+    seen
+}
+
+/// One row per operation and one column per compression, the fastest in each row marked
+fn by_compression(title: &str, results: &[BenchmarkResult], value: impl Fn(&BenchmarkResult) -> f64, higher_is_better: bool) -> Table {
+    let compressions = distinct(results.iter().map(|result| result.compression.as_str()));
+    let rows = distinct(results.iter().map(|result| result.operation.as_str()))
+        .into_iter()
+        .map(|operation| {
+            let values: Vec<Option<f64>> = compressions
+                .iter()
+                .map(|&compression| {
+                    results
+                        .iter()
+                        .find(|result| result.operation == operation && result.compression == compression)
+                        .map(&value)
+                })
+                .collect();
+            let best = values.iter().flatten().copied().reduce(if higher_is_better { f64::max } else { f64::min });
+            let cells = values.iter().map(|value| match value {
+                Some(value) => (format!("{value:.1}"), if Some(*value) == best && compressions.len() > 1 { Mark::Better } else { Mark::Plain }),
+                None => ("-".to_string(), Mark::Plain),
+            });
+            std::iter::once((operation.to_string(), Mark::Plain)).chain(cells).collect()
+        })
+        .collect();
+    Table {
+        title: title.to_string(),
+        headers: std::iter::once("operation").chain(compressions).map(str::to_string).collect(),
+        rows,
+    }
+}
+
+fn tables(report: &Report) -> Vec<Table> {
+    let mut tables = vec![
+        by_compression("Throughput (MB/s)", report.results, |result| result.throughput_mb_per_sec, true),
+        by_compression("Time per operation (us)", report.results, |result| result.avg_time_per_op_ns / 1000.0, false),
+    ];
+    if let Some((name, comparisons)) = report.baseline {
+        tables.push(Table {
+            title: format!("{} against {name}", report.label),
+            headers: vec![
+                "operation".to_string(),
+                "compression".to_string(),
+                format!("{name} (us)"),
+                format!("{} (us)", report.label),
+                "change".to_string(),
+            ],
+            rows: comparisons
+                .iter()
+                .map(|comparison| {
+                    let mark = match comparison.regressed {
+                        true => Mark::Worse,
+                        false if comparison.change_percent < -report.threshold => Mark::Better,
+                        false => Mark::Plain,
+                    };
+                    vec![
+                        (comparison.operation.clone(), Mark::Plain),
+                        (comparison.compression.clone(), Mark::Plain),
+                        (format!("{:.1}", comparison.baseline_ns / 1000.0), Mark::Plain),
+                        (format!("{:.1}", comparison.current_ns / 1000.0), Mark::Plain),
+                        (format!("{:+.1}%", comparison.change_percent), mark),
+                    ]
+                })
+                .collect(),
+        });
+    }
+    tables
+}
+
+/// `4096` as `4 KiB`, exact sizes only
+fn size(bytes: usize) -> String {
+    match bytes {
+        _ if bytes >= 1 << 20 && bytes.is_multiple_of(1 << 20) => format!("{} MiB", bytes >> 20),
+        _ if bytes >= 1 << 10 && bytes.is_multiple_of(1 << 10) => format!("{} KiB", bytes >> 10),
+        _ => format!("{bytes} bytes"),
+    }
+}
+
+/// One line describing the runs: sizes of the encoded inputs, iterations, threshold
+fn summary(report: &Report) -> String {
+    let encodes = report.results.iter().filter(|result| result.operation.starts_with("encode"));
+    let sizes: Vec<_> = distinct(encodes.clone().map(|result| result.operation.as_str()))
+        .into_iter()
+        .filter_map(|operation| encodes.clone().find(|result| result.operation == operation))
+        .map(|result| size(result.data_size))
+        .collect();
+    let iterations = report.results.first().map_or(0, |result| result.iterations);
+    let mut summary = format!(
+        "{}: gxcore {}, {} of sample data, {iterations} iterations per operation.",
+        report.label,
+        env!("CARGO_PKG_VERSION"),
+        sizes.join(", "),
+    );
+    if report.baseline.is_some() {
+        write!(summary, " Changes over {}% are marked.", report.threshold).unwrap();
+    }
+    summary
+}
+
+fn markdown(report: &Report) -> String {
+    let cell = |text: &str| text.replace('|', "\\|");
+    let mut out = format!("# gxcore benchmark\n\n{}\n", cell(&summary(report)));
+    for table in tables(report) {
+        write!(out, "\n## {}\n\n", table.title).unwrap();
+        let headers: Vec<String> = table.headers.iter().map(|header| cell(header)).collect();
+        writeln!(out, "| {} |", headers.join(" | ")).unwrap();
+        let align: Vec<&str> = (0..headers.len()).map(|i| if i == 0 { ":--" } else { "--:" }).collect();
+        writeln!(out, "| {} |", align.join(" | ")).unwrap();
+        for row in &table.rows {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|(text, mark)| match mark {
+                    Mark::Plain => cell(text),
+                    Mark::Better => format!("**{}**", cell(text)),
+                    Mark::Worse => format!("**{}** regressed", cell(text)),
+                })
+                .collect();
+            writeln!(out, "| {} |", cells.join(" | ")).unwrap();
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+.better { background: #d8f5d8; font-weight: bold; }
+.worse { background: #f8d4d4; font-weight: bold; }";
+
+fn html(report: &Report) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>gxcore benchmark</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>gxcore benchmark</h1>\n<p>{}</p>\n",
+        escape(&summary(report))
+    );
+    for table in tables(report) {
+        write!(out, "<h2>{}</h2>\n<table>\n<tr>", escape(&table.title)).unwrap();
+        for header in &table.headers {
+            write!(out, "<th>{}</th>", escape(header)).unwrap();
+        }
+        out.push_str("</tr>\n");
+        for row in &table.rows {
+            out.push_str("<tr>");
+            for (text, mark) in row {
+                match mark {
+                    Mark::Plain => write!(out, "<td>{}</td>", escape(text)),
+                    Mark::Better => write!(out, "<td class=\"better\">{}</td>", escape(text)),
+                    Mark::Worse => write!(out, "<td class=\"worse\">{}</td>", escape(text)),
+                }
+                .unwrap();
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Write `report.html` and `report.md` into `dir`, creating it if needed
+pub fn write(dir: &Path, report: &Report) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {e}", dir.display()))?;
+    for (name, contents) in [("report.html", html(report)), ("report.md", markdown(report))] {
+        let path = dir.join(name);
+        std::fs::write(&path, contents).map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gxcore_core::bench::compare;
+
+    #[test]
+    fn test_tables_mark_fastest_and_regressions() {
+        let result = |compression: &str, avg_ns: f64| BenchmarkResult {
+            operation: "encode_sample".to_string(),
+            data_size: 4096,
+            compression: compression.to_string(),
+            iterations: 10,
+            total_time_ms: avg_ns * 10.0 / 1e6,
+            avg_time_per_op_ns: avg_ns,
+            throughput_mb_per_sec: 4096.0 / avg_ns * 1e9 / (1024.0 * 1024.0),
+        };
+        let run = |lz4_ns: f64| vec![result("none", 1000.0), result("lz4", lz4_ns)];
+        let (baseline, current) = (run(500.0), run(2000.0));
+        let comparisons = compare(&baseline, &current, 10.0);
+        let report = Report {
+            label: "this run",
+            results: &current,
+            baseline: Some(("main <old>", &comparisons)),
+            threshold: 10.0,
+        };
+
+        let markdown = markdown(&report);
+        assert!(markdown.contains("this run: gxcore"));
+        assert!(markdown.contains("4 KiB of sample data, 10 iterations per operation. Changes over 10% are marked."));
+        assert!(markdown.contains("| operation | none | lz4 |\n| :-- | --: | --: |\n| encode_sample | **1.0** | 2.0 |\n"));
+        assert!(markdown.contains("| encode_sample | lz4 | 0.5 | 2.0 | **+300.0%** regressed |"));
+
+        let html = html(&report);
+        assert!(html.contains("<h2>this run against main &lt;old&gt;</h2>"));
+        assert!(html.contains("<td class=\"worse\">+300.0%</td>"));
+        assert_eq!(html.matches("<table>").count(), 3);
+    }
+}
@@ -0,0 +1,183 @@
+use gxcore_core::CompressionAlgorithm;
+use gxcore_core::envelope::Envelope;
+use gxcore_core::skin::{self, Skin};
+use serde::Serialize;
+
+use crate::armor::{armor, unarmor};
+use crate::io::{describe, print_json, read_input, write_output};
+use crate::seed::fingerprint;
+use crate::CodecArgs;
+
+/// `--json` result of an encode or decode
+#[derive(Serialize)]
+pub struct CodecReport {
+    input: String,
+    output: String,
+    /// Unknown when streaming from stdin
+    input_bytes: Option<u64>,
+    output_bytes: u64,
+    /// Absent when left to the server in remote mode
+    compression: Option<&'static str>,
+    seed_fingerprint: Option<String>,
+    key_id: Option<String>,
+}
+
+impl CodecReport {
+    pub fn new(args: &CodecArgs, input_bytes: Option<u64>, output_bytes: u64) -> Self {
+        CodecReport {
+            input: describe(args.input.as_ref()),
+            output: args.output.as_ref().map_or("stdout".to_string(), |path| path.display().to_string()),
+            input_bytes,
+            output_bytes,
+            compression: args.compression.map(CompressionAlgorithm::name),
+            seed_fingerprint: None,
+            key_id: None,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: &[u8]) -> Self {
+        self.seed_fingerprint = Some(fingerprint(seed));
+        self
+    }
+
+    #[cfg(feature = "remote")]
+    pub fn with_key_id(mut self, key_id: &str) -> Self {
+        self.key_id = Some(key_id.to_string());
+        self
+    }
+
+    pub fn with_compression(mut self, compression: CompressionAlgorithm) -> Self {
+        self.compression = Some(compression.name());
+        self
+    }
+
+    pub fn print(&self) -> Result<(), String> {
+        print_json(self)
+    }
+}
+
+/// Envelope of `data`, armored when asked
+pub fn seal(data: &[u8], seed: &[u8], compression: CompressionAlgorithm, armored: bool) -> Result<Vec<u8>, String> {
+    let sealed = Envelope::seal(data, seed, compression).to_bytes()?;
+    Ok(if armored { armor(&sealed) } else { sealed })
+}
+
+/// Envelope bytes of input dressed in a skin or armored; other input is returned unchanged
+fn unwrap_input(input: &[u8]) -> Result<Vec<u8>, String> {
+    // Skins are recognized by their symbols and armor by its marker, so neither flag is needed
+    if Envelope::detect_format(input).is_none()
+        && let Some((text, skin)) = std::str::from_utf8(input).ok().and_then(|text| Some((text, Skin::detect(text)?)))
+    {
+        return skin::unwrap(text, skin).map_err(|e| format!("{} skin: {e}", skin.name()));
+    }
+    unarmor(input)
+}
+
+/// Contents of an envelope, armored, skinned or not, read from `name`
+pub fn open(name: &str, input: &[u8], seed: &[u8]) -> Result<Vec<u8>, String> {
+    let sealed = unwrap_input(input).map_err(|e| format!("{name}: {e}"))?;
+    let envelope = Envelope::parse(&sealed).map_err(|e| format!("{name}: {e}"))?;
+    envelope.open(seed).map_err(|e| format!("{name}: {e}"))
+}
+
+pub fn encode(args: &CodecArgs, json: bool) -> Result<(), String> {
+    let seed = args.seed.load()?;
+    let data = read_input(args.input.as_ref())?;
+    let sealed = match args.skin {
+        Some(skin) => skin::wrap(&seal(&data, &seed, args.compression(), false)?, skin).into_bytes(),
+        None => seal(&data, &seed, args.compression(), args.armor)?,
+    };
+    write_output(args.output.as_ref(), &sealed)?;
+    if json {
+        CodecReport::new(args, Some(data.len() as u64), sealed.len() as u64)
+            .with_compression(args.compression())
+            .with_seed(&seed)
+            .print()?;
+    }
+    Ok(())
+}
+
+pub fn decode(args: &CodecArgs, json: bool) -> Result<(), String> {
+    let seed = args.seed.load()?;
+    let name = describe(args.input.as_ref());
+    let input = read_input(args.input.as_ref())?;
+    let envelope = unwrap_input(&input)
+        .and_then(|sealed| Envelope::parse(&sealed).map_err(str::to_string))
+        .map_err(|e| format!("{name}: {e}"))?;
+    let data = envelope.open(&seed).map_err(|e| format!("{name}: {e}"))?;
+    write_output(args.output.as_ref(), &data)?;
+    if json {
+        CodecReport::new(args, Some(input.len() as u64), data.len() as u64)
+            .with_compression(envelope.header.compression)
+            .with_seed(&seed)
+            .print()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Cli;
+    use clap::Parser;
+    use std::fs;
+
+    #[test]
+    fn test_files_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-codec-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("k"), b"seed").unwrap();
+        fs::write(dir.join("in.bin"), b"ops data").unwrap();
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+        run(&["gxcore", "encode", "--seed-file", &path("k"), "--compression", "lz4", &path("in.bin"), "-o", &path("out.gx")]).unwrap();
+        assert_ne!(fs::read(dir.join("out.gx")).unwrap(), b"ops data");
+        run(&["gxcore", "decode", "--seed-file", &path("k"), &path("out.gx"), "-o", &path("back.bin")]).unwrap();
+        assert_eq!(fs::read(dir.join("back.bin")).unwrap(), b"ops data");
+
+        fs::write(dir.join("wrong"), b"other").unwrap();
+        assert!(run(&["gxcore", "decode", "--seed-file", &path("wrong"), &path("out.gx"), "-o", &path("x")]).is_err());
+
+        run(&["gxcore", "--json", "encode", "--seed-file", &path("k"), &path("in.bin"), "-o", &path("json.gx")]).unwrap();
+        assert!(run(&["gxcore", "encode", "--json", "--seed-file", &path("k"), &path("in.bin")]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_armored_with_env_seed() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-armor-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("in.bin"), b"piped").unwrap();
+        // SAFETY: no other test reads this variable
+        unsafe { std::env::set_var("GX_TEST_ARMOR_SEED", "seed") };
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+        run(&["gxcore", "encode", "--seed-env", "GX_TEST_ARMOR_SEED", "--armor", &path("in.bin"), "-o", &path("out.asc")]).unwrap();
+        assert!(fs::read_to_string(dir.join("out.asc")).unwrap().starts_with("-----BEGIN GXCORE ENVELOPE-----\n"));
+        run(&["gxcore", "decode", "--seed-env", "GX_TEST_ARMOR_SEED", &path("out.asc"), "-o", &path("back.bin")]).unwrap();
+        assert_eq!(fs::read(dir.join("back.bin")).unwrap(), b"piped");
+        assert!(Cli::try_parse_from(["gxcore", "encode", "--seed-env", "A", "--seed-file", "k"]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_skinned_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-skin-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("k"), b"seed").unwrap();
+        fs::write(dir.join("in.bin"), b"skinned").unwrap();
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+        for skin in ["emoji", "homoglyph", "zero-width"] {
+            run(&["gxcore", "encode", "--seed-file", &path("k"), "--skin", skin, &path("in.bin"), "-o", &path("out.txt")]).unwrap();
+            assert!(!fs::read_to_string(dir.join("out.txt")).unwrap().is_ascii());
+            run(&["gxcore", "decode", "--seed-file", &path("k"), &path("out.txt"), "-o", &path("back.bin")]).unwrap();
+            assert_eq!(fs::read(dir.join("back.bin")).unwrap(), b"skinned");
+        }
+        assert!(Cli::try_parse_from(["gxcore", "encode", "--skin", "emoji", "--armor"]).is_err());
+        assert!(Cli::try_parse_from(["gxcore", "encode", "--skin", "plaid"]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use clap::{Args, CommandFactory};
+use clap_complete::{Shell, generate, generate_to};
+
+use crate::Cli;
+use crate::io::print_json;
+
+/// Shells whose completions are written for packaging
+const PACKAGED_SHELLS: [Shell; 3] = [Shell::Bash, Shell::Zsh, Shell::Fish];
+
+#[derive(Args)]
+pub struct CompletionsArgs {
+    /// Shell to print completions for: `bash`, `zsh`, `fish`, `elvish` or `powershell`
+    #[arg(required_unless_present = "out_dir")]
+    shell: Option<Shell>,
+    /// Write bash, zsh and fish completions and the man pages to this directory instead, for packaging
+    #[arg(long, value_name = "DIR", conflicts_with = "shell")]
+    out_dir: Option<PathBuf>,
+}
+
+/// Print one shell's completions, or write every packaged artifact, all generated from the clap definitions
+pub fn run(args: &CompletionsArgs, json: bool) -> Result<(), String> {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    if let Some(shell) = args.shell {
+        if json {
+            return Err("--json needs --out-dir, as stdout carries the completions".to_string());
+        }
+        generate(shell, &mut command, name, &mut io::stdout());
+        return Ok(());
+    }
+    let dir = args.out_dir.as_ref().unwrap();
+    fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {e}", dir.display()))?;
+    let mut written = Vec::new();
+    for shell in PACKAGED_SHELLS {
+        let path = generate_to(shell, &mut command, &name, dir).map_err(|e| format!("cannot write {shell} completions: {e}"))?;
+        written.push(path);
+    }
+    // One page for `gxcore` and one per subcommand, such as gxcore-encode.1
+    clap_mangen::generate_to(command, dir).map_err(|e| format!("cannot write man pages: {e}"))?;
+    written.push(dir.join(format!("{name}.1")));
+    if json {
+        print_json(&written)?;
+    } else {
+        for path in &written {
+            eprintln!("{}", path.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Cli;
+    use clap::Parser;
+    use std::fs;
+
+    #[test]
+    fn test_packaged_artifacts() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-completions-{}", std::process::id()));
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+
+        run(&["gxcore", "completions", "--out-dir", dir.to_str().unwrap()]).unwrap();
+        for file in ["gxcore.bash", "_gxcore", "gxcore.fish", "gxcore.1", "gxcore-encode.1"] {
+            assert!(dir.join(file).exists(), "{file} missing");
+        }
+        assert!(fs::read_to_string(dir.join("gxcore.bash")).unwrap().contains("--seed-file"));
+        assert!(Cli::try_parse_from(["gxcore", "completions"]).is_err());
+        assert!(Cli::try_parse_from(["gxcore", "completions", "tcsh"]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use clap::{Args, Subcommand};
+use gxcore_core::conformance::{corpus, Case, Corpus, Expected, CORPUS_VERSION};
+use serde_json::json;
+
+use crate::io::{describe, print_json, read_input, write_output};
+
+#[derive(Args)]
+pub struct ConformanceArgs {
+    #[command(subcommand)]
+    action: Action,
+}
+
+#[derive(Subcommand)]
+enum Action {
+    /// Write the corpus of inputs and the outputs this build produces, as JSON
+    Emit {
+        /// Output file, stdout when absent or `-`
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Run a corpus against this build, or against another implementation through an adapter
+    Verify {
+        /// Corpus from `emit`, stdin when absent or `-`
+        corpus: Option<PathBuf>,
+        /// Command answering one JSON request per line on stdin with one JSON result per line
+        #[arg(long, num_args = 1.., allow_hyphen_values = true, value_name = "COMMAND")]
+        adapter: Vec<String>,
+    },
+}
+
+/// Another implementation, spoken to one line of JSON per case
+struct Adapter {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Adapter {
+    fn spawn(command: &[String]) -> Result<Self, String> {
+        let mut child = Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("cannot run {}: {e}", command[0]))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(Adapter { child, stdin, stdout })
+    }
+
+    fn run(&mut self, case: &Case) -> Result<Vec<u8>, String> {
+        ask(case, &mut self.stdin, &mut self.stdout)
+    }
+}
+
+impl Drop for Adapter {
+    fn drop(&mut self) {
+        // Stops adapters that keep waiting for input once the corpus is done
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Send `case` without its expected result and read back `{"output": hex}` or `{"error": message}`
+fn ask(case: &Case, requests: &mut impl Write, results: &mut impl BufRead) -> Result<Vec<u8>, String> {
+    let request = json!({
+        "operation": case.operation,
+        "seed": hex::encode(&case.seed),
+        "compression": case.compression,
+        "input": hex::encode(&case.input),
+    });
+    writeln!(requests, "{request}")
+        .and_then(|_| requests.flush())
+        .map_err(|e| format!("adapter: cannot write request: {e}"))?;
+    let mut line = String::new();
+    match results.read_line(&mut line) {
+        Ok(0) => Err("adapter: exited early".to_string()),
+        Ok(_) => match serde_json::from_str(&line) {
+            Ok(Expected::Output(output)) => Ok(output),
+            Ok(Expected::Error(e)) => Err(e),
+            Err(e) => Err(format!("adapter: invalid result: {e}")),
+        },
+        Err(e) => Err(format!("adapter: cannot read result: {e}")),
+    }
+}
+
+fn verify(path: Option<&PathBuf>, adapter: &[String], json: bool) -> Result<(), String> {
+    let corpus: Corpus = serde_json::from_slice(&read_input(path)?).map_err(|e| format!("{} is not a corpus: {e}", describe(path)))?;
+    if corpus.version != CORPUS_VERSION {
+        return Err(format!("corpus version {} is not {CORPUS_VERSION}", corpus.version));
+    }
+    let failures = match adapter {
+        [] => corpus.verify(Case::run),
+        command => {
+            let mut adapter = Adapter::spawn(command)?;
+            corpus.verify(|case| adapter.run(case))
+        }
+    };
+    if json {
+        print_json(&json!({ "cases": corpus.cases.len(), "failures": failures }))?;
+    } else {
+        for failure in &failures {
+            println!("FAIL  {failure}");
+        }
+        println!("{} of {} cases passed", corpus.cases.len() - failures.len(), corpus.cases.len());
+    }
+    match failures.len() {
+        0 => Ok(()),
+        failed => Err(format!("{failed} case(s) failed")),
+    }
+}
+
+pub fn run(args: &ConformanceArgs, json: bool) -> Result<(), String> {
+    match &args.action {
+        Action::Emit { output } => {
+            let corpus = serde_json::to_vec_pretty(&corpus()).map_err(|e| e.to_string())?;
+            write_output(output.as_ref(), &corpus)
+        }
+        Action::Verify { corpus, adapter } => verify(corpus.as_ref(), adapter, json),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::Parser;
+    use gxcore_core::conformance::Operation;
+    use gxcore_core::CompressionAlgorithm;
+
+    #[test]
+    fn test_emitted_corpus_verifies() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-conformance-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("corpus.json");
+        let path_arg = path.to_str().unwrap();
+        crate::run(Cli::try_parse_from(["gxcore", "conformance", "emit", "-o", path_arg]).unwrap()).unwrap();
+        crate::run(Cli::try_parse_from(["gxcore", "conformance", "verify", path_arg]).unwrap()).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_adapter_protocol() {
+        let case = Case {
+            name: "case".to_string(),
+            operation: Operation::StreamDecode,
+            seed: b"seed".to_vec(),
+            compression: CompressionAlgorithm::Lz4,
+            input: vec![0xab],
+            expected: Expected::Error(String::new()),
+        };
+        let mut requests = Vec::new();
+        let mut results = "{\"output\":\"0102\"}\n{\"error\":\"Checksum mismatch\"}\nnot json\n".as_bytes();
+        assert_eq!(ask(&case, &mut requests, &mut results), Ok(vec![1, 2]));
+        assert_eq!(ask(&case, &mut requests, &mut results), Err("Checksum mismatch".to_string()));
+        assert!(ask(&case, &mut requests, &mut results).unwrap_err().starts_with("adapter: invalid result"));
+        assert_eq!(ask(&case, &mut requests, &mut results), Err("adapter: exited early".to_string()));
+
+        let request: serde_json::Value = serde_json::from_slice(requests.split(|&b| b == b'\n').next().unwrap()).unwrap();
+        assert_eq!(
+            request,
+            json!({ "operation": "stream_decode", "seed": "73656564", "compression": "lz4", "input": "ab" })
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+use std::path::PathBuf;
+
+use clap::Args;
+use gxcore_core::envelope::Envelope;
+use gxcore_core::stream::DEFAULT_CHUNK_SIZE;
+use serde_json::json;
+
+use crate::armor::unarmor;
+use crate::io::{print_json, read_input};
+use crate::profile::Profile;
+use crate::seed::SeedArgs;
+
+/// Differing ranges listed before the rest are only counted
+const MAX_RANGES: usize = 16;
+/// Bytes of each side printed per range with `--show`
+const SHOW_BYTES: usize = 64;
+
+#[derive(Args)]
+pub struct DiffArgs {
+    a: PathBuf,
+    b: PathBuf,
+    #[command(flatten)]
+    seed: SeedArgs,
+    /// Also print the differing bytes of both payloads, which reveals plaintext
+    #[arg(long)]
+    show: bool,
+}
+
+impl DiffArgs {
+    /// Fill what the command line left out from the profile
+    pub fn or_profile(mut self, profile: &Profile) -> Self {
+        self.seed.or_profile(profile);
+        self
+    }
+}
+
+/// Byte ranges where `a` and `b` differ, bytes past the shorter one included
+fn differing_ranges(a: &[u8], b: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for at in (0..a.len().max(b.len())).filter(|&at| a.get(at) != b.get(at)) {
+        match ranges.last_mut() {
+            Some(range) if range.end == at => range.end += 1,
+            _ => ranges.push(at..at + 1),
+        }
+    }
+    ranges
+}
+
+/// Indices of the `DEFAULT_CHUNK_SIZE` chunks, as `--stream` frames them, touched by `ranges`
+fn differing_chunks(ranges: &[Range<usize>]) -> BTreeSet<usize> {
+    ranges
+        .iter()
+        .flat_map(|range| range.start / DEFAULT_CHUNK_SIZE..=(range.end - 1) / DEFAULT_CHUNK_SIZE)
+        .collect()
+}
+
+/// Header fields and metadata entries that differ, as `name: a -> b`
+fn header_differences(a: &Envelope, b: &Envelope) -> Vec<String> {
+    let mut differences = Vec::new();
+    let mut field = |name: &str, a: String, b: String| {
+        if a != b {
+            differences.push(format!("{name}: {a} -> {b}"));
+        }
+    };
+    field("compression", a.header.compression.name().to_string(), b.header.compression.name().to_string());
+    field("payload format", format!("{:?}", a.header.format), format!("{:?}", b.header.format));
+    field("integrity", format!("{:?}", a.header.integrity), format!("{:?}", b.header.integrity));
+    let keys: BTreeSet<&String> = a.metadata.keys().chain(b.metadata.keys()).collect();
+    let value = |metadata: &BTreeMap<String, String>, key: &str| metadata.get(key).map_or("(absent)".to_string(), |v| format!("{v:?}"));
+    for key in keys {
+        field(&format!("metadata {key}"), value(&a.metadata, key), value(&b.metadata, key));
+    }
+    differences
+}
+
+fn open(path: &PathBuf, seed: &[u8]) -> Result<(Envelope, Vec<u8>), String> {
+    let name = path.display();
+    let sealed = unarmor(&read_input(Some(path))?).map_err(|e| format!("{name}: {e}"))?;
+    let envelope = Envelope::parse(&sealed).map_err(|e| format!("{name}: {e}"))?;
+    let data = envelope.open(seed).map_err(|e| format!("{name}: {e}"))?;
+    Ok((envelope, data))
+}
+
+/// Up to `SHOW_BYTES` of `data` within `range`, escaped for printing
+fn shown(data: &[u8], range: &Range<usize>) -> String {
+    let end = range.end.min(range.start + SHOW_BYTES).min(data.len());
+    data[range.start.min(end)..end].escape_ascii().to_string()
+}
+
+fn print_human(args: &DiffArgs, headers: &[String], ranges: &[Range<usize>], data_a: &[u8], data_b: &[u8]) {
+    for difference in headers {
+        println!("{difference}");
+    }
+    if ranges.is_empty() {
+        println!("payloads identical ({} bytes)", data_a.len());
+        return;
+    }
+    let differing: usize = ranges.iter().map(ExactSizeIterator::len).sum();
+    println!(
+        "payloads differ: {} and {} bytes, {differing} byte(s) in {} range(s)",
+        data_a.len(),
+        data_b.len(),
+        ranges.len()
+    );
+    let chunks: Vec<String> = differing_chunks(ranges).iter().map(usize::to_string).collect();
+    println!("chunks of {DEFAULT_CHUNK_SIZE} bytes: {}", chunks.join(", "));
+    for range in ranges.iter().take(MAX_RANGES) {
+        println!("  {}..{}", range.start, range.end);
+        if args.show {
+            println!("    a: \"{}\"", shown(data_a, range));
+            println!("    b: \"{}\"", shown(data_b, range));
+        }
+    }
+    if ranges.len() > MAX_RANGES {
+        println!("  and {} more", ranges.len() - MAX_RANGES);
+    }
+}
+
+fn print_json_report(args: &DiffArgs, headers: &[String], ranges: &[Range<usize>], data_a: &[u8], data_b: &[u8]) -> Result<(), String> {
+    let listed: Vec<_> = ranges
+        .iter()
+        .take(MAX_RANGES)
+        .map(|range| {
+            if args.show {
+                json!({ "start": range.start, "end": range.end, "a": shown(data_a, range), "b": shown(data_b, range) })
+            } else {
+                json!({ "start": range.start, "end": range.end })
+            }
+        })
+        .collect();
+    print_json(&json!({
+        "identical": headers.is_empty() && ranges.is_empty(),
+        "headers": headers,
+        "a_bytes": data_a.len(),
+        "b_bytes": data_b.len(),
+        "differing_bytes": ranges.iter().map(ExactSizeIterator::len).sum::<usize>(),
+        "ranges": listed,
+        "unlisted_ranges": ranges.len().saturating_sub(MAX_RANGES),
+        "chunk_size": DEFAULT_CHUNK_SIZE,
+        "chunks": differing_chunks(ranges),
+    }))
+}
+
+/// Report how two envelopes differ, failing when they do, like diff(1)
+pub fn run(args: &DiffArgs, json: bool) -> Result<(), String> {
+    let seed = args.seed.load()?;
+    let (a, data_a) = open(&args.a, &seed)?;
+    let (b, data_b) = open(&args.b, &seed)?;
+    let headers = header_differences(&a, &b);
+    let ranges = differing_ranges(&data_a, &data_b);
+    if json {
+        print_json_report(args, &headers, &ranges, &data_a, &data_b)?;
+    } else {
+        print_human(args, &headers, &ranges, &data_a, &data_b);
+    }
+    if headers.is_empty() && ranges.is_empty() {
+        Ok(())
+    } else {
+        Err("blobs differ".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{differing_chunks, differing_ranges};
+    use crate::Cli;
+    use clap::Parser;
+    use gxcore_core::envelope::Envelope;
+    use gxcore_core::stream::DEFAULT_CHUNK_SIZE;
+    use gxcore_core::CompressionAlgorithm;
+    use std::fs;
+
+    #[test]
+    fn test_ranges_and_chunks() {
+        assert!(differing_ranges(b"same", b"same").is_empty());
+        assert_eq!(differing_ranges(b"abcdef", b"aXYdeZ"), vec![1..3, 5..6]);
+        assert_eq!(differing_ranges(b"abc", b"abcde"), vec![3..5]);
+        let ranges = [10..20, DEFAULT_CHUNK_SIZE * 2 - 1..DEFAULT_CHUNK_SIZE * 2 + 1];
+        assert_eq!(differing_chunks(&ranges).into_iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_diff_envelopes() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-diff-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("k"), b"seed").unwrap();
+        let seal = |data: &[u8], compression| Envelope::seal(data, b"seed", compression).to_bytes().unwrap();
+        fs::write(dir.join("a.gx"), seal(b"release 1.0", CompressionAlgorithm::None)).unwrap();
+        fs::write(dir.join("same.gx"), seal(b"release 1.0", CompressionAlgorithm::None)).unwrap();
+        fs::write(dir.join("b.gx"), seal(b"release 1.1", CompressionAlgorithm::None)).unwrap();
+        fs::write(dir.join("lz4.gx"), seal(b"release 1.0", CompressionAlgorithm::Lz4)).unwrap();
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+        run(&["gxcore", "diff", &path("a.gx"), &path("same.gx"), "--seed-file", &path("k")]).unwrap();
+        assert!(run(&["gxcore", "diff", &path("a.gx"), &path("b.gx"), "--seed-file", &path("k"), "--show"]).is_err());
+        assert!(run(&["gxcore", "diff", &path("a.gx"), &path("lz4.gx"), "--seed-file", &path("k")]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
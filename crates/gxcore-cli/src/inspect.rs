@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use gxcore_core::inspect::{inspect, Inspection};
+
+use crate::armor::unarmor;
+use crate::io::{describe, print_json, read_input};
+
+#[derive(Args)]
+pub struct InspectArgs {
+    /// Envelope or bare encoded payload, stdin when absent or `-`
+    input: Option<PathBuf>,
+}
+
+/// Whether nothing found contradicts a well-formed blob
+pub fn structurally_valid(report: &Inspection) -> bool {
+    report.error.is_none() && report.checksum_valid != Some(false) && report.foreign_characters == 0 && report.sizes.inner.is_some()
+}
+
+fn print_human(report: &Inspection) {
+    let field = |value: Option<&str>| value.unwrap_or("-").to_string();
+    println!("kind:         {}", report.kind);
+    println!("version:      {}", report.version.map_or("-".to_string(), |v| v.to_string()));
+    println!("compression:  {}", field(report.compression));
+    println!("format:       {}", field(report.payload_format));
+    println!("integrity:    {}", field(report.integrity));
+    println!("key id:       {}", field(report.key_id.as_deref()));
+    for (key, value) in report.metadata.iter().filter(|(key, _)| key.as_str() != "key_id") {
+        println!("metadata:     {key}={value}");
+    }
+    let checksum = match report.checksum_valid {
+        Some(true) => "valid",
+        Some(false) => "mismatch",
+        None => "-",
+    };
+    println!("checksum:     {checksum}");
+    let inner = report.sizes.inner.map_or("-".to_string(), |inner| inner.to_string());
+    println!("sizes:        total {}, payload {}, inner {inner}", report.sizes.total, report.sizes.payload);
+    if let Some(error) = report.error {
+        println!("error:        {error}");
+    }
+    println!("structure:    {}", if structurally_valid(report) { "valid" } else { "invalid" });
+}
+
+pub fn run(args: &InspectArgs, json: bool) -> Result<(), String> {
+    let blob = unarmor(&read_input(args.input.as_ref())?).map_err(|e| format!("{}: {e}", describe(args.input.as_ref())))?;
+    let report = inspect(&blob);
+    if json {
+        print_json(&report)?;
+    } else {
+        print_human(&report);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gxcore_core::envelope::Envelope;
+    use gxcore_core::CompressionAlgorithm;
+
+    #[test]
+    fn test_structural_validity() {
+        let sealed = Envelope::seal(b"artifact", b"seed", CompressionAlgorithm::Lz4).to_bytes().unwrap();
+        assert!(structurally_valid(&inspect(&sealed)));
+        let mut corrupt = sealed.clone();
+        let last = corrupt.len() - 6;
+        corrupt[last] ^= 1;
+        assert!(!structurally_valid(&inspect(&corrupt)));
+        assert!(!structurally_valid(&inspect(b"not~encoded")));
+    }
+}
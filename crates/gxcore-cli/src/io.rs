@@ -0,0 +1,56 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Whether `path` means stdin or stdout: absent or `-`
+pub fn is_std(path: Option<&PathBuf>) -> bool {
+    path.is_none_or(|path| path.as_path() == Path::new("-"))
+}
+
+/// Contents of `path`, or all of stdin
+pub fn read_input(path: Option<&PathBuf>) -> Result<Vec<u8>, String> {
+    if is_std(path) {
+        let mut data = Vec::new();
+        io::stdin().lock().read_to_end(&mut data).map_err(|e| format!("cannot read stdin: {e}"))?;
+        return Ok(data);
+    }
+    let path = path.unwrap();
+    fs::read(path).map_err(|e| format!("cannot read {}: {e}", path.display()))
+}
+
+/// Write `data` to `path`, or unchanged to stdout so binary output survives pipes
+pub fn write_output(path: Option<&PathBuf>, data: &[u8]) -> Result<(), String> {
+    if is_std(path) {
+        let mut stdout = io::stdout().lock();
+        return stdout
+            .write_all(data)
+            .and_then(|_| stdout.flush())
+            .map_err(|e| format!("cannot write stdout: {e}"));
+    }
+    let path = path.unwrap();
+    fs::write(path, data).map_err(|e| format!("cannot write {}: {e}", path.display()))
+}
+
+/// Print `value` as the `--json` result on stdout
+pub fn print_json(value: &impl Serialize) -> Result<(), String> {
+    println!("{}", serde_json::to_string_pretty(value).map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+/// Refuse `--json` when `path` is stdout, which the JSON result takes
+pub fn json_output(json: bool, path: Option<&PathBuf>) -> Result<(), String> {
+    if json && is_std(path) {
+        return Err("--json needs an output file, as stdout carries the JSON".to_string());
+    }
+    Ok(())
+}
+
+/// Name of an input in diagnostics
+pub fn describe(path: Option<&PathBuf>) -> String {
+    match path {
+        Some(path) if !is_std(Some(path)) => path.display().to_string(),
+        _ => "stdin".to_string(),
+    }
+}
@@ -0,0 +1,101 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+
+use crate::io::print_json;
+use crate::platform::{create_secret, keychain_set};
+use crate::seed::{fingerprint, generate_seed};
+use crate::shamir;
+
+#[derive(Args)]
+pub struct KeygenArgs {
+    /// Seed file to create, readable by its owner only
+    #[arg(short, long, required_unless_present = "keychain")]
+    output: Option<PathBuf>,
+    /// Store the seed in the OS keychain under this name instead
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["output", "shares"])]
+    keychain: Option<String>,
+    /// Also split the seed into this many Shamir shares, written to `<output>.1` and on
+    #[arg(long, requires = "threshold", requires = "output")]
+    shares: Option<u8>,
+    /// Shares needed to rebuild the seed
+    #[arg(long, requires = "shares")]
+    threshold: Option<u8>,
+    /// Rebuild the seed in `--output` from these shares instead of generating one
+    #[arg(long, num_args = 2.., value_name = "SHARE", conflicts_with_all = ["shares", "keychain"])]
+    combine: Vec<PathBuf>,
+}
+
+/// Create `path` with owner-only permissions, refusing to replace an existing file
+fn write_secret(path: &Path, data: &[u8]) -> Result<(), String> {
+    create_secret(path)
+        .and_then(|mut file| file.write_all(data))
+        .map_err(|e| format!("cannot create {}: {e}", path.display()))
+}
+
+pub fn keygen(args: &KeygenArgs, json: bool) -> Result<(), String> {
+    let seed = match args.combine.as_slice() {
+        [] => generate_seed(),
+        paths => {
+            let shares = paths
+                .iter()
+                .map(|path| fs::read(path).map_err(|e| format!("cannot read {}: {e}", path.display())))
+                .collect::<Result<Vec<_>, _>>()?;
+            shamir::combine(&shares)?
+        }
+    };
+    match (&args.keychain, &args.output) {
+        (Some(name), _) => keychain_set(name, &seed)?,
+        (None, Some(path)) => write_secret(path, &seed)?,
+        (None, None) => return Err("no output given".to_string()),
+    }
+    let mut shares = Vec::new();
+    if let (Some(count), Some(threshold), Some(path)) = (args.shares, args.threshold, &args.output) {
+        for share in shamir::split(&seed, count, threshold)? {
+            let mut share_path = path.clone().into_os_string();
+            share_path.push(format!(".{}", share[0]));
+            write_secret(Path::new(&share_path), &share)?;
+            shares.push(PathBuf::from(share_path));
+        }
+    }
+    if json {
+        print_json(&serde_json::json!({
+            "fingerprint": fingerprint(&seed),
+            "output": args.output,
+            "keychain": args.keychain,
+            "shares": shares,
+        }))
+    } else {
+        println!("fingerprint: {}", fingerprint(&seed));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Cli;
+    use clap::Parser;
+    use std::fs;
+
+    #[test]
+    fn test_keygen_with_shares() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-keygen-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+        run(&["gxcore", "keygen", "-o", &path("k"), "--shares", "3", "--threshold", "2"]).unwrap();
+        let seed = fs::read(dir.join("k")).unwrap();
+        assert_eq!(seed.len(), crate::seed::SEED_LEN);
+        #[cfg(unix)]
+        assert_eq!(std::os::unix::fs::PermissionsExt::mode(&fs::metadata(dir.join("k")).unwrap().permissions()) & 0o777, 0o600);
+        // Never overwrites an existing seed
+        assert!(run(&["gxcore", "keygen", "-o", &path("k")]).is_err());
+
+        run(&["gxcore", "keygen", "-o", &path("rebuilt"), "--combine", &path("k.3"), &path("k.1")]).unwrap();
+        assert_eq!(fs::read(dir.join("rebuilt")).unwrap(), seed);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use gxcore_core::CompressionAlgorithm;
+use gxcore_core::skin::Skin;
+
+mod armor;
+mod bench;
+mod codec;
+mod completions;
+mod conformance;
+mod diff;
+mod inspect;
+mod io;
+mod keygen;
+mod pack;
+mod platform;
+mod profile;
+#[cfg(feature = "remote")]
+mod remote;
+mod seed;
+mod selftest;
+mod shamir;
+mod stream;
+mod verify;
+mod watch;
+
+/// Encode and decode gxcore envelopes without the HTTP service.
+/// Inputs default to stdin and outputs to stdout; diagnostics only go to stderr.
+#[derive(Parser)]
+#[command(name = "gxcore", version)]
+struct Cli {
+    /// Config file with named profiles, instead of ~/.config/gxcore/config.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Profile of the config supplying flags not given, `default` when it exists
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+    /// Encode and decode through the HTTP service at this URL, or the profile's server
+    #[arg(long, global = true, value_name = "URL", num_args = 0..=1)]
+    remote: Option<Option<String>>,
+    /// API key sent to the service in remote mode
+    #[arg(long, global = true, env = "GXCORE_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+    /// Print results, or the error, as JSON on stdout instead of text
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Seal a file in an envelope
+    Encode(CodecArgs),
+    /// Open an envelope, with the compression its header names
+    Decode(CodecArgs),
+    /// Generate a random seed and print its fingerprint
+    Keygen(keygen::KeygenArgs),
+    /// Describe an envelope's header, sizes and structure without its seed
+    Inspect(inspect::InspectArgs),
+    /// Time encode and decode locally, as the HTTP benchmark does, and compare compressions
+    Bench(bench::BenchArgs),
+    /// Check golden vectors, round trips and timing to validate this build on this machine
+    Selftest(selftest::SelftestArgs),
+    /// Check an envelope's structure, and with a seed that it opens; fails with a nonzero exit
+    Verify(verify::VerifyArgs),
+    /// Open two envelopes and report where their payloads and headers differ; fails when they do
+    Diff(diff::DiffArgs),
+    /// Seal or open files as they appear in a directory, recording them in a manifest
+    Watch(watch::WatchArgs),
+    /// Archive a directory's files, paths and permissions in one sealed envelope
+    Pack(pack::PackArgs),
+    /// Restore a directory archived with `pack`
+    Unpack(pack::UnpackArgs),
+    /// Print shell completions, or write them with the man pages for packaging
+    Completions(completions::CompletionsArgs),
+    /// Write the cross-language conformance corpus, or check an implementation against one
+    Conformance(conformance::ConformanceArgs),
+}
+
+#[derive(Args)]
+struct CodecArgs {
+    /// Input file, stdin when absent or `-`
+    input: Option<PathBuf>,
+    /// Output file, stdout when absent or `-`
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    #[command(flatten)]
+    seed: seed::SeedArgs,
+    /// Server-held key to use in remote mode instead of a seed
+    #[arg(long, value_name = "ID", conflicts_with = "SeedArgs")]
+    key_id: Option<String>,
+    /// `none` (the default), `lz4`, `lz4frame` or `brotli`; decoding envelopes reads it from their header
+    #[arg(long, value_parser = parse_compression)]
+    compression: Option<CompressionAlgorithm>,
+    /// Write the envelope as base64 text between markers; decoding recognizes armor itself
+    #[arg(long, conflicts_with = "stream")]
+    armor: bool,
+    /// Write the envelope as `emoji`, `homoglyph` or `zero-width` symbols instead of bytes, for
+    /// channels that block base64; decoding recognizes skins itself
+    #[arg(long, value_parser = parse_skin, conflicts_with_all = ["stream", "armor"])]
+    skin: Option<Skin>,
+    /// Process in chunks with a progress bar instead of loading the whole input, for large files
+    #[arg(long)]
+    stream: bool,
+    /// Continue an interrupted `--stream` run from what the output already holds
+    #[arg(long, requires = "stream")]
+    resume: bool,
+}
+
+impl CodecArgs {
+    /// Fill what the command line left out from the profile
+    fn or_profile(mut self, profile: &profile::Profile) -> Self {
+        self.seed.or_profile(profile);
+        self.compression = self.compression.or(profile.compression);
+        self.armor |= profile.armor && !self.stream;
+        self
+    }
+
+    fn compression(&self) -> CompressionAlgorithm {
+        self.compression.unwrap_or_default()
+    }
+}
+
+/// Remote mode of builds without the HTTP client, such as WASI ones, which refuses `--remote`
+#[cfg(not(feature = "remote"))]
+mod remote {
+    use crate::CodecArgs;
+    use crate::profile::Profile;
+
+    pub enum Remote {}
+
+    impl Remote {
+        pub fn new(remote: Option<Option<String>>, _api_key: Option<String>, _profile: &Profile) -> Result<Option<Self>, String> {
+            match remote {
+                Some(_) => Err("--remote needs a build with the remote feature".to_string()),
+                None => Ok(None),
+            }
+        }
+
+        pub fn encode(&self, _args: &CodecArgs, _json: bool) -> Result<(), String> {
+            match *self {}
+        }
+
+        pub fn decode(&self, _args: &CodecArgs, _json: bool) -> Result<(), String> {
+            match *self {}
+        }
+    }
+}
+
+/// Compression names accepted by the HTTP API
+fn parse_compression(name: &str) -> Result<CompressionAlgorithm, String> {
+    match name {
+        "none" => Ok(CompressionAlgorithm::None),
+        "lz4" => Ok(CompressionAlgorithm::Lz4),
+        "brotli" => Ok(CompressionAlgorithm::Brotli),
+        "lz4frame" => Ok(CompressionAlgorithm::Lz4Frame),
+        _ => Err(format!("unknown compression {name}")),
+    }
+}
+
+fn parse_skin(name: &str) -> Result<Skin, String> {
+    Skin::from_name(name).map_err(|_| format!("unknown skin {name}"))
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let profile = profile::Profile::load(cli.config.as_deref(), cli.profile.as_deref())?;
+    let remote = remote::Remote::new(cli.remote, cli.api_key, &profile)?;
+    let json = cli.json;
+    match cli.command {
+        Command::Encode(args) | Command::Decode(args) if remote.is_none() && args.key_id.is_some() => {
+            Err("--key-id names a server-held key and needs --remote".to_string())
+        }
+        Command::Encode(args) => {
+            let args = args.or_profile(&profile);
+            io::json_output(json, args.output.as_ref())?;
+            match &remote {
+                Some(remote) => remote.encode(&args, json),
+                None if args.stream => stream::encode(&args, json),
+                None => codec::encode(&args, json),
+            }
+        }
+        Command::Decode(args) => {
+            let args = args.or_profile(&profile);
+            io::json_output(json, args.output.as_ref())?;
+            match &remote {
+                Some(remote) => remote.decode(&args, json),
+                None if args.stream => stream::decode(&args, json),
+                None => codec::decode(&args, json),
+            }
+        }
+        Command::Keygen(args) => keygen::keygen(&args, json),
+        Command::Inspect(args) => inspect::run(&args, json),
+        Command::Bench(args) => bench::run(&args, json),
+        Command::Selftest(args) => selftest::run(&args, json),
+        Command::Verify(args) => verify::run(&args, json),
+        Command::Diff(args) => diff::run(&args.or_profile(&profile), json),
+        Command::Watch(args) => watch::run(&args.or_profile(&profile), json),
+        Command::Pack(args) => pack::pack(&args.or_profile(&profile), json),
+        Command::Unpack(args) => pack::unpack(&args.or_profile(&profile), json),
+        Command::Completions(args) => completions::run(&args, json),
+        Command::Conformance(args) => conformance::run(&args, json),
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let json = cli.json;
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) if json => {
+            println!("{}", serde_json::json!({ "error": e }));
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("gxcore: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
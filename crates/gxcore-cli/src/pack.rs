@@ -0,0 +1,173 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use gxcore_core::CompressionAlgorithm;
+use gxcore_core::archive::{Archive, EntryKind};
+use gxcore_core::envelope::Envelope;
+use serde_json::json;
+
+use crate::armor::unarmor;
+use crate::io::{describe, print_json, read_input, write_output};
+use crate::parse_compression;
+use crate::platform::{mode_of, set_mode};
+use crate::profile::Profile;
+use crate::seed::SeedArgs;
+
+#[derive(Args)]
+pub struct PackArgs {
+    /// Directory to archive
+    dir: PathBuf,
+    /// Archive to write, stdout when absent or `-`
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    #[command(flatten)]
+    seed: SeedArgs,
+    /// `none` (the default), `lz4`, `lz4frame` or `brotli`
+    #[arg(long, value_parser = parse_compression)]
+    compression: Option<CompressionAlgorithm>,
+}
+
+#[derive(Args)]
+pub struct UnpackArgs {
+    /// Archive, stdin when absent or `-`
+    input: Option<PathBuf>,
+    /// Directory to restore into, created when missing
+    #[arg(short = 'C', long, default_value = ".")]
+    directory: PathBuf,
+    #[command(flatten)]
+    seed: SeedArgs,
+}
+
+impl PackArgs {
+    /// Fill what the command line left out from the profile
+    pub fn or_profile(mut self, profile: &Profile) -> Self {
+        self.seed.or_profile(profile);
+        self.compression = self.compression.or(profile.compression);
+        self
+    }
+}
+
+impl UnpackArgs {
+    /// Fill what the command line left out from the profile
+    pub fn or_profile(mut self, profile: &Profile) -> Self {
+        self.seed.or_profile(profile);
+        self
+    }
+}
+
+/// Add everything under `dir` to the archive as `prefix/...`, in name order so archives are reproducible.
+/// Symlinks and special files are skipped with a warning.
+fn walk(archive: &mut Archive, dir: &Path, prefix: &str) -> Result<(), String> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("cannot read {}: {e}", dir.display()))?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| format!("{} is not valid UTF-8", path.display()))?;
+        let archived = if prefix.is_empty() { name } else { format!("{prefix}/{name}") };
+        let metadata = fs::symlink_metadata(&path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+        if metadata.is_dir() {
+            archive.add_directory(&archived, mode_of(&metadata)).map_err(|e| format!("{archived}: {e}"))?;
+            walk(archive, &path, &archived)?;
+        } else if metadata.is_file() {
+            let data = fs::read(&path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+            archive.add_file(&archived, mode_of(&metadata), data).map_err(|e| format!("{archived}: {e}"))?;
+        } else {
+            eprintln!("skipping {}: not a regular file or directory", path.display());
+        }
+    }
+    Ok(())
+}
+
+pub fn pack(args: &PackArgs, json: bool) -> Result<(), String> {
+    crate::io::json_output(json, args.output.as_ref())?;
+    let seed = args.seed.load()?;
+    let mut archive = Archive::new();
+    walk(&mut archive, &args.dir, "")?;
+    let envelope = archive
+        .seal(&seed, args.compression.unwrap_or_default())
+        .map_err(|e| format!("{}: {e}", args.dir.display()))?;
+    let bytes = envelope.to_bytes()?;
+    write_output(args.output.as_ref(), &bytes)?;
+    if json {
+        print_json(&json!({
+            "output": describe(args.output.as_ref()),
+            "entries": archive.entries.len(),
+            "bytes": bytes.len(),
+        }))?;
+    }
+    Ok(())
+}
+
+/// Restore every entry under `--directory`. Directory permissions are applied last,
+/// so read-only directories still receive their files.
+pub fn unpack(args: &UnpackArgs, json: bool) -> Result<(), String> {
+    let seed = args.seed.load()?;
+    let name = describe(args.input.as_ref());
+    let sealed = unarmor(&read_input(args.input.as_ref())?).map_err(|e| format!("{name}: {e}"))?;
+    let archive = Envelope::parse(&sealed)
+        .and_then(|envelope| Archive::open(&envelope, &seed))
+        .map_err(|e| format!("{name}: {e}"))?;
+    let root = &args.directory;
+    fs::create_dir_all(root).map_err(|e| format!("cannot create {}: {e}", root.display()))?;
+    for entry in &archive.entries {
+        let path = root.join(&entry.path);
+        let written = match entry.kind {
+            EntryKind::Directory => fs::create_dir_all(&path),
+            EntryKind::File => path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, &entry.data))
+                .and_then(|_| set_mode(&path, entry.mode)),
+        };
+        written.map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+    }
+    for entry in archive.entries.iter().rev().filter(|entry| entry.kind == EntryKind::Directory) {
+        let path = root.join(&entry.path);
+        set_mode(&path, entry.mode).map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+    }
+    if json {
+        print_json(&json!({ "directory": root, "entries": archive.entries.len() }))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Cli;
+    use clap::Parser;
+    use std::fs;
+
+    #[test]
+    fn test_directory_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-pack-{}", std::process::id()));
+        fs::create_dir_all(dir.join("tree/nested/empty")).unwrap();
+        fs::write(dir.join("k"), b"seed").unwrap();
+        fs::write(dir.join("tree/top.txt"), b"top").unwrap();
+        fs::write(dir.join("tree/nested/run.sh"), b"#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        fs::set_permissions(dir.join("tree/nested/run.sh"), std::os::unix::fs::PermissionsExt::from_mode(0o750)).unwrap();
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+        run(&["gxcore", "pack", &path("tree"), "-o", &path("bundle.gxar"), "--seed-file", &path("k"), "--compression", "brotli"]).unwrap();
+        run(&["gxcore", "unpack", &path("bundle.gxar"), "-C", &path("restored"), "--seed-file", &path("k")]).unwrap();
+        assert_eq!(fs::read(dir.join("restored/top.txt")).unwrap(), b"top");
+        assert_eq!(fs::read(dir.join("restored/nested/run.sh")).unwrap(), b"#!/bin/sh\n");
+        assert!(dir.join("restored/nested/empty").is_dir());
+        #[cfg(unix)]
+        assert_eq!(
+            std::os::unix::fs::PermissionsExt::mode(&fs::metadata(dir.join("restored/nested/run.sh")).unwrap().permissions()) & 0o777,
+            0o750
+        );
+
+        fs::write(dir.join("wrong"), b"other").unwrap();
+        assert!(run(&["gxcore", "unpack", &path("bundle.gxar"), "-C", &path("x"), "--seed-file", &path("wrong")]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs::{self, File, OpenOptions};
+use std::path::Path;
+use std::time::Duration;
+
+/// Keychain service seeds are stored under
+#[cfg(not(target_os = "wasi"))]
+const KEYCHAIN_SERVICE: &str = "gxcore";
+
+/// Read a seed stored with `keychain_set`
+#[cfg(not(target_os = "wasi"))]
+pub fn keychain_get(name: &str) -> Result<Vec<u8>, String> {
+    keychain::Entry::new(KEYCHAIN_SERVICE, name)
+        .and_then(|entry| entry.get_secret())
+        .map_err(|e| format!("cannot read {name} from the keychain: {e}"))
+}
+
+#[cfg(not(target_os = "wasi"))]
+pub fn keychain_set(name: &str, secret: &[u8]) -> Result<(), String> {
+    keychain::Entry::new(KEYCHAIN_SERVICE, name)
+        .and_then(|entry| entry.set_secret(secret))
+        .map_err(|e| format!("cannot store {name} in the keychain: {e}"))
+}
+
+#[cfg(target_os = "wasi")]
+pub fn keychain_get(name: &str) -> Result<Vec<u8>, String> {
+    Err(format!("cannot read {name}: there is no keychain under WASI, use --seed-file or --seed-env"))
+}
+
+#[cfg(target_os = "wasi")]
+pub fn keychain_set(name: &str, _secret: &[u8]) -> Result<(), String> {
+    Err(format!("cannot store {name}: there is no keychain under WASI, use --output"))
+}
+
+/// Create `path` for a secret, readable by its owner only where files have permissions,
+/// refusing to replace an existing file
+pub fn create_secret(path: &Path) -> std::io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+#[cfg(unix)]
+pub fn mode_of(metadata: &fs::Metadata) -> u32 {
+    std::os::unix::fs::PermissionsExt::mode(&metadata.permissions()) & 0o7777
+}
+
+/// Conventional permission bits where the platform has none
+#[cfg(not(unix))]
+pub fn mode_of(metadata: &fs::Metadata) -> u32 {
+    if metadata.is_dir() { 0o755 } else { 0o644 }
+}
+
+#[cfg(unix)]
+pub fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    fs::set_permissions(path, std::os::unix::fs::PermissionsExt::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+pub fn set_mode(_path: &Path, _mode: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Changes to the files of a directory, from OS notifications
+#[cfg(not(target_os = "wasi"))]
+pub struct Changes {
+    _watcher: notify::RecommendedWatcher,
+    events: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl Changes {
+    pub fn watch(dir: &Path) -> Result<Self, String> {
+        use notify::Watcher;
+
+        let (sender, events) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(|e| format!("cannot watch: {e}"))?;
+        watcher
+            .watch(dir, notify::RecursiveMode::NonRecursive)
+            .map_err(|e| format!("cannot watch {}: {e}", dir.display()))?;
+        Ok(Changes { _watcher: watcher, events })
+    }
+
+    /// Block until something changes, then until nothing has for `settle`
+    pub fn wait(&mut self, settle: Duration) -> Result<(), String> {
+        if let Err(e) = self.events.recv().map_err(|_| "watcher stopped".to_string())? {
+            eprintln!("gxcore: {e}");
+        }
+        while self.events.recv_timeout(settle).is_ok() {}
+        Ok(())
+    }
+}
+
+/// Names, sizes and modification times of a directory's entries, in name order
+#[cfg(target_os = "wasi")]
+type Listing = Vec<(std::ffi::OsString, u64, Option<std::time::SystemTime>)>;
+
+/// Changes to the files of a directory, found by listing it periodically since WASI has no notifications
+#[cfg(target_os = "wasi")]
+pub struct Changes {
+    dir: std::path::PathBuf,
+    listing: Listing,
+}
+
+#[cfg(target_os = "wasi")]
+impl Changes {
+    pub fn watch(dir: &Path) -> Result<Self, String> {
+        let mut changes = Changes { dir: dir.to_path_buf(), listing: Vec::new() };
+        changes.listing = changes.list()?;
+        Ok(changes)
+    }
+
+    fn list(&self) -> Result<Listing, String> {
+        let mut listing: Vec<_> = fs::read_dir(&self.dir)
+            .map_err(|e| format!("cannot read {}: {e}", self.dir.display()))?
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                Some((entry.file_name(), metadata.len(), metadata.modified().ok()))
+            })
+            .collect();
+        listing.sort();
+        Ok(listing)
+    }
+
+    /// Poll until the listing changes, then until it has not for `settle`
+    pub fn wait(&mut self, settle: Duration) -> Result<(), String> {
+        loop {
+            std::thread::sleep(settle);
+            let listing = self.list()?;
+            if listing != self.listing {
+                self.listing = listing;
+                break;
+            }
+        }
+        loop {
+            std::thread::sleep(settle);
+            let listing = self.list()?;
+            if listing == self.listing {
+                return Ok(());
+            }
+            self.listing = listing;
+        }
+    }
+}
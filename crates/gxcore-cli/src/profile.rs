@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use gxcore_core::CompressionAlgorithm;
+use figment::Figment;
+use figment::providers::{Format, Toml};
+use serde::Deserialize;
+
+/// Profile applied when `--profile` is not given, if the config defines it
+const DEFAULT_PROFILE: &str = "default";
+
+/// Defaults for flags left off the command line, from a `[profiles.NAME]` table of the config
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub seed_file: Option<PathBuf>,
+    pub seed_env: Option<String>,
+    pub seed_keychain: Option<String>,
+    pub compression: Option<CompressionAlgorithm>,
+    #[serde(default)]
+    pub armor: bool,
+    /// Base URL of the HTTP service for remote mode
+    pub server: Option<String>,
+    /// Server-held key remote mode uses when no seed or `--key-id` is given
+    #[cfg_attr(not(feature = "remote"), allow(dead_code))]
+    pub key_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CliConfig {
+    #[serde(default)]
+    profiles: HashMap<String, Profile>,
+}
+
+/// `$XDG_CONFIG_HOME/gxcore/config.toml`, falling back to `~/.config`
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("gxcore").join("config.toml"))
+}
+
+impl Profile {
+    /// Profile `name` from the config at `path` or the default location.
+    /// Naming a profile requires it to exist; otherwise `default` is used when defined, and no defaults when not.
+    pub fn load(path: Option<&Path>, name: Option<&str>) -> Result<Self, String> {
+        let Some(path) = path.map(Path::to_path_buf).or_else(default_path) else {
+            return match name {
+                Some(name) => Err(format!("no config to read profile {name} from")),
+                None => Ok(Profile::default()),
+            };
+        };
+        if !path.exists() {
+            return match name {
+                Some(name) => Err(format!("cannot read profile {name}: {} does not exist", path.display())),
+                None => Ok(Profile::default()),
+            };
+        }
+        let mut config: CliConfig = Figment::new()
+            .merge(Toml::file_exact(&path))
+            .extract()
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        let profile = match name {
+            Some(name) => config
+                .profiles
+                .remove(name)
+                .ok_or_else(|| format!("{} has no profile {name}", path.display()))?,
+            None => config.profiles.remove(DEFAULT_PROFILE).unwrap_or_default(),
+        };
+        profile.validate().map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(profile)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let seeds = [self.seed_file.is_some(), self.seed_env.is_some(), self.seed_keychain.is_some()];
+        if seeds.into_iter().filter(|&set| set).count() > 1 {
+            return Err("a profile sets at most one of seed_file, seed_env and seed_keychain".to_string());
+        }
+        if self.compression == Some(CompressionAlgorithm::Huffman) {
+            return Err("compression must be none, lz4 or brotli".to_string());
+        }
+        if let Some(server) = &self.server
+            && !(server.starts_with("http://") || server.starts_with("https://"))
+        {
+            return Err(format!("server {server} is not an http(s) URL"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Cli;
+    use clap::Parser;
+    use std::fs;
+
+    #[test]
+    fn test_profile_supplies_flags() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-profile-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("k"), b"seed").unwrap();
+        fs::write(dir.join("in.bin"), b"profiled").unwrap();
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        let config = format!(
+            "[profiles.ops]\nseed_file = {:?}\ncompression = \"brotli\"\narmor = true\n\n[profiles.bad]\ncompression = \"huffman\"\n",
+            path("k")
+        );
+        fs::write(dir.join("config.toml"), config).unwrap();
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let with = |profile: &'static str, rest: &[&str]| {
+            let config = path("config.toml");
+            let mut args = vec!["gxcore".to_string(), "--config".to_string(), config, "--profile".to_string(), profile.to_string()];
+            args.extend(rest.iter().map(|arg| arg.to_string()));
+            crate::run(Cli::try_parse_from(args).unwrap())
+        };
+
+        with("ops", &["encode", &path("in.bin"), "-o", &path("out.asc")]).unwrap();
+        assert!(fs::read_to_string(dir.join("out.asc")).unwrap().starts_with("-----BEGIN GXCORE ENVELOPE-----"));
+        with("ops", &["decode", &path("out.asc"), "-o", &path("back.bin")]).unwrap();
+        assert_eq!(fs::read(dir.join("back.bin")).unwrap(), b"profiled");
+
+        // Flags on the command line win over the profile
+        fs::write(dir.join("other"), b"other").unwrap();
+        assert!(with("ops", &["decode", "--seed-file", &path("other"), &path("out.asc")]).is_err());
+        assert!(with("bad", &["encode", &path("in.bin")]).is_err());
+        assert!(with("missing", &["encode", &path("in.bin")]).is_err());
+        assert!(run(&["gxcore", "--config", &path("config.toml"), "encode", &path("in.bin")]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
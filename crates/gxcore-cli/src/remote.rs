@@ -0,0 +1,164 @@
+use base64::{Engine as _, engine::general_purpose};
+use gxcore_api::api::ratelimit::API_KEY_HEADER;
+use gxcore_api::api::{COMPRESSION_HEADER, KEY_ID_HEADER, SEED_HEADER};
+
+use crate::CodecArgs;
+use crate::codec::CodecReport;
+use crate::io::{describe, read_input, write_output};
+use crate::profile::Profile;
+
+/// HTTP service the encode and decode subcommands call instead of running locally
+pub struct Remote {
+    url: String,
+    api_key: Option<String>,
+    /// Server-held key of the profile, used when `--key-id` and seeds are absent
+    key_id: Option<String>,
+    client: reqwest::Client,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Remote {
+    /// Remote mode when `--remote` is given, at its URL or else the profile's server
+    pub fn new(remote: Option<Option<String>>, api_key: Option<String>, profile: &Profile) -> Result<Option<Self>, String> {
+        let Some(url) = remote else {
+            return Ok(None);
+        };
+        let url = url
+            .or_else(|| profile.server.clone())
+            .ok_or("--remote needs a URL, or a profile with a server")?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| format!("cannot start the HTTP client: {e}"))?;
+        Ok(Some(Remote {
+            url: url.trim_end_matches('/').to_string(),
+            api_key,
+            key_id: profile.key_id.clone(),
+            client: reqwest::Client::new(),
+            runtime,
+        }))
+    }
+
+    fn key_id<'a>(&'a self, args: &'a CodecArgs) -> Option<&'a String> {
+        args.key_id.as_ref().or(self.key_id.as_ref())
+    }
+
+    /// Send `body` to a raw endpoint, naming a server-held key or else sending the seed
+    fn call(&self, path: &str, args: &CodecArgs, body: &[u8]) -> Result<Vec<u8>, String> {
+        let mut request = self.client.post(format!("{}{path}", self.url)).body(body.to_vec());
+        if let Some(api_key) = &self.api_key {
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        request = match self.key_id(args) {
+            Some(key_id) => request.header(KEY_ID_HEADER, key_id),
+            None => request.header(SEED_HEADER, general_purpose::STANDARD.encode(args.seed.load()?)),
+        };
+        // The server default applies when no compression is given
+        if let Some(compression) = args.compression {
+            request = request.header(COMPRESSION_HEADER, compression.name());
+        }
+        self.runtime.block_on(async {
+            let response = request.send().await.map_err(|e| format!("{}: {e}", self.url))?;
+            let status = response.status();
+            let body = response.bytes().await.map_err(|e| format!("{}: {e}", self.url))?;
+            if !status.is_success() {
+                return Err(format!("{path} failed with {status}"));
+            }
+            Ok(body.to_vec())
+        })
+    }
+
+    /// `--json` result; the seed is only fingerprinted when it was sent
+    fn report(&self, args: &CodecArgs, input_bytes: usize, output_bytes: usize) -> Result<(), String> {
+        let report = CodecReport::new(args, Some(input_bytes as u64), output_bytes as u64);
+        match self.key_id(args) {
+            Some(key_id) => report.with_key_id(key_id),
+            None => report.with_seed(&args.seed.load()?),
+        }
+        .print()
+    }
+
+    /// Encode with the service, producing its encoded payload rather than an envelope
+    pub fn encode(&self, args: &CodecArgs, json: bool) -> Result<(), String> {
+        if args.stream || args.armor || args.skin.is_some() {
+            return Err("--stream, --armor and --skin are not supported with --remote".to_string());
+        }
+        let data = read_input(args.input.as_ref())?;
+        let encoded = self.call("/v1/encode/raw", args, &data)?;
+        write_output(args.output.as_ref(), &encoded)?;
+        if json { self.report(args, data.len(), encoded.len()) } else { Ok(()) }
+    }
+
+    /// Decode a payload the service encoded
+    pub fn decode(&self, args: &CodecArgs, json: bool) -> Result<(), String> {
+        if args.stream {
+            return Err("--stream is not supported with --remote".to_string());
+        }
+        let name = describe(args.input.as_ref());
+        let encoded = read_input(args.input.as_ref())?;
+        let decoded = self.call("/v1/decode/raw", args, &encoded).map_err(|e| format!("{name}: {e}"))?;
+        write_output(args.output.as_ref(), &decoded)?;
+        if json { self.report(args, encoded.len(), decoded.len()) } else { Ok(()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Cli;
+    use clap::Parser;
+    use gxcore_api::api::ApiConfig;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Directory removed when dropped, so a failed assertion does not leave it behind
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Serve the API on a background thread with the admin routes open, returning its base URL
+    fn serve() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async {
+                listener.set_nonblocking(true).unwrap();
+                let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                let config = ApiConfig { unauthenticated_admin: true, ..Default::default() };
+                axum::serve(listener, gxcore_api::api::create_router_with(config)).await.unwrap();
+            });
+        });
+        url
+    }
+
+    #[test]
+    fn test_server_held_key() {
+        let url = serve();
+        let add_key = reqwest::Client::new()
+            .post(format!("{url}/v1/admin/keys"))
+            .header("content-type", "application/json")
+            .body(r#"{"id":"ops","seed":"c2VlZA=="}"#)
+            .send();
+        let added = tokio::runtime::Runtime::new().unwrap().block_on(add_key).unwrap();
+        assert_eq!(added.status(), 201);
+        let dir = TempDir(std::env::temp_dir().join(format!("gx-cli-remote-{}", std::process::id())));
+        let dir = &dir.0;
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("in.bin"), b"thin client").unwrap();
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+        run(&["gxcore", "--remote", &url, "encode", "--key-id", "ops", "--compression", "lz4", &path("in.bin"), "-o", &path("out.enc")]).unwrap();
+        let encoded = fs::read(dir.join("out.enc")).unwrap();
+        assert_eq!(gxcore_core::decode(&encoded, b"seed", gxcore_core::CompressionAlgorithm::Lz4).unwrap(), b"thin client");
+        run(&["gxcore", "--remote", &url, "decode", "--key-id", "ops", "--compression", "lz4", &path("out.enc"), "-o", &path("back.bin")]).unwrap();
+        assert_eq!(fs::read(dir.join("back.bin")).unwrap(), b"thin client");
+
+        assert!(run(&["gxcore", "--remote", &url, "decode", "--key-id", "missing", &path("out.enc")]).is_err());
+        assert!(run(&["gxcore", "encode", "--key-id", "ops", &path("in.bin")]).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use clap::Args;
+use sha2::{Digest, Sha256};
+
+use crate::platform::keychain_get;
+use crate::profile::Profile;
+
+/// Bytes of generated seeds, as many as the server's keyring generates
+pub const SEED_LEN: usize = 32;
+
+/// Where the seed comes from, the profile's source when none is given
+#[derive(Args)]
+#[group(multiple = false)]
+pub struct SeedArgs {
+    /// File holding the raw seed bytes
+    #[arg(long)]
+    pub seed_file: Option<PathBuf>,
+    /// Environment variable holding the seed, kept out of the process list
+    #[arg(long, value_name = "VAR")]
+    pub seed_env: Option<String>,
+    /// Name of a seed stored in the OS keychain by `gxcore keygen --keychain`
+    #[arg(long, value_name = "NAME")]
+    pub seed_keychain: Option<String>,
+}
+
+impl SeedArgs {
+    /// Take the profile's seed source unless one was given on the command line
+    pub fn or_profile(&mut self, profile: &Profile) {
+        if self.seed_file.is_none() && self.seed_env.is_none() && self.seed_keychain.is_none() {
+            self.seed_file = profile.seed_file.clone();
+            self.seed_env = profile.seed_env.clone();
+            self.seed_keychain = profile.seed_keychain.clone();
+        }
+    }
+
+    pub fn load(&self) -> Result<Vec<u8>, String> {
+        let seed = match (&self.seed_file, &self.seed_env, &self.seed_keychain) {
+            (Some(path), _, _) => std::fs::read(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?,
+            (None, Some(var), _) => std::env::var_os(var)
+                .ok_or_else(|| format!("{var} is not set"))?
+                .into_encoded_bytes(),
+            (None, None, Some(name)) => keychain_get(name)?,
+            (None, None, None) => {
+                return Err("no seed given: pass --seed-file, --seed-env or --seed-keychain, or set one in a profile".to_string());
+            }
+        };
+        if seed.is_empty() {
+            return Err("seed is empty".to_string());
+        }
+        Ok(seed)
+    }
+}
+
+/// Short identifier of a seed, safe to print and compare
+pub fn fingerprint(seed: &[u8]) -> String {
+    format!("sha256:{}", hex::encode(&Sha256::digest(seed)[..16]))
+}
+
+/// Random seed from the OS generator
+pub fn generate_seed() -> Vec<u8> {
+    let mut seed = vec![0u8; SEED_LEN];
+    OsRng.fill_bytes(&mut seed);
+    seed
+}
@@ -0,0 +1,109 @@
+use clap::Args;
+use gxcore_core::bench::{measure, sample_data};
+use gxcore_core::envelope::Envelope;
+use gxcore_core::vectors::{check_envelope, VECTORS};
+use gxcore_core::{decode, encode, CompressionAlgorithm};
+use serde_json::json;
+
+use crate::io::print_json;
+use crate::seed::generate_seed;
+
+/// Compressions the CLI accepts, each round-tripped
+const ALGORITHMS: [CompressionAlgorithm; 3] = [CompressionAlgorithm::None, CompressionAlgorithm::Lz4, CompressionAlgorithm::Brotli];
+
+/// Bytes of sample data per round trip and timing run
+const SAMPLE_SIZE: usize = 256 * 1024;
+
+/// Encode and decode rates below this, in MB/s, point at a broken build or machine
+const MIN_THROUGHPUT: f64 = 0.5;
+
+#[derive(Args)]
+pub struct SelftestArgs {
+    /// Timed encodes and decodes per compression
+    #[arg(long, default_value_t = 5)]
+    iterations: usize,
+}
+
+fn roundtrip(compression: CompressionAlgorithm) -> Result<(), String> {
+    let seed = generate_seed();
+    let data = sample_data(SAMPLE_SIZE);
+    let encoded = encode(&data, &seed, compression);
+    if decode(&encoded, &seed, compression).map_err(str::to_string)? != data {
+        return Err("payload changed".to_string());
+    }
+    let sealed = Envelope::seal(&data, &seed, compression)
+        .authenticate(&seed)
+        .and_then(|envelope| envelope.to_bytes())
+        .map_err(str::to_string)?;
+    let opened = Envelope::parse(&sealed).and_then(|envelope| envelope.open(&seed)).map_err(str::to_string)?;
+    if opened != data {
+        return Err("envelope contents changed".to_string());
+    }
+    if Envelope::parse(&sealed).and_then(|envelope| envelope.open(b"wrong seed")).is_ok() {
+        return Err("envelope opened with the wrong seed".to_string());
+    }
+    Ok(())
+}
+
+fn timing(compression: CompressionAlgorithm, iterations: usize) -> Result<String, String> {
+    let [encoded, decoded] = measure("selftest", &sample_data(SAMPLE_SIZE), b"selftest", compression, iterations);
+    let summary = format!(
+        "encode {:.1} MB/s, decode {:.1} MB/s",
+        encoded.throughput_mb_per_sec, decoded.throughput_mb_per_sec
+    );
+    // Infinite when the clock did not advance, as broken as too slow
+    let sane = |rate: f64| rate.is_finite() && rate >= MIN_THROUGHPUT;
+    if sane(encoded.throughput_mb_per_sec) && sane(decoded.throughput_mb_per_sec) {
+        Ok(summary)
+    } else {
+        Err(format!("{summary}, outside {MIN_THROUGHPUT} MB/s and a running clock"))
+    }
+}
+
+/// Print one line per check, or a JSON list of them, failing if any did
+pub fn run(args: &SelftestArgs, json: bool) -> Result<(), String> {
+    let mut checks: Vec<(String, Result<String, String>)> = Vec::new();
+    for vector in VECTORS {
+        checks.push((format!("vector {}", vector.name), vector.check().map(|_| String::new())));
+    }
+    checks.push(("vector envelope".to_string(), check_envelope().map(|_| String::new())));
+    for compression in ALGORITHMS {
+        checks.push((format!("roundtrip {}", compression.name()), roundtrip(compression).map(|_| String::new())));
+    }
+    for compression in ALGORITHMS {
+        checks.push((format!("timing {}", compression.name()), timing(compression, args.iterations)));
+    }
+    if json {
+        let checks: Vec<_> = checks
+            .iter()
+            .map(|(check, result)| match result {
+                Ok(detail) => json!({ "check": check, "ok": true, "detail": detail }),
+                Err(e) => json!({ "check": check, "ok": false, "error": e }),
+            })
+            .collect();
+        print_json(&json!({ "checks": checks }))?;
+    } else {
+        for (check, result) in &checks {
+            match result {
+                Ok(detail) if detail.is_empty() => println!("ok    {check}"),
+                Ok(detail) => println!("ok    {check}: {detail}"),
+                Err(e) => println!("FAIL  {check}: {e}"),
+            }
+        }
+    }
+    match checks.iter().filter(|(_, result)| result.is_err()).count() {
+        0 => Ok(()),
+        failed => Err(format!("{failed} check(s) failed")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Cli;
+    use clap::Parser;
+
+    #[test]
+    fn test_selftest_passes() {
+        crate::run(Cli::try_parse_from(["gxcore", "selftest", "--iterations", "1"]).unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+
+/// Product in GF(2^8) with the AES polynomial
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Inverse in GF(2^8), `a^254`
+fn inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp != 0 {
+        if exp & 1 != 0 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// One share: the x coordinate, then one polynomial value per secret byte
+pub type Share = Vec<u8>;
+
+/// Split `secret` into `count` shares, any `threshold` of which rebuild it
+pub fn split(secret: &[u8], count: u8, threshold: u8) -> Result<Vec<Share>, String> {
+    if threshold < 2 || threshold > count {
+        return Err("threshold must be between 2 and the number of shares".to_string());
+    }
+    let mut coefficients = vec![0u8; secret.len() * (threshold as usize - 1)];
+    OsRng.fill_bytes(&mut coefficients);
+    let shares = (1..=count)
+        .map(|x| {
+            let mut share = vec![x];
+            for (i, &byte) in secret.iter().enumerate() {
+                let terms = &coefficients[i * (threshold as usize - 1)..(i + 1) * (threshold as usize - 1)];
+                // Horner's rule, highest coefficient first
+                let value = terms.iter().rev().fold(0, |acc, &c| mul(acc, x) ^ c);
+                share.push(mul(value, x) ^ byte);
+            }
+            share
+        })
+        .collect();
+    Ok(shares)
+}
+
+/// Secret at x = 0 by Lagrange interpolation; too few shares give a wrong secret, not an error
+pub fn combine(shares: &[Share]) -> Result<Vec<u8>, String> {
+    let len = shares.first().map(Vec::len).ok_or("no shares given")?;
+    if len < 2 || shares.iter().any(|share| share.len() != len) {
+        return Err("shares have different lengths".to_string());
+    }
+    let xs: Vec<u8> = shares.iter().map(|share| share[0]).collect();
+    if xs.iter().enumerate().any(|(i, x)| *x == 0 || xs[..i].contains(x)) {
+        return Err("shares are repeated or invalid".to_string());
+    }
+    let weights: Vec<u8> = xs
+        .iter()
+        .map(|&xi| {
+            xs.iter().filter(|&&xj| xj != xi).fold(1, |acc, &xj| mul(acc, mul(xj, inv(xj ^ xi))))
+        })
+        .collect();
+    Ok((1..len)
+        .map(|i| shares.iter().zip(&weights).fold(0, |acc, (share, &w)| acc ^ mul(share[i], w)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_any_threshold_subset_rebuilds() {
+        let secret: Vec<u8> = (0..32).collect();
+        let shares = split(&secret, 5, 3).unwrap();
+        assert_eq!(combine(&shares[..3]).unwrap(), secret);
+        assert_eq!(combine(&[shares[4].clone(), shares[0].clone(), shares[2].clone()]).unwrap(), secret);
+        assert_eq!(combine(&shares).unwrap(), secret);
+        assert_ne!(combine(&shares[..2]).unwrap(), secret);
+        assert!(combine(&[shares[0].clone(), shares[0].clone()]).is_err());
+        assert!(split(&secret, 2, 3).is_err());
+    }
+}
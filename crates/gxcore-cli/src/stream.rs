@@ -0,0 +1,221 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use gxcore_core::stream::{decode_stream, encode_stream_pipelined, DEFAULT_CHUNK_SIZE, FRAME_DELIMITER};
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::codec::CodecReport;
+use crate::io::is_std;
+use crate::CodecArgs;
+
+/// Bar over `len` input bytes with throughput and ETA, a spinner when the length is unknown.
+/// Drawn on stderr and hidden when it is not a terminal.
+fn progress(len: Option<u64>) -> ProgressBar {
+    match len {
+        Some(len) => ProgressBar::new(len).with_style(
+            ProgressStyle::with_template("{bar:40} {bytes}/{total_bytes} {bytes_per_sec} ETA {eta}")
+                .unwrap(),
+        ),
+        None => ProgressBar::new_spinner()
+            .with_style(ProgressStyle::with_template("{spinner} {bytes} {bytes_per_sec}").unwrap()),
+    }
+}
+
+/// Input file positioned at `offset`, or stdin
+fn open_input(path: Option<&PathBuf>, offset: u64) -> Result<(Box<dyn Read + Send>, Option<u64>), String> {
+    if is_std(path) {
+        return Ok((Box::new(io::stdin()), None));
+    }
+    let path = path.unwrap();
+    let mut file = File::open(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    let len = file.metadata().map_err(|e| format!("cannot read {}: {e}", path.display()))?.len();
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    Ok((Box::new(file), Some(len)))
+}
+
+/// Output file truncated to `keep` bytes and opened for appending, or stdout
+fn open_output(path: Option<&PathBuf>, keep: u64) -> Result<Box<dyn Write>, String> {
+    if is_std(path) {
+        return Ok(Box::new(io::stdout().lock()));
+    }
+    Ok(Box::new(io::BufWriter::new(output_file(path.unwrap(), keep)?)))
+}
+
+/// Output file truncated to `keep` bytes and positioned at its end
+fn output_file(path: &PathBuf, keep: u64) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(keep == 0)
+        .open(path)
+        .and_then(|mut file| {
+            file.set_len(keep)?;
+            file.seek(SeekFrom::End(0))?;
+            Ok(file)
+        })
+        .map_err(|e| format!("cannot write {}: {e}", path.display()))
+}
+
+/// Input and output files to go through io_uring, when neither is stdin or stdout and the kernel
+/// allows it, with the output truncated to `keep` bytes
+#[cfg(all(feature = "uring", target_os = "linux"))]
+fn ring_files(args: &CodecArgs, keep: u64) -> Result<Option<(File, File)>, String> {
+    let (Some(input), Some(output)) = (args.input.as_ref(), args.output.as_ref()) else {
+        return Ok(None);
+    };
+    if is_std(Some(input)) || is_std(Some(output)) || !gxcore_core::uring::available() {
+        return Ok(None);
+    }
+    let input_file = File::open(input).map_err(|e| format!("cannot read {}: {e}", input.display()))?;
+    Ok(Some((input_file, output_file(output, keep)?)))
+}
+
+/// Output path of a resumable run, which must be a file kept between runs
+fn resumable_output(args: &CodecArgs) -> Result<Option<&PathBuf>, String> {
+    if !args.resume {
+        return Ok(None);
+    }
+    if is_std(args.input.as_ref()) || is_std(args.output.as_ref()) {
+        return Err("--resume needs input and output files".to_string());
+    }
+    Ok(args.output.as_ref().filter(|path| path.exists()))
+}
+
+/// `--json` result once the output file is complete
+fn report(args: &CodecArgs, input_bytes: Option<u64>, seed: &[u8]) -> Result<(), String> {
+    let path = args.output.as_ref().unwrap();
+    let output_bytes = fs::metadata(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?.len();
+    CodecReport::new(args, input_bytes, output_bytes)
+        .with_compression(args.compression())
+        .with_seed(seed)
+        .print()
+}
+
+/// Frames already written by an interrupted encode, and the output length they end at
+fn complete_frames(path: &PathBuf) -> Result<(u64, u64), String> {
+    let error = |e: io::Error| format!("cannot read {}: {e}", path.display());
+    // Scanned a buffer at a time, as the output of a long run can be far larger than memory
+    let mut reader = BufReader::new(File::open(path).map_err(error)?);
+    let (mut frames, mut end, mut offset) = (0u64, 0u64, 0u64);
+    loop {
+        let buf = reader.fill_buf().map_err(error)?;
+        if buf.is_empty() {
+            return Ok((frames, end));
+        }
+        for (i, _) in buf.iter().enumerate().filter(|&(_, &b)| b == FRAME_DELIMITER) {
+            frames += 1;
+            end = offset + i as u64 + 1;
+        }
+        let len = buf.len();
+        offset += len as u64;
+        reader.consume(len);
+    }
+}
+
+/// Read past the first `frames` frames of `input`, returning their length
+fn skip_frames(input: &mut impl BufRead, frames: u64) -> Result<u64, String> {
+    let mut skip = Vec::new();
+    let mut skipped = 0u64;
+    for _ in 0..frames {
+        skip.clear();
+        skipped += input.read_until(FRAME_DELIMITER, &mut skip).map_err(|e| format!("cannot read input: {e}"))? as u64;
+    }
+    Ok(skipped)
+}
+
+/// Encode into newline-delimited frames of `DEFAULT_CHUNK_SIZE` input bytes.
+/// With `--resume`, frames already in the output are kept and their input skipped.
+pub fn encode(args: &CodecArgs, json: bool) -> Result<(), String> {
+    let seed = args.seed.load()?;
+    let (frames, keep) = match resumable_output(args)? {
+        Some(path) => complete_frames(path)?,
+        None => (0, 0),
+    };
+    let offset = frames * DEFAULT_CHUNK_SIZE as u64;
+    let (input, len) = open_input(args.input.as_ref(), offset)?;
+    // Every frame but the last holds a full chunk, so reaching the length means the run had finished
+    if frames > 0 && len.is_some_and(|len| offset >= len) {
+        eprintln!("already complete");
+        return if json { report(args, len, &seed) } else { Ok(()) };
+    }
+    let bar = progress(len);
+    bar.set_position(offset);
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    if let Some((input, output)) = ring_files(args, keep)? {
+        gxcore_core::uring::encode(&input, offset, &output, keep, &seed, args.compression(), |n| bar.inc(n))
+            .map_err(|e| format!("encoding failed: {e}"))?;
+        bar.finish();
+        return if json { report(args, len, &seed) } else { Ok(()) };
+    }
+    let output = open_output(args.output.as_ref(), keep)?;
+    encode_stream_pipelined(bar.wrap_read(input), output, &seed, args.compression()).map_err(|e| format!("encoding failed: {e}"))?;
+    bar.finish();
+    if json { report(args, len, &seed) } else { Ok(()) }
+}
+
+/// Decode newline-delimited frames, with `--compression` as used when encoding.
+/// With `--resume`, the output is cut to whole chunks and the frames they came from skipped.
+pub fn decode(args: &CodecArgs, json: bool) -> Result<(), String> {
+    let seed = args.seed.load()?;
+    let frames = match resumable_output(args)? {
+        Some(path) => fs::metadata(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?.len() / DEFAULT_CHUNK_SIZE as u64,
+        None => 0,
+    };
+    let (input, len) = open_input(args.input.as_ref(), 0)?;
+    let bar = progress(len);
+    let keep = frames * DEFAULT_CHUNK_SIZE as u64;
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    if let Some((input, output)) = ring_files(args, keep)? {
+        let skipped = skip_frames(&mut BufReader::new(&input), frames)?;
+        bar.set_position(skipped);
+        gxcore_core::uring::decode(&input, skipped, &output, keep, &seed, args.compression(), |n| bar.inc(n))
+            .map_err(|e| format!("decoding failed: {e}"))?;
+        bar.finish();
+        return if json { report(args, len, &seed) } else { Ok(()) };
+    }
+    let mut input = BufReader::new(bar.wrap_read(input));
+    skip_frames(&mut input, frames)?;
+    let output = open_output(args.output.as_ref(), keep)?;
+    decode_stream(input, output, &seed, args.compression()).map_err(|e| format!("decoding failed: {e}"))?;
+    bar.finish();
+    if json { report(args, len, &seed) } else { Ok(()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Cli;
+    use clap::Parser;
+    use gxcore_core::stream::DEFAULT_CHUNK_SIZE;
+    use std::fs;
+
+    #[test]
+    fn test_resume_interrupted_runs() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-stream-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("k"), b"seed").unwrap();
+        let data: Vec<u8> = (0..DEFAULT_CHUNK_SIZE * 3 + 100).map(|i| (i % 251) as u8).collect();
+        fs::write(dir.join("big.bin"), &data).unwrap();
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        let encode = ["gxcore", "encode", "--stream", "--resume", "--seed-file", &path("k"), &path("big.bin"), "-o", &path("big.gx")];
+        let decode = ["gxcore", "decode", "--stream", "--resume", "--seed-file", &path("k"), &path("big.gx"), "-o", &path("back.bin")];
+
+        run(&encode).unwrap();
+        let full = fs::read(dir.join("big.gx")).unwrap();
+        // Interrupted halfway through the third frame
+        let second_end = full.iter().enumerate().filter(|(_, b)| **b == b'\n').nth(1).unwrap().0;
+        fs::write(dir.join("big.gx"), &full[..second_end + 10]).unwrap();
+        run(&encode).unwrap();
+        assert_eq!(fs::read(dir.join("big.gx")).unwrap(), full);
+
+        // Interrupted with a chunk and a half decoded
+        fs::write(dir.join("back.bin"), &data[..DEFAULT_CHUNK_SIZE * 3 / 2]).unwrap();
+        run(&decode).unwrap();
+        assert_eq!(fs::read(dir.join("back.bin")).unwrap(), data);
+
+        assert!(run(&["gxcore", "encode", "--stream", "--resume", "--seed-file", &path("k")]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
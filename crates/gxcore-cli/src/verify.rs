@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use gxcore_core::envelope::Envelope;
+use gxcore_core::inspect::inspect;
+
+use crate::armor::unarmor;
+use crate::inspect::structurally_valid;
+use crate::io::{describe, print_json, read_input};
+use crate::seed::fingerprint;
+use crate::seed::SeedArgs;
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Envelope or bare encoded payload, stdin when absent or `-`
+    input: Option<PathBuf>,
+    /// Also open the envelope, checking its checksum and authentication tag; structure only without a seed
+    #[command(flatten)]
+    seed: Option<SeedArgs>,
+}
+
+/// Fail unless the blob is well formed and, given a seed, opens with it
+pub fn run(args: &VerifyArgs, json: bool) -> Result<(), String> {
+    let name = describe(args.input.as_ref());
+    let blob = unarmor(&read_input(args.input.as_ref())?).map_err(|e| format!("{name}: {e}"))?;
+    let report = inspect(&blob);
+    if !structurally_valid(&report) {
+        let reason = report.error.unwrap_or("checksum mismatch");
+        return Err(format!("{name}: structurally invalid: {reason}"));
+    }
+    let Some(seed) = &args.seed else {
+        if json {
+            return print_json(&serde_json::json!({ "input": name, "structure": "valid", "keyed": false }));
+        }
+        println!("{name}: structure ok");
+        return Ok(());
+    };
+    let seed = seed.load()?;
+    Envelope::parse(&blob)
+        .and_then(|envelope| envelope.open(&seed))
+        .map_err(|e| format!("{name}: {e}"))?;
+    if json {
+        return print_json(&serde_json::json!({
+            "input": name,
+            "structure": "valid",
+            "keyed": true,
+            "seed_fingerprint": fingerprint(&seed),
+        }));
+    }
+    println!("{name}: ok");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Cli;
+    use clap::Parser;
+    use gxcore_core::envelope::Envelope;
+    use gxcore_core::CompressionAlgorithm;
+    use std::fs;
+
+    #[test]
+    fn test_structural_and_keyed() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-verify-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("k"), b"seed").unwrap();
+        fs::write(dir.join("wrong"), b"other").unwrap();
+        let sealed = Envelope::seal(b"release", b"seed", CompressionAlgorithm::Brotli).authenticate(b"seed").unwrap().to_bytes().unwrap();
+        fs::write(dir.join("blob.gx"), &sealed).unwrap();
+        let mut corrupt = sealed.clone();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 1;
+        fs::write(dir.join("corrupt.gx"), corrupt).unwrap();
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+        run(&["gxcore", "verify", &path("blob.gx")]).unwrap();
+        run(&["gxcore", "verify", &path("blob.gx"), "--seed-file", &path("k")]).unwrap();
+        assert!(run(&["gxcore", "verify", &path("blob.gx"), "--seed-file", &path("wrong")]).is_err());
+        assert!(run(&["gxcore", "verify", &path("corrupt.gx")]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
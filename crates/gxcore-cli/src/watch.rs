@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::Args;
+use gxcore_core::CompressionAlgorithm;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::codec::{open, seal};
+use crate::parse_compression;
+use crate::platform::Changes;
+use crate::profile::Profile;
+use crate::seed::SeedArgs;
+
+/// Manifest of processed files, kept in the output directory
+const MANIFEST: &str = ".gxcore-manifest.jsonl";
+
+/// Quiet time after the last change before files are picked up, so copies in progress finish first
+const SETTLE: Duration = Duration::from_millis(500);
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Directory new files are dropped in
+    #[arg(long = "in", value_name = "DIR")]
+    input: PathBuf,
+    /// Directory results are written to, with the manifest
+    #[arg(long = "out", value_name = "DIR")]
+    output: PathBuf,
+    #[command(flatten)]
+    seed: SeedArgs,
+    /// Open envelopes instead of sealing files
+    #[arg(long)]
+    decode: bool,
+    /// `none` (the default), `lz4`, `lz4frame` or `brotli` when encoding
+    #[arg(long, value_parser = parse_compression, conflicts_with = "decode")]
+    compression: Option<CompressionAlgorithm>,
+    /// Write envelopes as base64 text between markers
+    #[arg(long, conflicts_with = "decode")]
+    armor: bool,
+    /// Process the files already there and exit instead of watching
+    #[arg(long)]
+    once: bool,
+}
+
+impl WatchArgs {
+    /// Fill what the command line left out from the profile
+    pub fn or_profile(mut self, profile: &Profile) -> Self {
+        self.seed.or_profile(profile);
+        self.compression = self.compression.or(profile.compression);
+        self.armor |= profile.armor;
+        self
+    }
+}
+
+/// One processed file, as a line of the manifest
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    file: String,
+    sha256: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Digest of every file in the manifest by name, the latest entry winning
+fn load_manifest(path: &Path) -> Result<HashMap<String, String>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(format!("cannot read {}: {e}", path.display())),
+    };
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str::<Entry>(line)
+                .map(|entry| (entry.file, entry.sha256))
+                .map_err(|e| format!("{}: {e}", path.display()))
+        })
+        .collect()
+}
+
+fn append_manifest(path: &Path, entry: &Entry) -> Result<(), String> {
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{line}"))
+        .map_err(|e| format!("cannot write {}: {e}", path.display()))
+}
+
+/// Name of the result of processing `file`: `.gx` added when encoding, removed when decoding
+fn output_name(file: &str, decode: bool) -> String {
+    match (decode, file.strip_suffix(".gx").or_else(|| file.strip_suffix(".asc"))) {
+        (false, _) => format!("{file}.gx"),
+        (true, Some(stem)) if !stem.is_empty() => stem.to_string(),
+        (true, _) => format!("{file}.out"),
+    }
+}
+
+/// Seal or open one file into the output directory, renaming into place so readers never see it half written
+fn process(args: &WatchArgs, seed: &[u8], file: &str, data: &[u8]) -> Result<String, String> {
+    let result = if args.decode { open(file, data, seed)? } else { seal(data, seed, args.compression.unwrap_or_default(), args.armor)? };
+    let name = output_name(file, args.decode);
+    let partial = args.output.join(format!(".{name}.partial"));
+    fs::write(&partial, result)
+        .and_then(|_| fs::rename(&partial, args.output.join(&name)))
+        .map_err(|e| format!("cannot write {name}: {e}"))?;
+    Ok(name)
+}
+
+/// Process the files in the input directory that are new or changed since the manifest saw them.
+/// Failures are recorded and not retried until the file changes; hidden files are skipped.
+fn process_pending(args: &WatchArgs, seed: &[u8], seen: &mut HashMap<String, String>, json: bool) -> Result<(), String> {
+    let manifest = args.output.join(MANIFEST);
+    let mut files: Vec<_> = fs::read_dir(&args.input)
+        .map_err(|e| format!("cannot read {}: {e}", args.input.display()))?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|file| !file.starts_with('.'))
+        .collect();
+    files.sort();
+    for file in files {
+        // Vanished or unreadable files are picked up again on their next change
+        let Ok(data) = fs::read(args.input.join(&file)) else { continue };
+        let sha256 = hex::encode(Sha256::digest(&data));
+        if seen.get(&file) == Some(&sha256) {
+            continue;
+        }
+        let (output, error) = match process(args, seed, &file, &data) {
+            Ok(output) => {
+                eprintln!("{file} -> {output}");
+                (Some(output), None)
+            }
+            Err(e) => {
+                eprintln!("gxcore: {e}");
+                (None, Some(e))
+            }
+        };
+        let entry = Entry { file: file.clone(), sha256: sha256.clone(), output, error };
+        append_manifest(&manifest, &entry)?;
+        if json {
+            // One line per file, as the manifest has, so consumers can follow the stream
+            println!("{}", serde_json::to_string(&entry).map_err(|e| e.to_string())?);
+        }
+        seen.insert(file, sha256);
+    }
+    Ok(())
+}
+
+/// Process what the input directory holds, then each batch of changes once it settles
+pub fn run(args: &WatchArgs, json: bool) -> Result<(), String> {
+    let seed = args.seed.load()?;
+    fs::create_dir_all(&args.output).map_err(|e| format!("cannot create {}: {e}", args.output.display()))?;
+    let same = fs::canonicalize(&args.input).ok() == fs::canonicalize(&args.output).ok();
+    if same {
+        return Err("--in and --out must be different directories".to_string());
+    }
+    let mut seen = load_manifest(&args.output.join(MANIFEST))?;
+    if args.once {
+        return process_pending(args, &seed, &mut seen, json);
+    }
+    let mut changes = Changes::watch(&args.input)?;
+    eprintln!("watching {}", args.input.display());
+    loop {
+        process_pending(args, &seed, &mut seen, json)?;
+        // Missed changes are harmless, since every batch rescans the whole directory
+        changes.wait(SETTLE)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::output_name;
+    use crate::Cli;
+    use clap::Parser;
+    use std::fs;
+
+    #[test]
+    fn test_drop_directory_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("gx-cli-watch-{}", std::process::id()));
+        for sub in ["drop", "encoded", "decoded"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        fs::write(dir.join("k"), b"seed").unwrap();
+        fs::write(dir.join("drop/a.bin"), b"first").unwrap();
+        fs::write(dir.join("drop/.partial"), b"skipped").unwrap();
+        let run = |args: &[&str]| crate::run(Cli::try_parse_from(args).unwrap());
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        let encode = ["gxcore", "watch", "--once", "--in", &path("drop"), "--out", &path("encoded"), "--seed-file", &path("k")];
+        let decode = ["gxcore", "watch", "--once", "--decode", "--in", &path("encoded"), "--out", &path("decoded"), "--seed-file", &path("k")];
+
+        run(&encode).unwrap();
+        fs::write(dir.join("drop/b.bin"), b"second").unwrap();
+        run(&encode).unwrap();
+        let manifest = fs::read_to_string(dir.join("encoded/.gxcore-manifest.jsonl")).unwrap();
+        assert_eq!(manifest.lines().count(), 2);
+        assert!(!dir.join("encoded/.partial.gx").exists());
+
+        run(&decode).unwrap();
+        assert_eq!(fs::read(dir.join("decoded/a.bin")).unwrap(), b"first");
+        assert_eq!(fs::read(dir.join("decoded/b.bin")).unwrap(), b"second");
+
+        // A changed file is processed again
+        fs::write(dir.join("drop/a.bin"), b"updated").unwrap();
+        run(&encode).unwrap();
+        run(&decode).unwrap();
+        assert_eq!(fs::read(dir.join("decoded/a.bin")).unwrap(), b"updated");
+        assert!(run(&["gxcore", "watch", "--in", &path("drop"), "--out", &path("drop"), "--seed-file", &path("k")]).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_output_name() {
+        assert_eq!(output_name("report.pdf", false), "report.pdf.gx");
+        assert_eq!(output_name("report.pdf.gx", true), "report.pdf");
+        assert_eq!(output_name("note.asc", true), "note");
+        assert_eq!(output_name("blob", true), "blob.out");
+    }
+}
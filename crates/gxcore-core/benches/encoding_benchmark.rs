@@ -0,0 +1,355 @@
+use std::io::{Read, Write};
+
+use base64::{engine::general_purpose, Engine as _};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use gxcore_core::auto::{choose, choose_exhaustive};
+use gxcore_core::batch::encode_batch;
+use gxcore_core::bench::sample_data;
+use gxcore_core::fixed::{decode_small, encode_small, SMALL_MAX};
+use gxcore_core::stream::{encode_stream, encode_stream_pipelined};
+use gxcore_core::translate::Translator;
+use gxcore_core::{derive_alphabet, encode, encode_with, decode, CompressionAlgorithm, Scratch};
+
+fn bench_encode_small_data(c: &mut Criterion) {
+    let data = b"Hello, Solana World!";
+    let seed = b"benchmark_secret_key";
+
+    c.bench_function("encode_small_none", |b| {
+        b.iter(|| encode(black_box(data), black_box(seed), CompressionAlgorithm::None))
+    });
+
+    c.bench_function("encode_small_lz4", |b| {
+        b.iter(|| encode(black_box(data), black_box(seed), CompressionAlgorithm::Lz4))
+    });
+
+    c.bench_function("encode_small_brotli", |b| {
+        b.iter(|| encode(black_box(data), black_box(seed), CompressionAlgorithm::Brotli))
+    });
+}
+
+fn bench_encode_medium_data(c: &mut Criterion) {
+    let data = b"This is a medium-sized test data for benchmarking CypherSolBase encoding performance with different compression algorithms. We want to measure how the library performs with realistic data sizes that might be used in Solana programs.";
+    let seed = b"benchmark_secret_key_12345";
+
+    c.bench_function("encode_medium_none", |b| {
+        b.iter(|| encode(black_box(data), black_box(seed), CompressionAlgorithm::None))
+    });
+
+    c.bench_function("encode_medium_lz4", |b| {
+        b.iter(|| encode(black_box(data), black_box(seed), CompressionAlgorithm::Lz4))
+    });
+
+    c.bench_function("encode_medium_brotli", |b| {
+        b.iter(|| encode(black_box(data), black_box(seed), CompressionAlgorithm::Brotli))
+    });
+}
+
+fn bench_encode_large_data(c: &mut Criterion) {
+    let data = include_bytes!("../src/lib.rs"); // Use the source code as large test data
+    let seed = b"benchmark_secret_key_large_data_test";
+
+    c.bench_function("encode_large_none", |b| {
+        b.iter(|| encode(black_box(data), black_box(seed), CompressionAlgorithm::None))
+    });
+
+    c.bench_function("encode_large_lz4", |b| {
+        b.iter(|| encode(black_box(data), black_box(seed), CompressionAlgorithm::Lz4))
+    });
+
+    c.bench_function("encode_large_brotli", |b| {
+        b.iter(|| encode(black_box(data), black_box(seed), CompressionAlgorithm::Brotli))
+    });
+}
+
+fn bench_decode_small_data(c: &mut Criterion) {
+    let data = b"Hello, Solana World!";
+    let seed = b"benchmark_secret_key";
+
+    let encoded_none = encode(data, seed, CompressionAlgorithm::None);
+    let encoded_lz4 = encode(data, seed, CompressionAlgorithm::Lz4);
+    let encoded_brotli = encode(data, seed, CompressionAlgorithm::Brotli);
+
+    c.bench_function("decode_small_none", |b| {
+        b.iter(|| decode(black_box(&encoded_none), black_box(seed), CompressionAlgorithm::None))
+    });
+
+    c.bench_function("decode_small_lz4", |b| {
+        b.iter(|| decode(black_box(&encoded_lz4), black_box(seed), CompressionAlgorithm::Lz4))
+    });
+
+    c.bench_function("decode_small_brotli", |b| {
+        b.iter(|| decode(black_box(&encoded_brotli), black_box(seed), CompressionAlgorithm::Brotli))
+    });
+}
+
+fn bench_decode_medium_data(c: &mut Criterion) {
+    let data = b"This is a medium-sized test data for benchmarking CypherSolBase encoding performance with different compression algorithms. We want to measure how the library performs with realistic data sizes that might be used in Solana programs.";
+    let seed = b"benchmark_secret_key_12345";
+
+    let encoded_none = encode(data, seed, CompressionAlgorithm::None);
+    let encoded_lz4 = encode(data, seed, CompressionAlgorithm::Lz4);
+    let encoded_brotli = encode(data, seed, CompressionAlgorithm::Brotli);
+
+    c.bench_function("decode_medium_none", |b| {
+        b.iter(|| decode(black_box(&encoded_none), black_box(seed), CompressionAlgorithm::None))
+    });
+
+    c.bench_function("decode_medium_lz4", |b| {
+        b.iter(|| decode(black_box(&encoded_lz4), black_box(seed), CompressionAlgorithm::Lz4))
+    });
+
+    c.bench_function("decode_medium_brotli", |b| {
+        b.iter(|| decode(black_box(&encoded_brotli), black_box(seed), CompressionAlgorithm::Brotli))
+    });
+}
+
+fn bench_roundtrip_consistency(c: &mut Criterion) {
+    let data = b"Roundtrip test data for CypherSolBase";
+    let seed = b"consistency_test_key";
+
+    c.bench_function("roundtrip_none", |b| {
+        b.iter(|| {
+            let encoded = encode(black_box(data), black_box(seed), CompressionAlgorithm::None);
+            decode(black_box(&encoded), black_box(seed), CompressionAlgorithm::None)
+        })
+    });
+
+    c.bench_function("roundtrip_lz4", |b| {
+        b.iter(|| {
+            let encoded = encode(black_box(data), black_box(seed), CompressionAlgorithm::Lz4);
+            decode(black_box(&encoded), black_box(seed), CompressionAlgorithm::Lz4)
+        })
+    });
+}
+
+fn bench_encode_reusing_scratch(c: &mut Criterion) {
+    let data = b"Hello, Solana World!";
+    let seed = b"benchmark_secret_key";
+    let mut scratch = Scratch::default();
+    let mut out = Vec::new();
+
+    c.bench_function("encode_small_lz4_allocating", |b| {
+        b.iter(|| encode(black_box(data), black_box(seed), CompressionAlgorithm::Lz4))
+    });
+
+    c.bench_function("encode_small_lz4_scratch", |b| {
+        b.iter(|| {
+            out.clear();
+            encode_with(black_box(data), black_box(seed), CompressionAlgorithm::Lz4, &mut scratch, &mut out)
+        })
+    });
+}
+
+fn bench_alphabet_translation(c: &mut Criterion) {
+    // Base64 text as the substitution pass sees it, 1 MiB
+    let data: Vec<u8> = include_bytes!("../src/lib.rs").iter().copied().cycle().take(768 * 1024).collect();
+    let input = encode(&data, b"", CompressionAlgorithm::None);
+    let translator = Translator::encoding(&derive_alphabet(b"benchmark_secret_key"));
+    let mut out = Vec::with_capacity(input.len());
+
+    let mut group = c.benchmark_group("translate_1mib");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("scalar", |b| {
+        b.iter(|| {
+            out.clear();
+            translator.translate_scalar(black_box(&input), &mut out)
+        })
+    });
+    group.bench_function("simd", |b| {
+        b.iter(|| {
+            out.clear();
+            translator.translate(black_box(&input), &mut out)
+        })
+    });
+    group.finish();
+}
+
+fn bench_pipeline_stages(c: &mut Criterion) {
+    // Each stage of `encode` and `decode` alone, on the 64 KiB it would see for the same message
+    let data: Vec<u8> = include_bytes!("../src/lib.rs").iter().copied().cycle().take(64 * 1024).collect();
+    let seed = b"benchmark_secret_key";
+    let base64 = general_purpose::STANDARD.encode(&data).into_bytes();
+    let alphabet = derive_alphabet(seed);
+    let substituted = encode(&data, seed, CompressionAlgorithm::None);
+    let block = lz4::block::compress(&data, None, true).unwrap();
+    let mut frame = lz4::EncoderBuilder::new().build(Vec::new()).unwrap();
+    frame.write_all(&data).unwrap();
+    let frame = frame.finish().0;
+
+    c.bench_function("stage_derive_alphabet", |b| b.iter(|| derive_alphabet(black_box(seed))));
+
+    let mut group = c.benchmark_group("stages_64kib");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("lz4_compress", |b| b.iter(|| lz4::block::compress(black_box(&data), None, true)));
+    group.bench_function("lz4_decompress", |b| b.iter(|| lz4::block::decompress(black_box(&block), None)));
+    group.bench_function("lz4_frame_compress", |b| {
+        b.iter(|| {
+            let mut encoder = lz4::EncoderBuilder::new().build(Vec::new()).unwrap();
+            encoder.write_all(black_box(&data)).unwrap();
+            encoder.finish()
+        })
+    });
+    group.bench_function("lz4_frame_decompress", |b| {
+        b.iter(|| {
+            let mut out = Vec::with_capacity(data.len());
+            lz4::Decoder::new(black_box(frame.as_slice())).unwrap().read_to_end(&mut out)
+        })
+    });
+    group.bench_function("crc32", |b| b.iter(|| crc32fast::hash(black_box(&data))));
+    group.bench_function("base64_encode", |b| b.iter(|| general_purpose::STANDARD.encode(black_box(&data))));
+    group.bench_function("base64_decode", |b| b.iter(|| general_purpose::STANDARD.decode(black_box(&base64))));
+    let encoding = Translator::encoding(&alphabet);
+    let decoding = Translator::decoding(&alphabet);
+    let mut out = Vec::with_capacity(base64.len());
+    group.bench_function("substitute_encode", |b| {
+        b.iter(|| {
+            out.clear();
+            encoding.translate(black_box(&base64), &mut out)
+        })
+    });
+    group.bench_function("substitute_decode", |b| {
+        b.iter(|| {
+            out.clear();
+            decoding.translate(black_box(&substituted), &mut out)
+        })
+    });
+    // Both steps in one pass over 48-byte / 64-character blocks, as `encode` and `decode` run them
+    group.bench_function("armor", |b| {
+        b.iter(|| {
+            out.clear();
+            encoding.armor(black_box(&data), &mut out)
+        })
+    });
+    group.bench_function("unarmor", |b| {
+        b.iter(|| {
+            out.clear();
+            decoding.unarmor(black_box(&substituted), &mut out)
+        })
+    });
+    group.finish();
+}
+
+fn bench_auto_compression(c: &mut Criterion) {
+    let mut state = 0x2545_f491u32;
+    let noise: Vec<u8> = (0..1 << 20)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+    let text = sample_data(1 << 20);
+    let mut mixed = text.clone();
+    mixed[1 << 19..].copy_from_slice(&noise[1 << 19..]);
+    // Compressible only in the middle, which sampling cannot see
+    let mut middle = noise.clone();
+    middle[1 << 18..3 << 18].copy_from_slice(&text[1 << 18..3 << 18]);
+    let data_sets = [("text", text), ("noise", noise), ("mixed", mixed), ("middle", middle)];
+
+    // Accuracy: the sampled choice against the smallest encoding, and the bytes it costs
+    for (name, data) in &data_sets {
+        let (sampled, best) = (choose(data), choose_exhaustive(data));
+        let overhead = encode(data, b"", sampled).len() as f64 / encode(data, b"", best).len() as f64 - 1.0;
+        eprintln!("auto_compression/{name}: sampled {} exhaustive {} overhead {:.1}%", sampled.name(), best.name(), overhead * 100.0);
+    }
+
+    let mut group = c.benchmark_group("auto_compression_1mib");
+    group.throughput(Throughput::Bytes(1 << 20));
+    for (name, data) in &data_sets {
+        group.bench_function(format!("sampled_{name}"), |b| b.iter(|| choose(black_box(data))));
+        group.bench_function(format!("exhaustive_{name}"), |b| b.iter(|| choose_exhaustive(black_box(data))));
+    }
+    group.finish();
+}
+
+fn bench_encode_batch(c: &mut Criterion) {
+    // Messages of a Kafka partition or an API batch: many small ones under one seed
+    let inputs: Vec<Vec<u8>> = (0..1000).map(|i| sample_data(64 + i % 200)).collect();
+    let seed = b"benchmark_secret_key";
+
+    let mut group = c.benchmark_group("encode_1000_messages");
+    group.bench_function("single_calls", |b| {
+        b.iter(|| inputs.iter().map(|input| encode(black_box(input), seed, CompressionAlgorithm::Lz4)).collect::<Vec<_>>())
+    });
+    group.bench_function("batch", |b| b.iter(|| encode_batch(black_box(&inputs), seed, CompressionAlgorithm::Lz4)));
+    group.finish();
+}
+
+fn bench_decode_failures(c: &mut Criterion) {
+    // What rejecting hostile traffic costs next to decoding a valid message of the same size
+    let seed = b"benchmark_secret_key";
+    for (size, name) in [(256, "decode_failures_256b"), (64 * 1024, "decode_failures_64kib")] {
+        let encoded = encode(&sample_data(size), seed, CompressionAlgorithm::Lz4);
+        let mut corrupted = encoded.clone();
+        let middle = corrupted.len() / 2;
+        corrupted.swap(middle, middle + 1);
+        let truncated = &encoded[..encoded.len() - 5];
+        let garbage: Vec<u8> = encoded.iter().map(|&b| if b % 7 == 0 { b'!' } else { b }).collect();
+
+        let mut group = c.benchmark_group(name);
+        group.throughput(Throughput::Bytes(encoded.len() as u64));
+        for (case, input) in [
+            ("valid", encoded.as_slice()),
+            ("corrupted", corrupted.as_slice()),
+            ("truncated", truncated),
+            ("invalid_characters", garbage.as_slice()),
+        ] {
+            group.bench_function(case, |b| b.iter(|| decode(black_box(input), seed, CompressionAlgorithm::Lz4)));
+        }
+        group.bench_function("wrong_seed", |b| {
+            b.iter(|| decode(black_box(&encoded), b"attacker_guess", CompressionAlgorithm::Lz4))
+        });
+        group.finish();
+    }
+}
+
+fn bench_small_inline(c: &mut Criterion) {
+    // A Solana memo at the largest size `encode_small` takes
+    let memo = sample_data(SMALL_MAX);
+    let seed = b"benchmark_secret_key";
+    let encoded = encode(&memo, seed, CompressionAlgorithm::None);
+
+    let mut group = c.benchmark_group("memo_192b");
+    group.bench_function("encode_heap", |b| b.iter(|| encode(black_box(&memo), seed, CompressionAlgorithm::None)));
+    group.bench_function("encode_inline", |b| b.iter(|| encode_small(black_box(&memo), seed)));
+    group.bench_function("decode_heap", |b| b.iter(|| decode(black_box(&encoded), seed, CompressionAlgorithm::None)));
+    group.bench_function("decode_inline", |b| b.iter(|| decode_small(black_box(&encoded), seed)));
+    group.finish();
+}
+
+fn bench_stream_pipelining(c: &mut Criterion) {
+    // A large file through the streaming path, with compression overlapping encoding or not
+    let data = sample_data(32 * 1024 * 1024);
+    let seed = b"benchmark_secret_key";
+
+    let mut group = c.benchmark_group("stream_32mib");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.sample_size(10);
+    group.bench_function("sequential", |b| {
+        b.iter(|| encode_stream(black_box(data.as_slice()), std::io::sink(), seed, CompressionAlgorithm::Lz4))
+    });
+    group.bench_function("pipelined", |b| {
+        b.iter(|| encode_stream_pipelined(black_box(data.as_slice()), std::io::sink(), seed, CompressionAlgorithm::Lz4))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_encode_small_data,
+    bench_encode_medium_data,
+    bench_encode_large_data,
+    bench_decode_small_data,
+    bench_decode_medium_data,
+    bench_roundtrip_consistency,
+    bench_encode_reusing_scratch,
+    bench_alphabet_translation,
+    bench_pipeline_stages,
+    bench_auto_compression,
+    bench_encode_batch,
+    bench_decode_failures,
+    bench_small_inline,
+    bench_stream_pipelining
+);
+criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,17 @@
+fn main() {
+    #[cfg(feature = "proto")]
+    compile_protos();
+}
+
+/// Generate prost types from `proto/` without requiring a system `protoc`
+#[cfg(feature = "proto")]
+fn compile_protos() {
+    let files = ["proto/gxcore/v1/gxcore.proto"];
+    println!("cargo:rerun-if-changed=proto");
+
+    let descriptors = protox::compile(files, ["proto"]).expect("Failed to parse protos");
+
+    prost_build::Config::new()
+        .compile_fds(descriptors)
+        .expect("Failed to generate protobuf types");
+}
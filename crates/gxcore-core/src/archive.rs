@@ -0,0 +1,493 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::envelope::{Envelope, PayloadFormat};
+use crate::{auto, decode, encode, CompressionAlgorithm};
+
+/// Metadata entry marking an envelope as an archive
+pub const CONTENT_METADATA: &str = "content";
+/// Value of `CONTENT_METADATA` for archives
+pub const ARCHIVE_CONTENT: &str = "gxar";
+/// Value of `CONTENT_METADATA` for the manifests of container files
+pub const MANIFEST_CONTENT: &str = "gxar-manifest";
+
+/// Magic opening and closing container files
+const CONTAINER_MAGIC: &[u8; 4] = b"GXAR";
+const CONTAINER_VERSION: u8 = 1;
+/// Magic and version
+const CONTAINER_HEADER_LEN: u64 = 5;
+/// Manifest offset (u64), manifest length (u32) and magic
+const CONTAINER_TRAILER_LEN: u64 = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    File,
+    Directory,
+}
+
+/// File or directory of an archive
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// Relative path with `/` separators
+    pub path: String,
+    pub kind: EntryKind,
+    /// Unix permission bits
+    pub mode: u32,
+    /// CRC32 of `data`, checked per entry when opening
+    pub checksum: u32,
+    /// Contents, empty for directories
+    #[serde(with = "serde_bytes")]
+    pub data: Vec<u8>,
+}
+
+/// Directory tree sealed in a single authenticated envelope, entries in the order added
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Archive {
+    pub entries: Vec<ArchiveEntry>,
+}
+
+/// Entry of a container file's manifest, locating its encoded payload in the file
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Relative path with `/` separators
+    pub path: String,
+    pub kind: EntryKind,
+    /// Unix permission bits
+    pub mode: u32,
+    /// Length of the decoded contents
+    pub size: u64,
+    /// CRC32 of the decoded contents
+    pub checksum: u32,
+    /// Compression chosen for this entry alone
+    pub compression: CompressionAlgorithm,
+    /// Position of the encoded payload in the file
+    pub offset: u64,
+    /// Length of the encoded payload
+    pub length: u64,
+}
+
+/// Entries of a container file, in the order added
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Manifest {
+    entries: Vec<ManifestEntry>,
+}
+
+fn invalid(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Whether `path` stays inside the directory it is unpacked to
+fn is_relative(path: &str) -> bool {
+    !path.is_empty()
+        && !path.contains('\\')
+        && path.split('/').all(|part| !part.is_empty() && part != "." && part != "..")
+}
+
+impl Archive {
+    pub fn new() -> Self {
+        Archive::default()
+    }
+
+    pub fn add_file(&mut self, path: &str, mode: u32, data: Vec<u8>) -> Result<(), &'static str> {
+        self.add(path, EntryKind::File, mode, data)
+    }
+
+    pub fn add_directory(&mut self, path: &str, mode: u32) -> Result<(), &'static str> {
+        self.add(path, EntryKind::Directory, mode, Vec::new())
+    }
+
+    fn add(&mut self, path: &str, kind: EntryKind, mode: u32, data: Vec<u8>) -> Result<(), &'static str> {
+        if !is_relative(path) {
+            return Err("Archive path must be relative");
+        }
+        if self.entries.iter().any(|entry| entry.path == path) {
+            return Err("Duplicate archive path");
+        }
+        self.entries.push(ArchiveEntry {
+            path: path.to_string(),
+            kind,
+            mode,
+            checksum: crc32fast::hash(&data),
+            data,
+        });
+        Ok(())
+    }
+
+    /// Seal the entries with bincode in an HMAC-authenticated envelope
+    pub fn seal(&self, seed: &[u8], compression: CompressionAlgorithm) -> Result<Envelope, &'static str> {
+        let data = bincode::serialize(self).map_err(|_| "Bincode serialization failed")?;
+        Envelope::seal(&data, seed, compression)
+            .with_format(PayloadFormat::Bincode)
+            .with_metadata(CONTENT_METADATA, ARCHIVE_CONTENT)
+            .authenticate(seed)
+    }
+
+    /// Open an archive envelope, rejecting unsafe paths and entries whose checksum does not match
+    pub fn open(envelope: &Envelope, seed: &[u8]) -> Result<Self, &'static str> {
+        if envelope.metadata.get(CONTENT_METADATA).map(String::as_str) != Some(ARCHIVE_CONTENT) {
+            return Err("Envelope is not an archive");
+        }
+        let archive: Archive =
+            bincode::deserialize(&envelope.open(seed)?).map_err(|_| "Bincode deserialization failed")?;
+        for entry in &archive.entries {
+            if !is_relative(&entry.path) {
+                return Err("Archive path must be relative");
+            }
+            if crc32fast::hash(&entry.data) != entry.checksum {
+                return Err("Archive entry checksum mismatch");
+            }
+        }
+        Ok(archive)
+    }
+
+    /// Write the entries to a new container file at `path`. Unlike `seal`, each entry is encoded
+    /// on its own, with the compression `auto::choose` picks for it, so entries can be listed,
+    /// read and appended without decoding the others.
+    ///
+    /// The file holds the magic `GXAR` and a version byte, the encoded entries, then the manifest
+    /// in an authenticated envelope, closed by the manifest's offset (u64), length (u32) and the
+    /// magic again.
+    pub fn create(&self, path: &Path, seed: &[u8]) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(CONTAINER_MAGIC)?;
+        file.write_all(&[CONTAINER_VERSION])?;
+        let mut manifest = Manifest::default();
+        let end = self.write_entries(&mut file, CONTAINER_HEADER_LEN, seed, &mut manifest)?;
+        write_manifest(&mut file, end, &manifest, seed)
+    }
+
+    /// Add the entries to the container file at `path`, refusing paths it already holds. They
+    /// and a new manifest are appended as a section after what the file holds, which is left in
+    /// place; a failed append is truncated away, leaving the previous contents readable.
+    pub fn append(&self, path: &Path, seed: &[u8]) -> io::Result<()> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let (mut manifest, _) = read_manifest(&mut file, seed)?;
+        if self.entries.iter().any(|entry| manifest.entries.iter().any(|existing| existing.path == entry.path)) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "Duplicate archive path"));
+        }
+        self.write_section(&mut file, seed, &mut manifest)
+    }
+
+    /// `append`, replacing entries the container already holds under the same paths. Their old
+    /// payloads stay in the file, unreferenced, until `compact` drops them.
+    pub fn replace(&self, path: &Path, seed: &[u8]) -> io::Result<()> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let (mut manifest, _) = read_manifest(&mut file, seed)?;
+        self.write_section(&mut file, seed, &mut manifest)
+    }
+
+    /// Remove the entries named `names` from the container file at `path` by appending a section
+    /// whose manifest leaves them out. Their payloads stay in the file until `compact`.
+    pub fn remove(path: &Path, seed: &[u8], names: &[&str]) -> io::Result<()> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let (mut manifest, _) = read_manifest(&mut file, seed)?;
+        for name in names {
+            let before = manifest.entries.len();
+            manifest.entries.retain(|entry| entry.path != *name);
+            if manifest.entries.len() == before {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "No such archive entry"));
+            }
+        }
+        Archive::new().write_section(&mut file, seed, &mut manifest)
+    }
+
+    /// Bytes of the container file at `path` that its manifest no longer references: payloads
+    /// replaced or removed and the manifests of earlier sections
+    pub fn stale_bytes(path: &Path, seed: &[u8]) -> io::Result<u64> {
+        let (manifest, offset) = read_manifest(&mut File::open(path)?, seed)?;
+        let live: u64 = manifest.entries.iter().map(|entry| entry.length).sum();
+        Ok(offset - CONTAINER_HEADER_LEN - live)
+    }
+
+    /// Rewrite the container file at `path` as a single section holding only the entries its
+    /// manifest references, copying their payloads without decoding them, and return the bytes
+    /// saved. The new file is written beside it and renamed over it, so the container stays
+    /// readable if compaction fails.
+    pub fn compact(path: &Path, seed: &[u8]) -> io::Result<u64> {
+        let mut file = File::open(path)?;
+        let (manifest, _) = read_manifest(&mut file, seed)?;
+        let before = file.metadata()?.len();
+        let mut name = path.as_os_str().to_owned();
+        name.push(".compact");
+        let compacted = PathBuf::from(name);
+
+        let write = |file: &mut File| -> io::Result<u64> {
+            let mut out = File::create(&compacted)?;
+            out.write_all(CONTAINER_MAGIC)?;
+            out.write_all(&[CONTAINER_VERSION])?;
+            let mut rewritten = Manifest::default();
+            let mut offset = CONTAINER_HEADER_LEN;
+            for entry in &manifest.entries {
+                file.seek(SeekFrom::Start(entry.offset))?;
+                if io::copy(&mut Read::by_ref(file).take(entry.length), &mut out)? != entry.length {
+                    return Err(invalid("Archive entry outside the container"));
+                }
+                rewritten.entries.push(ManifestEntry {
+                    offset,
+                    ..entry.clone()
+                });
+                offset += entry.length;
+            }
+            write_manifest(&mut out, offset, &rewritten, seed)?;
+            out.metadata().map(|metadata| metadata.len())
+        };
+        match write(&mut file) {
+            Ok(after) => {
+                fs::rename(&compacted, path)?;
+                Ok(before.saturating_sub(after))
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&compacted);
+                Err(e)
+            }
+        }
+    }
+
+    /// Entries of the container file at `path`, from its authenticated manifest
+    pub fn list(path: &Path, seed: &[u8]) -> io::Result<Vec<ManifestEntry>> {
+        Ok(read_manifest(&mut File::open(path)?, seed)?.0.entries)
+    }
+
+    /// Contents of the entry named `name` in the container file at `path`, reading only its payload
+    pub fn read(path: &Path, seed: &[u8], name: &str) -> io::Result<Vec<u8>> {
+        let mut file = File::open(path)?;
+        let (manifest, _) = read_manifest(&mut file, seed)?;
+        let entry = manifest
+            .entries
+            .iter()
+            .find(|entry| entry.path == name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No such archive entry"))?;
+        let mut encoded = vec![0; entry.length as usize];
+        file.seek(SeekFrom::Start(entry.offset))?;
+        file.read_exact(&mut encoded)?;
+        let data = decode(&encoded, seed, entry.compression).map_err(invalid)?;
+        if data.len() as u64 != entry.size || crc32fast::hash(&data) != entry.checksum {
+            return Err(invalid("Archive entry checksum mismatch"));
+        }
+        Ok(data)
+    }
+
+    /// Append the entries and `manifest` updated with them to `file` as a new section, truncating
+    /// the file back to where it was on failure so the previous trailer closes it again
+    fn write_section(&self, file: &mut File, seed: &[u8], manifest: &mut Manifest) -> io::Result<()> {
+        let start = file.seek(SeekFrom::End(0))?;
+        let result = self
+            .write_entries(file, start, seed, manifest)
+            .and_then(|end| write_manifest(file, end, manifest, seed));
+        if result.is_err() {
+            let _ = file.set_len(start);
+        }
+        result
+    }
+
+    /// Encode the entries into `file` from `offset`, recording them in `manifest` in place of
+    /// entries with the same path, and return where they end
+    fn write_entries(&self, file: &mut File, mut offset: u64, seed: &[u8], manifest: &mut Manifest) -> io::Result<u64> {
+        for entry in &self.entries {
+            let compression = auto::choose(&entry.data);
+            let encoded = encode(&entry.data, seed, compression);
+            file.write_all(&encoded)?;
+            let written = ManifestEntry {
+                path: entry.path.clone(),
+                kind: entry.kind,
+                mode: entry.mode,
+                size: entry.data.len() as u64,
+                checksum: entry.checksum,
+                compression,
+                offset,
+                length: encoded.len() as u64,
+            };
+            match manifest.entries.iter_mut().find(|existing| existing.path == entry.path) {
+                Some(existing) => *existing = written,
+                None => manifest.entries.push(written),
+            }
+            offset += encoded.len() as u64;
+        }
+        Ok(offset)
+    }
+}
+
+/// Write `manifest` and the trailer pointing at it to `file` at `offset`, its current position
+fn write_manifest(file: &mut File, offset: u64, manifest: &Manifest, seed: &[u8]) -> io::Result<()> {
+    let data = bincode::serialize(manifest).map_err(|_| invalid("Bincode serialization failed"))?;
+    let bytes = Envelope::seal(&data, seed, CompressionAlgorithm::Lz4)
+        .with_format(PayloadFormat::Bincode)
+        .with_metadata(CONTENT_METADATA, MANIFEST_CONTENT)
+        .authenticate(seed)
+        .and_then(|envelope| envelope.to_bytes())
+        .map_err(invalid)?;
+    let mut out = Vec::with_capacity(bytes.len() + CONTAINER_TRAILER_LEN as usize);
+    out.extend_from_slice(&bytes);
+    out.extend_from_slice(&offset.to_le_bytes());
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(CONTAINER_MAGIC);
+    file.write_all(&out)?;
+    file.sync_data()
+}
+
+/// The manifest the trailer of `file` points at and its offset, checking that it is authentic and
+/// that its entries have safe paths and lie before it
+fn read_manifest(file: &mut File, seed: &[u8]) -> io::Result<(Manifest, u64)> {
+    let mut header = [0; CONTAINER_HEADER_LEN as usize];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut header).map_err(|_| invalid("Not an archive container"))?;
+    if header[..4] != *CONTAINER_MAGIC {
+        return Err(invalid("Not an archive container"));
+    }
+    if header[4] != CONTAINER_VERSION {
+        return Err(invalid("Unsupported archive container version"));
+    }
+
+    let len = file.seek(SeekFrom::End(0))?;
+    if len < CONTAINER_HEADER_LEN + CONTAINER_TRAILER_LEN {
+        return Err(invalid("Truncated archive container"));
+    }
+    let mut trailer = [0; CONTAINER_TRAILER_LEN as usize];
+    file.seek(SeekFrom::Start(len - CONTAINER_TRAILER_LEN))?;
+    file.read_exact(&mut trailer)?;
+    if trailer[12..] != *CONTAINER_MAGIC {
+        return Err(invalid("Truncated archive container"));
+    }
+    let offset = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+    let manifest_len = u64::from(u32::from_le_bytes(trailer[8..12].try_into().unwrap()));
+    if offset < CONTAINER_HEADER_LEN || offset + manifest_len != len - CONTAINER_TRAILER_LEN {
+        return Err(invalid("Invalid archive manifest offset"));
+    }
+
+    let mut bytes = vec![0; manifest_len as usize];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut bytes)?;
+    let envelope = Envelope::from_bytes(&bytes).map_err(invalid)?;
+    if envelope.metadata.get(CONTENT_METADATA).map(String::as_str) != Some(MANIFEST_CONTENT) {
+        return Err(invalid("Envelope is not an archive manifest"));
+    }
+    let data = envelope.open(seed).map_err(invalid)?;
+    let manifest: Manifest = bincode::deserialize(&data).map_err(|_| invalid("Bincode deserialization failed"))?;
+    for entry in &manifest.entries {
+        if !is_relative(&entry.path) {
+            return Err(invalid("Archive path must be relative"));
+        }
+        if entry.offset < CONTAINER_HEADER_LEN || entry.offset.saturating_add(entry.length) > offset {
+            return Err(invalid("Archive entry outside the container"));
+        }
+    }
+    Ok((manifest, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let mut archive = Archive::new();
+        archive.add_directory("docs", 0o755).unwrap();
+        archive.add_file("docs/readme.txt", 0o644, b"hello".to_vec()).unwrap();
+        archive.add_file("run.sh", 0o755, b"#!/bin/sh\n".to_vec()).unwrap();
+        let bytes = archive.seal(b"seed", CompressionAlgorithm::Lz4).unwrap().to_bytes().unwrap();
+
+        let opened = Archive::open(&Envelope::parse(&bytes).unwrap(), b"seed").unwrap();
+        assert_eq!(opened, archive);
+        assert_eq!(
+            Archive::open(&Envelope::parse(&bytes).unwrap(), b"other"),
+            Err("Envelope authentication failed")
+        );
+    }
+
+    #[test]
+    fn test_archive_rejects_unsafe_entries() {
+        let mut archive = Archive::new();
+        for path in ["", "/etc/passwd", "../up", "a/../b", "a//b", "a\\b"] {
+            assert_eq!(archive.add_file(path, 0o644, Vec::new()), Err("Archive path must be relative"));
+        }
+        archive.add_file("a", 0o644, b"data".to_vec()).unwrap();
+        assert_eq!(archive.add_directory("a", 0o755), Err("Duplicate archive path"));
+
+        archive.entries[0].data = b"tampered".to_vec();
+        let envelope = archive.seal(b"seed", CompressionAlgorithm::None).unwrap();
+        assert_eq!(Archive::open(&envelope, b"seed"), Err("Archive entry checksum mismatch"));
+
+        let plain = Envelope::seal(b"data", b"seed", CompressionAlgorithm::None);
+        assert_eq!(Archive::open(&plain, b"seed"), Err("Envelope is not an archive"));
+    }
+
+    #[test]
+    fn test_container_create_append_read() {
+        let dir = std::env::temp_dir().join(format!("gx-core-container-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bundle.gxar");
+
+        let mut archive = Archive::new();
+        archive.add_directory("docs", 0o755).unwrap();
+        archive.add_file("docs/readme.txt", 0o644, b"hello".to_vec()).unwrap();
+        archive.add_file("data.bin", 0o644, crate::bench::sample_data(100_000)).unwrap();
+        archive.create(&path, b"seed").unwrap();
+
+        let entries = Archive::list(&path, b"seed").unwrap();
+        let paths: Vec<_> = entries.iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(paths, ["docs", "docs/readme.txt", "data.bin"]);
+        assert_eq!(entries[1].size, 5);
+        assert_eq!(entries[2].compression, CompressionAlgorithm::Lz4);
+        assert_eq!(Archive::read(&path, b"seed", "docs/readme.txt").unwrap(), b"hello");
+
+        let mut more = Archive::new();
+        more.add_file("notes.txt", 0o600, b"appended".to_vec()).unwrap();
+        more.append(&path, b"seed").unwrap();
+        assert_eq!(Archive::list(&path, b"seed").unwrap().len(), 4);
+        assert_eq!(Archive::read(&path, b"seed", "notes.txt").unwrap(), b"appended");
+        assert_eq!(Archive::read(&path, b"seed", "data.bin").unwrap(), archive.entries[2].data);
+
+        let error = more.append(&path, b"seed").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        let error = Archive::read(&path, b"seed", "missing").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        let error = Archive::list(&path, b"other").unwrap_err();
+        assert_eq!(error.to_string(), "Envelope authentication failed");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_container_replace_remove_compact() {
+        let dir = std::env::temp_dir().join(format!("gx-core-container-compact-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bundle.gxar");
+
+        let mut archive = Archive::new();
+        archive.add_file("a.txt", 0o644, b"first".to_vec()).unwrap();
+        archive.add_file("b.bin", 0o644, crate::bench::sample_data(50_000)).unwrap();
+        archive.add_file("c.txt", 0o644, b"third".to_vec()).unwrap();
+        archive.create(&path, b"seed").unwrap();
+        assert_eq!(Archive::stale_bytes(&path, b"seed").unwrap(), 0);
+
+        let mut update = Archive::new();
+        update.add_file("a.txt", 0o600, b"first, edited".to_vec()).unwrap();
+        update.add_file("d.txt", 0o644, b"fourth".to_vec()).unwrap();
+        update.replace(&path, b"seed").unwrap();
+        Archive::remove(&path, b"seed", &["b.bin"]).unwrap();
+        let error = Archive::remove(&path, b"seed", &["b.bin"]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        let entries = Archive::list(&path, b"seed").unwrap();
+        let paths: Vec<_> = entries.iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(paths, ["a.txt", "c.txt", "d.txt"]);
+        assert_eq!(entries[0].mode, 0o600);
+        assert_eq!(Archive::read(&path, b"seed", "a.txt").unwrap(), b"first, edited");
+        assert_eq!(Archive::read(&path, b"seed", "b.bin").unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        let stale = Archive::stale_bytes(&path, b"seed").unwrap();
+        assert!(stale > 0);
+        let before = std::fs::metadata(&path).unwrap().len();
+        let saved = Archive::compact(&path, b"seed").unwrap();
+        assert_eq!(saved, before - std::fs::metadata(&path).unwrap().len());
+        assert!(saved > 0);
+        assert_eq!(Archive::stale_bytes(&path, b"seed").unwrap(), 0);
+        assert_eq!(Archive::list(&path, b"seed").unwrap().len(), 3);
+        assert_eq!(Archive::read(&path, b"seed", "a.txt").unwrap(), b"first, edited");
+        assert_eq!(Archive::read(&path, b"seed", "d.txt").unwrap(), b"fourth");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,111 @@
+use lz4::block::compress;
+
+use crate::{encode, CompressionAlgorithm};
+
+/// Compressions worth trying; Brotli and Huffman are placeholders that store data as is
+const CANDIDATES: [CompressionAlgorithm; 2] = [CompressionAlgorithm::None, CompressionAlgorithm::Lz4];
+
+/// Bytes sampled from each end of large inputs
+pub const SAMPLE_SIZE: usize = 16 * 1024;
+
+/// Inputs up to this size are tried whole, as sampling them saves little
+const SAMPLE_ABOVE: usize = 4 * SAMPLE_SIZE;
+
+/// Bits per byte above which a sample is taken as compressed or random without trying it
+const INCOMPRESSIBLE_ENTROPY: f64 = 7.5;
+
+/// Samples must shrink below this fraction of their size for compression to be chosen, leaving a
+/// margin for the parts of the input between them
+const WORTHWHILE_RATIO: f64 = 0.95;
+
+/// Shannon entropy of the byte distribution of `data`, in bits per byte
+pub fn entropy(data: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &b in data {
+        counts[usize::from(b)] += 1;
+    }
+    let len = data.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// The compression giving the smallest encoding of `data`, found by encoding it with each one
+pub fn choose_exhaustive(data: &[u8]) -> CompressionAlgorithm {
+    CANDIDATES
+        .into_iter()
+        .min_by_key(|&compression| encode(data, b"", compression).len())
+        .expect("candidates are not empty")
+}
+
+/// The compression to use for `data`. Large inputs are judged from their first and last
+/// `SAMPLE_SIZE` bytes: skipped when their entropy shows them random or already compressed, and
+/// otherwise compressed alone with LZ4. Smaller ones are tried whole as `choose_exhaustive` does.
+pub fn choose(data: &[u8]) -> CompressionAlgorithm {
+    if data.len() <= SAMPLE_ABOVE {
+        return choose_exhaustive(data);
+    }
+    let (head, tail) = (&data[..SAMPLE_SIZE], &data[data.len() - SAMPLE_SIZE..]);
+    if entropy(head).min(entropy(tail)) > INCOMPRESSIBLE_ENTROPY {
+        return CompressionAlgorithm::None;
+    }
+    let compressed: usize = [head, tail]
+        .into_iter()
+        .map(|sample| compress(sample, None, false).map_or(sample.len(), |compressed| compressed.len()))
+        .sum();
+    if (compressed as f64) < (2 * SAMPLE_SIZE) as f64 * WORTHWHILE_RATIO {
+        CompressionAlgorithm::Lz4
+    } else {
+        CompressionAlgorithm::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::sample_data;
+
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_entropy() {
+        assert_eq!(entropy(&[7; 100]), 0.0);
+        assert_eq!(entropy(&(0..=255).collect::<Vec<u8>>()), 8.0);
+        assert!(entropy(&sample_data(10_000)) < 6.0);
+        assert!(entropy(&noise(10_000)) > 7.9);
+    }
+
+    #[test]
+    fn test_choice_matches_exhaustive() {
+        let text = sample_data(1 << 20);
+        let random = noise(1 << 20);
+        let mut mixed = text.clone();
+        mixed[1 << 19..].copy_from_slice(&random[1 << 19..]);
+
+        for (data, expected) in [
+            (&text, CompressionAlgorithm::Lz4),
+            (&random, CompressionAlgorithm::None),
+            (&mixed, CompressionAlgorithm::Lz4),
+        ] {
+            assert_eq!(choose(data), expected);
+            assert_eq!(choose_exhaustive(data), expected);
+        }
+        assert_eq!(choose(b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"), CompressionAlgorithm::Lz4);
+        assert_eq!(choose(b"short"), CompressionAlgorithm::None);
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::translate::Translator;
+use crate::{decode_translated, derive_alphabet, encode_translated, CompressionAlgorithm, Scratch};
+
+/// Encode each of `inputs` as `encode` does, deriving the alphabet once and reusing one set of
+/// intermediate buffers for them all
+pub fn encode_batch<T: AsRef<[u8]>>(inputs: &[T], seed: &[u8], compression: CompressionAlgorithm) -> Vec<Vec<u8>> {
+    let translator = Translator::encoding(&derive_alphabet(seed));
+    let mut scratch = Scratch::default();
+    inputs
+        .iter()
+        .map(|input| encode_one(input.as_ref(), &translator, compression, &mut scratch))
+        .collect()
+}
+
+/// Decode each of `inputs` as `decode` does, with a result per input so one bad item does not
+/// fail the rest
+pub fn decode_batch<T: AsRef<[u8]>>(inputs: &[T], seed: &[u8], compression: CompressionAlgorithm) -> Vec<Result<Vec<u8>, &'static str>> {
+    let translator = Translator::decoding(&derive_alphabet(seed));
+    let mut scratch = Scratch::default();
+    inputs
+        .iter()
+        .map(|input| decode_one(input.as_ref(), &translator, compression, &mut scratch))
+        .collect()
+}
+
+/// `encode_batch` spread over the current rayon pool, the global one unless called within
+/// `ThreadPool::install`. Results keep the order of `inputs`.
+#[cfg(feature = "parallel")]
+pub fn encode_batch_parallel<T: AsRef<[u8]> + Sync>(inputs: &[T], seed: &[u8], compression: CompressionAlgorithm) -> Vec<Vec<u8>> {
+    let translator = Translator::encoding(&derive_alphabet(seed));
+    inputs
+        .par_iter()
+        .map_init(Scratch::default, |scratch, input| encode_one(input.as_ref(), &translator, compression, scratch))
+        .collect()
+}
+
+/// `decode_batch` spread over the current rayon pool, as `encode_batch_parallel` is
+#[cfg(feature = "parallel")]
+pub fn decode_batch_parallel<T: AsRef<[u8]> + Sync>(
+    inputs: &[T],
+    seed: &[u8],
+    compression: CompressionAlgorithm,
+) -> Vec<Result<Vec<u8>, &'static str>> {
+    let translator = Translator::decoding(&derive_alphabet(seed));
+    inputs
+        .par_iter()
+        .map_init(Scratch::default, |scratch, input| decode_one(input.as_ref(), &translator, compression, scratch))
+        .collect()
+}
+
+fn encode_one(input: &[u8], translator: &Translator, compression: CompressionAlgorithm, scratch: &mut Scratch) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_translated(input, translator, compression, true, scratch, &mut out);
+    out
+}
+
+fn decode_one(input: &[u8], translator: &Translator, compression: CompressionAlgorithm, scratch: &mut Scratch) -> Result<Vec<u8>, &'static str> {
+    let mut out = Vec::new();
+    decode_translated(input, translator, compression, true, scratch, &mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::sample_data;
+    use crate::{decode, encode};
+
+    fn inputs() -> Vec<Vec<u8>> {
+        (0..50).map(|i| sample_data(i * 97)).collect()
+    }
+
+    #[test]
+    fn test_batch_matches_single_calls() {
+        let inputs = inputs();
+        for compression in [CompressionAlgorithm::None, CompressionAlgorithm::Lz4, CompressionAlgorithm::Lz4Frame] {
+            let encoded = encode_batch(&inputs, b"seed", compression);
+            for (input, encoded) in inputs.iter().zip(&encoded) {
+                assert_eq!(*encoded, encode(input, b"seed", compression));
+            }
+
+            let mut encoded = encoded;
+            encoded[3] = b"!!!!".to_vec();
+            let decoded = decode_batch(&encoded, b"seed", compression);
+            for (i, (decoded, encoded)) in decoded.iter().zip(&encoded).enumerate() {
+                assert_eq!(*decoded, decode(encoded, b"seed", compression));
+                assert_eq!(decoded.is_ok(), i != 3);
+            }
+        }
+        assert!(encode_batch::<&[u8]>(&[], b"seed", CompressionAlgorithm::None).is_empty());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_batch_keeps_order() {
+        let inputs = inputs();
+        let encoded = encode_batch_parallel(&inputs, b"seed", CompressionAlgorithm::Lz4);
+        assert_eq!(encoded, encode_batch(&inputs, b"seed", CompressionAlgorithm::Lz4));
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+        let decoded = pool.install(|| decode_batch_parallel(&encoded, b"other", CompressionAlgorithm::Lz4));
+        assert!(decoded.iter().all(Result::is_err));
+        let decoded = decode_batch_parallel(&encoded, b"seed", CompressionAlgorithm::Lz4);
+        assert_eq!(decoded.into_iter().collect::<Result<Vec<_>, _>>().unwrap(), inputs);
+    }
+}
@@ -0,0 +1,217 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")]
+use utoipa::ToSchema;
+
+use crate::{decode, encode, CompressionAlgorithm};
+
+/// Timing of one operation repeated over the same data
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema))]
+pub struct BenchmarkResult {
+    /// `encode_` or `decode_` followed by the data set name
+    pub operation: String,
+    pub data_size: usize,
+    pub compression: String,
+    pub iterations: usize,
+    pub total_time_ms: f64,
+    pub avg_time_per_op_ns: f64,
+    pub throughput_mb_per_sec: f64,
+}
+
+impl BenchmarkResult {
+    fn new(operation: String, data_size: usize, compression: CompressionAlgorithm, iterations: usize, total_ns: u128) -> Self {
+        let seconds = total_ns as f64 / 1e9;
+        BenchmarkResult {
+            operation,
+            data_size,
+            compression: compression.name().to_string(),
+            iterations,
+            total_time_ms: (total_ns / 1_000_000) as f64,
+            avg_time_per_op_ns: total_ns as f64 / iterations as f64,
+            throughput_mb_per_sec: (data_size * iterations) as f64 / seconds / (1024.0 * 1024.0),
+        }
+    }
+}
+
+/// Time `iterations` encodes of `data`, then as many decodes of the result
+pub fn measure(name: &str, data: &[u8], seed: &[u8], compression: CompressionAlgorithm, iterations: usize) -> [BenchmarkResult; 2] {
+    let iterations = iterations.max(1);
+    let encode_start = Instant::now();
+    for _ in 0..iterations {
+        let _ = encode(data, seed, compression);
+    }
+    let encode_ns = encode_start.elapsed().as_nanos();
+
+    let encoded = encode(data, seed, compression);
+    let decode_start = Instant::now();
+    for _ in 0..iterations {
+        let _ = decode(&encoded, seed, compression);
+    }
+    let decode_ns = decode_start.elapsed().as_nanos();
+
+    [
+        BenchmarkResult::new(format!("encode_{name}"), data.len(), compression, iterations, encode_ns),
+        BenchmarkResult::new(format!("decode_{name}"), encoded.len(), compression, iterations, decode_ns),
+    ]
+}
+
+/// Change in one operation's average time from a baseline run to the current one
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema))]
+pub struct Comparison {
+    pub operation: String,
+    pub compression: String,
+    pub baseline_ns: f64,
+    pub current_ns: f64,
+    /// Percent slower than the baseline, negative when faster
+    pub change_percent: f64,
+    /// Slower by more than the threshold
+    pub regressed: bool,
+}
+
+/// Compare `current` with the `baseline` results of the same operation and compression, flagging
+/// those more than `threshold_percent` slower. Results missing from either run are left out.
+pub fn compare(baseline: &[BenchmarkResult], current: &[BenchmarkResult], threshold_percent: f64) -> Vec<Comparison> {
+    current
+        .iter()
+        .filter_map(|result| {
+            let before = baseline
+                .iter()
+                .find(|before| before.operation == result.operation && before.compression == result.compression)?;
+            let change_percent = (result.avg_time_per_op_ns / before.avg_time_per_op_ns - 1.0) * 100.0;
+            Some(Comparison {
+                operation: result.operation.clone(),
+                compression: result.compression.clone(),
+                baseline_ns: before.avg_time_per_op_ns,
+                current_ns: result.avg_time_per_op_ns,
+                change_percent,
+                regressed: change_percent > threshold_percent,
+            })
+        })
+        .collect()
+}
+
+/// Line-per-result layouts for loading results into other tools
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schema", derive(ToSchema))]
+pub enum RowFormat {
+    /// One JSON object per line
+    Ndjson,
+    /// A header of the field names, then one line per result
+    Csv,
+}
+
+impl RowFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            RowFormat::Ndjson => "application/x-ndjson",
+            RowFormat::Csv => "text/csv",
+        }
+    }
+}
+
+/// Quote a CSV field holding a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// `results` one per line in `format`, each line ending in a newline
+pub fn to_rows(results: &[BenchmarkResult], format: RowFormat) -> String {
+    let mut rows = String::new();
+    match format {
+        RowFormat::Ndjson => {
+            for result in results {
+                rows.push_str(&serde_json::to_string(result).expect("results serialize"));
+                rows.push('\n');
+            }
+        }
+        RowFormat::Csv => {
+            rows.push_str("operation,data_size,compression,iterations,total_time_ms,avg_time_per_op_ns,throughput_mb_per_sec\n");
+            for result in results {
+                rows.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    csv_field(&result.operation),
+                    result.data_size,
+                    csv_field(&result.compression),
+                    result.iterations,
+                    result.total_time_ms,
+                    result.avg_time_per_op_ns,
+                    result.throughput_mb_per_sec,
+                ));
+            }
+        }
+    }
+    rows
+}
+
+/// `size` bytes of source text, compressible like typical payloads
+pub fn sample_data(size: usize) -> Vec<u8> {
+    include_bytes!("lib.rs").iter().copied().cycle().take(size).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_both_directions() {
+        let data = sample_data(10_000);
+        assert_eq!(data.len(), 10_000);
+        let [encoded, decoded] = measure("sample", &data, b"seed", CompressionAlgorithm::Lz4, 3);
+        assert_eq!(encoded.operation, "encode_sample");
+        assert_eq!(encoded.data_size, 10_000);
+        assert_eq!(decoded.operation, "decode_sample");
+        assert_eq!(decoded.compression, "lz4");
+        assert!(decoded.data_size < 10_000);
+    }
+
+    #[test]
+    fn test_compare_flags_slower_runs() {
+        let result = |operation: &str, compression, ns| BenchmarkResult::new(operation.to_string(), 100, compression, 10, ns);
+        let baseline = [
+            result("encode_sample", CompressionAlgorithm::Lz4, 1000),
+            result("decode_sample", CompressionAlgorithm::Lz4, 1000),
+            result("encode_sample", CompressionAlgorithm::None, 1000),
+        ];
+        let current = [
+            result("encode_sample", CompressionAlgorithm::Lz4, 1050),
+            result("decode_sample", CompressionAlgorithm::Lz4, 1200),
+            result("encode_sample", CompressionAlgorithm::Brotli, 5000),
+        ];
+        let comparisons = compare(&baseline, &current, 10.0);
+        assert_eq!(comparisons.len(), 2);
+        assert!((comparisons[0].change_percent - 5.0).abs() < 1e-9);
+        assert!(!comparisons[0].regressed);
+        assert_eq!(comparisons[1].operation, "decode_sample");
+        assert!(comparisons[1].regressed);
+
+        let json = serde_json::to_string(&baseline).unwrap();
+        let loaded: Vec<BenchmarkResult> = serde_json::from_str(&json).unwrap();
+        assert!(compare(&loaded, &baseline, 0.0).iter().all(|comparison| !comparison.regressed));
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut results = measure("sample", &sample_data(100), b"seed", CompressionAlgorithm::None, 1).to_vec();
+        results[1].operation = "decode \"a,b\"".to_string();
+
+        let ndjson = to_rows(&results, RowFormat::Ndjson);
+        let lines: Vec<serde_json::Value> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["operation"], "encode_sample");
+
+        let csv = to_rows(&results, RowFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("operation,data_size,compression,"));
+        assert!(lines[1].starts_with("encode_sample,100,none,1,"));
+        assert!(lines[2].starts_with("\"decode \"\"a,b\"\"\","));
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+
+#[cfg(loom)]
+use loom::sync::{Arc, Mutex};
+#[cfg(not(loom))]
+use std::sync::{Arc, Mutex};
+
+use sha2::{Digest, Sha256};
+
+use crate::translate::Translator;
+use crate::{decode_translated, derive_alphabet, encode_translated, CompressionAlgorithm, Scratch};
+
+/// Both translators of one seed
+pub struct SeedTranslators {
+    pub encoding: Translator,
+    pub decoding: Translator,
+}
+
+/// Translators of the most recently used seeds, for a server sharing one cache across its
+/// handlers so each alphabet is derived once rather than per request. Seeds are kept only as
+/// their SHA-256 hashes.
+pub struct TranslatorCache {
+    capacity: usize,
+    /// Least recently used first
+    entries: Mutex<VecDeque<([u8; 32], Arc<SeedTranslators>)>>,
+}
+
+/// Move the entry for `key` to the most recently used end, returning its translators
+fn touch(entries: &mut VecDeque<([u8; 32], Arc<SeedTranslators>)>, key: &[u8; 32]) -> Option<Arc<SeedTranslators>> {
+    let position = entries.iter().position(|(k, _)| k == key)?;
+    let entry = entries.remove(position)?;
+    let translators = entry.1.clone();
+    entries.push_back(entry);
+    Some(translators)
+}
+
+impl TranslatorCache {
+    /// Cache holding up to `capacity` seeds, at least one
+    pub fn new(capacity: usize) -> Self {
+        TranslatorCache {
+            capacity: capacity.max(1),
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Translators of `seed`. A miss derives them without holding the lock, so other lookups never
+    /// wait on it; when two threads miss on the same seed, both get the first one stored.
+    pub fn get(&self, seed: &[u8]) -> Arc<SeedTranslators> {
+        let key: [u8; 32] = Sha256::digest(seed).into();
+        if let Some(translators) = touch(&mut self.entries.lock().unwrap(), &key) {
+            return translators;
+        }
+
+        let alphabet = derive_alphabet(seed);
+        let derived = Arc::new(SeedTranslators {
+            encoding: Translator::encoding(&alphabet),
+            decoding: Translator::decoding(&alphabet),
+        });
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(translators) = touch(&mut entries, &key) {
+            return translators;
+        }
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back((key, derived.clone()));
+        derived
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `encode` through the cached translators of `seed`
+    pub fn encode(&self, data: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_with(data, seed, compression, &mut Scratch::default(), &mut out);
+        out
+    }
+
+    /// `decode` through the cached translators of `seed`
+    pub fn decode(&self, encoded: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Result<Vec<u8>, &'static str> {
+        let mut out = Vec::new();
+        self.decode_with(encoded, seed, compression, &mut Scratch::default(), &mut out)?;
+        Ok(out)
+    }
+
+    /// `encode_with` through the cached translators of `seed`
+    pub fn encode_with(&self, data: &[u8], seed: &[u8], compression: CompressionAlgorithm, scratch: &mut Scratch, out: &mut Vec<u8>) {
+        encode_translated(data, &self.get(seed).encoding, compression, true, scratch, out);
+    }
+
+    /// `decode_with` through the cached translators of `seed`
+    pub fn decode_with(
+        &self,
+        encoded: &[u8],
+        seed: &[u8],
+        compression: CompressionAlgorithm,
+        scratch: &mut Scratch,
+        out: &mut Vec<u8>,
+    ) -> Result<(), &'static str> {
+        decode_translated(encoded, &self.get(seed).decoding, compression, true, scratch, out)
+    }
+}
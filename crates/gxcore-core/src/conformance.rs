@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+
+use crate::stream::{StreamDecoder, StreamEncoder, DEFAULT_CHUNK_SIZE};
+use crate::vectors::VECTORS;
+use crate::{decode, encode, CompressionAlgorithm};
+
+/// Version of the corpus layout, raised when fields change meaning
+pub const CORPUS_VERSION: u32 = 1;
+
+/// Compressions every binding accepts, by their HTTP API names
+const COMPRESSIONS: [CompressionAlgorithm; 3] = [CompressionAlgorithm::None, CompressionAlgorithm::Lz4, CompressionAlgorithm::Brotli];
+
+/// Input lengths around the base64 padding boundaries and the LZ4 minimum match
+const LENGTHS: [usize; 10] = [0, 1, 2, 3, 4, 5, 12, 13, 255, 4096];
+
+/// Call a case exercises, named as the bindings name theirs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Encode,
+    Decode,
+    /// Feed the whole input to a streaming encoder, then finish it
+    StreamEncode,
+    /// Feed the whole input to a streaming decoder, then finish it
+    StreamDecode,
+}
+
+/// What the Rust core produced for a case
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expected {
+    /// Exact output bytes, in hex
+    Output(#[serde(with = "hex::serde")] Vec<u8>),
+    /// The call must fail; the message is informative, other implementations may word it differently
+    Error(String),
+}
+
+/// One call and its expected result, with bytes in hex so any language can read it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Case {
+    pub name: String,
+    pub operation: Operation,
+    #[serde(with = "hex::serde")]
+    pub seed: Vec<u8>,
+    pub compression: CompressionAlgorithm,
+    #[serde(with = "hex::serde")]
+    pub input: Vec<u8>,
+    pub expected: Expected,
+}
+
+/// Cases every binding and port must reproduce byte for byte
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Corpus {
+    pub version: u32,
+    /// Input bytes per frame of the stream cases
+    pub chunk_size: usize,
+    pub cases: Vec<Case>,
+}
+
+impl Case {
+    fn new(name: String, operation: Operation, seed: &[u8], compression: CompressionAlgorithm, input: Vec<u8>) -> Self {
+        let mut case = Case {
+            name,
+            operation,
+            seed: seed.to_vec(),
+            compression,
+            input,
+            expected: Expected::Error(String::new()),
+        };
+        case.expected = match case.run() {
+            Ok(output) => Expected::Output(output),
+            Err(e) => Expected::Error(e),
+        };
+        case
+    }
+
+    /// Run the case against the Rust core, the reference implementation
+    pub fn run(&self) -> Result<Vec<u8>, String> {
+        match self.operation {
+            Operation::Encode => Ok(encode(&self.input, &self.seed, self.compression)),
+            Operation::Decode => decode(&self.input, &self.seed, self.compression).map_err(str::to_string),
+            Operation::StreamEncode => {
+                let mut encoder = StreamEncoder::new(&self.seed, self.compression);
+                let mut output = encoder.update(&self.input);
+                output.extend(encoder.finish());
+                Ok(output)
+            }
+            Operation::StreamDecode => {
+                let mut decoder = StreamDecoder::new(&self.seed, self.compression);
+                let mut output = decoder.update(&self.input)?;
+                output.extend(decoder.finish()?);
+                Ok(output)
+            }
+        }
+    }
+
+    /// Compare what an implementation returned with the expected result. Any error satisfies an
+    /// expected one.
+    pub fn check(&self, actual: &Result<Vec<u8>, String>) -> Result<(), String> {
+        match (&self.expected, actual) {
+            (Expected::Output(expected), Ok(output)) if output == expected => Ok(()),
+            (Expected::Output(_), Ok(_)) => Err(format!("{}: output differs", self.name)),
+            (Expected::Output(_), Err(e)) => Err(format!("{}: failed with {e}", self.name)),
+            (Expected::Error(_), Ok(_)) => Err(format!("{}: succeeded but should fail", self.name)),
+            (Expected::Error(_), Err(_)) => Ok(()),
+        }
+    }
+}
+
+impl Corpus {
+    /// Run every case through `run`, returning a description of each mismatch
+    pub fn verify(&self, mut run: impl FnMut(&Case) -> Result<Vec<u8>, String>) -> Vec<String> {
+        self.cases.iter().filter_map(|case| case.check(&run(case)).err()).collect()
+    }
+}
+
+/// Deterministic bytes of `len`, mixing text runs that compress with noise that does not, so
+/// corpora from different versions share their inputs
+fn pattern(len: usize, salt: u8) -> Vec<u8> {
+    let mut state = 0x9e37_79b9u32 ^ u32::from(salt);
+    (0..len)
+        .map(|i| {
+            if (i / 64) % 2 == 0 {
+                b"gxcore conformance "[i % 19]
+            } else {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            }
+        })
+        .collect()
+}
+
+/// Build the corpus from the golden vectors and generated inputs, with results from this build
+pub fn corpus() -> Corpus {
+    let seeds: [(&str, Vec<u8>); 4] = [
+        ("empty", Vec::new()),
+        ("text", b"seed".to_vec()),
+        ("bytes", (0..32).collect()),
+        ("long", vec![0xff; 100]),
+    ];
+    let mut cases = Vec::new();
+
+    for vector in VECTORS {
+        let name = format!("vector {} {}", vector.name, vector.compression.name());
+        cases.push(Case::new(format!("{name} encode"), Operation::Encode, vector.seed, vector.compression, vector.data.to_vec()));
+        cases.push(Case::new(format!("{name} decode"), Operation::Decode, vector.seed, vector.compression, vector.encoded.to_vec()));
+    }
+
+    for (seed_name, seed) in &seeds {
+        for compression in COMPRESSIONS {
+            for (salt, len) in LENGTHS.into_iter().enumerate() {
+                let name = format!("seed {seed_name} {} {len} bytes", compression.name());
+                let data = pattern(len, salt as u8);
+                let encoded = encode(&data, seed, compression);
+                cases.push(Case::new(format!("{name} encode"), Operation::Encode, seed, compression, data));
+                cases.push(Case::new(format!("{name} decode"), Operation::Decode, seed, compression, encoded));
+            }
+        }
+    }
+
+    // Failures: every implementation must reject these rather than return data
+    let encoded = encode(b"conformance", b"seed", CompressionAlgorithm::None);
+    cases.push(Case::new("decode wrong seed".to_string(), Operation::Decode, b"other", CompressionAlgorithm::None, encoded.clone()));
+    let mut corrupt = encoded.clone();
+    corrupt.swap(0, 1);
+    cases.push(Case::new("decode corrupt".to_string(), Operation::Decode, b"seed", CompressionAlgorithm::None, corrupt));
+    cases.push(Case::new("decode invalid character".to_string(), Operation::Decode, b"seed", CompressionAlgorithm::None, b"!!!!".to_vec()));
+    cases.push(Case::new("decode truncated".to_string(), Operation::Decode, b"seed", CompressionAlgorithm::None, encoded[..4].to_vec()));
+
+    for compression in COMPRESSIONS {
+        for (name, len) in [("empty", 0), ("one chunk", 1000), ("several chunks", DEFAULT_CHUNK_SIZE * 2 + 100)] {
+            let name = format!("stream {} {name}", compression.name());
+            let data = pattern(len, compression.id());
+            let mut encoder = StreamEncoder::new(b"seed", compression);
+            let mut encoded = encoder.update(&data);
+            encoded.extend(encoder.finish());
+            cases.push(Case::new(format!("{name} encode"), Operation::StreamEncode, b"seed", compression, data));
+            cases.push(Case::new(format!("{name} decode"), Operation::StreamDecode, b"seed", compression, encoded));
+        }
+    }
+    cases.push(Case::new("stream decode corrupt frame".to_string(), Operation::StreamDecode, b"seed", CompressionAlgorithm::None, b"!!!!\n".to_vec()));
+
+    Corpus {
+        version: CORPUS_VERSION,
+        chunk_size: DEFAULT_CHUNK_SIZE,
+        cases,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corpus_passes_against_core() {
+        let corpus = corpus();
+        assert!(corpus.verify(Case::run).is_empty());
+        for case in corpus.cases.iter().filter(|case| case.name.starts_with("decode ")) {
+            assert!(matches!(case.expected, Expected::Error(_)), "{}", case.name);
+        }
+        assert!(corpus.cases.iter().any(|case| case.operation == Operation::StreamDecode));
+    }
+
+    #[test]
+    fn test_corpus_json_roundtrip() {
+        let corpus = corpus();
+        let json = serde_json::to_string(&corpus).unwrap();
+        assert_eq!(serde_json::from_str::<Corpus>(&json).unwrap(), corpus);
+        let case: serde_json::Value = serde_json::from_str(&serde_json::to_string(&corpus.cases[0]).unwrap()).unwrap();
+        assert_eq!(case["operation"], "encode");
+        assert_eq!(case["compression"], "none");
+        assert_eq!(case["seed"], hex::encode(b"seed"));
+        assert!(case["expected"]["output"].is_string());
+    }
+
+    #[test]
+    fn test_verify_reports_mismatches() {
+        let corpus = corpus();
+        let failures = corpus.verify(|case| match case.operation {
+            Operation::Encode => Ok(Vec::new()),
+            _ => case.run(),
+        });
+        assert_eq!(failures.len(), corpus.cases.iter().filter(|case| case.operation == Operation::Encode).count());
+        assert!(failures[0].ends_with("output differs"));
+        assert!(!corpus.verify(|_| Err("unsupported".to_string())).is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+
+use crate::{decode, encode, CompressionAlgorithm};
+
+static DEFAULT_SEED: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Configure the seed used by `GxEncoded` columns with the default key.
+/// Returns an error if a seed was already configured.
+pub fn configure(seed: &[u8]) -> Result<(), &'static str> {
+    DEFAULT_SEED
+        .set(seed.to_vec())
+        .map_err(|_| "Column seed already configured")
+}
+
+/// Seed and compression used to encode a column
+pub trait ColumnKey {
+    fn seed() -> Option<&'static [u8]>;
+
+    fn compression() -> CompressionAlgorithm {
+        CompressionAlgorithm::None
+    }
+}
+
+/// Key backed by the process-wide seed set with `configure`
+pub struct DefaultKey;
+
+impl ColumnKey for DefaultKey {
+    fn seed() -> Option<&'static [u8]> {
+        DEFAULT_SEED.get().map(Vec::as_slice)
+    }
+}
+
+/// Values that can be stored in a `GxEncoded` column
+pub trait ColumnValue: Sized {
+    fn to_column_bytes(&self) -> Vec<u8>;
+    fn from_column_bytes(bytes: Vec<u8>) -> Result<Self, &'static str>;
+}
+
+impl ColumnValue for Vec<u8> {
+    fn to_column_bytes(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn from_column_bytes(bytes: Vec<u8>) -> Result<Self, &'static str> {
+        Ok(bytes)
+    }
+}
+
+impl ColumnValue for String {
+    fn to_column_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_column_bytes(bytes: Vec<u8>) -> Result<Self, &'static str> {
+        String::from_utf8(bytes).map_err(|_| "Invalid UTF-8")
+    }
+}
+
+/// Column value encoded with the key `K` when written and decoded on read
+#[cfg_attr(feature = "diesel", derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow))]
+#[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Text))]
+pub struct GxEncoded<T, K = DefaultKey>(pub T, PhantomData<K>);
+
+impl<T, K> GxEncoded<T, K> {
+    pub fn new(value: T) -> Self {
+        GxEncoded(value, PhantomData)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: ColumnValue, K: ColumnKey> GxEncoded<T, K> {
+    /// Encode the value as stored in the database
+    pub fn to_encoded(&self) -> Result<String, &'static str> {
+        let seed = K::seed().ok_or("Column seed not configured")?;
+        let encoded = encode(&self.0.to_column_bytes(), seed, K::compression());
+        // Encoded output only contains alphabet characters and padding
+        String::from_utf8(encoded).map_err(|_| "Invalid encoded output")
+    }
+
+    /// Decode a value as read from the database
+    pub fn from_encoded(encoded: &str) -> Result<Self, &'static str> {
+        let seed = K::seed().ok_or("Column seed not configured")?;
+        let bytes = decode(encoded.as_bytes(), seed, K::compression())?;
+        T::from_column_bytes(bytes).map(Self::new)
+    }
+}
+
+impl<T, K> From<T> for GxEncoded<T, K> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T, K> std::ops::Deref for GxEncoded<T, K> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Clone, K> Clone for GxEncoded<T, K> {
+    fn clone(&self) -> Self {
+        Self::new(self.0.clone())
+    }
+}
+
+impl<T: PartialEq, K> PartialEq for GxEncoded<T, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: fmt::Debug, K> fmt::Debug for GxEncoded<T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GxEncoded").field(&self.0).finish()
+    }
+}
+
+#[cfg(feature = "sqlx")]
+mod sqlx_impl {
+    use super::{ColumnKey, ColumnValue, GxEncoded};
+    use sqlx::encode::IsNull;
+    use sqlx::error::BoxDynError;
+    use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef, Postgres};
+    use sqlx::{Decode, Encode, Type};
+
+    impl<T, K> Type<Postgres> for GxEncoded<T, K> {
+        fn type_info() -> PgTypeInfo {
+            <String as Type<Postgres>>::type_info()
+        }
+
+        fn compatible(ty: &PgTypeInfo) -> bool {
+            <String as Type<Postgres>>::compatible(ty)
+        }
+    }
+
+    impl<T: ColumnValue, K: ColumnKey> Encode<'_, Postgres> for GxEncoded<T, K> {
+        fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+            let encoded = self.to_encoded()?;
+            <String as Encode<Postgres>>::encode_by_ref(&encoded, buf)
+        }
+    }
+
+    impl<'r, T: ColumnValue, K: ColumnKey> Decode<'r, Postgres> for GxEncoded<T, K> {
+        fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            let encoded = <&str as Decode<Postgres>>::decode(value)?;
+            Ok(Self::from_encoded(encoded)?)
+        }
+    }
+}
+
+#[cfg(feature = "diesel")]
+mod diesel_impl {
+    use super::{ColumnKey, ColumnValue, GxEncoded};
+    use diesel::deserialize::{self, FromSql};
+    use diesel::pg::{Pg, PgValue};
+    use diesel::serialize::{self, IsNull, Output, ToSql};
+    use diesel::sql_types::Text;
+    use std::fmt::Debug;
+    use std::io::Write;
+
+    impl<T: ColumnValue + Debug, K: ColumnKey> ToSql<Text, Pg> for GxEncoded<T, K> {
+        fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+            let encoded = self.to_encoded()?;
+            out.write_all(encoded.as_bytes())?;
+            Ok(IsNull::No)
+        }
+    }
+
+    impl<T: ColumnValue, K: ColumnKey> FromSql<Text, Pg> for GxEncoded<T, K> {
+        fn from_sql(value: PgValue<'_>) -> deserialize::Result<Self> {
+            let encoded = <String as FromSql<Text, Pg>>::from_sql(value)?;
+            Ok(Self::from_encoded(&encoded)?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestKey;
+
+    impl ColumnKey for TestKey {
+        fn seed() -> Option<&'static [u8]> {
+            Some(b"column_key")
+        }
+
+        fn compression() -> CompressionAlgorithm {
+            CompressionAlgorithm::Lz4
+        }
+    }
+
+    #[test]
+    fn test_column_roundtrip() {
+        let value: GxEncoded<String, TestKey> = GxEncoded::new("alice@example.com".to_string());
+        let stored = value.to_encoded().unwrap();
+        assert_ne!(stored, "alice@example.com");
+        let read = GxEncoded::<String, TestKey>::from_encoded(&stored).unwrap();
+        assert_eq!(read, value);
+    }
+
+    #[test]
+    fn test_column_wrong_type() {
+        let value: GxEncoded<Vec<u8>, TestKey> = GxEncoded::new(vec![0xff, 0xfe]);
+        let stored = value.to_encoded().unwrap();
+        assert!(GxEncoded::<String, TestKey>::from_encoded(&stored).is_err());
+    }
+}
@@ -0,0 +1,529 @@
+use std::collections::BTreeMap;
+
+use crc32fast::Hasher as Crc32Hasher;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{decode, decode_without_checksum, encode, encode_without_checksum, CompressionAlgorithm};
+
+const MAGIC: &[u8; 2] = b"GX";
+
+/// Current envelope format version
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Metadata entry naming the keyring key the payload was sealed with
+pub const KEY_ID_METADATA: &str = "key_id";
+
+/// Serialization used for an envelope
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvelopeFormat {
+    /// Compact hand-rolled byte layout starting with `GX`
+    Binary,
+    /// Self-describing CBOR map
+    Cbor,
+}
+
+/// Serialization of the data inside the payload
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadFormat {
+    /// Opaque bytes
+    #[default]
+    Raw,
+    /// A Rust value serialized with bincode
+    Bincode,
+}
+
+impl PayloadFormat {
+    pub fn id(self) -> u8 {
+        match self {
+            PayloadFormat::Raw => 0,
+            PayloadFormat::Bincode => 1,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self, &'static str> {
+        match id {
+            0 => Ok(PayloadFormat::Raw),
+            1 => Ok(PayloadFormat::Bincode),
+            _ => Err("Unknown payload format"),
+        }
+    }
+}
+
+/// How the envelope contents are protected
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegrityMode {
+    /// CRC32 of the payload, detects corruption only
+    #[default]
+    Crc32,
+    /// HMAC-SHA256 over header, metadata and payload, keyed by the seed
+    HmacSha256,
+    /// No checksum on the envelope nor trailer in the payload, for channels that already detect
+    /// corruption. Tampering and most corruption go unnoticed.
+    None,
+}
+
+impl IntegrityMode {
+    pub fn id(self) -> u8 {
+        match self {
+            IntegrityMode::Crc32 => 0,
+            IntegrityMode::HmacSha256 => 1,
+            IntegrityMode::None => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self, &'static str> {
+        match id {
+            0 => Ok(IntegrityMode::Crc32),
+            1 => Ok(IntegrityMode::HmacSha256),
+            2 => Ok(IntegrityMode::None),
+            _ => Err("Unknown integrity mode"),
+        }
+    }
+}
+
+/// Envelope fields readable without the seed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Header {
+    pub version: u8,
+    pub compression: CompressionAlgorithm,
+    #[serde(default)]
+    pub format: PayloadFormat,
+    #[serde(default)]
+    pub integrity: IntegrityMode,
+}
+
+/// Encoded payload wrapped with a header, metadata and checksum
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope {
+    pub header: Header,
+    pub metadata: BTreeMap<String, String>,
+    /// CRC32 of the encoded payload
+    pub checksum: u32,
+    /// HMAC-SHA256 tag, empty unless the integrity mode is `HmacSha256`
+    #[serde(default, with = "serde_bytes")]
+    pub mac: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    pub payload: Vec<u8>,
+}
+
+const MAC_LEN: usize = 32;
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32Hasher::new();
+    crc.update(data);
+    crc.finalize()
+}
+
+/// LZ4 is sealed in the frame format, which standard tools read
+fn sealed_compression(compression: CompressionAlgorithm) -> CompressionAlgorithm {
+    match compression {
+        CompressionAlgorithm::Lz4 => CompressionAlgorithm::Lz4Frame,
+        compression => compression,
+    }
+}
+
+/// Keep the MAC key distinct from the alphabet derived from the same seed
+fn mac_key(seed: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"gxcore-envelope-mac");
+    hasher.update(seed);
+    hasher.finalize().into()
+}
+
+impl Envelope {
+    /// Encode data and wrap it in an envelope. LZ4 is written in the frame format, which standard
+    /// tools read; envelopes of the block format from earlier versions still open.
+    pub fn seal(data: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Self {
+        let compression = sealed_compression(compression);
+        let payload = encode(data, seed, compression);
+        Envelope {
+            header: Header {
+                version: FORMAT_VERSION,
+                compression,
+                format: PayloadFormat::Raw,
+                integrity: IntegrityMode::Crc32,
+            },
+            metadata: BTreeMap::new(),
+            checksum: crc32(&payload),
+            mac: Vec::new(),
+            payload,
+        }
+    }
+
+    /// `seal` with `IntegrityMode::None`, saving the CRC32 passes and their 8 bytes for data carried
+    /// over channels that already detect corruption, such as QUIC or Kafka
+    pub fn seal_without_checksum(data: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Self {
+        let compression = sealed_compression(compression);
+        Envelope {
+            header: Header {
+                version: FORMAT_VERSION,
+                compression,
+                format: PayloadFormat::Raw,
+                integrity: IntegrityMode::None,
+            },
+            metadata: BTreeMap::new(),
+            checksum: 0,
+            mac: Vec::new(),
+            payload: encode_without_checksum(data, seed, compression),
+        }
+    }
+
+    /// `seal` with the compression `auto::choose` picks for `data`, which the header records
+    pub fn seal_auto(data: &[u8], seed: &[u8]) -> Self {
+        Envelope::seal(data, seed, crate::auto::choose(data))
+    }
+
+    pub fn with_format(mut self, format: PayloadFormat) -> Self {
+        self.header.format = format;
+        self
+    }
+
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Record the keyring key used, readable without the seed
+    pub fn with_key_id(self, key_id: &str) -> Self {
+        self.with_metadata(KEY_ID_METADATA, key_id)
+    }
+
+    pub fn key_id(&self) -> Option<&str> {
+        self.metadata.get(KEY_ID_METADATA).map(String::as_str)
+    }
+
+    /// Switch to HMAC integrity and tag the current contents.
+    /// Must be called after the metadata is final. Envelopes sealed without checksum are refused,
+    /// as their payload lacks the trailer HMAC envelopes are opened with.
+    pub fn authenticate(mut self, seed: &[u8]) -> Result<Self, &'static str> {
+        if self.header.integrity == IntegrityMode::None {
+            return Err("Envelope sealed without checksum cannot be authenticated");
+        }
+        self.header.integrity = IntegrityMode::HmacSha256;
+        self.mac = self.compute_mac(seed)?.finalize().into_bytes().to_vec();
+        Ok(self)
+    }
+
+    fn compute_mac(&self, seed: &[u8]) -> Result<Hmac<Sha256>, &'static str> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&mac_key(seed)).expect("HMAC accepts any key length");
+        let mut body = Vec::with_capacity(self.payload.len() + 16);
+        self.write_body(&mut body)?;
+        mac.update(&body);
+        Ok(mac)
+    }
+
+    /// Check the envelope checksum without the seed; envelopes without one always pass
+    pub fn verify_checksum(&self) -> bool {
+        self.header.integrity == IntegrityMode::None || crc32(&self.payload) == self.checksum
+    }
+
+    /// Check the HMAC tag; envelopes without HMAC integrity always pass
+    pub fn verify_mac(&self, seed: &[u8]) -> bool {
+        match self.header.integrity {
+            IntegrityMode::Crc32 | IntegrityMode::None => true,
+            IntegrityMode::HmacSha256 => self.compute_mac(seed).is_ok_and(|mac| mac.verify_slice(&self.mac).is_ok()),
+        }
+    }
+
+    /// Verify and decode the payload
+    pub fn open(&self, seed: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if !self.verify_checksum() {
+            return Err("Envelope checksum mismatch");
+        }
+        if !self.verify_mac(seed) {
+            return Err("Envelope authentication failed");
+        }
+        match self.header.integrity {
+            IntegrityMode::None => decode_without_checksum(&self.payload, seed, self.header.compression),
+            _ => decode(&self.payload, seed, self.header.compression),
+        }
+    }
+
+    pub fn serialize(&self, format: EnvelopeFormat) -> Result<Vec<u8>, &'static str> {
+        match format {
+            EnvelopeFormat::Binary => self.to_bytes(),
+            EnvelopeFormat::Cbor => Ok(self.to_cbor()),
+        }
+    }
+
+    pub fn deserialize(bytes: &[u8], format: EnvelopeFormat) -> Result<Self, &'static str> {
+        match format {
+            EnvelopeFormat::Binary => Self::from_bytes(bytes),
+            EnvelopeFormat::Cbor => Self::from_cbor(bytes),
+        }
+    }
+
+    /// Detect the serialization of an envelope
+    pub fn detect_format(bytes: &[u8]) -> Option<EnvelopeFormat> {
+        if bytes.starts_with(MAGIC) {
+            return Some(EnvelopeFormat::Binary);
+        }
+        // CBOR major type 5 (map)
+        match bytes.first() {
+            Some(b) if b >> 5 == 5 => Some(EnvelopeFormat::Cbor),
+            _ => None,
+        }
+    }
+
+    /// Version byte of a binary envelope, readable even when the version is unsupported
+    pub fn peek_version(bytes: &[u8]) -> Option<u8> {
+        bytes.strip_prefix(MAGIC)?.first().copied()
+    }
+
+    /// Parse an envelope in either serialization
+    pub fn parse(bytes: &[u8]) -> Result<Self, &'static str> {
+        let format = Self::detect_format(bytes).ok_or("Unknown envelope format")?;
+        Self::deserialize(bytes, format)
+    }
+
+    /// Serialize with the binary layout:
+    /// magic, version, compression, payload format, integrity mode, metadata count (u16),
+    /// entries, payload length (u32), payload, MAC (HMAC mode only), checksum (absent in mode none).
+    /// Fails when a count or length does not fit its field.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, &'static str> {
+        let mut out = Vec::with_capacity(self.payload.len() + 16 + self.mac.len());
+        self.write_body(&mut out)?;
+        out.extend_from_slice(&self.mac);
+        if self.header.integrity != IntegrityMode::None {
+            out.extend_from_slice(&self.checksum.to_le_bytes());
+        }
+        Ok(out)
+    }
+
+    /// Everything up to the payload, which is also the input of the MAC
+    fn write_body(&self, out: &mut Vec<u8>) -> Result<(), &'static str> {
+        out.extend_from_slice(MAGIC);
+        out.push(self.header.version);
+        out.push(self.header.compression.id());
+        out.push(self.header.format.id());
+        out.push(self.header.integrity.id());
+        let count = u16::try_from(self.metadata.len()).map_err(|_| "Too many envelope metadata entries")?;
+        out.extend_from_slice(&count.to_le_bytes());
+        for (key, value) in &self.metadata {
+            for field in [key, value] {
+                let len = u16::try_from(field.len()).map_err(|_| "Envelope metadata field too long")?;
+                out.extend_from_slice(&len.to_le_bytes());
+                out.extend_from_slice(field.as_bytes());
+            }
+        }
+        let len = u32::try_from(self.payload.len()).map_err(|_| "Envelope payload too large")?;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&self.payload);
+        Ok(())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = Reader { bytes };
+        if reader.take(2)? != MAGIC {
+            return Err("Invalid envelope magic");
+        }
+        let version = reader.u8()?;
+        if version != FORMAT_VERSION {
+            return Err("Unsupported envelope version");
+        }
+        let compression = CompressionAlgorithm::from_id(reader.u8()?)?;
+        let format = PayloadFormat::from_id(reader.u8()?)?;
+        let integrity = IntegrityMode::from_id(reader.u8()?)?;
+
+        let mut metadata = BTreeMap::new();
+        for _ in 0..reader.u16()? {
+            let key = reader.string()?;
+            let value = reader.string()?;
+            metadata.insert(key, value);
+        }
+
+        let payload_len = reader.u32()? as usize;
+        let payload = reader.take(payload_len)?.to_vec();
+        let mac = match integrity {
+            IntegrityMode::Crc32 | IntegrityMode::None => Vec::new(),
+            IntegrityMode::HmacSha256 => reader.take(MAC_LEN)?.to_vec(),
+        };
+        let checksum = match integrity {
+            IntegrityMode::None => 0,
+            _ => reader.u32()?,
+        };
+        if !reader.bytes.is_empty() {
+            return Err("Trailing envelope data");
+        }
+
+        Ok(Envelope {
+            header: Header {
+                version,
+                compression,
+                format,
+                integrity,
+            },
+            metadata,
+            checksum,
+            mac,
+            payload,
+        })
+    }
+
+    /// Serialize as a CBOR map with `header`, `metadata`, `checksum`, `mac` and `payload` fields
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.payload.len() + 64);
+        // Writing into a Vec cannot fail
+        ciborium::into_writer(self, &mut out).expect("CBOR serialization failed");
+        out
+    }
+
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, &'static str> {
+        let envelope: Envelope = ciborium::from_reader(bytes).map_err(|_| "Invalid CBOR envelope")?;
+        if envelope.header.version != FORMAT_VERSION {
+            return Err("Unsupported envelope version");
+        }
+        Ok(envelope)
+    }
+}
+
+/// Cursor over the binary envelope layout
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], &'static str> {
+        if self.bytes.len() < len {
+            return Err("Envelope truncated");
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, &'static str> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, &'static str> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, &'static str> {
+        let len = self.u16()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| "Invalid metadata string")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let envelope = Envelope::seal(b"envelope data", b"seed", CompressionAlgorithm::Lz4)
+            .with_metadata("content-type", "text/plain");
+        let bytes = envelope.to_bytes().unwrap();
+        assert_eq!(Envelope::detect_format(&bytes), Some(EnvelopeFormat::Binary));
+        let parsed = Envelope::parse(&bytes).unwrap();
+        assert_eq!(parsed, envelope);
+        assert_eq!(parsed.open(b"seed").unwrap(), b"envelope data");
+    }
+
+    #[test]
+    fn test_cbor_roundtrip() {
+        let envelope = Envelope::seal(b"envelope data", b"seed", CompressionAlgorithm::None)
+            .with_metadata("origin", "test");
+        let bytes = envelope.to_cbor();
+        assert_eq!(Envelope::detect_format(&bytes), Some(EnvelopeFormat::Cbor));
+        let parsed = Envelope::parse(&bytes).unwrap();
+        assert_eq!(parsed, envelope);
+        assert_eq!(parsed.open(b"seed").unwrap(), b"envelope data");
+    }
+
+    #[test]
+    fn test_hmac_envelope() {
+        let envelope = Envelope::seal(b"envelope data", b"seed", CompressionAlgorithm::None)
+            .with_metadata("role", "user")
+            .authenticate(b"seed")
+            .unwrap();
+        assert_eq!(envelope.header.integrity, IntegrityMode::HmacSha256);
+
+        let parsed = Envelope::from_bytes(&envelope.to_bytes().unwrap()).unwrap();
+        assert_eq!(parsed.open(b"seed").unwrap(), b"envelope data");
+        assert_eq!(parsed.open(b"other"), Err("Envelope authentication failed"));
+
+        // Metadata is covered by the MAC
+        let forged = parsed.with_metadata("role", "admin");
+        assert_eq!(forged.open(b"seed"), Err("Envelope authentication failed"));
+    }
+
+    #[test]
+    fn test_lz4_sealed_as_frames_and_blocks_still_open() {
+        let envelope = Envelope::seal(b"envelope data", b"seed", CompressionAlgorithm::Lz4);
+        assert_eq!(envelope.header.compression, CompressionAlgorithm::Lz4Frame);
+        assert_eq!(Envelope::parse(&envelope.to_bytes().unwrap()).unwrap().open(b"seed").unwrap(), b"envelope data");
+
+        // Envelopes of earlier versions hold LZ4 blocks under compression id 2
+        let mut old = envelope.clone();
+        old.header.compression = CompressionAlgorithm::Lz4;
+        old.payload = encode(b"envelope data", b"seed", CompressionAlgorithm::Lz4);
+        old.checksum = crc32(&old.payload);
+        let bytes = old.to_bytes().unwrap();
+        assert_eq!(bytes[3], 2);
+        assert_eq!(Envelope::parse(&bytes).unwrap().open(b"seed").unwrap(), b"envelope data");
+
+        let data = crate::bench::sample_data(100_000);
+        let envelope = Envelope::seal_auto(&data, b"seed");
+        assert_eq!(envelope.header.compression, CompressionAlgorithm::Lz4Frame);
+        assert_eq!(envelope.open(b"seed").unwrap(), data);
+        assert_eq!(Envelope::seal_auto(b"short", b"seed").header.compression, CompressionAlgorithm::None);
+    }
+
+    #[test]
+    fn test_envelope_without_checksum() {
+        let data = b"already checksummed by the transport";
+        let checked = Envelope::seal(data, b"seed", CompressionAlgorithm::None);
+        let envelope = Envelope::seal_without_checksum(data, b"seed", CompressionAlgorithm::Lz4);
+        assert_eq!(envelope.header.integrity, IntegrityMode::None);
+        assert_eq!(envelope.header.compression, CompressionAlgorithm::Lz4Frame);
+
+        let bytes = envelope.to_bytes().unwrap();
+        assert_eq!(bytes[5], 2);
+        let unchecked = Envelope::seal_without_checksum(data, b"seed", CompressionAlgorithm::None).to_bytes().unwrap();
+        // The payload loses its trailer, which base64 spreads over 8 characters here, and the envelope its checksum
+        assert_eq!(checked.to_bytes().unwrap().len() - unchecked.len(), 8 + 4);
+        for parsed in [Envelope::parse(&bytes).unwrap(), Envelope::parse(&envelope.to_cbor()).unwrap()] {
+            assert!(parsed.verify_checksum());
+            assert_eq!(parsed.open(b"seed").unwrap(), data);
+        }
+        assert!(Envelope::from_bytes(&[&bytes[..], &[0; 4]].concat()).is_err());
+        assert_eq!(
+            envelope.authenticate(b"seed").err(),
+            Some("Envelope sealed without checksum cannot be authenticated")
+        );
+    }
+
+    #[test]
+    fn test_tampered_envelope() {
+        let mut envelope = Envelope::seal(b"envelope data", b"seed", CompressionAlgorithm::None);
+        envelope.payload[0] ^= 1;
+        assert!(!envelope.verify_checksum());
+        assert_eq!(envelope.open(b"seed"), Err("Envelope checksum mismatch"));
+
+        let bytes = envelope.to_bytes().unwrap();
+        assert!(Envelope::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+    #[test]
+    fn test_oversized_fields_are_refused() {
+        let long = "x".repeat(usize::from(u16::MAX) + 1);
+        let envelope = Envelope::seal(b"data", b"seed", CompressionAlgorithm::None).with_metadata("note", &long);
+        assert_eq!(envelope.to_bytes(), Err("Envelope metadata field too long"));
+        assert_eq!(envelope.authenticate(b"seed").err(), Some("Envelope metadata field too long"));
+
+        let mut envelope = Envelope::seal(b"data", b"seed", CompressionAlgorithm::None);
+        for i in 0..=u16::MAX as usize {
+            envelope.metadata.insert(i.to_string(), String::new());
+        }
+        assert_eq!(envelope.to_bytes(), Err("Too many envelope metadata entries"));
+    }
+}
@@ -0,0 +1,115 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::stream::{decode_stream, encode_chunks_pipelined, encode_stream_pipelined, StreamDecoder, DEFAULT_CHUNK_SIZE};
+use crate::CompressionAlgorithm;
+
+/// Encoded bytes handed to the decoder at once, bounding the decoded output held in memory
+const DECODE_WINDOW: usize = 16 * DEFAULT_CHUNK_SIZE;
+
+/// `file` mapped read-only, or `None` when it cannot be, such as for pipes
+#[cfg(any(unix, windows))]
+fn map(file: &File) -> Option<memmap2::Mmap> {
+    // SAFETY: the map is only read. As with buffered reads, the caller must not modify the file
+    // while it is processed; truncating it would fault on access.
+    let map = unsafe { memmap2::Mmap::map(file) }.ok()?;
+    #[cfg(unix)]
+    let _ = map.advise(memmap2::Advice::Sequential);
+    Some(map)
+}
+
+/// Targets without memory mapping always read through a buffer
+#[cfg(not(any(unix, windows)))]
+fn map(_file: &File) -> Option<&'static [u8]> {
+    None
+}
+
+/// Open `input`, then create `output`, refusing to truncate the file being read
+fn open(input: &Path, output: &Path) -> io::Result<File> {
+    let file = File::open(input)?;
+    if fs::canonicalize(output).is_ok_and(|output| fs::canonicalize(input).is_ok_and(|input| input == output)) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "input and output are the same file"));
+    }
+    Ok(file)
+}
+
+/// Encode the file at `input` into newline-delimited frames at `output`, as `encode_stream` does,
+/// returning the number of input bytes. The input is memory-mapped and encoded in place where the
+/// platform allows, and read through a buffer otherwise. Compression runs a chunk ahead of encoding
+/// on a worker thread, as in `encode_stream_pipelined`. With the `uring` feature on Linux, files
+/// are read and written through io_uring instead, as `uring::encode` does, where the kernel allows.
+pub fn encode_file(input: &Path, output: &Path, seed: &[u8], compression: CompressionAlgorithm) -> io::Result<u64> {
+    let file = open(input, output)?;
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    if crate::uring::available() {
+        return crate::uring::encode(&file, 0, &File::create(output)?, 0, seed, compression, |_| {});
+    }
+    if let Some(map) = map(&file) {
+        let writer = BufWriter::new(File::create(output)?);
+        // Whole chunks go straight from the map into frames without being buffered
+        return encode_chunks_pipelined(map.chunks(DEFAULT_CHUNK_SIZE).map(Ok), writer, seed, compression);
+    }
+    encode_stream_pipelined(file, BufWriter::new(File::create(output)?), seed, compression)
+}
+
+/// Decode the frames in the file at `input` into `output`, as `decode_stream` does, returning the
+/// number of output bytes. The input is memory-mapped where the platform allows, or read through
+/// io_uring as `encode_file` describes.
+pub fn decode_file(input: &Path, output: &Path, seed: &[u8], compression: CompressionAlgorithm) -> io::Result<u64> {
+    let file = open(input, output)?;
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    if crate::uring::available() {
+        return crate::uring::decode(&file, 0, &File::create(output)?, 0, seed, compression, |_| {});
+    }
+    if let Some(map) = map(&file) {
+        let mut writer = BufWriter::new(File::create(output)?);
+        let mut decoder = StreamDecoder::new(seed, compression);
+        let mut total = 0u64;
+        // Frames within a window decode from the map; only those crossing its end are copied
+        for window in map.chunks(DECODE_WINDOW) {
+            let out = decoder.update(window).map_err(io::Error::other)?;
+            total += out.len() as u64;
+            writer.write_all(&out)?;
+        }
+        let out = decoder.finish().map_err(io::Error::other)?;
+        total += out.len() as u64;
+        writer.write_all(&out)?;
+        writer.flush()?;
+        return Ok(total);
+    }
+    decode_stream(BufReader::new(file), BufWriter::new(File::create(output)?), seed, compression)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::sample_data;
+    use crate::stream::encode_stream;
+
+    #[test]
+    fn test_file_roundtrip_matches_stream() {
+        let dir = std::env::temp_dir().join(format!("gx-core-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (plain, encoded, decoded) = (dir.join("plain"), dir.join("encoded"), dir.join("decoded"));
+
+        for len in [0, 10, DECODE_WINDOW * 2 + 12_345] {
+            let data = sample_data(len);
+            fs::write(&plain, &data).unwrap();
+            assert_eq!(encode_file(&plain, &encoded, b"seed", CompressionAlgorithm::Lz4).unwrap(), len as u64);
+            let mut expected = Vec::new();
+            encode_stream(data.as_slice(), &mut expected, b"seed", CompressionAlgorithm::Lz4).unwrap();
+            assert_eq!(fs::read(&encoded).unwrap(), expected);
+
+            assert_eq!(decode_file(&encoded, &decoded, b"seed", CompressionAlgorithm::Lz4).unwrap(), len as u64);
+            assert_eq!(fs::read(&decoded).unwrap(), data);
+        }
+
+        let error = decode_file(&encoded, &decoded, b"wrong", CompressionAlgorithm::Lz4).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Other);
+        let error = encode_file(&plain, &plain, b"seed", CompressionAlgorithm::None).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(fs::read(&plain).unwrap().len(), DECODE_WINDOW * 2 + 12_345);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
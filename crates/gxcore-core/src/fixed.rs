@@ -0,0 +1,198 @@
+use std::ops::Deref;
+
+use crate::derive_alphabet;
+
+const CHECKSUM_LEN: usize = 4;
+
+/// Largest input of `encode_small`, covering typical Solana memos
+pub const SMALL_MAX: usize = 192;
+/// Marks bytes outside the alphabet in the reverse lookup table
+const INVALID: u8 = 0xff;
+
+/// Length of `encode_to_slice` output for `data_len` bytes, usable to size arrays at compile time
+pub const fn encoded_len(data_len: usize) -> usize {
+    (data_len + CHECKSUM_LEN).div_ceil(3) * 4
+}
+
+/// Upper bound of the data length held in `encoded_len` bytes of input, exact when it has no padding
+pub const fn max_decoded_len(encoded_len: usize) -> usize {
+    (encoded_len / 4 * 3).saturating_sub(CHECKSUM_LEN)
+}
+
+/// Encode without compression into `out`, returning the bytes written, which match `encode`
+/// with `CompressionAlgorithm::None`. Neither this nor `decode_to_slice` allocates, for firmware
+/// without a heap.
+pub fn encode_to_slice(data: &[u8], seed: &[u8], out: &mut [u8]) -> Result<usize, &'static str> {
+    let len = encoded_len(data.len());
+    let out = out.get_mut(..len).ok_or("Output buffer too small")?;
+    let alphabet = derive_alphabet(seed);
+    let checksum = crc32fast::hash(data).to_le_bytes();
+    let mut bytes = data.iter().chain(&checksum).copied();
+    for group in out.chunks_exact_mut(4) {
+        let taken = [bytes.next(), bytes.next(), bytes.next()];
+        let word = taken.iter().fold(0u32, |word, byte| word << 8 | u32::from(byte.unwrap_or(0)));
+        for (i, slot) in group.iter_mut().enumerate() {
+            // A group of n bytes fills n + 1 characters, the rest is padding
+            *slot = if i == 0 || taken[i - 1].is_some() { alphabet[(word >> (18 - 6 * i) & 0x3f) as usize] } else { b'=' };
+        }
+    }
+    Ok(len)
+}
+
+/// Decode output of `encode_to_slice`, or of `encode` without compression, into `out`,
+/// returning the data length
+pub fn decode_to_slice(encoded: &[u8], seed: &[u8], out: &mut [u8]) -> Result<usize, &'static str> {
+    if encoded.is_empty() || !encoded.len().is_multiple_of(4) {
+        return Err("Invalid base64");
+    }
+    let padding = encoded.iter().rev().take(2).take_while(|&&b| b == b'=').count();
+    let total = encoded.len() / 4 * 3 - padding;
+    let data_len = total.checked_sub(CHECKSUM_LEN).ok_or("Data too short")?;
+    let out = out.get_mut(..data_len).ok_or("Output buffer too small")?;
+
+    let mut lookup = [INVALID; 256];
+    for (value, &c) in derive_alphabet(seed).iter().enumerate() {
+        lookup[c as usize] = value as u8;
+    }
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    let mut at = 0;
+    let groups = encoded.len() / 4;
+    for (index, group) in encoded.chunks_exact(4).enumerate() {
+        let last = index + 1 == groups;
+        let pad = if last { padding } else { 0 };
+        let mut word = 0u32;
+        for (i, &c) in group.iter().enumerate() {
+            let value = match (i >= 4 - pad, lookup[c as usize]) {
+                (true, _) => 0,
+                (false, INVALID) if c == b'=' => return Err("Invalid base64"),
+                (false, INVALID) => return Err("Invalid character"),
+                (false, value) => value,
+            };
+            word = word << 6 | u32::from(value);
+        }
+        // Bits past the last byte must be zero, as the base64 decoder behind `decode` requires
+        if word & ((1 << (8 * pad)) - 1) != 0 {
+            return Err("Invalid base64");
+        }
+        for byte in word.to_be_bytes()[1..4 - pad].iter() {
+            match out.get_mut(at) {
+                Some(slot) => *slot = *byte,
+                None => checksum[at - data_len] = *byte,
+            }
+            at += 1;
+        }
+    }
+    if crc32fast::hash(out) != u32::from_le_bytes(checksum) {
+        return Err("Checksum mismatch");
+    }
+    Ok(data_len)
+}
+
+/// `encode_to_slice` into an array sized by the caller, e.g. with `encoded_len`
+pub fn encode_to_array<const N: usize>(data: &[u8], seed: &[u8]) -> Result<([u8; N], usize), &'static str> {
+    let mut out = [0u8; N];
+    let len = encode_to_slice(data, seed, &mut out)?;
+    Ok((out, len))
+}
+
+/// Up to `N` bytes held inline, so small results live on the stack rather than the heap
+#[derive(Clone, Copy)]
+pub struct Inline<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Deref for Inline<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for Inline<N> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<const N: usize> std::fmt::Debug for Inline<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<const N: usize> PartialEq for Inline<N> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<const N: usize> Eq for Inline<N> {}
+
+/// `encode` without compression for inputs up to `SMALL_MAX` bytes, returned inline without
+/// allocating
+pub fn encode_small(data: &[u8], seed: &[u8]) -> Result<Inline<{ encoded_len(SMALL_MAX) }>, &'static str> {
+    if data.len() > SMALL_MAX {
+        return Err("Data too long");
+    }
+    let mut bytes = [0u8; encoded_len(SMALL_MAX)];
+    let len = encode_to_slice(data, seed, &mut bytes)?;
+    Ok(Inline { bytes, len })
+}
+
+/// Decode output of `encode_small`, or of `encode` without compression, returned inline
+pub fn decode_small(encoded: &[u8], seed: &[u8]) -> Result<Inline<SMALL_MAX>, &'static str> {
+    let mut bytes = [0u8; SMALL_MAX];
+    let len = decode_to_slice(encoded, seed, &mut bytes)?;
+    Ok(Inline { bytes, len })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode, CompressionAlgorithm};
+
+    #[test]
+    fn test_matches_allocating_codec() {
+        let data: Vec<u8> = (0..40u8).map(|i| i.wrapping_mul(37)).collect();
+        for len in 0..data.len() {
+            let mut out = [0u8; encoded_len(40)];
+            let written = encode_to_slice(&data[..len], b"firmware", &mut out).unwrap();
+            assert_eq!(&out[..written], encode(&data[..len], b"firmware", CompressionAlgorithm::None));
+
+            let mut back = [0u8; 40];
+            let decoded = decode_to_slice(&out[..written], b"firmware", &mut back).unwrap();
+            assert_eq!(&back[..decoded], &data[..len]);
+            assert!(max_decoded_len(written) >= len);
+        }
+    }
+
+    #[test]
+    fn test_rejects_what_decode_rejects() {
+        let (encoded, len) = encode_to_array::<{ encoded_len(5) }>(b"meter", b"seed").unwrap();
+        assert_eq!(len, encoded.len());
+        let mut out = [0u8; 5];
+        assert_eq!(decode_to_slice(&encoded, b"other", &mut out).is_err(), decode(&encoded, b"other", CompressionAlgorithm::None).is_err());
+        assert_eq!(decode_to_slice(&encoded, b"seed", &mut [0u8; 4]), Err("Output buffer too small"));
+        assert_eq!(encode_to_slice(b"meter", b"seed", &mut [0u8; 8]), Err("Output buffer too small"));
+        let mut corrupt = encoded;
+        corrupt[2] = if corrupt[2] == encoded[3] { encoded[4] } else { encoded[3] };
+        assert!(decode_to_slice(&corrupt, b"seed", &mut out).is_err());
+        assert!(decode_to_slice(b"abc", b"seed", &mut out).is_err());
+    }
+
+    #[test]
+    fn test_small_payloads_inline() {
+        let memo = [b'm'; SMALL_MAX];
+        for len in [0, 1, 100, SMALL_MAX] {
+            let encoded = encode_small(&memo[..len], b"memo").unwrap();
+            assert_eq!(*encoded, encode(&memo[..len], b"memo", CompressionAlgorithm::None));
+            assert_eq!(*decode_small(&encoded, b"memo").unwrap(), memo[..len]);
+        }
+        assert_eq!(encode_small(&[0; SMALL_MAX + 1], b"memo").unwrap_err(), "Data too long");
+        let large = encode(&[0; SMALL_MAX + 1], b"memo", CompressionAlgorithm::None);
+        assert_eq!(decode_small(&large, b"memo").unwrap_err(), "Output buffer too small");
+        assert!(decode_small(&encode_small(b"memo", b"memo").unwrap(), b"other").is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+#[cfg(feature = "schema")]
+use utoipa::ToSchema;
+
+use crate::envelope::{Envelope, EnvelopeFormat, IntegrityMode, PayloadFormat};
+
+const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const CHECKSUM_LEN: usize = 4;
+
+/// Byte counts of the blob and what its payload should hold
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema))]
+pub struct Sizes {
+    pub total: usize,
+    /// Alphabet-encoded payload, the whole blob unless it parsed as an envelope
+    pub payload: usize,
+    /// Bytes under the alphabet encoding without the trailing CRC32; the compressed size
+    /// when compression is used. Absent when the payload length is not a multiple of 4.
+    pub inner: Option<usize>,
+}
+
+/// What can be told about a blob without its seed
+#[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema))]
+#[cfg_attr(feature = "schema", schema(as = InspectResponse))]
+pub struct Inspection {
+    /// `binary` or `cbor` for envelopes, `bare` for output of the encode endpoints
+    pub kind: &'static str,
+    /// Why the envelope could not be parsed; fields read before the failure are still set
+    pub error: Option<&'static str>,
+    pub version: Option<u8>,
+    pub compression: Option<&'static str>,
+    /// Whether the compression actually compresses; placeholders pass data through unchanged
+    pub compression_implemented: Option<bool>,
+    pub payload_format: Option<&'static str>,
+    pub integrity: Option<&'static str>,
+    pub key_id: Option<String>,
+    pub metadata: BTreeMap<String, String>,
+    /// Envelope CRC32 over the encoded payload, absent when sealed without one
+    pub checksum_valid: Option<bool>,
+    /// Characters no seed's alphabet contains, a sign of corruption or a foreign format
+    pub foreign_characters: usize,
+    pub sizes: Sizes,
+}
+
+fn payload_format_name(format: PayloadFormat) -> &'static str {
+    match format {
+        PayloadFormat::Raw => "raw",
+        PayloadFormat::Bincode => "bincode",
+    }
+}
+
+fn integrity_name(integrity: IntegrityMode) -> &'static str {
+    match integrity {
+        IntegrityMode::Crc32 => "crc32",
+        IntegrityMode::HmacSha256 => "hmac-sha256",
+        IntegrityMode::None => "none",
+    }
+}
+
+/// Every alphabet is a permutation of the base64 characters, so the shape is seed independent.
+/// `trailer` tells whether the payload ends in a checksum.
+fn payload_shape(payload: &[u8], trailer: bool) -> (usize, Option<usize>) {
+    let foreign = payload.iter().filter(|&&b| b != b'=' && !BASE64_CHARS.contains(&b)).count();
+    let inner = payload.len().is_multiple_of(4).then(|| {
+        let padding = payload.iter().rev().take(2).take_while(|&&b| b == b'=').count();
+        let trailer = if trailer { CHECKSUM_LEN } else { 0 };
+        (payload.len() / 4 * 3 - padding).saturating_sub(trailer)
+    });
+    (foreign, inner)
+}
+
+/// Describe an envelope or encoded payload without the seed
+pub fn inspect(blob: &[u8]) -> Inspection {
+    let (kind, parsed) = match Envelope::detect_format(blob) {
+        Some(EnvelopeFormat::Binary) => ("binary", Some(Envelope::from_bytes(blob))),
+        Some(EnvelopeFormat::Cbor) => ("cbor", Some(Envelope::from_cbor(blob))),
+        None => ("bare", None),
+    };
+    let mut response = Inspection {
+        kind,
+        error: None,
+        version: Envelope::peek_version(blob),
+        compression: None,
+        compression_implemented: None,
+        payload_format: None,
+        integrity: None,
+        key_id: None,
+        metadata: BTreeMap::new(),
+        checksum_valid: None,
+        foreign_characters: 0,
+        sizes: Sizes {
+            total: blob.len(),
+            payload: blob.len(),
+            inner: None,
+        },
+    };
+
+    let mut trailer = true;
+    let payload = match parsed {
+        None => blob,
+        Some(Err(e)) => {
+            response.error = Some(e);
+            blob
+        }
+        Some(Ok(ref envelope)) => {
+            let header = envelope.header;
+            response.version = Some(header.version);
+            response.compression = Some(header.compression.name());
+            response.compression_implemented = Some(header.compression.is_implemented());
+            response.payload_format = Some(payload_format_name(header.format));
+            response.integrity = Some(integrity_name(header.integrity));
+            response.key_id = envelope.key_id().map(str::to_string);
+            response.metadata = envelope.metadata.clone();
+            trailer = header.integrity != IntegrityMode::None;
+            response.checksum_valid = trailer.then(|| envelope.verify_checksum());
+            &envelope.payload
+        }
+    };
+    let (foreign, inner) = payload_shape(payload, trailer);
+    response.foreign_characters = foreign;
+    response.sizes.payload = payload.len();
+    response.sizes.inner = inner;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode, CompressionAlgorithm};
+
+    #[test]
+    fn test_inspect_envelope() {
+        let envelope = Envelope::seal(b"support ticket", b"seed", CompressionAlgorithm::Lz4)
+            .with_key_id("billing")
+            .authenticate(b"seed")
+            .unwrap();
+        let report = inspect(&envelope.to_bytes().unwrap());
+        assert_eq!(report.kind, "binary");
+        assert_eq!(report.version, Some(1));
+        assert_eq!(report.compression, Some("lz4frame"));
+        assert_eq!(report.integrity, Some("hmac-sha256"));
+        assert_eq!(report.key_id.as_deref(), Some("billing"));
+        assert_eq!(report.checksum_valid, Some(true));
+        assert_eq!(report.foreign_characters, 0);
+
+        let mut future = envelope.to_bytes().unwrap();
+        future[2] = 9;
+        let report = inspect(&future);
+        assert_eq!(report.error, Some("Unsupported envelope version"));
+        assert_eq!(report.version, Some(9));
+
+        let envelope = Envelope::seal_without_checksum(b"abc", b"seed", CompressionAlgorithm::None);
+        let report = inspect(&envelope.to_bytes().unwrap());
+        assert_eq!(report.integrity, Some("none"));
+        assert_eq!(report.checksum_valid, None);
+        assert_eq!(report.sizes.inner, Some(3));
+    }
+
+    #[test]
+    fn test_inspect_bare() {
+        let encoded = encode(b"abc", b"seed", CompressionAlgorithm::None);
+        let report = inspect(&encoded);
+        assert_eq!(report.kind, "bare");
+        assert_eq!(report.sizes.inner, Some(3));
+
+        let report = inspect(b"not~encoded");
+        assert_eq!(report.foreign_characters, 1);
+        assert_eq!(report.sizes.inner, None);
+    }
+}
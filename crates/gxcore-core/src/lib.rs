@@ -0,0 +1,479 @@
+use std::io::{Read, Write};
+
+use base64::{Engine as _, engine::general_purpose};
+use crc32fast::Hasher as Crc32Hasher;
+use sha2::{Digest, Sha256};
+use lz4::block::{compress_bound, compress_to_buffer, decompress_to_buffer};
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::{Operation, Stage};
+use crate::translate::Translator;
+
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionAlgorithm {
+    #[default]
+    None,
+    Huffman, // Placeholder for future implementation
+    Lz4,
+    Brotli,
+    /// LZ4 frame format with a content checksum, readable by the `lz4` tool once decoded
+    Lz4Frame,
+}
+
+impl CompressionAlgorithm {
+    /// Identifier stored in envelope headers
+    pub fn id(self) -> u8 {
+        match self {
+            CompressionAlgorithm::None => 0,
+            CompressionAlgorithm::Huffman => 1,
+            CompressionAlgorithm::Lz4 => 2,
+            CompressionAlgorithm::Brotli => 3,
+            CompressionAlgorithm::Lz4Frame => 4,
+        }
+    }
+
+    /// Name used by the HTTP API and serialized formats
+    pub fn name(self) -> &'static str {
+        match self {
+            CompressionAlgorithm::None => "none",
+            CompressionAlgorithm::Huffman => "huffman",
+            CompressionAlgorithm::Lz4 => "lz4",
+            CompressionAlgorithm::Brotli => "brotli",
+            CompressionAlgorithm::Lz4Frame => "lz4frame",
+        }
+    }
+
+    /// Whether the algorithm actually compresses; placeholders pass data through unchanged
+    pub fn is_implemented(self) -> bool {
+        matches!(self, CompressionAlgorithm::None | CompressionAlgorithm::Lz4 | CompressionAlgorithm::Lz4Frame)
+    }
+
+    pub fn from_id(id: u8) -> Result<Self, &'static str> {
+        match id {
+            0 => Ok(CompressionAlgorithm::None),
+            1 => Ok(CompressionAlgorithm::Huffman),
+            2 => Ok(CompressionAlgorithm::Lz4),
+            3 => Ok(CompressionAlgorithm::Brotli),
+            4 => Ok(CompressionAlgorithm::Lz4Frame),
+            _ => Err("Unknown compression algorithm"),
+        }
+    }
+}
+
+pub(crate) const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Derive a permuted alphabet from a seed using SHA-256
+pub fn derive_alphabet(seed: &[u8]) -> [u8; 64] {
+    let mut hasher = Sha256::new();
+    hasher.update(seed);
+    let hash = hasher.finalize();
+
+    let mut alphabet = *BASE64_ALPHABET;
+    // Simple deterministic permutation using hash
+    for i in 0..64 {
+        let swap_idx = (hash[i % 32] as usize + i) % 64;
+        alphabet.swap(i, swap_idx);
+    }
+    alphabet
+}
+
+/// Append one LZ4 frame with a content checksum to `out`
+fn compress_frame(data: &[u8], out: &mut Vec<u8>) {
+    let mut encoder = lz4::EncoderBuilder::new()
+        .checksum(lz4::ContentChecksum::ChecksumEnabled)
+        .build(out)
+        .expect("LZ4 frame encoder");
+    // Writing into a Vec cannot fail
+    encoder.write_all(data).expect("LZ4 frame compression failed");
+    encoder.finish().1.expect("LZ4 frame compression failed");
+}
+
+/// Append the data of LZ4 frames to `out`, checking their content checksums
+fn decompress_frame(frame: &[u8], out: &mut Vec<u8>) -> std::io::Result<()> {
+    lz4::Decoder::new(frame)?.read_to_end(out)?;
+    Ok(())
+}
+
+/// Append the data of an LZ4 block prefixed with its size to `out`
+fn decompress_block(block: &[u8], out: &mut Vec<u8>) -> std::io::Result<()> {
+    let size = block.get(..4).ok_or(std::io::ErrorKind::InvalidInput)?;
+    let size = usize::try_from(i32::from_le_bytes(size.try_into().unwrap())).map_err(|_| std::io::ErrorKind::InvalidInput)?;
+    // LZ4 expands at most 255 times, so larger prefixes are corrupt and not worth allocating for
+    if size > block.len().saturating_mul(255) {
+        return Err(std::io::ErrorKind::InvalidData.into());
+    }
+    let start = out.len();
+    out.resize(start + size, 0);
+    let len = decompress_to_buffer(block, None, &mut out[start..])?;
+    out.truncate(start + len);
+    Ok(())
+}
+
+/// Buffers for the intermediate stages of `encode_with` and `decode_with`. Reusing one across
+/// calls leaves them allocation-free once it has grown to the largest message.
+#[derive(Debug, Default)]
+pub struct Scratch {
+    /// Compressed data followed by its checksum
+    compressed: Vec<u8>,
+}
+
+/// Encode data with optional compression, checksum, and custom alphabet
+pub fn encode(data: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_with(data, seed, compression, &mut Scratch::default(), &mut out);
+    out
+}
+
+/// `encode`, appending to `out` and keeping intermediate buffers in `scratch`, for callers
+/// encoding many messages that want to reuse both
+pub fn encode_with(data: &[u8], seed: &[u8], compression: CompressionAlgorithm, scratch: &mut Scratch, out: &mut Vec<u8>) {
+    encode_translated(data, &Translator::encoding(&derive_alphabet(seed)), compression, true, scratch, out);
+}
+
+/// `encode` without the CRC32 trailer, for data carried over channels that already detect
+/// corruption, such as QUIC or Kafka. Only `decode_without_checksum` reads the result.
+pub fn encode_without_checksum(data: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_translated(data, &Translator::encoding(&derive_alphabet(seed)), compression, false, &mut Scratch::default(), &mut out);
+    out
+}
+
+/// `encode_with` for a seed already turned into its encoding `translator`, appending the CRC32
+/// trailer when `checksum` is set
+pub(crate) fn encode_translated(
+    data: &[u8],
+    translator: &Translator,
+    compression: CompressionAlgorithm,
+    checksum: bool,
+    scratch: &mut Scratch,
+    out: &mut Vec<u8>,
+) {
+    let span = metrics::span(Operation::Encode, data.len());
+    let start = out.len();
+    compress_into(data, compression, checksum, &mut scratch.compressed);
+    armor_into(&scratch.compressed, translator, out);
+    span.finish(Ok(out.len() - start));
+}
+
+/// First stage of encoding: `data` compressed into `processed`, followed by its CRC32 when
+/// `checksum` is set
+pub(crate) fn compress_into(data: &[u8], compression: CompressionAlgorithm, checksum: bool, processed_data: &mut Vec<u8>) {
+    let _timer = metrics::time(Stage::Compress);
+    processed_data.clear();
+    match compression {
+        CompressionAlgorithm::None => processed_data.extend_from_slice(data),
+        CompressionAlgorithm::Lz4 => {
+            processed_data.resize(compress_bound(data.len()).unwrap() + 4, 0);
+            let len = compress_to_buffer(data, Default::default(), true, processed_data).unwrap();
+            processed_data.truncate(len);
+        }
+        CompressionAlgorithm::Lz4Frame => compress_frame(data, processed_data),
+        CompressionAlgorithm::Brotli => processed_data.extend_from_slice(data), // Placeholder - Brotli compression to implement
+        CompressionAlgorithm::Huffman => processed_data.extend_from_slice(data), // Placeholder
+    }
+
+    // Add CRC32 checksum
+    if checksum {
+        let mut crc = Crc32Hasher::new();
+        crc.update(processed_data);
+        let checksum = crc.finalize();
+        processed_data.extend_from_slice(&checksum.to_le_bytes());
+    }
+}
+
+/// Second stage of encoding: `processed_data` in base64 with `translator`'s alphabet, appended to
+/// `out` block by block
+pub(crate) fn armor_into(processed_data: &[u8], translator: &Translator, out: &mut Vec<u8>) {
+    let _timer = metrics::time(Stage::Armor);
+    translator.armor(processed_data, out);
+}
+
+/// Decode data, verify checksum
+pub fn decode(encoded: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Result<Vec<u8>, &'static str> {
+    let mut out = Vec::new();
+    decode_with(encoded, seed, compression, &mut Scratch::default(), &mut out)?;
+    Ok(out)
+}
+
+/// `decode`, appending to `out` and keeping intermediate buffers in `scratch` as `encode_with`
+/// does. On failure `out` is left as it was.
+pub fn decode_with(
+    encoded: &[u8],
+    seed: &[u8],
+    compression: CompressionAlgorithm,
+    scratch: &mut Scratch,
+    out: &mut Vec<u8>,
+) -> Result<(), &'static str> {
+    decode_translated(encoded, &Translator::decoding(&derive_alphabet(seed)), compression, true, scratch, out)
+}
+
+/// Decode what `encode_without_checksum` produced. Corruption goes unnoticed unless it breaks the
+/// base64 or the compression.
+pub fn decode_without_checksum(encoded: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Result<Vec<u8>, &'static str> {
+    let mut out = Vec::new();
+    let translator = Translator::decoding(&derive_alphabet(seed));
+    decode_translated(encoded, &translator, compression, false, &mut Scratch::default(), &mut out)?;
+    Ok(out)
+}
+
+/// `decode_with` for a seed already turned into its decoding `translator`, expecting and
+/// verifying the CRC32 trailer when `checksum` is set
+pub(crate) fn decode_translated(
+    encoded: &[u8],
+    translator: &Translator,
+    compression: CompressionAlgorithm,
+    checksum: bool,
+    scratch: &mut Scratch,
+    out: &mut Vec<u8>,
+) -> Result<(), &'static str> {
+    let span = metrics::span(Operation::Decode, encoded.len());
+    let start = out.len();
+    let result = decode_into(encoded, translator, compression, checksum, scratch, out);
+    if result.is_err() {
+        out.truncate(start);
+    }
+    span.finish(result.map(|()| out.len() - start));
+    result
+}
+
+fn decode_into(
+    encoded: &[u8],
+    translator: &Translator,
+    compression: CompressionAlgorithm,
+    checksum: bool,
+    scratch: &mut Scratch,
+    out: &mut Vec<u8>,
+) -> Result<(), &'static str> {
+    let timer = metrics::time(Stage::Unarmor);
+    // Straight from the custom alphabet, without standard base64 in between
+    let decoded = &mut scratch.compressed;
+    decoded.clear();
+    translator.unarmor(encoded, decoded)?;
+    drop(timer);
+
+    // Extract data and checksum
+    let timer = metrics::time(Stage::Verify);
+    let data = if checksum {
+        if decoded.len() < 4 {
+            return Err("Data too short");
+        }
+        let data_len = decoded.len() - 4;
+        let data = &decoded[..data_len];
+        let checksum_bytes = &decoded[data_len..];
+        let expected_checksum = u32::from_le_bytes(checksum_bytes.try_into().unwrap());
+
+        // Verify checksum
+        let mut crc = Crc32Hasher::new();
+        crc.update(data);
+        if crc.finalize() != expected_checksum {
+            return Err("Checksum mismatch");
+        }
+        data
+    } else {
+        &decoded[..]
+    };
+    drop(timer);
+
+    decompress_into(data, compression, out)
+}
+
+/// Last stage of decoding: `data` decompressed and appended to `out`
+pub(crate) fn decompress_into(data: &[u8], compression: CompressionAlgorithm, out: &mut Vec<u8>) -> Result<(), &'static str> {
+    let _timer = metrics::time(Stage::Decompress);
+    match compression {
+        CompressionAlgorithm::None => out.extend_from_slice(data),
+        CompressionAlgorithm::Lz4 => decompress_block(data, out).map_err(|_| "Decompression LZ4 failed")?,
+        CompressionAlgorithm::Lz4Frame => decompress_frame(data, out).map_err(|_| "Decompression LZ4 frame failed")?,
+        CompressionAlgorithm::Brotli => out.extend_from_slice(data), // Placeholder - Brotli decompression to implement
+        CompressionAlgorithm::Huffman => out.extend_from_slice(data), // Placeholder
+    }
+    Ok(())
+}
+
+/// `encode` for text, returning the encoded payload as a `String`
+pub fn encode_str(text: &str, seed: &[u8], compression: CompressionAlgorithm) -> String {
+    String::from_utf8(encode(text.as_bytes(), seed, compression)).expect("encoded output is ASCII")
+}
+
+/// `decode` for payloads made by `encode_str`, failing when the decoded bytes are not UTF-8
+pub fn decode_to_string(encoded: &str, seed: &[u8], compression: CompressionAlgorithm) -> Result<String, &'static str> {
+    let decoded = decode(encoded.as_bytes(), seed, compression)?;
+    String::from_utf8(decoded).map_err(|_| "Invalid UTF-8")
+}
+
+/// Partial verification without key: decode with default alphabet and check checksum
+pub fn partial_verify(encoded: &[u8]) -> bool {
+    // Use default alphabet; invalid characters become `A` rather than fail
+    let standard_encoded: Vec<u8> = encoded
+        .iter()
+        .map(|&b| if b == b'=' || BASE64_ALPHABET.contains(&b) { b } else { b'A' })
+        .collect();
+
+    if let Ok(decoded) = general_purpose::STANDARD.decode(&standard_encoded)
+        && decoded.len() >= 4
+    {
+        let data_len = decoded.len() - 4;
+        let data = &decoded[..data_len];
+        let checksum_bytes = &decoded[data_len..];
+        let expected_checksum = u32::from_le_bytes(checksum_bytes.try_into().unwrap());
+
+        let mut crc = Crc32Hasher::new();
+        crc.update(data);
+        return crc.finalize() == expected_checksum;
+    }
+    false
+}
+
+/// Basic ZK integration: verifies checksum (placeholder for actual ZK proof using halo2)
+pub fn zk_checksum_verify(data: &[u8], checksum: u32) -> bool {
+    let mut crc = Crc32Hasher::new();
+    crc.update(data);
+    crc.finalize() == checksum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let data = b"Hello, Solana!";
+        let seed = b"secret_key";
+        let encoded = encode(data, seed, CompressionAlgorithm::None);
+        let decoded = decode(&encoded, seed, CompressionAlgorithm::None).unwrap();
+        assert_eq!(data, decoded.as_slice());
+    }
+
+    #[test]
+    fn test_str_roundtrip() {
+        let encoded = encode_str("Grüße, Solana!", b"seed", CompressionAlgorithm::Lz4);
+        assert_eq!(decode_to_string(&encoded, b"seed", CompressionAlgorithm::Lz4).unwrap(), "Grüße, Solana!");
+
+        let binary = String::from_utf8(encode(&[0xff, 0xfe], b"seed", CompressionAlgorithm::None)).unwrap();
+        assert_eq!(decode_to_string(&binary, b"seed", CompressionAlgorithm::None), Err("Invalid UTF-8"));
+    }
+
+    #[test]
+    fn test_partial_verify() {
+        let data = b"Test data";
+        let seed = b"key";
+        let encoded = encode(data, seed, CompressionAlgorithm::None);
+        // With correct key, should decode
+        assert!(decode(&encoded, seed, CompressionAlgorithm::None).is_ok());
+        // Partial verify with wrong key should fail or be false
+        // Since it's obfuscated, partial_verify uses default, so checksum won't match
+        assert!(!partial_verify(&encoded));
+    }
+
+    #[test]
+    fn test_zk_verify() {
+        let data = b"ZK test";
+        let mut crc = Crc32Hasher::new();
+        crc.update(data);
+        let checksum = crc.finalize();
+        assert!(zk_checksum_verify(data, checksum));
+    }
+
+    #[test]
+    fn test_encode_decode_lz4() {
+        let data = b"Repeated data for compression test: test test test test";
+        let seed = b"secret_key";
+        let encoded = encode(data, seed, CompressionAlgorithm::Lz4);
+        let decoded = decode(&encoded, seed, CompressionAlgorithm::Lz4).unwrap();
+        assert_eq!(data, decoded.as_slice());
+    }
+
+    #[test]
+    fn test_scratch_reuse_matches_and_stops_allocating() {
+        let mut scratch = Scratch::default();
+        let mut encoded = Vec::new();
+        let mut decoded = Vec::new();
+        let messages: Vec<Vec<u8>> = (0..50).map(|i| bench::sample_data(100 + i * 37)).collect();
+        for compression in [CompressionAlgorithm::None, CompressionAlgorithm::Lz4, CompressionAlgorithm::Lz4Frame] {
+            for (i, message) in messages.iter().enumerate() {
+                encoded.clear();
+                encode_with(message, b"seed", compression, &mut scratch, &mut encoded);
+                assert_eq!(encoded, encode(message, b"seed", compression));
+                decoded.clear();
+                decode_with(&encoded, b"seed", compression, &mut scratch, &mut decoded).unwrap();
+                assert_eq!(&decoded, message);
+
+                // Buffers sized by the largest message so far are reused for smaller ones
+                let buffers = (scratch.compressed.as_ptr(), encoded.as_ptr(), decoded.as_ptr());
+                if i > 0 && message.len() < messages[i - 1].len() {
+                    encode_with(message, b"seed", compression, &mut scratch, &mut encoded);
+                    assert_eq!(buffers, (scratch.compressed.as_ptr(), encoded.as_ptr(), decoded.as_ptr()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_with_appends_and_leaves_output_on_failure() {
+        let mut scratch = Scratch::default();
+        let mut out = b"prefix ".to_vec();
+        let encoded = encode(b"message", b"seed", CompressionAlgorithm::Lz4);
+        decode_with(&encoded, b"seed", CompressionAlgorithm::Lz4, &mut scratch, &mut out).unwrap();
+        assert_eq!(out, b"prefix message");
+        assert_eq!(decode_with(&encoded, b"other", CompressionAlgorithm::Lz4, &mut scratch, &mut out), Err("Checksum mismatch"));
+        let none = encode(b"\xff\xff\xff\x7fnot lz4", b"seed", CompressionAlgorithm::None);
+        assert_eq!(decode_with(&none, b"seed", CompressionAlgorithm::Lz4, &mut scratch, &mut out), Err("Decompression LZ4 failed"));
+        assert_eq!(out, b"prefix message");
+    }
+
+    #[test]
+    fn test_lz4_frame_readable_by_standard_tools() {
+        let data = b"Repeated data for compression test: test test test test";
+        let encoded = encode(data, b"secret_key", CompressionAlgorithm::Lz4Frame);
+        assert_eq!(decode(&encoded, b"secret_key", CompressionAlgorithm::Lz4Frame).unwrap(), data);
+
+        // Under the alphabet and checksum is a plain frame, starting with the LZ4 frame magic
+        let mut standard = Vec::new();
+        translate::Translator::decoding(&derive_alphabet(b"secret_key")).translate(&encoded, &mut standard).unwrap();
+        let inner = general_purpose::STANDARD.decode(&standard).unwrap();
+        let frame = &inner[..inner.len() - 4];
+        assert_eq!(frame[..4], [0x04, 0x22, 0x4d, 0x18]);
+        let mut decompressed = Vec::new();
+        decompress_frame(frame, &mut decompressed).unwrap();
+        assert_eq!(decompressed, data);
+
+        // Block and frame formats are not interchangeable
+        assert!(decode(&encoded, b"secret_key", CompressionAlgorithm::Lz4).is_err());
+    }
+}
+
+pub mod archive;
+pub mod auto;
+pub mod batch;
+pub mod bench;
+pub mod cache;
+#[cfg(feature = "arrow")]
+pub mod columnar;
+pub mod conformance;
+pub mod db;
+pub mod envelope;
+pub mod file;
+pub mod fixed;
+pub mod inspect;
+pub mod log;
+pub mod metrics;
+pub mod patch;
+#[cfg(feature = "proto")]
+pub mod proto;
+#[cfg(feature = "qr")]
+pub mod qr;
+pub mod skin;
+pub mod snapshot;
+pub mod store;
+pub mod stream;
+pub mod token;
+pub mod translate;
+pub mod typed;
+#[cfg(all(feature = "uring", target_os = "linux"))]
+pub mod uring;
+pub mod vectors;
+
+pub use typed::{decode_bincode, encode_bincode};
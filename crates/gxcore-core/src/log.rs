@@ -0,0 +1,248 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::{decode, encode, CompressionAlgorithm};
+
+/// Magic opening log files
+const LOG_MAGIC: &[u8; 4] = b"GXLG";
+const LOG_VERSION: u8 = 1;
+/// Magic and version
+const LOG_HEADER_LEN: u64 = 5;
+/// Payload length (u32), CRC32 (u32) and compression id preceding each payload
+const FRAME_HEADER_LEN: u64 = 9;
+
+/// Append-only file of records, each encoded on its own and framed with its length, compression
+/// and a CRC32 of the compression id and payload. Opening a log drops a torn or corrupt tail, so a
+/// crash mid-append loses at most the record being written.
+pub struct Log {
+    path: PathBuf,
+    file: File,
+    seed: Vec<u8>,
+    compression: CompressionAlgorithm,
+    /// Offset the next record is written at
+    end: u64,
+}
+
+/// CRC32 of a frame's compression id and payload
+fn frame_checksum(compression: u8, payload: &[u8]) -> u32 {
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(&[compression]);
+    crc.update(payload);
+    crc.finalize()
+}
+
+/// A frame cut short by the end of the file as `InvalidData`, leaving other read errors as they are
+fn truncated(e: io::Error) -> io::Error {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => io::Error::new(io::ErrorKind::InvalidData, "Truncated log record"),
+        _ => e,
+    }
+}
+
+/// Next frame of `reader`: its compression and payload, `None` at the end of the log, or an
+/// `InvalidData` error when the frame is cut short or fails its checksum. `remaining` bounds the
+/// length read from the frame, so a corrupt one never allocates past the end of the file.
+fn read_frame(reader: &mut impl Read, remaining: u64) -> io::Result<Option<(CompressionAlgorithm, Vec<u8>)>> {
+    let mut header = [0; FRAME_HEADER_LEN as usize];
+    match reader.read(&mut header[..1])? {
+        0 => return Ok(None),
+        _ => reader.read_exact(&mut header[1..]).map_err(truncated)?,
+    }
+    let len = u64::from(u32::from_le_bytes(header[..4].try_into().unwrap()));
+    if len > remaining.saturating_sub(FRAME_HEADER_LEN) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Truncated log record"));
+    }
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload).map_err(truncated)?;
+    if frame_checksum(header[8], &payload) != u32::from_le_bytes(header[4..8].try_into().unwrap()) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Log record checksum mismatch"));
+    }
+    let compression =
+        CompressionAlgorithm::from_id(header[8]).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Some((compression, payload)))
+}
+
+/// Check the magic and version at the start of `file`
+fn check_header(file: &mut impl Read) -> io::Result<()> {
+    let mut header = [0; LOG_HEADER_LEN as usize];
+    file.read_exact(&mut header)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Not a log file"))?;
+    if header[..4] != *LOG_MAGIC {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a log file"));
+    }
+    if header[4] != LOG_VERSION {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Unsupported log version"));
+    }
+    Ok(())
+}
+
+impl Log {
+    /// Open the log at `path`, creating it when missing, with records appended under `seed` and
+    /// `compression`. An existing log is scanned and truncated at its first frame that is cut short
+    /// or fails its checksum; other read errors fail the open and leave the file as it was. Only
+    /// checksums are checked, so a wrong seed truncates nothing; it fails when records are read.
+    pub fn open(path: &Path, seed: &[u8], compression: CompressionAlgorithm) -> io::Result<Self> {
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        let len = file.metadata()?.len();
+        let end = if len == 0 {
+            file.write_all(LOG_MAGIC)?;
+            file.write_all(&[LOG_VERSION])?;
+            file.sync_data()?;
+            LOG_HEADER_LEN
+        } else {
+            let mut reader = BufReader::new(&mut file);
+            check_header(&mut reader)?;
+            let mut end = LOG_HEADER_LEN;
+            loop {
+                match read_frame(&mut reader, len - end) {
+                    Ok(Some((_, payload))) => end += FRAME_HEADER_LEN + payload.len() as u64,
+                    Ok(None) => break,
+                    Err(e) if e.kind() == io::ErrorKind::InvalidData => break,
+                    Err(e) => return Err(e),
+                }
+            }
+            if end < len {
+                file.set_len(end)?;
+                file.sync_data()?;
+            }
+            end
+        };
+        file.seek(SeekFrom::Start(end))?;
+        Ok(Log {
+            path: path.to_path_buf(),
+            file,
+            seed: seed.to_vec(),
+            compression,
+            end,
+        })
+    }
+
+    /// Encode `record` and append it as one frame, returning its offset. The frame is written with
+    /// a single write; call `sync` to make it durable.
+    pub fn append(&mut self, record: &[u8]) -> io::Result<u64> {
+        self.append_with(record, self.compression)
+    }
+
+    /// `append` with `compression` instead of the log's, which the frame records for reading
+    pub fn append_with(&mut self, record: &[u8], compression: CompressionAlgorithm) -> io::Result<u64> {
+        let payload = encode(record, &self.seed, compression);
+        let compression = compression.id();
+        let mut frame = Vec::with_capacity(FRAME_HEADER_LEN as usize + payload.len());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&frame_checksum(compression, &payload).to_le_bytes());
+        frame.push(compression);
+        frame.extend_from_slice(&payload);
+        let offset = self.end;
+        if let Err(e) = self.file.write_all(&frame) {
+            // Drop a partial frame so later appends are not hidden behind it
+            let _ = self.file.set_len(offset);
+            let _ = self.file.seek(SeekFrom::Start(offset));
+            return Err(e);
+        }
+        self.end += frame.len() as u64;
+        Ok(offset)
+    }
+
+    /// Flush appended records to disk
+    pub fn sync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    /// Length of the log file in bytes
+    pub fn len(&self) -> u64 {
+        self.end
+    }
+
+    /// Whether the log holds no records
+    pub fn is_empty(&self) -> bool {
+        self.end == LOG_HEADER_LEN
+    }
+
+    /// Records in the order appended, decoded with the log's seed, read through a separate handle
+    /// so appending can continue meanwhile
+    pub fn iter(&self) -> io::Result<Records> {
+        let mut file = File::open(&self.path)?;
+        check_header(&mut file)?;
+        Ok(Records {
+            reader: BufReader::new(file),
+            seed: self.seed.clone(),
+            remaining: self.end - LOG_HEADER_LEN,
+        })
+    }
+}
+
+/// Iterator over the records of a `Log` up to its length when created
+pub struct Records {
+    reader: BufReader<File>,
+    seed: Vec<u8>,
+    remaining: u64,
+}
+
+impl Iterator for Records {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let (compression, payload) = match read_frame(&mut self.reader, self.remaining) {
+            Ok(Some(frame)) => frame,
+            Ok(None) => return None,
+            Err(e) => {
+                self.remaining = 0;
+                return Some(Err(e));
+            }
+        };
+        self.remaining -= FRAME_HEADER_LEN + payload.len() as u64;
+        Some(decode(&payload, &self.seed, compression).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_log_roundtrip_and_recovery() {
+        let dir = std::env::temp_dir().join(format!("gx-core-log-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.gxlog");
+
+        let mut log = Log::open(&path, b"seed", CompressionAlgorithm::Lz4).unwrap();
+        assert!(log.is_empty());
+        log.append(b"first").unwrap();
+        let second = log.append(b"second").unwrap();
+        log.append(&[]).unwrap();
+        log.sync().unwrap();
+        let records: Vec<_> = log.iter().unwrap().map(Result::unwrap).collect();
+        assert_eq!(records, [b"first".to_vec(), b"second".to_vec(), Vec::new()]);
+        let end = log.len();
+        drop(log);
+
+        // A torn write leaves a partial frame, which reopening drops
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[200, 0, 0, 0, 1, 2]).unwrap();
+        drop(file);
+        let mut log = Log::open(&path, b"seed", CompressionAlgorithm::None).unwrap();
+        assert_eq!(log.len(), end);
+        log.append(b"third").unwrap();
+        assert_eq!(log.iter().unwrap().count(), 4);
+        drop(log);
+
+        // A corrupt frame drops it and everything after it
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[second as usize + FRAME_HEADER_LEN as usize] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        let log = Log::open(&path, b"seed", CompressionAlgorithm::None).unwrap();
+        assert_eq!(log.len(), second);
+        let records: Vec<_> = log.iter().unwrap().map(Result::unwrap).collect();
+        assert_eq!(records, [b"first".to_vec()]);
+
+        let log = Log::open(&path, b"other", CompressionAlgorithm::None).unwrap();
+        assert_eq!(log.len(), second);
+        assert!(log.iter().unwrap().next().unwrap().is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
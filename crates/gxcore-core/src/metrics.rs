@@ -0,0 +1,215 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+static METRICS: OnceLock<Box<dyn Metrics>> = OnceLock::new();
+
+/// Codec call being reported
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Encode,
+    Decode,
+}
+
+impl Operation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Operation::Encode => "encode",
+            Operation::Decode => "decode",
+        }
+    }
+}
+
+/// Step of an encode or decode whose duration is reported
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Compression and the CRC32 trailer
+    Compress,
+    /// Base64 in the seeded alphabet
+    Armor,
+    /// Seeded alphabet back to bytes
+    Unarmor,
+    /// CRC32 check
+    Verify,
+    Decompress,
+}
+
+impl Stage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Compress => "compress",
+            Stage::Armor => "armor",
+            Stage::Unarmor => "unarmor",
+            Stage::Verify => "verify",
+            Stage::Decompress => "decompress",
+        }
+    }
+}
+
+/// Receiver of what the codec does, for integrators without the HTTP layer and its Prometheus
+/// endpoint. Every method does nothing by default, so implementations pick the events they want.
+/// They are called on the thread doing the work and should be cheap.
+pub trait Metrics: Send + Sync {
+    /// An encode or decode finished, turning `input` bytes into `output`
+    fn bytes(&self, _operation: Operation, _input: usize, _output: usize) {}
+
+    /// A stage of an encode or decode took `duration`
+    fn stage(&self, _stage: Stage, _duration: Duration) {}
+
+    /// A decode failed with `error`
+    fn error(&self, _operation: Operation, _error: &'static str) {}
+}
+
+/// Metrics that are discarded, as they are until `install` is called
+pub struct NoMetrics;
+
+impl Metrics for NoMetrics {}
+
+/// Report the codec's work to `metrics` for the rest of the process.
+/// Returns an error if metrics were already installed.
+pub fn install(metrics: impl Metrics + 'static) -> Result<(), &'static str> {
+    METRICS.set(Box::new(metrics)).map_err(|_| "Metrics already installed")
+}
+
+/// One encode or decode, reported when finished, and its span under the `tracing` feature
+pub(crate) struct Span {
+    operation: Operation,
+    input: usize,
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+}
+
+#[inline]
+pub(crate) fn span(operation: Operation, input: usize) -> Span {
+    Span {
+        operation,
+        input,
+        #[cfg(feature = "tracing")]
+        span: tracing::debug_span!(
+            "gxcore",
+            operation = operation.name(),
+            input_bytes = input,
+            output_bytes = tracing::field::Empty
+        )
+        .entered(),
+    }
+}
+
+impl Span {
+    /// Report the output size, or the error the operation failed with
+    #[inline]
+    pub(crate) fn finish(self, result: Result<usize, &'static str>) {
+        #[cfg(feature = "tracing")]
+        match result {
+            Ok(output) => {
+                self.span.record("output_bytes", output);
+            }
+            Err(error) => tracing::debug!(error, "{} failed", self.operation.name()),
+        }
+        record(self.operation, self.input, result);
+    }
+}
+
+/// Report the sizes or the error of an encode or decode
+#[inline]
+pub(crate) fn record(operation: Operation, input: usize, result: Result<usize, &'static str>) {
+    if let Some(metrics) = METRICS.get() {
+        match result {
+            Ok(output) => metrics.bytes(operation, input, output),
+            Err(error) => metrics.error(operation, error),
+        }
+    }
+}
+
+/// Times a stage until dropped. The clock is only read once metrics are installed.
+pub(crate) struct Timer {
+    stage: Stage,
+    start: Option<Instant>,
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+#[inline]
+pub(crate) fn time(stage: Stage) -> Timer {
+    Timer {
+        stage,
+        start: METRICS.get().map(|_| Instant::now()),
+        #[cfg(feature = "tracing")]
+        _span: tracing::trace_span!("gxcore_stage", stage = stage.name()).entered(),
+    }
+}
+
+impl Drop for Timer {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(start) = self.start
+            && let Some(metrics) = METRICS.get()
+        {
+            metrics.stage(self.stage, start.elapsed());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode, CompressionAlgorithm};
+    use std::sync::Mutex;
+    use std::thread::ThreadId;
+
+    /// Events from the test's own thread, as other tests encode concurrently
+    struct Recorder {
+        thread: ThreadId,
+        events: Mutex<Vec<String>>,
+    }
+
+    impl Recorder {
+        fn push(&self, event: String) {
+            if std::thread::current().id() == self.thread {
+                self.events.lock().unwrap().push(event);
+            }
+        }
+    }
+
+    impl Metrics for &'static Recorder {
+        fn bytes(&self, operation: Operation, input: usize, output: usize) {
+            self.push(format!("{} {input} {output}", operation.name()));
+        }
+
+        fn stage(&self, stage: Stage, _duration: Duration) {
+            self.push(stage.name().to_string());
+        }
+
+        fn error(&self, operation: Operation, error: &'static str) {
+            self.push(format!("{} {error}", operation.name()));
+        }
+    }
+
+    #[test]
+    fn test_installed_metrics_see_codec_calls() {
+        let recorder: &'static Recorder = Box::leak(Box::new(Recorder {
+            thread: std::thread::current().id(),
+            events: Mutex::new(Vec::new()),
+        }));
+        install(recorder).unwrap();
+        assert_eq!(install(NoMetrics), Err("Metrics already installed"));
+
+        let encoded = encode(b"observed", b"seed", CompressionAlgorithm::None);
+        decode(&encoded, b"seed", CompressionAlgorithm::None).unwrap();
+        decode(&encoded, b"wrong", CompressionAlgorithm::None).unwrap_err();
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            [
+                "compress",
+                "armor",
+                "encode 8 16",
+                "unarmor",
+                "verify",
+                "decompress",
+                "decode 16 8",
+                "unarmor",
+                "verify",
+                "decode Checksum mismatch",
+            ]
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::archive::CONTENT_METADATA;
+use crate::envelope::{Envelope, PayloadFormat};
+use crate::CompressionAlgorithm;
+
+/// Value of `CONTENT_METADATA` for patches
+pub const PATCH_CONTENT: &str = "gxpatch";
+
+/// Bytes of the windows matched between the old and new data. Shorter runs of common bytes are
+/// inserted, since a copy costs about as much to describe.
+const WINDOW: usize = 16;
+
+/// Step of a patch, building the new data front to back
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PatchOp {
+    /// Bytes of the old data
+    Copy { offset: u64, len: u64 },
+    /// Bytes only in the new data
+    Insert(#[serde(with = "serde_bytes")] Vec<u8>),
+}
+
+/// Difference between two versions of some data, describing the new one as copies from the old one
+/// and inserted bytes, as bsdiff and xdelta do
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Patch {
+    /// SHA-256 of the data the patch applies to
+    pub source_hash: [u8; 32],
+    /// SHA-256 of the data the patch produces
+    pub target_hash: [u8; 32],
+    pub ops: Vec<PatchOp>,
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// Patch turning `old` into `new`. Every `WINDOW`-aligned window of `old` is indexed, and `new` is
+/// scanned a byte at a time for them; each match is extended both ways as far as the bytes agree.
+pub fn diff(old: &[u8], new: &[u8]) -> Patch {
+    let mut index: HashMap<&[u8], usize> = HashMap::new();
+    for offset in (0..old.len().saturating_sub(WINDOW - 1)).step_by(WINDOW) {
+        index.entry(&old[offset..offset + WINDOW]).or_insert(offset);
+    }
+
+    let mut ops = Vec::new();
+    // Start of the bytes not yet covered by an op
+    let mut pending = 0;
+    let mut i = 0;
+    while i + WINDOW <= new.len() {
+        let Some(&offset) = index.get(&new[i..i + WINDOW]) else {
+            i += 1;
+            continue;
+        };
+        // Extend backwards into the pending bytes, then forwards
+        let back = new[pending..i]
+            .iter()
+            .rev()
+            .zip(old[..offset].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let forward = new[i..].iter().zip(&old[offset..]).take_while(|(a, b)| a == b).count();
+        let (start, source) = (i - back, offset - back);
+        if start > pending {
+            ops.push(PatchOp::Insert(new[pending..start].to_vec()));
+        }
+        ops.push(PatchOp::Copy {
+            offset: source as u64,
+            len: (back + forward) as u64,
+        });
+        i += forward;
+        pending = i;
+    }
+    if pending < new.len() {
+        ops.push(PatchOp::Insert(new[pending..].to_vec()));
+    }
+
+    Patch {
+        source_hash: sha256(old),
+        target_hash: sha256(new),
+        ops,
+    }
+}
+
+/// The data `patch` makes of `old`, refusing an `old` other than the one it was made from and
+/// checking the result against the hash recorded
+pub fn apply(old: &[u8], patch: &Patch) -> Result<Vec<u8>, &'static str> {
+    if sha256(old) != patch.source_hash {
+        return Err("Patch does not apply to this data");
+    }
+    let mut new = Vec::new();
+    for op in &patch.ops {
+        match op {
+            PatchOp::Copy { offset, len } => {
+                let start = usize::try_from(*offset).map_err(|_| "Patch copy out of range")?;
+                let end = usize::try_from(*len)
+                    .ok()
+                    .and_then(|len| start.checked_add(len))
+                    .ok_or("Patch copy out of range")?;
+                new.extend_from_slice(old.get(start..end).ok_or("Patch copy out of range")?);
+            }
+            PatchOp::Insert(data) => new.extend_from_slice(data),
+        }
+    }
+    if sha256(&new) != patch.target_hash {
+        return Err("Patch result hash mismatch");
+    }
+    Ok(new)
+}
+
+impl Patch {
+    /// Bytes the patch inserts rather than copies
+    pub fn inserted_len(&self) -> usize {
+        self.ops
+            .iter()
+            .map(|op| match op {
+                PatchOp::Insert(data) => data.len(),
+                PatchOp::Copy { .. } => 0,
+            })
+            .sum()
+    }
+
+    /// Seal the patch with bincode in an HMAC-authenticated envelope, as archives are
+    pub fn seal(&self, seed: &[u8]) -> Result<Envelope, &'static str> {
+        let data = bincode::serialize(self).map_err(|_| "Bincode serialization failed")?;
+        Envelope::seal(&data, seed, CompressionAlgorithm::Lz4)
+            .with_format(PayloadFormat::Bincode)
+            .with_metadata(CONTENT_METADATA, PATCH_CONTENT)
+            .authenticate(seed)
+    }
+
+    /// Open a patch envelope
+    pub fn open(envelope: &Envelope, seed: &[u8]) -> Result<Self, &'static str> {
+        if envelope.metadata.get(CONTENT_METADATA).map(String::as_str) != Some(PATCH_CONTENT) {
+            return Err("Envelope is not a patch");
+        }
+        bincode::deserialize(&envelope.open(seed)?).map_err(|_| "Bincode deserialization failed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::sample_data;
+    use crate::encode;
+
+    #[test]
+    fn test_diff_apply() {
+        let old = sample_data(200_000);
+        let mut new = old.clone();
+        new[1000] ^= 0xff;
+        new.splice(50_000..50_000, b"inserted in the middle".iter().copied());
+        new.drain(120_000..121_000);
+        new.extend_from_slice(b"appended");
+
+        let patch = diff(&old, &new);
+        assert!(patch.inserted_len() < 100);
+        assert_eq!(apply(&old, &patch).unwrap(), new);
+        assert_eq!(apply(&new, &patch), Err("Patch does not apply to this data"));
+
+        for (old, new) in [(&b""[..], &b"fresh"[..]), (&b"gone"[..], &b""[..]), (&b"same"[..], &b"same"[..])] {
+            assert_eq!(apply(old, &diff(old, new)).unwrap(), new);
+        }
+    }
+
+    #[test]
+    fn test_patch_envelope() {
+        // Patches between encoded bundles stay small, since a change only alters nearby characters
+        let old = encode(&sample_data(100_000), b"seed", CompressionAlgorithm::None);
+        let mut data = sample_data(100_000);
+        data[40_000..40_005].copy_from_slice(b"patch");
+        let new = encode(&data, b"seed", CompressionAlgorithm::None);
+
+        let bytes = diff(&old, &new).seal(b"seed").unwrap().to_bytes().unwrap();
+        assert!(bytes.len() < 1000);
+        let patch = Patch::open(&Envelope::parse(&bytes).unwrap(), b"seed").unwrap();
+        assert_eq!(apply(&old, &patch).unwrap(), new);
+        assert_eq!(
+            Patch::open(&Envelope::parse(&bytes).unwrap(), b"other"),
+            Err("Envelope authentication failed")
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use crate::CompressionAlgorithm;
+
+/// Types generated from `proto/gxcore/v1/gxcore.proto`
+pub mod v1 {
+    include!(concat!(env!("OUT_DIR"), "/gxcore.v1.rs"));
+}
+
+impl From<v1::Compression> for CompressionAlgorithm {
+    fn from(compression: v1::Compression) -> Self {
+        match compression {
+            v1::Compression::None => CompressionAlgorithm::None,
+            v1::Compression::Lz4 => CompressionAlgorithm::Lz4,
+            v1::Compression::Brotli => CompressionAlgorithm::Brotli,
+            v1::Compression::Lz4Frame => CompressionAlgorithm::Lz4Frame,
+        }
+    }
+}
+
+impl TryFrom<CompressionAlgorithm> for v1::Compression {
+    type Error = &'static str;
+
+    fn try_from(compression: CompressionAlgorithm) -> Result<Self, Self::Error> {
+        match compression {
+            CompressionAlgorithm::None => Ok(v1::Compression::None),
+            CompressionAlgorithm::Lz4 => Ok(v1::Compression::Lz4),
+            CompressionAlgorithm::Brotli => Ok(v1::Compression::Brotli),
+            CompressionAlgorithm::Lz4Frame => Ok(v1::Compression::Lz4Frame),
+            CompressionAlgorithm::Huffman => Err("Compression not supported by the protobuf schema"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+
+    #[test]
+    fn test_request_roundtrip() {
+        let request = v1::EncodeRequest {
+            data: b"proto data".to_vec(),
+            seed: b"seed".to_vec(),
+            compression: v1::Compression::Lz4 as i32,
+        };
+        let bytes = request.encode_to_vec();
+        let parsed = v1::EncodeRequest::decode(bytes.as_slice()).unwrap();
+        assert_eq!(parsed, request);
+        assert_eq!(CompressionAlgorithm::from(parsed.compression()), CompressionAlgorithm::Lz4);
+    }
+}
@@ -0,0 +1,249 @@
+use qrcode::render::{svg, unicode};
+use qrcode::{EcLevel, QrCode};
+use sha2::{Digest, Sha256};
+
+use crate::{compress_into, decompress_into, CompressionAlgorithm};
+
+/// Characters of the QR alphanumeric mode, which packs them at 5.5 bits each
+const QR_ALPHABET: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// Opens the header of every part: `GX:<id>:<index>/<total>:`
+const PART_PREFIX: &str = "GX:";
+/// Most parts `Assembler` accepts a payload split into, bounding what a forged header allocates.
+/// Even at 100 characters a part this is 1 MB of encoded text, more than anyone scans.
+pub const MAX_PARTS: usize = 10_000;
+
+/// Permutation of the alphanumeric characters derived from a seed, as `derive_alphabet` permutes
+/// the base64 ones. The hash is separated from the base64 alphabet's so the two do not correlate.
+pub fn derive_qr_alphabet(seed: &[u8]) -> [u8; 45] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"gxcore-qr");
+    hasher.update(seed);
+    let hash = hasher.finalize();
+
+    let mut alphabet = *QR_ALPHABET;
+    for i in 0..45 {
+        let swap_idx = (hash[i % 32] as usize + i) % 45;
+        alphabet.swap(i, swap_idx);
+    }
+    alphabet
+}
+
+/// Encode data as `encode` does, with base45 (RFC 9285) over a seeded permutation of the QR
+/// alphanumeric characters in place of base64, so QR codes hold it in alphanumeric mode
+pub fn encode_qr(data: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> String {
+    let mut processed = Vec::new();
+    compress_into(data, compression, true, &mut processed);
+    let alphabet = derive_qr_alphabet(seed);
+    let mut out = String::with_capacity(processed.len() / 2 * 3 + 2);
+    for pair in processed.chunks(2) {
+        let (mut n, digits) = match *pair {
+            [a, b] => (usize::from(a) * 256 + usize::from(b), 3),
+            [a] => (usize::from(a), 2),
+            _ => unreachable!("chunks of two"),
+        };
+        for _ in 0..digits {
+            out.push(char::from(alphabet[n % 45]));
+            n /= 45;
+        }
+    }
+    out
+}
+
+/// Decode what `encode_qr` produced, verifying the checksum
+pub fn decode_qr(encoded: &str, seed: &[u8], compression: CompressionAlgorithm) -> Result<Vec<u8>, &'static str> {
+    let alphabet = derive_qr_alphabet(seed);
+    let mut values = [u8::MAX; 256];
+    for (value, &c) in alphabet.iter().enumerate() {
+        values[usize::from(c)] = value as u8;
+    }
+
+    let mut decoded = Vec::with_capacity(encoded.len() / 3 * 2 + 1);
+    for group in encoded.as_bytes().chunks(3) {
+        let mut n = 0usize;
+        for &c in group.iter().rev() {
+            let value = values[usize::from(c)];
+            if value == u8::MAX {
+                return Err("Invalid QR character");
+            }
+            n = n * 45 + usize::from(value);
+        }
+        match group.len() {
+            3 if n <= 0xffff => decoded.extend_from_slice(&(n as u16).to_be_bytes()),
+            2 if n <= 0xff => decoded.push(n as u8),
+            _ => return Err("Invalid base45"),
+        }
+    }
+
+    if decoded.len() < 4 {
+        return Err("Data too short");
+    }
+    let (data, checksum) = decoded.split_at(decoded.len() - 4);
+    if crc32fast::hash(data) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+        return Err("Checksum mismatch");
+    }
+    let mut out = Vec::new();
+    decompress_into(data, compression, &mut out)?;
+    Ok(out)
+}
+
+/// Split encoded text into parts of at most `part_len` characters each, every one behind a
+/// `GX:<id>:<index>/<total>:` header with a 1-based index. The id, from the CRC32 of the whole
+/// text, tells parts of different payloads apart. Short text still gets one part, `1/1`.
+pub fn split(encoded: &str, part_len: usize) -> Vec<String> {
+    let id = format!("{:08X}", crc32fast::hash(encoded.as_bytes()));
+    let chunks: Vec<_> = match encoded.is_empty() {
+        true => vec![""],
+        // Encoded text is ASCII, so every byte boundary is a character boundary
+        false => encoded.as_bytes().chunks(part_len.max(1)).map(|chunk| std::str::from_utf8(chunk).unwrap()).collect(),
+    };
+    let total = chunks.len();
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| format!("{PART_PREFIX}{id}:{}/{total}:{chunk}", i + 1))
+        .collect()
+}
+
+/// Collects the parts made by `split` in any order, as they are scanned, and joins them once all
+/// have arrived
+#[derive(Debug, Default)]
+pub struct Assembler {
+    id: Option<String>,
+    parts: Vec<Option<String>>,
+}
+
+impl Assembler {
+    pub fn new() -> Self {
+        Assembler::default()
+    }
+
+    /// Add a scanned part. Scanning one twice is harmless; parts of another payload are refused.
+    pub fn push(&mut self, part: &str) -> Result<(), &'static str> {
+        let rest = part.strip_prefix(PART_PREFIX).ok_or("Missing QR part header")?;
+        let mut fields = rest.splitn(3, ':');
+        let (Some(id), Some(position), Some(chunk)) = (fields.next(), fields.next(), fields.next()) else {
+            return Err("Missing QR part header");
+        };
+        let (index, total) = position.split_once('/').ok_or("Invalid QR part position")?;
+        let index: usize = index.parse().map_err(|_| "Invalid QR part position")?;
+        let total: usize = total.parse().map_err(|_| "Invalid QR part position")?;
+        if index == 0 || index > total {
+            return Err("Invalid QR part position");
+        }
+        if total > MAX_PARTS {
+            return Err("Too many QR parts");
+        }
+
+        match &self.id {
+            None => {
+                self.id = Some(id.to_string());
+                self.parts = vec![None; total];
+            }
+            Some(expected) if expected != id || self.parts.len() != total => {
+                return Err("QR part belongs to another payload");
+            }
+            Some(_) => {}
+        }
+        self.parts[index - 1] = Some(chunk.to_string());
+        Ok(())
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.id.is_some() && self.parts.iter().all(Option::is_some)
+    }
+
+    /// 1-based indexes of the parts not yet scanned
+    pub fn missing(&self) -> Vec<usize> {
+        self.parts
+            .iter()
+            .enumerate()
+            .filter(|(_, part)| part.is_none())
+            .map(|(i, _)| i + 1)
+            .collect()
+    }
+
+    /// The encoded text, checked against the id in the headers, for `decode_qr`
+    pub fn finish(self) -> Result<String, &'static str> {
+        if !self.is_complete() {
+            return Err("QR parts missing");
+        }
+        let encoded: String = self.parts.into_iter().flatten().collect();
+        if self.id != Some(format!("{:08X}", crc32fast::hash(encoded.as_bytes()))) {
+            return Err("QR part checksum mismatch");
+        }
+        Ok(encoded)
+    }
+}
+
+fn qr_code(part: &str) -> Result<QrCode, &'static str> {
+    QrCode::with_error_correction_level(part, EcLevel::M).map_err(|_| "Data too long for a QR code")
+}
+
+/// A part as an SVG image
+pub fn render_svg(part: &str) -> Result<String, &'static str> {
+    Ok(qr_code(part)?.render::<svg::Color>().min_dimensions(256, 256).build())
+}
+
+/// A part drawn with Unicode half blocks, two modules per character, for terminals
+pub fn render_unicode(part: &str) -> Result<String, &'static str> {
+    Ok(qr_code(part)?
+        .render::<unicode::Dense1x2>()
+        .dark_color(unicode::Dense1x2::Light)
+        .light_color(unicode::Dense1x2::Dark)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::sample_data;
+
+    #[test]
+    fn test_qr_roundtrip() {
+        for len in [0, 1, 2, 3, 1000] {
+            let data = sample_data(len);
+            let encoded = encode_qr(&data, b"seed", CompressionAlgorithm::Lz4);
+            assert!(encoded.bytes().all(|c| QR_ALPHABET.contains(&c)));
+            assert_eq!(decode_qr(&encoded, b"seed", CompressionAlgorithm::Lz4).unwrap(), data);
+        }
+        let encoded = encode_qr(b"signing payload", b"seed", CompressionAlgorithm::None);
+        assert!(decode_qr(&encoded, b"other", CompressionAlgorithm::None).is_err());
+        assert_eq!(decode_qr("abc", b"seed", CompressionAlgorithm::None), Err("Invalid QR character"));
+    }
+
+    #[test]
+    fn test_parts_reassemble_in_any_order() {
+        let data = sample_data(5000);
+        let encoded = encode_qr(&data, b"seed", CompressionAlgorithm::None);
+        let parts = split(&encoded, 1000);
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(|part| part.bytes().all(|c| QR_ALPHABET.contains(&c))));
+
+        let mut assembler = Assembler::new();
+        for part in parts.iter().rev() {
+            assert!(!assembler.is_complete());
+            assembler.push(part).unwrap();
+        }
+        assembler.push(&parts[0]).unwrap();
+        assert!(assembler.missing().is_empty());
+        let joined = assembler.finish().unwrap();
+        assert_eq!(decode_qr(&joined, b"seed", CompressionAlgorithm::None).unwrap(), data);
+
+        let mut assembler = Assembler::new();
+        assembler.push(&parts[1]).unwrap();
+        assert_eq!(assembler.missing().len(), parts.len() - 1);
+        let other = split(&encode_qr(b"other", b"seed", CompressionAlgorithm::None), 1000);
+        assert_eq!(assembler.push(&other[0]), Err("QR part belongs to another payload"));
+        assert_eq!(assembler.finish(), Err("QR parts missing"));
+        assert_eq!(Assembler::new().push("GX:X:1/99999999999:"), Err("Too many QR parts"));
+    }
+
+    #[test]
+    fn test_render() {
+        let part = &split(&encode_qr(b"payload", b"seed", CompressionAlgorithm::None), 1000)[0];
+        assert!(render_svg(part).unwrap().starts_with("<?xml"));
+        assert!(!render_unicode(part).unwrap().is_empty());
+        assert!(render_svg(&"A".repeat(5000)).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+
+use crate::BASE64_ALPHABET;
+
+/// First of the 64 emoticons, U+1F600 to U+1F63F, standing for the base64 characters in order
+const EMOJI_BASE: u32 = 0x1F600;
+
+/// Characters drawn to look like base64 ones, which are kept where no lookalike exists
+const HOMOGLYPHS: [(u8, char); 39] = [
+    (b'A', '\u{0410}'),
+    (b'B', '\u{0412}'),
+    (b'C', '\u{0421}'),
+    (b'E', '\u{0415}'),
+    (b'F', '\u{03DC}'),
+    (b'G', '\u{050C}'),
+    (b'H', '\u{041D}'),
+    (b'I', '\u{0406}'),
+    (b'J', '\u{0408}'),
+    (b'K', '\u{041A}'),
+    (b'M', '\u{041C}'),
+    (b'N', '\u{039D}'),
+    (b'O', '\u{041E}'),
+    (b'P', '\u{0420}'),
+    (b'Q', '\u{051A}'),
+    (b'S', '\u{0405}'),
+    (b'T', '\u{0422}'),
+    (b'V', '\u{0474}'),
+    (b'W', '\u{051C}'),
+    (b'X', '\u{0425}'),
+    (b'Y', '\u{04AE}'),
+    (b'Z', '\u{0396}'),
+    (b'a', '\u{0430}'),
+    (b'c', '\u{0441}'),
+    (b'd', '\u{0501}'),
+    (b'e', '\u{0435}'),
+    (b'g', '\u{0261}'),
+    (b'h', '\u{04BB}'),
+    (b'i', '\u{0456}'),
+    (b'j', '\u{0458}'),
+    (b'o', '\u{043E}'),
+    (b'p', '\u{0440}'),
+    (b'q', '\u{051B}'),
+    (b's', '\u{0455}'),
+    (b'v', '\u{0475}'),
+    (b'w', '\u{051D}'),
+    (b'x', '\u{0445}'),
+    (b'y', '\u{0443}'),
+    (b'l', '\u{04CF}'),
+];
+
+/// Base-4 digits of the zero-width skin, three per base64 character: the invisible math operators,
+/// which ordinary text leaves alone, unlike the joiners inside emoji sequences and some scripts
+const ZERO_WIDTH: [char; 4] = ['\u{2061}', '\u{2062}', '\u{2063}', '\u{2064}'];
+
+/// Symbols standing in for the characters of encoded output, for places that block or mangle
+/// base64-looking text. Skins change how the text looks, not the payload under it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Skin {
+    /// One emoticon per character
+    Emoji,
+    /// Cyrillic and Greek lookalikes of the letters, which pass for text yet match no base64 filter
+    Homoglyph,
+    /// Three invisible characters per character, readable back from any text they are hidden in
+    ZeroWidth,
+}
+
+impl Skin {
+    pub fn name(self) -> &'static str {
+        match self {
+            Skin::Emoji => "emoji",
+            Skin::Homoglyph => "homoglyph",
+            Skin::ZeroWidth => "zero-width",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, &'static str> {
+        match name {
+            "emoji" => Ok(Skin::Emoji),
+            "homoglyph" => Ok(Skin::Homoglyph),
+            "zero-width" => Ok(Skin::ZeroWidth),
+            _ => Err("Unknown skin"),
+        }
+    }
+
+    /// Skin of `text`, from the symbols it holds; `None` for plain base64
+    pub fn detect(text: &str) -> Option<Self> {
+        if text.chars().any(|c| ZERO_WIDTH.contains(&c)) {
+            Some(Skin::ZeroWidth)
+        } else if text.chars().any(|c| emoji_value(c).is_some()) {
+            Some(Skin::Emoji)
+        } else if text.chars().any(|c| homoglyph_value(c).is_some()) {
+            Some(Skin::Homoglyph)
+        } else {
+            None
+        }
+    }
+}
+
+/// Position of a base64 character in the standard alphabet
+fn base64_value(c: u8) -> Option<usize> {
+    BASE64_ALPHABET.iter().position(|&b| b == c)
+}
+
+fn emoji_value(c: char) -> Option<usize> {
+    let value = u32::from(c).checked_sub(EMOJI_BASE)?;
+    (value < 64).then_some(value as usize)
+}
+
+/// Base64 character a lookalike stands for
+fn homoglyph_value(c: char) -> Option<u8> {
+    HOMOGLYPHS.iter().find(|&&(_, lookalike)| lookalike == c).map(|&(original, _)| original)
+}
+
+/// Re-dress encoded output, or any text in a base64 alphabet, in `skin`. Padding is dropped and
+/// restored by `remove`.
+pub fn apply(encoded: &[u8], skin: Skin) -> Result<String, &'static str> {
+    let mut out = String::with_capacity(encoded.len() * 4);
+    for &c in encoded.iter().filter(|&&c| c != b'=') {
+        let value = base64_value(c).ok_or("Invalid base64")?;
+        match skin {
+            Skin::Emoji => out.push(char::from_u32(EMOJI_BASE + value as u32).expect("emoticons are chars")),
+            Skin::Homoglyph => out.push(
+                HOMOGLYPHS
+                    .iter()
+                    .find(|&&(original, _)| original == c)
+                    .map_or(char::from(c), |&(_, lookalike)| lookalike),
+            ),
+            Skin::ZeroWidth => {
+                for shift in [4, 2, 0] {
+                    out.push(ZERO_WIDTH[(value >> shift) & 3]);
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// The text `apply` dressed in `skin`, with its padding. Whitespace is skipped, as is everything
+/// but the zero-width characters for that skin, so its text can be read from a message it hides in.
+pub fn remove(text: &str, skin: Skin) -> Result<Vec<u8>, &'static str> {
+    let mut out = Vec::with_capacity(text.len());
+    match skin {
+        Skin::ZeroWidth => {
+            let digits: Vec<usize> = text.chars().filter_map(|c| ZERO_WIDTH.iter().position(|&z| z == c)).collect();
+            if !digits.len().is_multiple_of(3) {
+                return Err("Truncated zero-width text");
+            }
+            for digit in digits.chunks(3) {
+                out.push(BASE64_ALPHABET[(digit[0] << 4) | (digit[1] << 2) | digit[2]]);
+            }
+        }
+        Skin::Emoji | Skin::Homoglyph => {
+            for c in text.chars().filter(|c| !c.is_whitespace()) {
+                let original = match skin {
+                    Skin::Emoji => emoji_value(c).map(|value| BASE64_ALPHABET[value]),
+                    _ => homoglyph_value(c).or_else(|| u8::try_from(c).ok().filter(|&b| base64_value(b).is_some())),
+                };
+                out.push(original.ok_or("Invalid skin symbol")?);
+            }
+        }
+    }
+    match out.len() % 4 {
+        0 => {}
+        1 => return Err("Invalid base64 length"),
+        padding => out.resize(out.len() + 4 - padding, b'='),
+    }
+    Ok(out)
+}
+
+/// Arbitrary bytes, such as a serialized envelope, as standard base64 in `skin`
+pub fn wrap(bytes: &[u8], skin: Skin) -> String {
+    apply(general_purpose::STANDARD.encode(bytes).as_bytes(), skin).expect("standard base64")
+}
+
+/// Bytes `wrap` dressed in `skin`
+pub fn unwrap(text: &str, skin: Skin) -> Result<Vec<u8>, &'static str> {
+    general_purpose::STANDARD
+        .decode(remove(text, skin)?)
+        .map_err(|_| "Invalid base64")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode, CompressionAlgorithm};
+
+    #[test]
+    fn test_skins_roundtrip_encoded_output() {
+        for len in [0, 1, 2, 3, 100] {
+            let data: Vec<u8> = (0..len as u8).collect();
+            let encoded = encode(&data, b"seed", CompressionAlgorithm::None);
+            for skin in [Skin::Emoji, Skin::Homoglyph, Skin::ZeroWidth] {
+                let skinned = apply(&encoded, skin).unwrap();
+                let restored = remove(&skinned, skin).unwrap();
+                assert_eq!(restored, encoded);
+                assert_eq!(decode(&restored, b"seed", CompressionAlgorithm::None).unwrap(), data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_skin_properties() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let emoji = wrap(&bytes, Skin::Emoji);
+        assert_eq!(Skin::detect(&emoji), Some(Skin::Emoji));
+        assert!(emoji.chars().all(|c| emoji_value(c).is_some()));
+        assert_eq!(unwrap(&emoji, Skin::Emoji).unwrap(), bytes);
+
+        let homoglyph = wrap(&bytes, Skin::Homoglyph);
+        assert_eq!(Skin::detect(&homoglyph), Some(Skin::Homoglyph));
+        assert!(homoglyph.chars().all(|c| homoglyph_value(c).is_some() || c.is_ascii_alphanumeric() || "+/".contains(c)));
+        assert_eq!(unwrap(&homoglyph, Skin::Homoglyph).unwrap(), bytes);
+
+        // Hidden zero-width text survives the message around it
+        let hidden = wrap(b"payload", Skin::ZeroWidth);
+        let message = format!("See you{hidden} at noon");
+        assert_eq!(Skin::detect(&message), Some(Skin::ZeroWidth));
+        assert_eq!(unwrap(&message, Skin::ZeroWidth).unwrap(), b"payload");
+        // Joiners in emoji sequences are not mistaken for hidden text
+        let emoji = "Pushed it \u{1F469}\u{200D}\u{1F4BB}";
+        assert_eq!(Skin::detect(emoji), None);
+        assert_eq!(unwrap(&format!("{emoji}{hidden}"), Skin::ZeroWidth).unwrap(), b"payload");
+
+        assert_eq!(Skin::detect("SGVsbG8="), None);
+        assert_eq!(remove("a!", Skin::Homoglyph), Err("Invalid skin symbol"));
+        assert_eq!(Skin::from_name("zero-width"), Ok(Skin::ZeroWidth));
+        let lookalikes: Vec<char> = HOMOGLYPHS.iter().map(|&(_, c)| c).collect();
+        assert!(lookalikes.iter().enumerate().all(|(i, c)| !lookalikes[..i].contains(c)));
+    }
+}
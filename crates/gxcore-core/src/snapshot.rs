@@ -0,0 +1,206 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::archive::CONTENT_METADATA;
+use crate::envelope::{Envelope, PayloadFormat};
+use crate::CompressionAlgorithm;
+
+/// Value of `CONTENT_METADATA` for snapshots
+pub const SNAPSHOT_CONTENT: &str = "gxsnap";
+/// Bytes of state compared at once by incremental snapshots
+pub const BLOCK_SIZE: usize = 64 * 1024;
+
+const CREATED_AT_METADATA: &str = "created_at";
+const APP_VERSION_METADATA: &str = "app_version";
+const CONTENT_HASH_METADATA: &str = "content_hash";
+const BASE_HASH_METADATA: &str = "base_hash";
+
+/// Snapshot fields readable without the seed, authenticated with the payload
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotMetadata {
+    /// Creation time in seconds since the Unix epoch
+    pub created_at: u64,
+    pub app_version: String,
+    /// SHA-256 of the state
+    pub content_hash: [u8; 32],
+    /// SHA-256 of the state an incremental snapshot was taken against
+    pub base_hash: Option<[u8; 32]>,
+}
+
+/// Block of state, carried or taken from the base at the same position
+#[derive(Serialize, Deserialize)]
+enum Block {
+    Data(#[serde(with = "serde_bytes")] Vec<u8>),
+    Unchanged,
+}
+
+/// State sealed in an authenticated envelope with its `SnapshotMetadata`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotFile {
+    pub envelope: Envelope,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+fn parse_hash(value: &str) -> Result<[u8; 32], &'static str> {
+    let mut hash = [0; 32];
+    hex::decode_to_slice(value, &mut hash).map_err(|_| "Invalid snapshot hash")?;
+    Ok(hash)
+}
+
+/// Snapshot of `state`, taken by `app_version`
+pub fn snapshot(state: &[u8], seed: &[u8], app_version: &str) -> Result<SnapshotFile, &'static str> {
+    let blocks = state.chunks(BLOCK_SIZE).map(|block| Block::Data(block.to_vec())).collect();
+    seal(state, blocks, None, seed, app_version)
+}
+
+/// Snapshot of `state` carrying only the `BLOCK_SIZE` blocks that differ from `previous`, the state
+/// of an earlier snapshot. Restoring it needs `previous` again.
+pub fn snapshot_incremental(
+    state: &[u8],
+    previous: &[u8],
+    seed: &[u8],
+    app_version: &str,
+) -> Result<SnapshotFile, &'static str> {
+    let mut previous_blocks = previous.chunks(BLOCK_SIZE);
+    let blocks = state
+        .chunks(BLOCK_SIZE)
+        .map(|block| match previous_blocks.next() {
+            Some(old) if old == block => Block::Unchanged,
+            _ => Block::Data(block.to_vec()),
+        })
+        .collect();
+    seal(state, blocks, Some(sha256(previous)), seed, app_version)
+}
+
+fn seal(
+    state: &[u8],
+    blocks: Vec<Block>,
+    base_hash: Option<[u8; 32]>,
+    seed: &[u8],
+    app_version: &str,
+) -> Result<SnapshotFile, &'static str> {
+    let data = bincode::serialize(&blocks).expect("blocks serialize");
+    let mut envelope = Envelope::seal(&data, seed, CompressionAlgorithm::Lz4)
+        .with_format(PayloadFormat::Bincode)
+        .with_metadata(CONTENT_METADATA, SNAPSHOT_CONTENT)
+        .with_metadata(CREATED_AT_METADATA, &now().to_string())
+        .with_metadata(APP_VERSION_METADATA, app_version)
+        .with_metadata(CONTENT_HASH_METADATA, &hex::encode(sha256(state)));
+    if let Some(base_hash) = base_hash {
+        envelope = envelope.with_metadata(BASE_HASH_METADATA, &hex::encode(base_hash));
+    }
+    Ok(SnapshotFile {
+        envelope: envelope.authenticate(seed)?,
+    })
+}
+
+impl SnapshotFile {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, &'static str> {
+        self.envelope.to_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let envelope = Envelope::parse(bytes)?;
+        if envelope.metadata.get(CONTENT_METADATA).map(String::as_str) != Some(SNAPSHOT_CONTENT) {
+            return Err("Envelope is not a snapshot");
+        }
+        Ok(SnapshotFile { envelope })
+    }
+
+    /// Metadata as recorded, unverified until the snapshot is restored
+    pub fn metadata(&self) -> Result<SnapshotMetadata, &'static str> {
+        let field = |key| self.envelope.metadata.get(key).map(String::as_str).ok_or("Missing snapshot metadata");
+        Ok(SnapshotMetadata {
+            created_at: field(CREATED_AT_METADATA)?.parse().map_err(|_| "Invalid snapshot creation time")?,
+            app_version: field(APP_VERSION_METADATA)?.to_string(),
+            content_hash: parse_hash(field(CONTENT_HASH_METADATA)?)?,
+            base_hash: self.envelope.metadata.get(BASE_HASH_METADATA).map(String::as_str).map(parse_hash).transpose()?,
+        })
+    }
+
+    /// Whether the snapshot needs the state of an earlier one to restore
+    pub fn is_incremental(&self) -> bool {
+        self.envelope.metadata.contains_key(BASE_HASH_METADATA)
+    }
+}
+
+/// State of `snapshot`, checking its authentication and, once rebuilt, its content hash.
+/// Incremental snapshots need `base`, the state they were taken against.
+pub fn restore(snapshot: &SnapshotFile, seed: &[u8], base: Option<&[u8]>) -> Result<Vec<u8>, &'static str> {
+    let metadata = snapshot.metadata()?;
+    let data = snapshot.envelope.open(seed)?;
+    let blocks: Vec<Block> = bincode::deserialize(&data).map_err(|_| "Bincode deserialization failed")?;
+
+    let base = match (metadata.base_hash, base) {
+        (None, _) => &[][..],
+        (Some(_), None) => return Err("Incremental snapshot needs its base"),
+        (Some(hash), Some(base)) if sha256(base) != hash => return Err("Snapshot base mismatch"),
+        (Some(_), Some(base)) => base,
+    };
+    let mut base_blocks = base.chunks(BLOCK_SIZE);
+    let mut state = Vec::new();
+    for block in blocks {
+        let old = base_blocks.next();
+        match block {
+            Block::Data(data) => state.extend_from_slice(&data),
+            Block::Unchanged => state.extend_from_slice(old.ok_or("Snapshot block missing from base")?),
+        }
+    }
+    if sha256(&state) != metadata.content_hash {
+        return Err("Snapshot content hash mismatch");
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::sample_data;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let state = sample_data(3 * BLOCK_SIZE + 100);
+        let bytes = snapshot(&state, b"seed", "1.4.2").unwrap().to_bytes().unwrap();
+        let file = SnapshotFile::from_bytes(&bytes).unwrap();
+        let metadata = file.metadata().unwrap();
+        assert_eq!(metadata.app_version, "1.4.2");
+        assert_eq!(metadata.content_hash, sha256(&state));
+        assert!(metadata.created_at > 0);
+        assert!(!file.is_incremental());
+        assert_eq!(restore(&file, b"seed", None).unwrap(), state);
+        assert_eq!(restore(&file, b"other", None), Err("Envelope authentication failed"));
+
+        let plain = Envelope::seal(b"data", b"seed", CompressionAlgorithm::None).to_bytes().unwrap();
+        assert_eq!(SnapshotFile::from_bytes(&plain), Err("Envelope is not a snapshot"));
+    }
+
+    #[test]
+    fn test_incremental_snapshot() {
+        let previous = sample_data(4 * BLOCK_SIZE);
+        let mut state = previous.clone();
+        state[BLOCK_SIZE + 7] ^= 0xff;
+        state.extend_from_slice(b"grown");
+
+        let full = snapshot(&state, b"seed", "1.0").unwrap().to_bytes().unwrap();
+        let file = snapshot_incremental(&state, &previous, b"seed", "1.0").unwrap();
+        assert!(file.is_incremental());
+        assert!(file.to_bytes().unwrap().len() < full.len());
+        assert_eq!(file.metadata().unwrap().base_hash, Some(sha256(&previous)));
+
+        assert_eq!(restore(&file, b"seed", Some(&previous)).unwrap(), state);
+        assert_eq!(restore(&file, b"seed", None), Err("Incremental snapshot needs its base"));
+        assert_eq!(restore(&file, b"seed", Some(&state)), Err("Snapshot base mismatch"));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::log::Log;
+use crate::{auto, CompressionAlgorithm};
+
+/// Change to the store, one per log record
+#[derive(Serialize, Deserialize)]
+enum Op {
+    Put {
+        #[serde(with = "serde_bytes")]
+        key: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        value: Vec<u8>,
+    },
+    Delete {
+        #[serde(with = "serde_bytes")]
+        key: Vec<u8>,
+    },
+}
+
+/// Key-value store persisted as a `Log` of puts and deletes, for caching data at rest without a
+/// database. Opening replays the log into memory, so reads never touch the file; each value is
+/// written with the compression `auto::choose` picks for it. Overwritten and deleted values stay in
+/// the log until `compact` rewrites it with only the live ones.
+pub struct GxStore {
+    path: PathBuf,
+    seed: Vec<u8>,
+    log: Log,
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Log records no longer needed to rebuild `entries`
+    stale: usize,
+}
+
+impl GxStore {
+    /// Open the store at `path`, creating it when missing. Fails when a record does not decode
+    /// under `seed`; a torn tail from a crash is dropped as `Log::open` describes.
+    pub fn open(path: &Path, seed: &[u8]) -> io::Result<Self> {
+        let log = Log::open(path, seed, CompressionAlgorithm::None)?;
+        let mut entries = BTreeMap::new();
+        let mut stale = 0;
+        for record in log.iter()? {
+            let op = bincode::deserialize(&record?)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Bincode deserialization failed"))?;
+            match op {
+                Op::Put { key, value } => {
+                    if entries.insert(key, value).is_some() {
+                        stale += 1;
+                    }
+                }
+                Op::Delete { key } => {
+                    // The delete and the put it undoes
+                    stale += 1 + usize::from(entries.remove(&key).is_some());
+                }
+            }
+        }
+        Ok(GxStore {
+            path: path.to_path_buf(),
+            seed: seed.to_vec(),
+            log,
+            entries,
+            stale,
+        })
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(Vec::as_slice)
+    }
+
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Store `value` under `key`, replacing any previous value
+    pub fn put(&mut self, key: &[u8], value: &[u8]) -> io::Result<()> {
+        let op = Op::Put {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        };
+        append(&mut self.log, &op, auto::choose(value))?;
+        if self.entries.insert(key.to_vec(), value.to_vec()).is_some() {
+            self.stale += 1;
+        }
+        Ok(())
+    }
+
+    /// Remove `key`, returning whether it was present. Absent keys write nothing.
+    pub fn delete(&mut self, key: &[u8]) -> io::Result<bool> {
+        if !self.entries.contains_key(key) {
+            return Ok(false);
+        }
+        append(&mut self.log, &Op::Delete { key: key.to_vec() }, CompressionAlgorithm::None)?;
+        self.entries.remove(key);
+        self.stale += 2;
+        Ok(true)
+    }
+
+    /// Entries in key order
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.entries.iter().map(|(key, value)| (key.as_slice(), value.as_slice()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Flush written changes to disk
+    pub fn sync(&self) -> io::Result<()> {
+        self.log.sync()
+    }
+
+    /// Log records that `compact` would drop
+    pub fn stale_records(&self) -> usize {
+        self.stale
+    }
+
+    /// Rewrite the log with one put per live entry. The new log is written beside the old one and
+    /// renamed over it, so the store stays intact if compaction fails.
+    pub fn compact(&mut self) -> io::Result<()> {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".compact");
+        let compacted = PathBuf::from(name);
+        // Left behind by a compaction that failed before
+        match fs::remove_file(&compacted) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+
+        let write = || -> io::Result<()> {
+            let mut log = Log::open(&compacted, &self.seed, CompressionAlgorithm::None)?;
+            for (key, value) in &self.entries {
+                let op = Op::Put {
+                    key: key.clone(),
+                    value: value.clone(),
+                };
+                append(&mut log, &op, auto::choose(value))?;
+            }
+            log.sync()
+        };
+        if let Err(e) = write() {
+            let _ = fs::remove_file(&compacted);
+            return Err(e);
+        }
+        fs::rename(&compacted, &self.path)?;
+        self.log = Log::open(&self.path, &self.seed, CompressionAlgorithm::None)?;
+        self.stale = 0;
+        Ok(())
+    }
+}
+
+/// Append `op` to `log` as one record
+fn append(log: &mut Log, op: &Op, compression: CompressionAlgorithm) -> io::Result<()> {
+    let record = bincode::serialize(op)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Bincode serialization failed"))?;
+    log.append_with(&record, compression).map(drop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::sample_data;
+
+    #[test]
+    fn test_store_persists_and_compacts() {
+        let dir = std::env::temp_dir().join(format!("gx-core-store-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cache.gxdb");
+
+        let mut store = GxStore::open(&path, b"seed").unwrap();
+        store.put(b"a", b"first").unwrap();
+        store.put(b"b", &sample_data(20_000)).unwrap();
+        store.put(b"a", b"second").unwrap();
+        store.put(b"c", b"third").unwrap();
+        assert!(store.delete(b"c").unwrap());
+        assert!(!store.delete(b"missing").unwrap());
+        store.sync().unwrap();
+        assert_eq!(store.stale_records(), 3);
+        drop(store);
+
+        let mut store = GxStore::open(&path, b"seed").unwrap();
+        assert_eq!(store.get(b"a"), Some(&b"second"[..]));
+        assert_eq!(store.get(b"b"), Some(sample_data(20_000).as_slice()));
+        assert!(!store.contains_key(b"c"));
+        assert_eq!(store.stale_records(), 3);
+
+        let before = fs::metadata(&path).unwrap().len();
+        store.compact().unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < before);
+        assert_eq!(store.stale_records(), 0);
+        store.put(b"d", b"after compaction").unwrap();
+        drop(store);
+
+        let store = GxStore::open(&path, b"seed").unwrap();
+        let keys: Vec<_> = store.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, [&b"a"[..], b"b", b"d"]);
+        assert_eq!(store.get(b"d"), Some(&b"after compaction"[..]));
+        assert!(GxStore::open(&path, b"other").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,411 @@
+use std::io::{self, BufRead, Read, Write};
+
+use crate::metrics::{self, Operation};
+use crate::translate::Translator;
+use crate::{armor_into, compress_into, decode, derive_alphabet, encode, CompressionAlgorithm};
+
+/// Input bytes per frame unless configured otherwise
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Frames never contain this byte, since encoded output only uses the alphabet and padding
+pub const FRAME_DELIMITER: u8 = b'\n';
+
+/// Compressed chunks waiting to be encoded before the compressing thread blocks
+const PIPELINE_DEPTH: usize = 2;
+
+/// Upper bound of an encoded frame for a given chunk size, including compression overhead
+pub fn max_frame_len(chunk_size: usize) -> usize {
+    (chunk_size + chunk_size / 255 + 32) * 4 / 3 + 8
+}
+
+/// Incremental encoder emitting one newline-terminated frame per chunk of input
+pub struct StreamEncoder {
+    seed: Vec<u8>,
+    compression: CompressionAlgorithm,
+    chunk_size: usize,
+    buffer: Vec<u8>,
+}
+
+impl StreamEncoder {
+    pub fn new(seed: &[u8], compression: CompressionAlgorithm) -> Self {
+        StreamEncoder {
+            seed: seed.to_vec(),
+            compression,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    fn push_frame(&self, chunk: &[u8], out: &mut Vec<u8>) {
+        out.extend_from_slice(&encode(chunk, &self.seed, self.compression));
+        out.push(FRAME_DELIMITER);
+    }
+
+    /// Feed input, returning the frames of every completed chunk
+    pub fn update(&mut self, mut data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        if !self.buffer.is_empty() {
+            let take = (self.chunk_size - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.buffer.len() < self.chunk_size {
+                return out;
+            }
+            let chunk = std::mem::take(&mut self.buffer);
+            self.push_frame(&chunk, &mut out);
+        }
+
+        let mut chunks = data.chunks_exact(self.chunk_size);
+        for chunk in &mut chunks {
+            self.push_frame(chunk, &mut out);
+        }
+        self.buffer.extend_from_slice(chunks.remainder());
+        out
+    }
+
+    /// Emit the frame of the final partial chunk
+    pub fn finish(self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if !self.buffer.is_empty() {
+            self.push_frame(&self.buffer, &mut out);
+        }
+        out
+    }
+}
+
+/// Incremental decoder for the output of `StreamEncoder`
+pub struct StreamDecoder {
+    seed: Vec<u8>,
+    /// Other seeds tried on the first frame, e.g. older versions of a rotated key
+    fallback_seeds: Vec<Vec<u8>>,
+    compression: CompressionAlgorithm,
+    max_frame_len: usize,
+    buffer: Vec<u8>,
+}
+
+impl StreamDecoder {
+    pub fn new(seed: &[u8], compression: CompressionAlgorithm) -> Self {
+        StreamDecoder {
+            seed: seed.to_vec(),
+            fallback_seeds: Vec::new(),
+            compression,
+            max_frame_len: max_frame_len(DEFAULT_CHUNK_SIZE),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Match the chunk size used for encoding, which bounds the buffered frame size
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.max_frame_len = max_frame_len(chunk_size.max(1));
+        self
+    }
+
+    /// Seeds to try when the first frame does not decode with the primary seed.
+    /// The first one that works is used for the rest of the stream.
+    pub fn with_fallback_seeds(mut self, seeds: Vec<Vec<u8>>) -> Self {
+        self.fallback_seeds = seeds;
+        self
+    }
+
+    fn decode_frame(&mut self, frame: &[u8], out: &mut Vec<u8>) -> Result<(), &'static str> {
+        let result = decode(frame, &self.seed, self.compression);
+        let decoded = match result {
+            Err(e) if !self.fallback_seeds.is_empty() => {
+                let fallback = std::mem::take(&mut self.fallback_seeds)
+                    .into_iter()
+                    .find_map(|seed| decode(frame, &seed, self.compression).ok().map(|d| (seed, d)));
+                let (seed, decoded) = fallback.ok_or(e)?;
+                self.seed = seed;
+                decoded
+            }
+            result => {
+                self.fallback_seeds.clear();
+                result?
+            }
+        };
+        out.extend_from_slice(&decoded);
+        Ok(())
+    }
+
+    /// Feed encoded input, returning the data of every completed frame
+    pub fn update(&mut self, data: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let mut out = Vec::new();
+        let mut rest = data;
+        while let Some(pos) = rest.iter().position(|&b| b == FRAME_DELIMITER) {
+            if self.buffer.is_empty() {
+                self.decode_frame(&rest[..pos], &mut out)?;
+            } else {
+                self.buffer.extend_from_slice(&rest[..pos]);
+                let frame = std::mem::take(&mut self.buffer);
+                self.decode_frame(&frame, &mut out)?;
+            }
+            rest = &rest[pos + 1..];
+        }
+
+        self.buffer.extend_from_slice(rest);
+        if self.buffer.len() > self.max_frame_len {
+            return Err("Frame too large");
+        }
+        Ok(out)
+    }
+
+    /// Decode a final frame missing its delimiter, if any
+    pub fn finish(mut self) -> Result<Vec<u8>, &'static str> {
+        let mut out = Vec::new();
+        if !self.buffer.is_empty() {
+            let frame = std::mem::take(&mut self.buffer);
+            self.decode_frame(&frame, &mut out)?;
+        }
+        Ok(out)
+    }
+}
+
+/// Encode everything from `reader` into `writer`, returning the number of input bytes
+pub fn encode_stream<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    seed: &[u8],
+    compression: CompressionAlgorithm,
+) -> io::Result<u64> {
+    let mut encoder = StreamEncoder::new(seed, compression);
+    let mut buf = vec![0u8; DEFAULT_CHUNK_SIZE];
+    let mut total = 0u64;
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        total += n as u64;
+        writer.write_all(&encoder.update(&buf[..n]))?;
+    }
+    writer.write_all(&encoder.finish())?;
+    writer.flush()?;
+    Ok(total)
+}
+
+/// Encode everything from `reader` into `writer` as `encode_stream` does, byte for byte, but
+/// compress the next chunk on a worker thread while the current one is encoded and written, so
+/// throughput approaches that of the slower stage rather than their sum
+pub fn encode_stream_pipelined<R: Read + Send, W: Write>(
+    reader: R,
+    writer: W,
+    seed: &[u8],
+    compression: CompressionAlgorithm,
+) -> io::Result<u64> {
+    encode_chunks_pipelined(ReadChunks { reader, done: false }, writer, seed, compression)
+}
+
+/// Full `DEFAULT_CHUNK_SIZE` chunks from `reader` but the last, matching the frames of `StreamEncoder`
+struct ReadChunks<R> {
+    reader: R,
+    done: bool,
+}
+
+impl<R: Read> Iterator for ReadChunks<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut chunk = vec![0u8; DEFAULT_CHUNK_SIZE];
+        let mut len = 0;
+        while len < chunk.len() {
+            match self.reader.read(&mut chunk[len..]) {
+                Ok(0) => self.done = true,
+                Ok(n) => len += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+            if self.done {
+                break;
+            }
+        }
+        chunk.truncate(len);
+        (len > 0).then_some(Ok(chunk))
+    }
+}
+
+/// Write one frame per chunk of `chunks`, which must all be `DEFAULT_CHUNK_SIZE` long but the
+/// last, compressing on a worker thread ahead of the encoding. Returns the number of input bytes.
+pub(crate) fn encode_chunks_pipelined<C, I, W>(chunks: I, writer: W, seed: &[u8], compression: CompressionAlgorithm) -> io::Result<u64>
+where
+    C: AsRef<[u8]>,
+    I: Iterator<Item = io::Result<C>> + Send,
+    W: Write,
+{
+    // With one core, or none to spawn on as under WASI, the worker would only take turns
+    let threaded = !cfg!(target_family = "wasm") && std::thread::available_parallelism().is_ok_and(|n| n.get() > 1);
+    encode_chunks(chunks, writer, seed, compression, threaded)
+}
+
+fn encode_chunks<C, I, W>(chunks: I, mut writer: W, seed: &[u8], compression: CompressionAlgorithm, threaded: bool) -> io::Result<u64>
+where
+    C: AsRef<[u8]>,
+    I: Iterator<Item = io::Result<C>> + Send,
+    W: Write,
+{
+    let compress = |chunk: C| {
+        let mut processed = Vec::new();
+        compress_into(chunk.as_ref(), compression, true, &mut processed);
+        (chunk.as_ref().len(), processed)
+    };
+    let translator = Translator::encoding(&derive_alphabet(seed));
+    let mut frame = Vec::new();
+    let mut total = 0u64;
+    let mut write_frame = |compressed: io::Result<(usize, Vec<u8>)>| {
+        let (len, processed) = compressed?;
+        total += len as u64;
+        frame.clear();
+        armor_into(&processed, &translator, &mut frame);
+        metrics::record(Operation::Encode, len, Ok(frame.len()));
+        frame.push(FRAME_DELIMITER);
+        writer.write_all(&frame)
+    };
+
+    if threaded {
+        std::thread::scope(|scope| {
+            let (sender, receiver) = std::sync::mpsc::sync_channel(PIPELINE_DEPTH);
+            scope.spawn(move || {
+                for chunk in chunks {
+                    let compressed = chunk.map(compress);
+                    let failed = compressed.is_err();
+                    // A closed channel means writing failed and the error is already on its way back
+                    if sender.send(compressed).is_err() || failed {
+                        break;
+                    }
+                }
+            });
+            // Returning early drops the receiver, which stops the worker at its next send
+            receiver.into_iter().try_for_each(&mut write_frame)
+        })?;
+    } else {
+        chunks.map(|chunk| chunk.map(compress)).try_for_each(&mut write_frame)?;
+    }
+
+    writer.flush()?;
+    Ok(total)
+}
+
+/// Decode frames from `reader` into `writer`, returning the number of output bytes
+pub fn decode_stream<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    seed: &[u8],
+    compression: CompressionAlgorithm,
+) -> io::Result<u64> {
+    let mut decoder = StreamDecoder::new(seed, compression);
+    let mut total = 0u64;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        let n = buf.len();
+        let out = decoder.update(buf).map_err(io::Error::other)?;
+        reader.consume(n);
+        total += out.len() as u64;
+        writer.write_all(&out)?;
+    }
+    let out = decoder.finish().map_err(io::Error::other)?;
+    total += out.len() as u64;
+    writer.write_all(&out)?;
+    writer.flush()?;
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunked_roundtrip() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        let mut encoder = StreamEncoder::new(b"seed", CompressionAlgorithm::Lz4).with_chunk_size(1000);
+        let mut encoded = Vec::new();
+        // Uneven writes exercise chunk boundaries
+        for piece in data.chunks(777) {
+            encoded.extend(encoder.update(piece));
+        }
+        encoded.extend(encoder.finish());
+        assert_eq!(encoded.iter().filter(|&&b| b == FRAME_DELIMITER).count(), 10);
+
+        let mut decoder = StreamDecoder::new(b"seed", CompressionAlgorithm::Lz4).with_chunk_size(1000);
+        let mut decoded = Vec::new();
+        for piece in encoded.chunks(333) {
+            decoded.extend(decoder.update(piece).unwrap());
+        }
+        decoded.extend(decoder.finish().unwrap());
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_io_helpers() {
+        let data = vec![7u8; 200_000];
+        let mut encoded = Vec::new();
+        encode_stream(data.as_slice(), &mut encoded, b"seed", CompressionAlgorithm::None).unwrap();
+        let mut decoded = Vec::new();
+        let n = decode_stream(encoded.as_slice(), &mut decoded, b"seed", CompressionAlgorithm::None).unwrap();
+        assert_eq!(n, data.len() as u64);
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_pipelined_matches_sequential() {
+        for len in [0, 10, DEFAULT_CHUNK_SIZE, DEFAULT_CHUNK_SIZE * 3 + 12_345] {
+            let data: Vec<u8> = (0..len as u32).map(|i| (i % 251) as u8).collect();
+            for compression in [CompressionAlgorithm::None, CompressionAlgorithm::Lz4] {
+                let (mut sequential, mut pipelined) = (Vec::new(), Vec::new());
+                encode_stream(data.as_slice(), &mut sequential, b"seed", compression).unwrap();
+                // Short reads must not change where frames split
+                let (head, tail) = data.split_at(len.min(777));
+                for threaded in [false, true] {
+                    pipelined.clear();
+                    let chunks = ReadChunks { reader: head.chain(tail), done: false };
+                    let n = encode_chunks(chunks, &mut pipelined, b"seed", compression, threaded).unwrap();
+                    assert_eq!(n, len as u64);
+                    assert_eq!(pipelined, sequential);
+                }
+            }
+        }
+
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk gone"))
+            }
+        }
+        for threaded in [false, true] {
+            let chunks = ReadChunks { reader: Failing, done: false };
+            let error = encode_chunks(chunks, Vec::new(), b"seed", CompressionAlgorithm::Lz4, threaded).unwrap_err();
+            assert_eq!(error.to_string(), "disk gone");
+        }
+    }
+
+    #[test]
+    fn test_fallback_seed() {
+        let mut encoder = StreamEncoder::new(b"old", CompressionAlgorithm::None).with_chunk_size(4);
+        let mut encoded = encoder.update(b"rotated key");
+        encoded.extend(encoder.finish());
+
+        let mut decoder =
+            StreamDecoder::new(b"new", CompressionAlgorithm::None).with_fallback_seeds(vec![b"old".to_vec()]);
+        let mut decoded = decoder.update(&encoded).unwrap();
+        decoded.extend(decoder.finish().unwrap());
+        assert_eq!(decoded, b"rotated key");
+    }
+
+    #[test]
+    fn test_oversized_frame_rejected() {
+        let mut decoder = StreamDecoder::new(b"seed", CompressionAlgorithm::None).with_chunk_size(16);
+        assert_eq!(decoder.update(&[b'A'; 100]), Err("Frame too large"));
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+
+use crate::envelope::{Envelope, IntegrityMode, PayloadFormat};
+use crate::CompressionAlgorithm;
+
+/// Claims carried by a token
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    /// Issue time in seconds since the Unix epoch
+    pub iat: u64,
+    /// Expiry in seconds since the Unix epoch
+    pub exp: u64,
+    pub custom: BTreeMap<String, String>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl Claims {
+    /// Claims for `sub` valid for `ttl` from now
+    pub fn new(sub: &str, ttl: Duration) -> Self {
+        let iat = now();
+        Claims {
+            sub: sub.to_string(),
+            iat,
+            exp: iat.saturating_add(ttl.as_secs()),
+            custom: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_claim(mut self, key: &str, value: &str) -> Self {
+        self.custom.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        now >= self.exp
+    }
+}
+
+/// Issue a URL-safe token for the claims, authenticated with the seed
+pub fn issue(claims: &Claims, seed: &[u8]) -> Result<String, &'static str> {
+    let data = bincode::serialize(claims).map_err(|_| "Claims serialization failed")?;
+    let envelope = Envelope::seal(&data, seed, CompressionAlgorithm::None)
+        .with_format(PayloadFormat::Bincode)
+        .authenticate(seed)?;
+    Ok(URL_SAFE_NO_PAD.encode(envelope.to_bytes()?))
+}
+
+/// Validate a token and return its claims if it is authentic and not expired
+pub fn validate(token: &str, seed: &[u8]) -> Result<Claims, &'static str> {
+    validate_at(token, seed, now())
+}
+
+/// Validate a token against an explicit current time in seconds since the Unix epoch
+pub fn validate_at(token: &str, seed: &[u8], now: u64) -> Result<Claims, &'static str> {
+    let bytes = URL_SAFE_NO_PAD.decode(token).map_err(|_| "Malformed token")?;
+    let envelope = Envelope::from_bytes(&bytes)?;
+    // Never accept a token that is only checksummed
+    if envelope.header.integrity != IntegrityMode::HmacSha256 {
+        return Err("Token is not authenticated");
+    }
+    if envelope.header.format != PayloadFormat::Bincode {
+        return Err("Malformed token");
+    }
+
+    let data = envelope.open(seed)?;
+    let claims: Claims = bincode::deserialize(&data).map_err(|_| "Malformed token")?;
+    if claims.is_expired_at(now) {
+        return Err("Token expired");
+    }
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_validate() {
+        let claims = Claims::new("service-a", Duration::from_secs(60)).with_claim("scope", "encode");
+        let token = issue(&claims, b"token_seed").unwrap();
+        assert!(token.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+        assert_eq!(validate(&token, b"token_seed").unwrap(), claims);
+        assert_eq!(validate(&token, b"wrong_seed"), Err("Envelope authentication failed"));
+    }
+
+    #[test]
+    fn test_expired_token() {
+        let claims = Claims::new("service-a", Duration::from_secs(60));
+        let token = issue(&claims, b"token_seed").unwrap();
+        assert_eq!(validate_at(&token, b"token_seed", claims.exp), Err("Token expired"));
+    }
+
+    #[test]
+    fn test_unauthenticated_envelope_rejected() {
+        let claims = Claims::new("service-a", Duration::from_secs(60));
+        let data = bincode::serialize(&claims).unwrap();
+        let envelope = Envelope::seal(&data, b"token_seed", CompressionAlgorithm::None)
+            .with_format(PayloadFormat::Bincode);
+        let token = URL_SAFE_NO_PAD.encode(envelope.to_bytes().unwrap());
+        assert_eq!(validate(&token, b"token_seed"), Err("Token is not authenticated"));
+    }
+}
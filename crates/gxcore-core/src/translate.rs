@@ -0,0 +1,502 @@
+use crate::BASE64_ALPHABET;
+
+/// Marks bytes outside the alphabet in the scalar table; no alphabet character has this value
+const INVALID: u8 = 0xff;
+
+/// Bytes the scalar path translates before checking for invalid ones, the base64 of 48 input bytes
+const BLOCK: usize = 64;
+/// Data bytes behind one block of base64
+const BLOCK_DATA: usize = BLOCK / 4 * 3;
+
+/// Substitution between the standard base64 alphabet and a seeded one, leaving `=` padding as is.
+/// Translates 16 or 32 bytes per step with SSSE3/SSE4.1, AVX2 or NEON table lookups when the CPU
+/// has them, byte by byte otherwise. `armor` and `unarmor` skip the standard text altogether,
+/// going between data and the seeded alphabet one 48-byte / 64-character block at a time through
+/// the same lookups.
+pub struct Translator {
+    /// Output character for each character
+    substitute: Lookup,
+    /// 6-bit value of the output for each character, what the character decodes to
+    values: Lookup,
+}
+
+/// Map from the 64 alphabet characters to bytes, `INVALID` for anything else
+struct Lookup {
+    /// Output by the character's index in the standard alphabet, for the vector paths and for
+    /// looking up indices
+    table: [u8; 64],
+    /// Output for each byte
+    scalar: [u8; 256],
+}
+
+impl Lookup {
+    fn new(table: [u8; 64], padding: Option<u8>) -> Self {
+        let mut scalar = [INVALID; 256];
+        for (&from, &to) in BASE64_ALPHABET.iter().zip(&table) {
+            scalar[usize::from(from)] = to;
+        }
+        if let Some(padding) = padding {
+            scalar[usize::from(b'=')] = padding;
+        }
+        Lookup { table, scalar }
+    }
+
+    /// Map `input` into `out` of the same length, failing on a byte outside the alphabet
+    fn map(&self, input: &[u8], out: &mut [u8]) -> Result<(), &'static str> {
+        // Vector paths stop at the first block with padding or an invalid byte, left to the scalar one
+        let done = self.simd(input, out, false);
+        self.map_scalar(&input[done..], &mut out[done..])
+    }
+
+    fn map_scalar(&self, input: &[u8], out: &mut [u8]) -> Result<(), &'static str> {
+        // Whole blocks are looked up without a branch per byte, then checked at once
+        for (from, to) in input.chunks(BLOCK).zip(out.chunks_mut(BLOCK)) {
+            let mut invalid = false;
+            for (to, &from) in to.iter_mut().zip(from) {
+                *to = self.scalar[usize::from(from)];
+                invalid |= *to == INVALID;
+            }
+            if invalid {
+                return Err("Invalid character");
+            }
+        }
+        Ok(())
+    }
+
+    /// Output for each of `indices`, all below 64, into `out` of the same length
+    fn lookup(&self, indices: &[u8], out: &mut [u8]) {
+        let done = self.simd(indices, out, true);
+        for (to, &index) in out[done..].iter_mut().zip(&indices[done..]) {
+            *to = self.table[usize::from(index)];
+        }
+    }
+
+    /// Map whole vectors of `input` into `out`, returning the bytes done. With `indexed`, `input`
+    /// holds indices below 64 rather than characters.
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    fn simd(&self, input: &[u8], out: &mut [u8], indexed: bool) -> usize {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: the CPU supports AVX2
+            unsafe { x86::translate_avx2(&self.table, input, out, indexed) }
+        } else if is_x86_feature_detected!("ssse3") && is_x86_feature_detected!("sse4.1") {
+            // SAFETY: the CPU supports SSSE3 and SSE4.1
+            unsafe { x86::translate_sse(&self.table, input, out, indexed) }
+        } else {
+            0
+        }
+    }
+
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    fn simd(&self, input: &[u8], out: &mut [u8], indexed: bool) -> usize {
+        // SAFETY: the target enables NEON
+        unsafe { neon::translate(&self.table, input, out, indexed) }
+    }
+
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", all(target_arch = "aarch64", target_feature = "neon"))))]
+    fn simd(&self, _input: &[u8], _out: &mut [u8], _indexed: bool) -> usize {
+        0
+    }
+}
+
+/// Index of `c` in the standard alphabet
+fn standard_index(c: u8) -> u8 {
+    BASE64_ALPHABET.iter().position(|&s| s == c).expect("alphabets hold the same characters") as u8
+}
+
+impl Translator {
+    fn new(table: [u8; 64]) -> Self {
+        Translator {
+            substitute: Lookup::new(table, Some(b'=')),
+            values: Lookup::new(table.map(standard_index), None),
+        }
+    }
+
+    /// Standard base64 to `alphabet`, as encoding does
+    pub fn encoding(alphabet: &[u8; 64]) -> Self {
+        Translator::new(*alphabet)
+    }
+
+    /// `alphabet` back to standard base64, as decoding does
+    pub fn decoding(alphabet: &[u8; 64]) -> Self {
+        let mut table = [0; 64];
+        for (index, &c) in alphabet.iter().enumerate() {
+            table[usize::from(standard_index(c))] = BASE64_ALPHABET[index];
+        }
+        Translator::new(table)
+    }
+
+    /// Append the translation of `input` to `out`, failing on a byte outside the alphabet and `=`.
+    /// On failure `out` is left as it was.
+    pub fn translate(&self, input: &[u8], out: &mut Vec<u8>) -> Result<(), &'static str> {
+        let start = out.len();
+        out.resize(start + input.len(), 0);
+        self.substitute.map(input, &mut out[start..]).inspect_err(|_| out.truncate(start))
+    }
+
+    /// `translate` without vector instructions, for comparison in benchmarks
+    pub fn translate_scalar(&self, input: &[u8], out: &mut Vec<u8>) -> Result<(), &'static str> {
+        let start = out.len();
+        out.resize(start + input.len(), 0);
+        self.substitute.map_scalar(input, &mut out[start..]).inspect_err(|_| out.truncate(start))
+    }
+
+    /// Append the base64 of `data` to `out`, written straight in the output alphabet of an encoding
+    /// translator. Each 48 bytes fill one 64-character block; the last group is padded with `=`.
+    pub fn armor(&self, data: &[u8], out: &mut Vec<u8>) {
+        let start = out.len();
+        out.resize(start + data.len().div_ceil(3) * 4, 0);
+        let mut indices = [0; BLOCK];
+        for (from, to) in data.chunks(BLOCK_DATA).zip(out[start..].chunks_mut(BLOCK)) {
+            // The block's 6-bit indices, then their characters looked up together
+            let (groups, rest) = from.as_chunks::<3>();
+            let (slots, _) = indices.as_chunks_mut::<4>();
+            for (&[a, b, c], slot) in groups.iter().zip(&mut *slots) {
+                *slot = [a >> 2, (a << 4 | b >> 4) & 0x3f, (b << 2 | c >> 6) & 0x3f, c & 0x3f];
+            }
+            if let Some(&a) = rest.first() {
+                let b = rest.get(1).copied().unwrap_or(0);
+                slots[groups.len()][..3].copy_from_slice(&[a >> 2, (a << 4 | b >> 4) & 0x3f, b << 2 & 0x3f]);
+            }
+            self.substitute.lookup(&indices[..to.len()], to);
+            // A last group of n bytes fills n + 1 characters, the rest is padding
+            let len = to.len();
+            to[len - (3 - rest.len()) % 3..].fill(b'=');
+        }
+    }
+
+    /// Append the data behind `encoded`, base64 in the input alphabet of a decoding translator, to
+    /// `out`. Padding and trailing bits must be canonical, as for the standard decoder. On failure
+    /// `out` is left as it was.
+    pub fn unarmor(&self, encoded: &[u8], out: &mut Vec<u8>) -> Result<(), &'static str> {
+        if !encoded.len().is_multiple_of(4) {
+            return Err("Invalid base64");
+        }
+        let padding = encoded.iter().rev().take(2).take_while(|&&c| c == b'=').count();
+        let start = out.len();
+        out.resize(start + encoded.len() / 4 * 3 - padding, 0);
+        self.unarmor_blocks(&encoded[..encoded.len() - padding], &mut out[start..])
+            .inspect_err(|_| out.truncate(start))
+    }
+
+    /// `unarmor` of `encoded` without its padding into `out`, sized for the data
+    fn unarmor_blocks(&self, encoded: &[u8], out: &mut [u8]) -> Result<(), &'static str> {
+        for (from, to) in encoded.chunks(BLOCK).zip(out.chunks_mut(BLOCK_DATA)) {
+            let mut values = [0; BLOCK];
+            self.values.map(from, &mut values[..from.len()]).map_err(|e| {
+                // `=` is in neither alphabet, but only misplaced rather than foreign
+                if from.iter().find(|c| !BASE64_ALPHABET.contains(c)) == Some(&b'=') { "Invalid base64" } else { e }
+            })?;
+            let (groups, _) = values.as_chunks::<4>();
+            let (slots, rest) = to.as_chunks_mut::<3>();
+            for (&[a, b, c, d], slot) in groups.iter().zip(&mut *slots) {
+                *slot = [a << 2 | b >> 4, b << 4 | c >> 2, c << 6 | d];
+            }
+            // The last group of the last block may be short, its missing values left at zero
+            if !rest.is_empty() {
+                let [a, b, c, _] = groups[slots.len()];
+                let bytes = [a << 2 | b >> 4, b << 4 | c >> 2, c << 6];
+                rest.copy_from_slice(&bytes[..rest.len()]);
+                // Bits past the last byte must be zero, as the standard decoder requires
+                if bytes[rest.len()] != 0 {
+                    return Err("Invalid base64");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Each character is first mapped to its index in the standard alphabet with range compares,
+/// then looked up in the 64-byte table as four 16-byte shuffles, selected by index bits 4 and 5.
+/// Indices computed by the caller skip the first step.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    /// Bytes within `lo..=hi`; bytes from 0x80 compare as negative and never match
+    #[target_feature(enable = "sse4.1")]
+    fn in_range_sse(c: __m128i, lo: u8, hi: u8) -> __m128i {
+        _mm_and_si128(_mm_cmpgt_epi8(c, _mm_set1_epi8(lo as i8 - 1)), _mm_cmpgt_epi8(_mm_set1_epi8(hi as i8 + 1), c))
+    }
+
+    /// Index of every character in the standard alphabet, `None` if any is outside it
+    #[target_feature(enable = "sse4.1")]
+    fn index_sse(c: __m128i) -> Option<__m128i> {
+        let upper = in_range_sse(c, b'A', b'Z');
+        let lower = in_range_sse(c, b'a', b'z');
+        let digit = in_range_sse(c, b'0', b'9');
+        let plus = _mm_cmpeq_epi8(c, _mm_set1_epi8(b'+' as i8));
+        let slash = _mm_cmpeq_epi8(c, _mm_set1_epi8(b'/' as i8));
+        let valid = _mm_or_si128(_mm_or_si128(_mm_or_si128(upper, lower), _mm_or_si128(digit, plus)), slash);
+        if _mm_movemask_epi8(valid) != 0xffff {
+            return None;
+        }
+        let shift = _mm_or_si128(
+            _mm_or_si128(_mm_and_si128(upper, _mm_set1_epi8(-65)), _mm_and_si128(lower, _mm_set1_epi8(-71))),
+            _mm_or_si128(
+                _mm_or_si128(_mm_and_si128(digit, _mm_set1_epi8(4)), _mm_and_si128(plus, _mm_set1_epi8(19))),
+                _mm_and_si128(slash, _mm_set1_epi8(16)),
+            ),
+        );
+        Some(_mm_add_epi8(c, shift))
+    }
+
+    #[target_feature(enable = "ssse3,sse4.1")]
+    fn lookup_sse(table: &[__m128i; 4], index: __m128i) -> __m128i {
+        // Indices are below 64, so the shuffles use their low four bits and never zero a lane
+        let bit4 = _mm_slli_epi16(index, 3);
+        let bit5 = _mm_slli_epi16(index, 2);
+        let low = _mm_blendv_epi8(_mm_shuffle_epi8(table[0], index), _mm_shuffle_epi8(table[1], index), bit4);
+        let high = _mm_blendv_epi8(_mm_shuffle_epi8(table[2], index), _mm_shuffle_epi8(table[3], index), bit4);
+        _mm_blendv_epi8(low, high, bit5)
+    }
+
+    /// Look up whole vectors of `input` into `out` while they hold only alphabet characters, or
+    /// indices below 64 with `indexed`, returning the bytes done
+    #[target_feature(enable = "ssse3,sse4.1")]
+    pub fn translate_sse(table: &[u8; 64], input: &[u8], out: &mut [u8], indexed: bool) -> usize {
+        // SAFETY: each load reads 16 of the table's 64 bytes
+        let table = [0, 16, 32, 48].map(|at| unsafe { _mm_loadu_si128(table[at..].as_ptr().cast()) });
+        let mut done = 0;
+        for (block, to) in input.chunks_exact(16).zip(out.chunks_exact_mut(16)) {
+            // SAFETY: `block` holds 16 bytes
+            let c = unsafe { _mm_loadu_si128(block.as_ptr().cast()) };
+            let Some(index) = (if indexed { Some(c) } else { index_sse(c) }) else {
+                break;
+            };
+            // SAFETY: `to` holds 16 bytes
+            unsafe { _mm_storeu_si128(to.as_mut_ptr().cast(), lookup_sse(&table, index)) };
+            done += 16;
+        }
+        done
+    }
+
+    #[target_feature(enable = "avx2")]
+    fn in_range_avx2(c: __m256i, lo: u8, hi: u8) -> __m256i {
+        _mm256_and_si256(_mm256_cmpgt_epi8(c, _mm256_set1_epi8(lo as i8 - 1)), _mm256_cmpgt_epi8(_mm256_set1_epi8(hi as i8 + 1), c))
+    }
+
+    #[target_feature(enable = "avx2")]
+    fn index_avx2(c: __m256i) -> Option<__m256i> {
+        let upper = in_range_avx2(c, b'A', b'Z');
+        let lower = in_range_avx2(c, b'a', b'z');
+        let digit = in_range_avx2(c, b'0', b'9');
+        let plus = _mm256_cmpeq_epi8(c, _mm256_set1_epi8(b'+' as i8));
+        let slash = _mm256_cmpeq_epi8(c, _mm256_set1_epi8(b'/' as i8));
+        let valid = _mm256_or_si256(_mm256_or_si256(_mm256_or_si256(upper, lower), _mm256_or_si256(digit, plus)), slash);
+        if _mm256_movemask_epi8(valid) != -1 {
+            return None;
+        }
+        let shift = _mm256_or_si256(
+            _mm256_or_si256(_mm256_and_si256(upper, _mm256_set1_epi8(-65)), _mm256_and_si256(lower, _mm256_set1_epi8(-71))),
+            _mm256_or_si256(
+                _mm256_or_si256(_mm256_and_si256(digit, _mm256_set1_epi8(4)), _mm256_and_si256(plus, _mm256_set1_epi8(19))),
+                _mm256_and_si256(slash, _mm256_set1_epi8(16)),
+            ),
+        );
+        Some(_mm256_add_epi8(c, shift))
+    }
+
+    #[target_feature(enable = "avx2")]
+    fn lookup_avx2(table: &[__m256i; 4], index: __m256i) -> __m256i {
+        // Shuffles work within 128-bit lanes, so each table is repeated in both
+        let bit4 = _mm256_slli_epi16(index, 3);
+        let bit5 = _mm256_slli_epi16(index, 2);
+        let low = _mm256_blendv_epi8(_mm256_shuffle_epi8(table[0], index), _mm256_shuffle_epi8(table[1], index), bit4);
+        let high = _mm256_blendv_epi8(_mm256_shuffle_epi8(table[2], index), _mm256_shuffle_epi8(table[3], index), bit4);
+        _mm256_blendv_epi8(low, high, bit5)
+    }
+
+    /// `translate_sse` 32 bytes at a time
+    #[target_feature(enable = "avx2")]
+    pub fn translate_avx2(table: &[u8; 64], input: &[u8], out: &mut [u8], indexed: bool) -> usize {
+        // SAFETY: each load reads 16 of the table's 64 bytes
+        let lanes = [0, 16, 32, 48].map(|at| _mm256_broadcastsi128_si256(unsafe { _mm_loadu_si128(table[at..].as_ptr().cast()) }));
+        let mut done = 0;
+        for (block, to) in input.chunks_exact(32).zip(out.chunks_exact_mut(32)) {
+            // SAFETY: `block` holds 32 bytes
+            let c = unsafe { _mm256_loadu_si256(block.as_ptr().cast()) };
+            let Some(index) = (if indexed { Some(c) } else { index_avx2(c) }) else {
+                break;
+            };
+            // SAFETY: `to` holds 32 bytes
+            unsafe { _mm256_storeu_si256(to.as_mut_ptr().cast(), lookup_avx2(&lanes, index)) };
+            done += 32;
+        }
+        // A final block of 16 to 31 bytes still fits the SSE path
+        done + translate_sse(table, &input[done..], &mut out[done..], indexed)
+    }
+}
+
+/// As on x86, with the 64-byte table looked up in one `TBL` instruction
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+mod neon {
+    use std::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    fn in_range(c: uint8x16_t, lo: u8, hi: u8) -> uint8x16_t {
+        vandq_u8(vcgeq_u8(c, vdupq_n_u8(lo)), vcleq_u8(c, vdupq_n_u8(hi)))
+    }
+
+    /// Index of every character in the standard alphabet, `None` if any is outside it
+    #[target_feature(enable = "neon")]
+    fn index(c: uint8x16_t) -> Option<uint8x16_t> {
+        let upper = in_range(c, b'A', b'Z');
+        let lower = in_range(c, b'a', b'z');
+        let digit = in_range(c, b'0', b'9');
+        let plus = vceqq_u8(c, vdupq_n_u8(b'+'));
+        let slash = vceqq_u8(c, vdupq_n_u8(b'/'));
+        let valid = vorrq_u8(vorrq_u8(vorrq_u8(upper, lower), vorrq_u8(digit, plus)), slash);
+        if vminvq_u8(valid) != 0xff {
+            return None;
+        }
+        let shift = vorrq_u8(
+            vorrq_u8(vandq_u8(upper, vdupq_n_u8(65u8.wrapping_neg())), vandq_u8(lower, vdupq_n_u8(71u8.wrapping_neg()))),
+            vorrq_u8(vorrq_u8(vandq_u8(digit, vdupq_n_u8(4)), vandq_u8(plus, vdupq_n_u8(19))), vandq_u8(slash, vdupq_n_u8(16))),
+        );
+        Some(vaddq_u8(c, shift))
+    }
+
+    #[target_feature(enable = "neon")]
+    pub fn translate(table: &[u8; 64], input: &[u8], out: &mut [u8], indexed: bool) -> usize {
+        // SAFETY: the load reads the table's 64 bytes
+        let table = unsafe { vld1q_u8_x4(table.as_ptr()) };
+        let mut done = 0;
+        for (block, to) in input.chunks_exact(16).zip(out.chunks_exact_mut(16)) {
+            // SAFETY: `block` holds 16 bytes
+            let c = unsafe { vld1q_u8(block.as_ptr()) };
+            let Some(index) = (if indexed { Some(c) } else { index(c) }) else {
+                break;
+            };
+            // SAFETY: `to` holds 16 bytes
+            unsafe { vst1q_u8(to.as_mut_ptr(), vqtbl4q_u8(table, index)) };
+            done += 16;
+        }
+        done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derive_alphabet;
+
+    /// Every alphabet character, then padding, repeated past several vector blocks
+    fn sample(len: usize) -> Vec<u8> {
+        BASE64_ALPHABET.iter().copied().cycle().take(len.saturating_sub(2)).chain(*b"==").take(len).collect()
+    }
+
+    type Translated = Result<Vec<u8>, &'static str>;
+
+    /// Output of the fastest path and of the scalar one
+    fn both(translator: &Translator, input: &[u8]) -> (Translated, Translated) {
+        let mut fast = Vec::new();
+        let mut scalar = Vec::new();
+        (
+            translator.translate(input, &mut fast).map(|_| fast),
+            translator.translate_scalar(input, &mut scalar).map(|_| scalar),
+        )
+    }
+
+    #[test]
+    fn test_vector_paths_match_scalar() {
+        for seed in [&b""[..], b"seed", b"\xff\x00"] {
+            let alphabet = derive_alphabet(seed);
+            let (encoding, decoding) = (Translator::encoding(&alphabet), Translator::decoding(&alphabet));
+            for len in 0..200 {
+                let input = sample(len);
+                let (fast, scalar) = both(&encoding, &input);
+                assert_eq!(fast, scalar);
+                let encoded = fast.unwrap();
+                assert_eq!(both(&decoding, &encoded), (Ok(input.clone()), Ok(input)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_bytes_rejected_anywhere() {
+        let translator = Translator::decoding(&derive_alphabet(b"seed"));
+        for invalid in [b'!', b'-', b'_', b'\n', 0, 0x80, 0xc3, 0xff] {
+            for at in [0, 15, 16, 31, 40, 99] {
+                let mut input = sample(100);
+                input[at] = invalid;
+                assert_eq!(both(&translator, &input), (Err("Invalid character"), Err("Invalid character")));
+                let mut out = b"kept".to_vec();
+                assert!(translator.translate(&input, &mut out).is_err());
+                assert_eq!(out, b"kept");
+            }
+        }
+    }
+
+    #[test]
+    fn test_armor_matches_standard_base64_then_translate() {
+        use base64::{Engine as _, engine::general_purpose};
+
+        let alphabet = derive_alphabet(b"seed");
+        let (encoding, decoding) = (Translator::encoding(&alphabet), Translator::decoding(&alphabet));
+        // Around the block size, so whole blocks, short tails and padding all occur
+        for len in (0..100).chain([143, 144, 145, 1000]) {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let mut expected = Vec::new();
+            encoding.translate(general_purpose::STANDARD.encode(&data).as_bytes(), &mut expected).unwrap();
+            let mut armored = Vec::new();
+            encoding.armor(&data, &mut armored);
+            assert_eq!(armored, expected);
+
+            let mut out = b"kept".to_vec();
+            decoding.unarmor(&armored, &mut out).unwrap();
+            assert_eq!(out[4..], data);
+        }
+    }
+
+    #[test]
+    fn test_unarmor_rejects_what_the_standard_decoder_does() {
+        let alphabet = derive_alphabet(b"seed");
+        let (encoding, decoding) = (Translator::encoding(&alphabet), Translator::decoding(&alphabet));
+        let mut armored = Vec::new();
+        encoding.armor(&[0xaa; 100], &mut armored);
+        let cases = [
+            (armored[..armored.len() - 1].to_vec(), "Invalid base64"),
+            ([&armored[..8], &b"="[..], &armored[9..]].concat(), "Invalid base64"),
+            ([&armored[..70], &b"!"[..], &armored[71..]].concat(), "Invalid character"),
+        ];
+        for (input, error) in cases {
+            let mut out = b"kept".to_vec();
+            assert_eq!(decoding.unarmor(&input, &mut out), Err(error));
+            assert_eq!(out, b"kept");
+        }
+
+        // 100 bytes leave one byte in the last group, so its second character carries 4 spare bits
+        let last = armored.len() - 3;
+        let spare = alphabet.iter().position(|&c| c == armored[last]).unwrap() ^ 1;
+        armored[last] = alphabet[spare];
+        assert_eq!(decoding.unarmor(&armored, &mut Vec::new()), Err("Invalid base64"));
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn test_sse_path_on_avx2_machines() {
+        if !(is_x86_feature_detected!("ssse3") && is_x86_feature_detected!("sse4.1")) {
+            return;
+        }
+        let alphabet = derive_alphabet(b"seed");
+        let input = sample(100);
+        let mut out = [0; 100];
+        // SAFETY: support was checked above
+        let done = unsafe { x86::translate_sse(&alphabet, &input, &mut out, false) };
+        assert_eq!(done, 96);
+        let mut expected = Vec::new();
+        Translator::encoding(&alphabet).translate_scalar(&input[..96], &mut expected).unwrap();
+        assert_eq!(out[..96], expected);
+
+        // Indices go straight to the table
+        let indices: Vec<u8> = (0..64).collect();
+        let mut out = [0; 64];
+        // SAFETY: support was checked above
+        assert_eq!(unsafe { x86::translate_sse(&alphabet, &indices, &mut out, true) }, 64);
+        assert_eq!(out, alphabet);
+    }
+}
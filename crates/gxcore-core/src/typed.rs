@@ -0,0 +1,94 @@
+use std::any::type_name;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::envelope::{Envelope, PayloadFormat};
+use crate::CompressionAlgorithm;
+
+/// Metadata entry naming the Rust type of a bincode payload. Type names are only stable within
+/// one build of the code, which is all these helpers are meant for.
+pub const TYPE_METADATA: &str = "type";
+
+/// Serialize a value with bincode and seal it in an envelope recording its type
+pub fn encode_bincode<T: Serialize>(
+    value: &T,
+    seed: &[u8],
+    compression: CompressionAlgorithm,
+) -> Result<Vec<u8>, &'static str> {
+    let data = bincode::serialize(value).map_err(|_| "Bincode serialization failed")?;
+    let envelope = Envelope::seal(&data, seed, compression)
+        .with_format(PayloadFormat::Bincode)
+        .with_metadata(TYPE_METADATA, type_name::<T>());
+    envelope.to_bytes()
+}
+
+/// Open an envelope produced by `encode_bincode` and deserialize its value, refusing one sealed
+/// for another type even when its bytes would parse as `T`
+pub fn decode_bincode<T: DeserializeOwned>(encoded: &[u8], seed: &[u8]) -> Result<T, &'static str> {
+    let envelope = Envelope::parse(encoded)?;
+    if envelope.header.format != PayloadFormat::Bincode {
+        return Err("Payload is not bincode");
+    }
+    if envelope.metadata.get(TYPE_METADATA).map(String::as_str) != Some(type_name::<T>()) {
+        return Err("Payload holds another type");
+    }
+    let data = envelope.open(seed)?;
+    bincode::deserialize(&data).map_err(|_| "Bincode deserialization failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Transfer {
+        from: String,
+        to: String,
+        lamports: u64,
+    }
+
+    #[test]
+    fn test_bincode_roundtrip() {
+        let transfer = Transfer {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            lamports: 42,
+        };
+        let encoded = encode_bincode(&transfer, b"seed", CompressionAlgorithm::Lz4).unwrap();
+        let decoded: Transfer = decode_bincode(&encoded, b"seed").unwrap();
+        assert_eq!(decoded, transfer);
+    }
+
+    #[test]
+    fn test_bincode_rejects_raw_payload() {
+        let raw = Envelope::seal(b"raw bytes", b"seed", CompressionAlgorithm::None).to_bytes().unwrap();
+        assert_eq!(decode_bincode::<Transfer>(&raw, b"seed"), Err("Payload is not bincode"));
+    }
+
+    #[test]
+    fn test_bincode_rejects_other_type_with_same_layout() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Refund {
+            account: String,
+            reason: String,
+            lamports: u64,
+        }
+
+        let transfer = Transfer {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            lamports: 42,
+        };
+        let encoded = encode_bincode(&transfer, b"seed", CompressionAlgorithm::None).unwrap();
+        assert_eq!(decode_bincode::<Refund>(&encoded, b"seed"), Err("Payload holds another type"));
+
+        // Untagged bincode of the same bytes would have parsed
+        let untagged = Envelope::seal(&bincode::serialize(&transfer).unwrap(), b"seed", CompressionAlgorithm::None)
+            .with_format(PayloadFormat::Bincode)
+            .to_bytes().unwrap();
+        assert_eq!(decode_bincode::<Refund>(&untagged, b"seed"), Err("Payload holds another type"));
+        let refund: Refund = bincode::deserialize(&Envelope::parse(&untagged).unwrap().open(b"seed").unwrap()).unwrap();
+        assert_eq!((refund.account.as_str(), refund.reason.as_str(), refund.lamports), ("alice", "bob", 42));
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::File;
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::{mpsc, OnceLock};
+
+use io_uring::{opcode, squeue, types, IoUring};
+
+use crate::stream::{max_frame_len, DEFAULT_CHUNK_SIZE, FRAME_DELIMITER};
+use crate::translate::Translator;
+use crate::{decode_translated, derive_alphabet, encode_translated, CompressionAlgorithm, Scratch};
+
+/// Reads and writes submitted at once; reads take at most half, so written frames are never held back
+const QUEUE_DEPTH: usize = 16;
+
+/// Encoded bytes per read when decoding, each holding many frames
+const DECODE_READ_SIZE: usize = 16 * DEFAULT_CHUNK_SIZE;
+
+/// Chunks or frames queued for and held by each worker, bounding the memory in use
+const PENDING_PER_WORKER: usize = 4;
+
+/// Whether the kernel lets this process set up a ring. Containers and seccomp profiles often do not.
+pub fn available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| IoUring::new(2).is_ok())
+}
+
+/// Encode `input` from `input_offset` into frames written to `output` from `output_offset`,
+/// identical to those of `encode_stream` for the same bytes. Reads and writes go through io_uring
+/// while chunks are encoded on one worker thread per core. `progress` is called with the number of
+/// input bytes of each read. Returns the number of input bytes.
+pub fn encode(
+    input: &File,
+    input_offset: u64,
+    output: &File,
+    output_offset: u64,
+    seed: &[u8],
+    compression: CompressionAlgorithm,
+    progress: impl FnMut(u64),
+) -> io::Result<u64> {
+    let translator = Translator::encoding(&derive_alphabet(seed));
+    let encode = |chunk: &[u8], scratch: &mut Scratch, out: &mut Vec<u8>| {
+        encode_translated(chunk, &translator, compression, true, scratch, out);
+        out.push(FRAME_DELIMITER);
+        Ok(())
+    };
+    // Every read is a chunk and every chunk a frame
+    let split = |chunk: Vec<u8>, _last: bool| Ok(vec![chunk]);
+    let (read, _) = run(input, input_offset, output, output_offset, DEFAULT_CHUNK_SIZE, split, encode, progress)?;
+    Ok(read)
+}
+
+/// Decode the frames of `input` from `input_offset`, as `decode_stream` does, into `output` from
+/// `output_offset`, with io_uring and worker threads as `encode` has them. Returns the number of
+/// output bytes.
+pub fn decode(
+    input: &File,
+    input_offset: u64,
+    output: &File,
+    output_offset: u64,
+    seed: &[u8],
+    compression: CompressionAlgorithm,
+    progress: impl FnMut(u64),
+) -> io::Result<u64> {
+    let translator = Translator::decoding(&derive_alphabet(seed));
+    let decode = |frame: &[u8], scratch: &mut Scratch, out: &mut Vec<u8>| {
+        decode_translated(frame, &translator, compression, true, scratch, out)
+    };
+    // Frames crossing the end of a read wait for the next one
+    let mut partial = Vec::new();
+    let split = move |window: Vec<u8>, last: bool| {
+        partial.extend_from_slice(&window);
+        let mut frames: Vec<Vec<u8>> = Vec::new();
+        let mut rest = partial.as_slice();
+        while let Some(pos) = rest.iter().position(|&b| b == FRAME_DELIMITER) {
+            frames.push(rest[..pos].to_vec());
+            rest = &rest[pos + 1..];
+        }
+        if rest.len() > max_frame_len(DEFAULT_CHUNK_SIZE) {
+            return Err(io::Error::other("Frame too large"));
+        }
+        if last && !rest.is_empty() {
+            frames.push(rest.to_vec());
+            rest = &[];
+        }
+        partial = rest.to_vec();
+        Ok(frames)
+    };
+    let (_, written) = run(input, input_offset, output, output_offset, DECODE_READ_SIZE, split, decode, progress)?;
+    Ok(written)
+}
+
+/// A read or write in flight, owning the buffer the kernel uses
+enum Op {
+    Read { index: u64, offset: u64, buf: Vec<u8>, done: usize },
+    Write { offset: u64, buf: Vec<u8>, done: usize },
+}
+
+impl Op {
+    fn entry(&mut self, input: RawFd, output: RawFd) -> squeue::Entry {
+        match self {
+            Op::Read { offset, buf, done, .. } => {
+                let rest = &mut buf[*done..];
+                opcode::Read::new(types::Fd(input), rest.as_mut_ptr(), rest.len() as u32)
+                    .offset(*offset + *done as u64)
+                    .build()
+            }
+            Op::Write { offset, buf, done } => {
+                let rest = &buf[*done..];
+                opcode::Write::new(types::Fd(output), rest.as_ptr(), rest.len() as u32)
+                    .offset(*offset + *done as u64)
+                    .build()
+            }
+        }
+    }
+}
+
+/// A ring between two files, resubmitting short reads and writes until they complete
+struct Ring {
+    ring: IoUring,
+    input: RawFd,
+    output: RawFd,
+    ops: HashMap<u64, Op>,
+    next_tag: u64,
+    reads: usize,
+}
+
+impl Ring {
+    fn new(input: &File, output: &File) -> io::Result<Self> {
+        Ok(Ring {
+            ring: IoUring::new(QUEUE_DEPTH as u32)?,
+            input: input.as_raw_fd(),
+            output: output.as_raw_fd(),
+            ops: HashMap::new(),
+            next_tag: 0,
+            reads: 0,
+        })
+    }
+
+    /// Submit `op`, or the rest of a short or interrupted one again, under `tag`
+    fn push(&mut self, tag: u64, mut op: Op) {
+        let entry = op.entry(self.input, self.output).user_data(tag);
+        // SAFETY: the buffer the entry points into is owned by `op`, kept in `ops` until the
+        // kernel completes it, and `Drop` waits for every one, or leaks them if it cannot
+        unsafe { self.ring.submission().push(&entry) }.expect("no more ops in flight than the queue holds");
+        if matches!(op, Op::Read { .. }) {
+            self.reads += 1;
+        }
+        self.ops.insert(tag, op);
+    }
+
+    fn submit(&mut self, op: Op) {
+        self.next_tag += 1;
+        self.push(self.next_tag, op);
+    }
+
+    /// Wait for at least one completion, returning the ops that finished
+    fn complete(&mut self) -> io::Result<Vec<Op>> {
+        self.ring.submit_and_wait(1)?;
+        let completions: Vec<_> = self.ring.completion().map(|cqe| (cqe.user_data(), cqe.result())).collect();
+        let mut finished = Vec::new();
+        let mut failure = None;
+        // Every completion is accounted for even after a failure, so `Drop` only waits for ops in flight
+        for (tag, result) in completions {
+            let mut op = self.ops.remove(&tag).expect("completions match submitted ops");
+            if matches!(op, Op::Read { .. }) {
+                self.reads -= 1;
+            }
+            if failure.is_some() {
+                continue;
+            }
+            if result < 0 {
+                let error = io::Error::from_raw_os_error(-result);
+                match error.kind() {
+                    io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock => self.push(tag, op),
+                    _ => failure = Some(error),
+                }
+                continue;
+            }
+            let (Op::Read { buf, done, .. } | Op::Write { buf, done, .. }) = &mut op;
+            *done += result as usize;
+            if *done == buf.len() {
+                finished.push(op);
+            } else if result > 0 {
+                self.push(tag, op);
+            } else {
+                failure = Some(match op {
+                    Op::Read { .. } => io::Error::new(io::ErrorKind::UnexpectedEof, "input shrank while being read"),
+                    Op::Write { .. } => io::ErrorKind::WriteZero.into(),
+                });
+            }
+        }
+        match failure {
+            Some(error) => Err(error),
+            None => Ok(finished),
+        }
+    }
+}
+
+impl Drop for Ring {
+    fn drop(&mut self) {
+        // After a failure, ops still in flight must not outlive their buffers
+        while !self.ops.is_empty() {
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    // The kernel may still own them, so leak the buffers rather than free them
+                    mem::forget(mem::take(&mut self.ops));
+                    break;
+                }
+            }
+            let tags: Vec<u64> = self.ring.completion().map(|cqe| cqe.user_data()).collect();
+            for tag in tags {
+                self.ops.remove(&tag);
+            }
+        }
+    }
+}
+
+/// Read `input` from `input_offset` in `read_size` pieces, `split` them in order into jobs, `work`
+/// each on a worker thread, and write the results to `output` from `output_offset` in order.
+/// Returns the bytes read and written.
+#[allow(clippy::too_many_arguments)]
+fn run<S, F>(
+    input: &File,
+    input_offset: u64,
+    output: &File,
+    output_offset: u64,
+    read_size: usize,
+    mut split: S,
+    work: F,
+    mut progress: impl FnMut(u64),
+) -> io::Result<(u64, u64)>
+where
+    S: FnMut(Vec<u8>, bool) -> io::Result<Vec<Vec<u8>>>,
+    F: Fn(&[u8], &mut Scratch, &mut Vec<u8>) -> Result<(), &'static str> + Sync,
+{
+    let len = input.metadata()?.len().saturating_sub(input_offset);
+    let reads = len.div_ceil(read_size as u64);
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut ring = Ring::new(input, output)?;
+
+    std::thread::scope(|scope| {
+        let (results, finished) = mpsc::channel::<(u64, Result<Vec<u8>, &'static str>)>();
+        let jobs: Vec<mpsc::Sender<(u64, Vec<u8>)>> = (0..workers)
+            .map(|_| {
+                let (sender, receiver) = mpsc::channel::<(u64, Vec<u8>)>();
+                let (results, work) = (results.clone(), &work);
+                scope.spawn(move || {
+                    let mut scratch = Scratch::default();
+                    for (index, job) in receiver {
+                        let mut out = Vec::new();
+                        let result = work(&job, &mut scratch, &mut out).map(|()| out);
+                        if results.send((index, result)).is_err() {
+                            break;
+                        }
+                    }
+                });
+                sender
+            })
+            .collect();
+        drop(results);
+
+        let (mut next_read, mut next_split) = (0u64, 0u64);
+        let (mut dispatched, mut next_write) = (0u64, 0u64);
+        let mut write_offset = output_offset;
+        let mut read_pieces = BTreeMap::new();
+        let mut outputs = BTreeMap::new();
+        let mut writes = VecDeque::new();
+
+        loop {
+            while next_read < reads
+                && ring.reads < QUEUE_DEPTH / 2
+                && (dispatched - next_write) as usize + ring.reads < workers * PENDING_PER_WORKER
+            {
+                let offset = input_offset + next_read * read_size as u64;
+                let size = (len - next_read * read_size as u64).min(read_size as u64) as usize;
+                ring.submit(Op::Read { index: next_read, offset, buf: vec![0; size], done: 0 });
+                next_read += 1;
+            }
+            while ring.ops.len() < QUEUE_DEPTH {
+                let Some((offset, buf)) = writes.pop_front() else { break };
+                ring.submit(Op::Write { offset, buf, done: 0 });
+            }
+
+            if !ring.ops.is_empty() {
+                for op in ring.complete()? {
+                    let Op::Read { index, buf, .. } = op else { continue };
+                    progress(buf.len() as u64);
+                    read_pieces.insert(index, buf);
+                    while let Some(piece) = read_pieces.remove(&next_split) {
+                        next_split += 1;
+                        for job in split(piece, next_split == reads)? {
+                            jobs[dispatched as usize % workers]
+                                .send((dispatched, job))
+                                .expect("workers run until the jobs are dropped");
+                            dispatched += 1;
+                        }
+                    }
+                }
+            } else if next_write < dispatched {
+                let (index, result) = finished.recv().expect("workers hold the sender until their jobs are done");
+                outputs.insert(index, result);
+            } else if next_read == reads && writes.is_empty() {
+                break;
+            }
+
+            outputs.extend(finished.try_iter());
+            while let Some(result) = outputs.remove(&next_write) {
+                let out = result.map_err(io::Error::other)?;
+                next_write += 1;
+                if !out.is_empty() {
+                    let size = out.len() as u64;
+                    writes.push_back((write_offset, out));
+                    write_offset += size;
+                }
+            }
+        }
+        Ok((len, write_offset - output_offset))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::sample_data;
+    use crate::stream::encode_stream;
+    use std::fs;
+
+    #[test]
+    fn test_matches_stream() {
+        if !available() {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("gx-core-uring-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (plain, encoded, decoded) = (dir.join("plain"), dir.join("encoded"), dir.join("decoded"));
+
+        for len in [0, 10, DEFAULT_CHUNK_SIZE, DECODE_READ_SIZE * 3 + 12_345] {
+            let data = sample_data(len);
+            fs::write(&plain, &data).unwrap();
+            let mut expected = Vec::new();
+            encode_stream(data.as_slice(), &mut expected, b"seed", CompressionAlgorithm::Lz4).unwrap();
+
+            let mut reported = 0;
+            let n = encode(&File::open(&plain).unwrap(), 0, &File::create(&encoded).unwrap(), 0, b"seed", CompressionAlgorithm::Lz4, |n| reported += n).unwrap();
+            assert_eq!((n, reported), (len as u64, len as u64));
+            assert_eq!(fs::read(&encoded).unwrap(), expected);
+
+            let n = decode(&File::open(&encoded).unwrap(), 0, &File::create(&decoded).unwrap(), 0, b"seed", CompressionAlgorithm::Lz4, |_| {}).unwrap();
+            assert_eq!(n, len as u64);
+            assert_eq!(fs::read(&decoded).unwrap(), data);
+        }
+
+        // Resuming after the first chunk appends the rest of the frames
+        let data = sample_data(DEFAULT_CHUNK_SIZE * 3);
+        fs::write(&plain, &data).unwrap();
+        let mut expected = Vec::new();
+        encode_stream(data.as_slice(), &mut expected, b"seed", CompressionAlgorithm::None).unwrap();
+        let first = expected.iter().position(|&b| b == FRAME_DELIMITER).unwrap() + 1;
+        fs::write(&encoded, &expected[..first]).unwrap();
+        let output = fs::OpenOptions::new().write(true).open(&encoded).unwrap();
+        encode(&File::open(&plain).unwrap(), DEFAULT_CHUNK_SIZE as u64, &output, first as u64, b"seed", CompressionAlgorithm::None, |_| {}).unwrap();
+        assert_eq!(fs::read(&encoded).unwrap(), expected);
+
+        let error = decode(&File::open(&encoded).unwrap(), 0, &File::create(&decoded).unwrap(), 0, b"wrong", CompressionAlgorithm::None, |_| {}).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Other);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,92 @@
+use crate::envelope::Envelope;
+use crate::{decode, encode, CompressionAlgorithm};
+
+/// Known input and the output every build must produce for it
+pub struct Vector {
+    pub name: &'static str,
+    pub seed: &'static [u8],
+    pub compression: CompressionAlgorithm,
+    pub data: &'static [u8],
+    pub encoded: &'static [u8],
+}
+
+/// Golden vectors pinning the alphabet derivation, checksum and compression framing
+pub const VECTORS: &[Vector] = &[
+    Vector {
+        name: "empty",
+        seed: b"seed",
+        compression: CompressionAlgorithm::None,
+        data: b"",
+        encoded: b"ZZZZZZ==",
+    },
+    Vector {
+        name: "text",
+        seed: b"gxcore",
+        compression: CompressionAlgorithm::None,
+        data: b"The quick brown fox jumps over the lazy dog",
+        encoded: b"obu41CYLhdFW1b82M3nv1btIqS6wndLTp26InOo21CgrtH6P5XNR1bgIt0OijGe=",
+    },
+    Vector {
+        name: "binary",
+        seed: b"\x00\x01\x02\xff",
+        compression: CompressionAlgorithm::None,
+        data: &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 255],
+        encoded: b"JJIZJ+eLp2QCZeidMJmPMHH+aFmQ",
+    },
+    Vector {
+        name: "lz4",
+        seed: b"seed",
+        compression: CompressionAlgorithm::Lz4,
+        data: b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaabbbbbbbbbbbbbbbb",
+        encoded: b"xZZZZiHcZoZyP2JiZPi6I2K6IffIPOl=",
+    },
+];
+
+/// Binary envelope of `golden envelope` sealed with seed `seed`, key id `k1` and HMAC integrity, in hex
+pub const ENVELOPE_VECTOR: &str = "475801000001010006006b65795f696402006b311c00000033734854335774664a577466713274544138696d61575143465a3d3d9c7c9fa273c243a78a28c93bd3f8bbb1c1ca9fc404e52e56ee7bd64201ffb0687b7d2165";
+
+impl Vector {
+    /// Encode and decode the vector, failing on any difference from the recorded values
+    pub fn check(&self) -> Result<(), String> {
+        if encode(self.data, self.seed, self.compression) != self.encoded {
+            return Err(format!("{}: encoding differs from the vector", self.name));
+        }
+        match decode(self.encoded, self.seed, self.compression) {
+            Ok(data) if data == self.data => Ok(()),
+            Ok(_) => Err(format!("{}: decoding differs from the vector", self.name)),
+            Err(e) => Err(format!("{}: {e}", self.name)),
+        }
+    }
+}
+
+/// Open `ENVELOPE_VECTOR` and seal its contents again, which must reproduce it byte for byte
+pub fn check_envelope() -> Result<(), String> {
+    let bytes = hex::decode(ENVELOPE_VECTOR).expect("vector is valid hex");
+    let envelope = Envelope::parse(&bytes).map_err(|e| format!("envelope: {e}"))?;
+    let data = envelope.open(b"seed").map_err(|e| format!("envelope: {e}"))?;
+    if data != b"golden envelope" || envelope.key_id() != Some("k1") {
+        return Err("envelope: contents differ from the vector".to_string());
+    }
+    let resealed = Envelope::seal(&data, b"seed", CompressionAlgorithm::None)
+        .with_key_id("k1")
+        .authenticate(b"seed")
+        .and_then(|envelope| envelope.to_bytes())
+        .map_err(|e| format!("envelope: {e}"))?;
+    if resealed != bytes {
+        return Err("envelope: sealing differs from the vector".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_golden_vectors() {
+        for vector in VECTORS {
+            vector.check().unwrap();
+        }
+        check_envelope().unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+//! Codec state shared across threads, as a server shares it across handlers
+
+use std::sync::Arc;
+
+use gxcore_core::bench::sample_data;
+use gxcore_core::cache::TranslatorCache;
+use gxcore_core::envelope::Envelope;
+use gxcore_core::stream::{StreamDecoder, StreamEncoder};
+use gxcore_core::translate::Translator;
+use gxcore_core::{decode, encode, CompressionAlgorithm, Scratch};
+
+#[test]
+fn test_codec_types_are_shareable() {
+    fn shareable<T: Send + Sync>() {}
+    fn sendable<T: Send>() {}
+    shareable::<Translator>();
+    shareable::<TranslatorCache>();
+    shareable::<Envelope>();
+    shareable::<StreamEncoder>();
+    shareable::<StreamDecoder>();
+    sendable::<Scratch>();
+}
+
+#[test]
+fn test_shared_cache_under_concurrent_encode_and_decode() {
+    // Fewer entries than seeds, so threads keep evicting translators others are using
+    let cache = Arc::new(TranslatorCache::new(2));
+    let seeds: [&[u8]; 3] = [b"", b"seed", b"other seed"];
+    let compressions = [CompressionAlgorithm::None, CompressionAlgorithm::Lz4, CompressionAlgorithm::Lz4Frame];
+    let messages: Vec<Vec<u8>> = (0..16).map(|i| sample_data(i * 997)).collect();
+
+    std::thread::scope(|scope| {
+        for thread in 0..8 {
+            let (cache, messages) = (cache.clone(), &messages);
+            scope.spawn(move || {
+                let mut scratch = Scratch::default();
+                let (mut encoded, mut decoded) = (Vec::new(), Vec::new());
+                for round in 0..100 {
+                    let seed = seeds[(thread + round) % seeds.len()];
+                    let message = &messages[(thread * 7 + round) % messages.len()];
+                    let compression = compressions[round % compressions.len()];
+                    encoded.clear();
+                    cache.encode_with(message, seed, compression, &mut scratch, &mut encoded);
+                    assert_eq!(decode(&encoded, seed, compression).unwrap(), *message);
+                    decoded.clear();
+                    cache.decode_with(&encode(message, seed, compression), seed, compression, &mut scratch, &mut decoded).unwrap();
+                    assert_eq!(decoded, *message);
+                    // Another seed fails without disturbing the shared translators
+                    let other = seeds[(thread + round + 1) % seeds.len()];
+                    assert!(cache.decode_with(&encoded, other, compression, &mut scratch, &mut decoded).is_err());
+                }
+            });
+        }
+    });
+    assert_eq!(cache.len(), 2);
+}
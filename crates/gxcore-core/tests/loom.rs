@@ -0,0 +1,52 @@
+//! Loom models of the translator cache, run with
+//! `RUSTFLAGS="--cfg loom" cargo test -p gxcore-core --test loom --release`
+#![cfg(loom)]
+
+use gxcore_core::cache::TranslatorCache;
+use gxcore_core::{decode, CompressionAlgorithm, Scratch};
+use loom::sync::Arc;
+use loom::thread;
+
+/// Run `f` under loom with two preemptions at most, which covers a lookup racing a miss or an
+/// eviction while keeping the run short enough for CI
+fn model(f: impl Fn() + Sync + Send + 'static) {
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound = Some(2);
+    builder.check(f);
+}
+
+/// Encode `data` through the cache and check it decodes as if no cache were involved
+fn roundtrip(cache: &TranslatorCache, seed: &[u8], data: &[u8]) {
+    let mut encoded = Vec::new();
+    cache.encode_with(data, seed, CompressionAlgorithm::None, &mut Scratch::default(), &mut encoded);
+    assert_eq!(decode(&encoded, seed, CompressionAlgorithm::None).unwrap(), data);
+}
+
+#[test]
+fn test_concurrent_misses_share_one_entry() {
+    model(|| {
+        let cache = Arc::new(TranslatorCache::new(1));
+        let other = {
+            let cache = cache.clone();
+            thread::spawn(move || cache.get(b"seed"))
+        };
+        let mine = cache.get(b"seed");
+        let theirs = other.join().unwrap();
+        assert!(Arc::ptr_eq(&mine, &theirs));
+        assert_eq!(cache.len(), 1);
+    });
+}
+
+#[test]
+fn test_eviction_keeps_capacity_and_translators_valid() {
+    model(|| {
+        let cache = Arc::new(TranslatorCache::new(1));
+        let other = {
+            let cache = cache.clone();
+            thread::spawn(move || roundtrip(&cache, b"first", b"from one thread"))
+        };
+        roundtrip(&cache, b"second", b"from another");
+        other.join().unwrap();
+        assert_eq!(cache.len(), 1);
+    });
+}
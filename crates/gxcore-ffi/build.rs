@@ -0,0 +1,37 @@
+fn main() {
+    // Leaves N-API symbols to be resolved by the Node process loading the library
+    #[cfg(feature = "node")]
+    napi_build::setup();
+
+    generate_header();
+
+    #[cfg(feature = "dotnet")]
+    generate_csharp();
+}
+
+/// Write `include/gxcore.h` for C and C++ callers from the C API in `src/lib.rs`
+fn generate_header() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let config = cbindgen::Config::from_file("cbindgen.toml").expect("Failed to read cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src("src/lib.rs")
+        .generate()
+        .expect("Failed to generate C header")
+        .write_to_file("include/gxcore.h");
+}
+
+/// Write the P/Invoke declarations of the .NET package from the C API in `src/lib.rs`
+#[cfg(feature = "dotnet")]
+fn generate_csharp() {
+    csbindgen::Builder::default()
+        .input_extern_file("src/lib.rs")
+        .csharp_dll_name("gxcore_ffi")
+        .csharp_namespace("Gxcore")
+        .csharp_class_name("NativeMethods")
+        .csharp_class_accessibility("internal")
+        .csharp_generate_const_filter(|name| name.starts_with("GX_"))
+        .generate_csharp_file("../../dotnet/Gxcore/NativeMethods.g.cs")
+        .expect("Failed to generate C# bindings");
+}
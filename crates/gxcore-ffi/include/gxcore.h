@@ -0,0 +1,189 @@
+/* Generated by cbindgen from src/lib.rs when building gxcore-ffi; do not edit. */
+
+#ifndef GXCORE_H
+#define GXCORE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define GX_COMPRESSION_NONE 0
+
+#define GX_COMPRESSION_LZ4 2
+
+#define GX_COMPRESSION_BROTLI 3
+
+#define GX_COMPRESSION_LZ4_FRAME 4
+
+/**
+ * Result of every call, zero on success
+ */
+typedef enum GxStatus {
+  GX_STATUS_OK = 0,
+  /**
+   * A required pointer was null, or an input pointer was null with a nonzero length
+   */
+  GX_STATUS_NULL_POINTER = 1,
+  /**
+   * The compression id is not one of the `GX_COMPRESSION_*` constants
+   */
+  GX_STATUS_UNKNOWN_COMPRESSION = 2,
+  /**
+   * The input is corrupt or was encoded with another seed or compression
+   */
+  GX_STATUS_DECODE_FAILED = 3,
+  /**
+   * The codec failed unexpectedly; nothing was written to the output
+   */
+  GX_STATUS_INTERNAL = 4,
+} GxStatus;
+
+/**
+ * Streaming decoder for the output of a `GxEncoder` or the CLI's `--stream` mode
+ */
+typedef struct GxDecoder GxDecoder;
+
+/**
+ * Streaming encoder, emitting newline-delimited frames as the CLI's `--stream` mode does
+ */
+typedef struct GxEncoder GxEncoder;
+
+/**
+ * Bytes allocated by the library, owned by the caller until passed to `gx_free`
+ */
+typedef struct GxBuffer {
+  uint8_t *data;
+  size_t len;
+} GxBuffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Encode `len` bytes of `data` as `/v1/encode/raw` does, storing the result in `out`.
+ *
+ * # Safety
+ * `data` and `seed` must be valid for reads of their lengths, or null with length zero, and
+ * `out` valid for writes. On success `out` must later be passed to `gx_free`.
+ */
+enum GxStatus gx_encode(const uint8_t *data,
+                        size_t len,
+                        const uint8_t *seed,
+                        size_t seed_len,
+                        uint8_t compression,
+                        struct GxBuffer *out);
+
+/**
+ * Decode output of `gx_encode` or of the HTTP API, storing the data in `out`.
+ *
+ * # Safety
+ * As for `gx_encode`.
+ */
+enum GxStatus gx_decode(const uint8_t *data,
+                        size_t len,
+                        const uint8_t *seed,
+                        size_t seed_len,
+                        uint8_t compression,
+                        struct GxBuffer *out);
+
+/**
+ * Start encoding a stream, storing the encoder in `out`.
+ *
+ * # Safety
+ * `seed` must be valid for reads of `seed_len` bytes, or null with `seed_len` zero, and `out`
+ * valid for writes. On success `out` must later be passed to `gx_encoder_finish` or
+ * `gx_encoder_free`.
+ */
+enum GxStatus gx_encoder_new(const uint8_t *seed,
+                             size_t seed_len,
+                             uint8_t compression,
+                             struct GxEncoder **out);
+
+/**
+ * Feed `len` bytes of `data`, storing the frames of every completed chunk, possibly none, in `out`.
+ *
+ * # Safety
+ * `encoder` must come from `gx_encoder_new` and not have been finished or freed, `data` be
+ * valid for reads of `len` bytes, or null with `len` zero, and `out` valid for writes.
+ */
+enum GxStatus gx_encoder_update(struct GxEncoder *encoder,
+                                const uint8_t *data,
+                                size_t len,
+                                struct GxBuffer *out);
+
+/**
+ * Store the frame of the final partial chunk in `out` and free the encoder, whatever the result.
+ *
+ * # Safety
+ * As for `gx_encoder_update`; the encoder cannot be used afterwards.
+ */
+enum GxStatus gx_encoder_finish(struct GxEncoder *encoder, struct GxBuffer *out);
+
+/**
+ * Abandon an encoder without finishing it; null is ignored.
+ *
+ * # Safety
+ * `encoder` must come from `gx_encoder_new` and not have been finished or freed.
+ */
+void gx_encoder_free(struct GxEncoder *encoder);
+
+/**
+ * Start decoding a stream, storing the decoder in `out`.
+ *
+ * # Safety
+ * As for `gx_encoder_new`, with `gx_decoder_finish` or `gx_decoder_free` to release it.
+ */
+enum GxStatus gx_decoder_new(const uint8_t *seed,
+                             size_t seed_len,
+                             uint8_t compression,
+                             struct GxDecoder **out);
+
+/**
+ * Feed encoded input, storing the data of every completed frame in `out`.
+ *
+ * # Safety
+ * As for `gx_encoder_update`, with a decoder from `gx_decoder_new`.
+ */
+enum GxStatus gx_decoder_update(struct GxDecoder *decoder,
+                                const uint8_t *data,
+                                size_t len,
+                                struct GxBuffer *out);
+
+/**
+ * Store the data of a final frame missing its delimiter in `out` and free the decoder,
+ * whatever the result.
+ *
+ * # Safety
+ * As for `gx_decoder_update`; the decoder cannot be used afterwards.
+ */
+enum GxStatus gx_decoder_finish(struct GxDecoder *decoder, struct GxBuffer *out);
+
+/**
+ * Abandon a decoder without finishing it; null is ignored.
+ *
+ * # Safety
+ * `decoder` must come from `gx_decoder_new` and not have been finished or freed.
+ */
+void gx_decoder_free(struct GxDecoder *decoder);
+
+/**
+ * Release a buffer returned by `gx_encode`, `gx_decode` or a stream call; a null `data` is ignored.
+ *
+ * # Safety
+ * `buffer` must come from this library unchanged and not have been freed already.
+ */
+void gx_free(struct GxBuffer buffer);
+
+/**
+ * Static, NUL-terminated description of a status
+ */
+const char *gx_status_message(enum GxStatus status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GXCORE_H */
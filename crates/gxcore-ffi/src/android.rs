@@ -0,0 +1,140 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use gxcore_core::CompressionAlgorithm;
+use jni::JNIEnv;
+use jni::objects::{JByteArray, JClass, JObject};
+use jni::sys::{jbyteArray, jint};
+use zeroize::Zeroizing;
+
+use crate::{compression, decode, encode, Codec, GxStatus};
+
+const ILLEGAL_ARGUMENT: &str = "java/lang/IllegalArgumentException";
+const ILLEGAL_STATE: &str = "java/lang/IllegalStateException";
+const NULL_POINTER: &str = "java/lang/NullPointerException";
+
+/// Why a call failed, raised in Java as an exception
+enum Failure {
+    /// A Java exception is already pending, such as one thrown by the seed provider
+    Pending,
+    /// Exception class and message to throw
+    Throw(&'static str, String),
+}
+
+impl From<jni::errors::Error> for Failure {
+    fn from(error: jni::errors::Error) -> Self {
+        match error {
+            jni::errors::Error::JavaException => Failure::Pending,
+            jni::errors::Error::NullPtr(what) | jni::errors::Error::NullDeref(what) => {
+                Failure::Throw(NULL_POINTER, format!("{what} is null"))
+            }
+            error => Failure::Throw(ILLEGAL_STATE, error.to_string()),
+        }
+    }
+}
+
+impl From<GxStatus> for Failure {
+    fn from(status: GxStatus) -> Self {
+        let class = match status {
+            GxStatus::Internal => ILLEGAL_STATE,
+            _ => ILLEGAL_ARGUMENT,
+        };
+        Failure::Throw(class, status.message().to_string_lossy().into_owned())
+    }
+}
+
+/// Compression from one of the `Gxcore.COMPRESSION_*` constants
+fn compression_of(id: jint) -> Result<CompressionAlgorithm, GxStatus> {
+    u8::try_from(id).map_err(|_| GxStatus::UnknownCompression).and_then(compression)
+}
+
+/// Ask the Java `SeedProvider` for the seed and overwrite the array it returned, leaving the
+/// returned copy as the only one, wiped when dropped
+fn take_seed(env: &mut JNIEnv, provider: &JObject) -> Result<Zeroizing<Vec<u8>>, Failure> {
+    if provider.is_null() {
+        return Err(Failure::Throw(NULL_POINTER, "seed provider is null".to_string()));
+    }
+    let array = JByteArray::from(env.call_method(provider, "seed", "()[B", &[])?.l()?);
+    if array.is_null() {
+        return Err(Failure::Throw(ILLEGAL_STATE, "seed provider returned null".to_string()));
+    }
+    let seed = Zeroizing::new(env.convert_byte_array(&array)?);
+    env.set_byte_array_region(&array, 0, &vec![0; seed.len()])?;
+    env.delete_local_ref(array)?;
+    Ok(seed)
+}
+
+/// Run `codec` over `data` with the provider's seed, which is held only for the call
+fn run<'local>(
+    env: &mut JNIEnv<'local>,
+    data: &JByteArray,
+    provider: &JObject,
+    compression_id: jint,
+    codec: Codec,
+) -> Result<JByteArray<'local>, Failure> {
+    let compression = compression_of(compression_id)?;
+    if data.is_null() {
+        return Err(Failure::Throw(NULL_POINTER, "data is null".to_string()));
+    }
+    let data = env.convert_byte_array(data)?;
+    let seed = take_seed(env, provider)?;
+    let output = panic::catch_unwind(AssertUnwindSafe(|| codec(&data, &seed, compression))).unwrap_or(Err(GxStatus::Internal))?;
+    drop(seed);
+    Ok(env.byte_array_from_slice(&output)?)
+}
+
+/// Hand `result` to Java, throwing and returning null on failure
+fn finish(env: &mut JNIEnv, result: Result<JByteArray, Failure>) -> jbyteArray {
+    match result {
+        Ok(array) => array.into_raw(),
+        Err(failure) => {
+            if let Failure::Throw(class, message) = failure {
+                // If even throwing fails the JVM is past reporting anything
+                let _ = env.throw_new(class, message);
+            }
+            JByteArray::default().into_raw()
+        }
+    }
+}
+
+/// `Gxcore.encode(byte[] data, SeedProvider seeds, int compression)`, matching `/v1/encode/raw`
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_gxcore_Gxcore_encode<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    data: JByteArray<'local>,
+    seeds: JObject<'local>,
+    compression: jint,
+) -> jbyteArray {
+    let result = run(&mut env, &data, &seeds, compression, encode);
+    finish(&mut env, result)
+}
+
+/// `Gxcore.decode(byte[] encoded, SeedProvider seeds, int compression)`, throwing
+/// `IllegalArgumentException` for corrupt input or the wrong seed
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_gxcore_Gxcore_decode<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    encoded: JByteArray<'local>,
+    seeds: JObject<'local>,
+    compression: jint,
+) -> jbyteArray {
+    let result = run(&mut env, &encoded, &seeds, compression, decode);
+    finish(&mut env, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GX_COMPRESSION_BROTLI, GX_COMPRESSION_LZ4, GX_COMPRESSION_NONE};
+
+    #[test]
+    fn test_compression_ids_match_c_api() {
+        assert_eq!(compression_of(GX_COMPRESSION_NONE.into()), Ok(CompressionAlgorithm::None));
+        assert_eq!(compression_of(GX_COMPRESSION_LZ4.into()), Ok(CompressionAlgorithm::Lz4));
+        assert_eq!(compression_of(GX_COMPRESSION_BROTLI.into()), Ok(CompressionAlgorithm::Brotli));
+        assert_eq!(compression_of(1), Err(GxStatus::UnknownCompression));
+        assert_eq!(compression_of(-1), Err(GxStatus::UnknownCompression));
+        assert_eq!(compression_of(256 + 2), Err(GxStatus::UnknownCompression));
+    }
+}
@@ -0,0 +1,446 @@
+use std::ffi::CStr;
+use std::panic::{self, AssertUnwindSafe};
+use std::{ptr, slice};
+
+use gxcore_core::stream::{StreamDecoder, StreamEncoder};
+use gxcore_core::CompressionAlgorithm;
+
+pub const GX_COMPRESSION_NONE: u8 = 0;
+pub const GX_COMPRESSION_LZ4: u8 = 2;
+pub const GX_COMPRESSION_BROTLI: u8 = 3;
+pub const GX_COMPRESSION_LZ4_FRAME: u8 = 4;
+
+/// Result of every call, zero on success
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GxStatus {
+    Ok = 0,
+    /// A required pointer was null, or an input pointer was null with a nonzero length
+    NullPointer = 1,
+    /// The compression id is not one of the `GX_COMPRESSION_*` constants
+    UnknownCompression = 2,
+    /// The input is corrupt or was encoded with another seed or compression
+    DecodeFailed = 3,
+    /// The codec failed unexpectedly; nothing was written to the output
+    Internal = 4,
+}
+
+impl GxStatus {
+    fn message(self) -> &'static CStr {
+        match self {
+            GxStatus::Ok => c"ok",
+            GxStatus::NullPointer => c"null pointer",
+            GxStatus::UnknownCompression => c"unknown compression",
+            GxStatus::DecodeFailed => c"decoding failed",
+            GxStatus::Internal => c"internal error",
+        }
+    }
+}
+
+/// Bytes allocated by the library, owned by the caller until passed to `gx_free`
+#[repr(C)]
+pub struct GxBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl GxBuffer {
+    fn from_vec(data: Vec<u8>) -> Self {
+        let len = data.len();
+        GxBuffer {
+            data: Box::into_raw(data.into_boxed_slice()).cast(),
+            len,
+        }
+    }
+}
+
+/// Encoding or decoding, with the status to report on failure
+type Codec = fn(&[u8], &[u8], CompressionAlgorithm) -> Result<Vec<u8>, GxStatus>;
+
+fn encode(data: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Result<Vec<u8>, GxStatus> {
+    Ok(gxcore_core::encode(data, seed, compression))
+}
+
+fn decode(data: &[u8], seed: &[u8], compression: CompressionAlgorithm) -> Result<Vec<u8>, GxStatus> {
+    gxcore_core::decode(data, seed, compression).map_err(|_| GxStatus::DecodeFailed)
+}
+
+/// Compressions the HTTP API accepts, by their envelope header id
+fn compression(id: u8) -> Result<CompressionAlgorithm, GxStatus> {
+    match CompressionAlgorithm::from_id(id) {
+        Ok(CompressionAlgorithm::Huffman) | Err(_) => Err(GxStatus::UnknownCompression),
+        Ok(compression) => Ok(compression),
+    }
+}
+
+/// # Safety
+/// `data` must be null with `len` zero, or valid for reads of `len` bytes.
+unsafe fn input<'a>(data: *const u8, len: usize) -> Result<&'a [u8], GxStatus> {
+    match (data.is_null(), len) {
+        (true, 0) => Ok(&[]),
+        (true, _) => Err(GxStatus::NullPointer),
+        // SAFETY: guaranteed by the caller
+        (false, _) => Ok(unsafe { slice::from_raw_parts(data, len) }),
+    }
+}
+
+/// Run `codec` over the inputs and hand its result to `out`, without unwinding into C
+///
+/// # Safety
+/// As for `gx_encode`.
+unsafe fn call(
+    data: *const u8,
+    len: usize,
+    seed: *const u8,
+    seed_len: usize,
+    compression_id: u8,
+    out: *mut GxBuffer,
+    codec: Codec,
+) -> GxStatus {
+    if out.is_null() {
+        return GxStatus::NullPointer;
+    }
+    // SAFETY: guaranteed by the caller
+    let inputs = unsafe { input(data, len).and_then(|data| Ok((data, input(seed, seed_len)?))) };
+    let result = inputs.and_then(|(data, seed)| {
+        let compression = compression(compression_id)?;
+        guarded(|| codec(data, seed, compression))
+    });
+    // SAFETY: checked non-null above, valid for writes per the caller
+    unsafe { hand_over(result, out) }
+}
+
+/// Run `f`, reporting a panic as `GxStatus::Internal` instead of unwinding into C
+fn guarded(f: impl FnOnce() -> Result<Vec<u8>, GxStatus>) -> Result<Vec<u8>, GxStatus> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(Err(GxStatus::Internal))
+}
+
+/// Store the output of a successful call in `out`
+///
+/// # Safety
+/// `out` must be non-null and valid for writes.
+unsafe fn hand_over(result: Result<Vec<u8>, GxStatus>, out: *mut GxBuffer) -> GxStatus {
+    match result {
+        Ok(output) => {
+            // SAFETY: guaranteed by the caller
+            unsafe { out.write(GxBuffer::from_vec(output)) };
+            GxStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Encode `len` bytes of `data` as `/v1/encode/raw` does, storing the result in `out`.
+///
+/// # Safety
+/// `data` and `seed` must be valid for reads of their lengths, or null with length zero, and
+/// `out` valid for writes. On success `out` must later be passed to `gx_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_encode(
+    data: *const u8,
+    len: usize,
+    seed: *const u8,
+    seed_len: usize,
+    compression: u8,
+    out: *mut GxBuffer,
+) -> GxStatus {
+    // SAFETY: guaranteed by the caller
+    unsafe { call(data, len, seed, seed_len, compression, out, encode) }
+}
+
+/// Decode output of `gx_encode` or of the HTTP API, storing the data in `out`.
+///
+/// # Safety
+/// As for `gx_encode`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_decode(
+    data: *const u8,
+    len: usize,
+    seed: *const u8,
+    seed_len: usize,
+    compression: u8,
+    out: *mut GxBuffer,
+) -> GxStatus {
+    // SAFETY: guaranteed by the caller
+    unsafe { call(data, len, seed, seed_len, compression, out, decode) }
+}
+
+/// Streaming encoder, emitting newline-delimited frames as the CLI's `--stream` mode does
+pub struct GxEncoder(StreamEncoder);
+
+/// Streaming decoder for the output of a `GxEncoder` or the CLI's `--stream` mode
+pub struct GxDecoder(StreamDecoder);
+
+/// Create a stream codec with `make` and store it in `out`
+///
+/// # Safety
+/// As for `gx_encoder_new`.
+unsafe fn open<T>(
+    seed: *const u8,
+    seed_len: usize,
+    compression_id: u8,
+    out: *mut *mut T,
+    make: fn(&[u8], CompressionAlgorithm) -> T,
+) -> GxStatus {
+    if out.is_null() {
+        return GxStatus::NullPointer;
+    }
+    // SAFETY: guaranteed by the caller
+    let codec = unsafe { input(seed, seed_len) }.and_then(|seed| Ok(make(seed, compression(compression_id)?)));
+    match codec {
+        Ok(codec) => {
+            // SAFETY: checked non-null above, valid for writes per the caller
+            unsafe { out.write(Box::into_raw(Box::new(codec))) };
+            GxStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Start encoding a stream, storing the encoder in `out`.
+///
+/// # Safety
+/// `seed` must be valid for reads of `seed_len` bytes, or null with `seed_len` zero, and `out`
+/// valid for writes. On success `out` must later be passed to `gx_encoder_finish` or
+/// `gx_encoder_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_encoder_new(seed: *const u8, seed_len: usize, compression: u8, out: *mut *mut GxEncoder) -> GxStatus {
+    // SAFETY: guaranteed by the caller
+    unsafe { open(seed, seed_len, compression, out, |seed, compression| GxEncoder(StreamEncoder::new(seed, compression))) }
+}
+
+/// Feed `len` bytes of `data`, storing the frames of every completed chunk, possibly none, in `out`.
+///
+/// # Safety
+/// `encoder` must come from `gx_encoder_new` and not have been finished or freed, `data` be
+/// valid for reads of `len` bytes, or null with `len` zero, and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_encoder_update(encoder: *mut GxEncoder, data: *const u8, len: usize, out: *mut GxBuffer) -> GxStatus {
+    // SAFETY: guaranteed by the caller
+    let (Some(encoder), false) = (unsafe { encoder.as_mut() }, out.is_null()) else {
+        return GxStatus::NullPointer;
+    };
+    // SAFETY: guaranteed by the caller
+    let result = unsafe { input(data, len) }.and_then(|data| guarded(|| Ok(encoder.0.update(data))));
+    // SAFETY: checked non-null above, valid for writes per the caller
+    unsafe { hand_over(result, out) }
+}
+
+/// Store the frame of the final partial chunk in `out` and free the encoder, whatever the result.
+///
+/// # Safety
+/// As for `gx_encoder_update`; the encoder cannot be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_encoder_finish(encoder: *mut GxEncoder, out: *mut GxBuffer) -> GxStatus {
+    if encoder.is_null() {
+        return GxStatus::NullPointer;
+    }
+    // SAFETY: allocated by `gx_encoder_new`, ownership returned by the caller
+    let encoder = unsafe { Box::from_raw(encoder) };
+    if out.is_null() {
+        return GxStatus::NullPointer;
+    }
+    let result = guarded(|| Ok(encoder.0.finish()));
+    // SAFETY: checked non-null above, valid for writes per the caller
+    unsafe { hand_over(result, out) }
+}
+
+/// Abandon an encoder without finishing it; null is ignored.
+///
+/// # Safety
+/// `encoder` must come from `gx_encoder_new` and not have been finished or freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_encoder_free(encoder: *mut GxEncoder) {
+    if !encoder.is_null() {
+        // SAFETY: guaranteed by the caller
+        drop(unsafe { Box::from_raw(encoder) });
+    }
+}
+
+/// Start decoding a stream, storing the decoder in `out`.
+///
+/// # Safety
+/// As for `gx_encoder_new`, with `gx_decoder_finish` or `gx_decoder_free` to release it.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_decoder_new(seed: *const u8, seed_len: usize, compression: u8, out: *mut *mut GxDecoder) -> GxStatus {
+    // SAFETY: guaranteed by the caller
+    unsafe { open(seed, seed_len, compression, out, |seed, compression| GxDecoder(StreamDecoder::new(seed, compression))) }
+}
+
+/// Feed encoded input, storing the data of every completed frame in `out`.
+///
+/// # Safety
+/// As for `gx_encoder_update`, with a decoder from `gx_decoder_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_decoder_update(decoder: *mut GxDecoder, data: *const u8, len: usize, out: *mut GxBuffer) -> GxStatus {
+    // SAFETY: guaranteed by the caller
+    let (Some(decoder), false) = (unsafe { decoder.as_mut() }, out.is_null()) else {
+        return GxStatus::NullPointer;
+    };
+    // SAFETY: guaranteed by the caller
+    let result = unsafe { input(data, len) }
+        .and_then(|data| guarded(|| decoder.0.update(data).map_err(|_| GxStatus::DecodeFailed)));
+    // SAFETY: checked non-null above, valid for writes per the caller
+    unsafe { hand_over(result, out) }
+}
+
+/// Store the data of a final frame missing its delimiter in `out` and free the decoder,
+/// whatever the result.
+///
+/// # Safety
+/// As for `gx_decoder_update`; the decoder cannot be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_decoder_finish(decoder: *mut GxDecoder, out: *mut GxBuffer) -> GxStatus {
+    if decoder.is_null() {
+        return GxStatus::NullPointer;
+    }
+    // SAFETY: allocated by `gx_decoder_new`, ownership returned by the caller
+    let decoder = unsafe { Box::from_raw(decoder) };
+    if out.is_null() {
+        return GxStatus::NullPointer;
+    }
+    let result = guarded(|| decoder.0.finish().map_err(|_| GxStatus::DecodeFailed));
+    // SAFETY: checked non-null above, valid for writes per the caller
+    unsafe { hand_over(result, out) }
+}
+
+/// Abandon a decoder without finishing it; null is ignored.
+///
+/// # Safety
+/// `decoder` must come from `gx_decoder_new` and not have been finished or freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_decoder_free(decoder: *mut GxDecoder) {
+    if !decoder.is_null() {
+        // SAFETY: guaranteed by the caller
+        drop(unsafe { Box::from_raw(decoder) });
+    }
+}
+
+/// Release a buffer returned by `gx_encode`, `gx_decode` or a stream call; a null `data` is ignored.
+///
+/// # Safety
+/// `buffer` must come from this library unchanged and not have been freed already.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gx_free(buffer: GxBuffer) {
+    if !buffer.data.is_null() {
+        // SAFETY: allocated by `GxBuffer::from_vec` with this length
+        drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)) });
+    }
+}
+
+/// Static, NUL-terminated description of a status
+#[unsafe(no_mangle)]
+pub extern "C" fn gx_status_message(status: GxStatus) -> *const std::ffi::c_char {
+    status.message().as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(
+        f: unsafe extern "C" fn(*const u8, usize, *const u8, usize, u8, *mut GxBuffer) -> GxStatus,
+        data: &[u8],
+        seed: &[u8],
+        compression: u8,
+    ) -> Result<Vec<u8>, GxStatus> {
+        let mut out = GxBuffer { data: ptr::null_mut(), len: 0 };
+        match unsafe { f(data.as_ptr(), data.len(), seed.as_ptr(), seed.len(), compression, &mut out) } {
+            GxStatus::Ok => {
+                let result = unsafe { slice::from_raw_parts(out.data, out.len) }.to_vec();
+                unsafe { gx_free(out) };
+                Ok(result)
+            }
+            status => Err(status),
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_through_c_abi() {
+        let encoded = run(gx_encode, b"client state", b"seed", GX_COMPRESSION_LZ4).unwrap();
+        assert_eq!(encoded, gxcore_core::encode(b"client state", b"seed", CompressionAlgorithm::Lz4));
+        assert_eq!(run(gx_decode, &encoded, b"seed", GX_COMPRESSION_LZ4).unwrap(), b"client state");
+        assert_eq!(run(gx_decode, &encoded, b"other", GX_COMPRESSION_LZ4), Err(GxStatus::DecodeFailed));
+        assert_eq!(run(gx_encode, b"x", b"seed", 1), Err(GxStatus::UnknownCompression));
+        assert_eq!(run(gx_decode, b"", b"seed", GX_COMPRESSION_NONE), Err(GxStatus::DecodeFailed));
+    }
+
+    /// Copy out and free a buffer filled by a successful call
+    fn take(status: GxStatus, out: GxBuffer) -> Result<Vec<u8>, GxStatus> {
+        if status != GxStatus::Ok {
+            return Err(status);
+        }
+        let result = unsafe { slice::from_raw_parts(out.data, out.len) }.to_vec();
+        unsafe { gx_free(out) };
+        Ok(result)
+    }
+
+    #[test]
+    fn test_stream_roundtrip_through_c_abi() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let mut encoder = ptr::null_mut();
+        assert_eq!(unsafe { gx_encoder_new(b"wallet".as_ptr(), 6, GX_COMPRESSION_LZ4, &mut encoder) }, GxStatus::Ok);
+        let mut encoded = Vec::new();
+        for chunk in data.chunks(10_000) {
+            let mut out = GxBuffer { data: ptr::null_mut(), len: 0 };
+            let status = unsafe { gx_encoder_update(encoder, chunk.as_ptr(), chunk.len(), &mut out) };
+            encoded.extend(take(status, out).unwrap());
+        }
+        let mut out = GxBuffer { data: ptr::null_mut(), len: 0 };
+        encoded.extend(take(unsafe { gx_encoder_finish(encoder, &mut out) }, out).unwrap());
+
+        let mut decoder = ptr::null_mut();
+        assert_eq!(unsafe { gx_decoder_new(b"wallet".as_ptr(), 6, GX_COMPRESSION_LZ4, &mut decoder) }, GxStatus::Ok);
+        let mut decoded = Vec::new();
+        for chunk in encoded.chunks(7_000) {
+            let mut out = GxBuffer { data: ptr::null_mut(), len: 0 };
+            let status = unsafe { gx_decoder_update(decoder, chunk.as_ptr(), chunk.len(), &mut out) };
+            decoded.extend(take(status, out).unwrap());
+        }
+        let mut out = GxBuffer { data: ptr::null_mut(), len: 0 };
+        decoded.extend(take(unsafe { gx_decoder_finish(decoder, &mut out) }, out).unwrap());
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_stream_errors() {
+        let mut decoder = ptr::null_mut();
+        assert_eq!(unsafe { gx_decoder_new(ptr::null(), 0, 1, &mut decoder) }, GxStatus::UnknownCompression);
+        assert_eq!(unsafe { gx_decoder_new(b"other".as_ptr(), 5, GX_COMPRESSION_NONE, &mut decoder) }, GxStatus::Ok);
+        let frame = gxcore_core::encode(b"frame", b"seed", CompressionAlgorithm::None);
+        let mut out = GxBuffer { data: ptr::null_mut(), len: 0 };
+        let status = unsafe { gx_decoder_update(decoder, frame.as_ptr(), frame.len(), &mut out) };
+        assert_eq!(take(status, out), Ok(Vec::new()));
+        let mut out = GxBuffer { data: ptr::null_mut(), len: 0 };
+        let status = unsafe { gx_decoder_finish(decoder, &mut out) };
+        assert_eq!(status, GxStatus::DecodeFailed);
+
+        let status = unsafe { gx_encoder_update(ptr::null_mut(), ptr::null(), 0, &mut out) };
+        assert_eq!(status, GxStatus::NullPointer);
+        unsafe { gx_encoder_free(ptr::null_mut()) };
+        let mut encoder = ptr::null_mut();
+        assert_eq!(unsafe { gx_encoder_new(ptr::null(), 0, GX_COMPRESSION_NONE, &mut encoder) }, GxStatus::Ok);
+        unsafe { gx_encoder_free(encoder) };
+    }
+
+    #[test]
+    fn test_null_pointers() {
+        let mut out = GxBuffer { data: ptr::null_mut(), len: 0 };
+        let status = unsafe { gx_encode(ptr::null(), 0, ptr::null(), 0, GX_COMPRESSION_NONE, &mut out) };
+        assert_eq!(status, GxStatus::Ok);
+        unsafe { gx_free(out) };
+        let mut out = GxBuffer { data: ptr::null_mut(), len: 0 };
+        let status = unsafe { gx_encode(ptr::null(), 3, ptr::null(), 0, GX_COMPRESSION_NONE, &mut out) };
+        assert_eq!(status, GxStatus::NullPointer);
+        let status = unsafe { gx_encode(ptr::null(), 0, ptr::null(), 0, GX_COMPRESSION_NONE, ptr::null_mut()) };
+        assert_eq!(status, GxStatus::NullPointer);
+        unsafe { gx_free(GxBuffer { data: ptr::null_mut(), len: 0 }) };
+    }
+}
+
+#[cfg(feature = "android")]
+pub mod android;
+#[cfg(feature = "node")]
+pub mod node;
+#[cfg(feature = "wasm")]
+pub mod wasm;
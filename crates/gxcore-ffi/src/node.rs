@@ -0,0 +1,95 @@
+use napi::bindgen_prelude::*;
+use napi::{Env, Task};
+use napi_derive::napi;
+use gxcore_core::CompressionAlgorithm;
+
+/// Compression names accepted by the HTTP API, `none` when absent
+fn parse_compression(name: Option<String>) -> Result<CompressionAlgorithm> {
+    match name.as_deref() {
+        None | Some("none") => Ok(CompressionAlgorithm::None),
+        Some("lz4") => Ok(CompressionAlgorithm::Lz4),
+        Some("brotli") => Ok(CompressionAlgorithm::Brotli),
+        Some("lz4frame") => Ok(CompressionAlgorithm::Lz4Frame),
+        Some(name) => Err(Error::new(Status::InvalidArg, format!("unknown compression {name}"))),
+    }
+}
+
+/// Encoding or decoding run on the libuv thread pool, so large payloads do not block the event loop.
+/// Inputs are copied when the call is made, since JavaScript may reuse its buffers before the task runs.
+pub struct Codec {
+    data: Vec<u8>,
+    seed: Vec<u8>,
+    compression: CompressionAlgorithm,
+    decode: bool,
+}
+
+impl Codec {
+    fn new(data: &[u8], seed: &[u8], compression: Option<String>, decode: bool) -> Result<Self> {
+        Ok(Codec {
+            data: data.to_vec(),
+            seed: seed.to_vec(),
+            compression: parse_compression(compression)?,
+            decode,
+        })
+    }
+
+    fn run(&self) -> Result<Vec<u8>> {
+        if self.decode {
+            gxcore_core::decode(&self.data, &self.seed, self.compression).map_err(|e| Error::new(Status::InvalidArg, e))
+        } else {
+            Ok(gxcore_core::encode(&self.data, &self.seed, self.compression))
+        }
+    }
+}
+
+impl Task for Codec {
+    type Output = Vec<u8>;
+    type JsValue = Buffer;
+
+    fn compute(&mut self) -> Result<Vec<u8>> {
+        self.run()
+    }
+
+    fn resolve(&mut self, _env: Env, output: Vec<u8>) -> Result<Buffer> {
+        Ok(output.into())
+    }
+}
+
+/// Encode `data` as `/v1/encode/raw` does, resolving off the main thread
+#[napi(ts_return_type = "Promise<Buffer>")]
+pub fn encode(data: Buffer, seed: Buffer, compression: Option<String>) -> Result<AsyncTask<Codec>> {
+    Ok(AsyncTask::new(Codec::new(&data, &seed, compression, false)?))
+}
+
+/// Decode output of `encode` or of the HTTP API, rejecting on a wrong seed or corruption
+#[napi(ts_return_type = "Promise<Buffer>")]
+pub fn decode(data: Buffer, seed: Buffer, compression: Option<String>) -> Result<AsyncTask<Codec>> {
+    Ok(AsyncTask::new(Codec::new(&data, &seed, compression, true)?))
+}
+
+/// `encode` on the calling thread, for small payloads where scheduling costs more than the work
+#[napi]
+pub fn encode_sync(data: Buffer, seed: Buffer, compression: Option<String>) -> Result<Buffer> {
+    Codec::new(&data, &seed, compression, false)?.run().map(Buffer::from)
+}
+
+/// `decode` on the calling thread
+#[napi]
+pub fn decode_sync(data: Buffer, seed: Buffer, compression: Option<String>) -> Result<Buffer> {
+    Codec::new(&data, &seed, compression, true)?.run().map(Buffer::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_tasks() {
+        let encoded = Codec::new(b"ledger", b"seed", Some("lz4".to_string()), false).unwrap().compute().unwrap();
+        assert_eq!(encoded, gxcore_core::encode(b"ledger", b"seed", CompressionAlgorithm::Lz4));
+        let mut decode = Codec::new(&encoded, b"seed", Some("lz4".to_string()), true).unwrap();
+        assert_eq!(decode.compute().unwrap(), b"ledger");
+        assert!(Codec::new(&encoded, b"other", Some("lz4".to_string()), true).unwrap().compute().is_err());
+        assert!(Codec::new(b"", b"seed", Some("huffman".to_string()), false).is_err());
+    }
+}
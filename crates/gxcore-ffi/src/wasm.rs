@@ -0,0 +1,116 @@
+use wasm_bindgen::prelude::*;
+use gxcore_core::stream::{StreamDecoder, StreamEncoder};
+use gxcore_core::CompressionAlgorithm;
+
+/// Compression names accepted by the HTTP API, so browsers produce what the service decodes
+fn parse_compression(name: &str) -> Result<CompressionAlgorithm, &'static str> {
+    match name {
+        "none" => Ok(CompressionAlgorithm::None),
+        "lz4" => Ok(CompressionAlgorithm::Lz4),
+        "brotli" => Ok(CompressionAlgorithm::Brotli),
+        "lz4frame" => Ok(CompressionAlgorithm::Lz4Frame),
+        _ => Err("Unknown compression algorithm"),
+    }
+}
+
+/// Encode `data` as `/v1/encode/raw` does, without it leaving the browser
+#[wasm_bindgen]
+pub fn encode(data: &[u8], seed: &[u8], compression: &str) -> Result<Vec<u8>, JsError> {
+    let compression = parse_compression(compression).map_err(JsError::new)?;
+    Ok(gxcore_core::encode(data, seed, compression))
+}
+
+/// Decode output of `encode` or of the HTTP API
+#[wasm_bindgen]
+pub fn decode(encoded: &[u8], seed: &[u8], compression: &str) -> Result<Vec<u8>, JsError> {
+    let compression = parse_compression(compression).map_err(JsError::new)?;
+    gxcore_core::decode(encoded, seed, compression).map_err(JsError::new)
+}
+
+/// Whether `encoded` decodes under `seed` with its checksum intact. Unlike `/v1/verify` this takes
+/// the seed, since the checksum sits under the seed's alphabet.
+#[wasm_bindgen]
+pub fn verify(encoded: &[u8], seed: &[u8], compression: &str) -> bool {
+    parse_compression(compression).and_then(|compression| gxcore_core::decode(encoded, seed, compression)).is_ok()
+}
+
+/// Streaming encoder fed chunk by chunk, e.g. from a `ReadableStream`, emitting newline-delimited frames
+#[wasm_bindgen]
+pub struct Encoder(StreamEncoder);
+
+#[wasm_bindgen]
+impl Encoder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: &[u8], compression: &str) -> Result<Encoder, JsError> {
+        let compression = parse_compression(compression).map_err(JsError::new)?;
+        Ok(Encoder(StreamEncoder::new(seed, compression)))
+    }
+
+    /// Feed input, returning the frames of every completed chunk, possibly none
+    pub fn update(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.0.update(chunk)
+    }
+
+    /// Frame of the final partial chunk; the encoder cannot be used afterwards
+    pub fn finish(self) -> Vec<u8> {
+        self.0.finish()
+    }
+}
+
+/// Streaming decoder for the output of `Encoder` or the `--stream` CLI mode
+#[wasm_bindgen]
+pub struct Decoder(StreamDecoder);
+
+#[wasm_bindgen]
+impl Decoder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: &[u8], compression: &str) -> Result<Decoder, JsError> {
+        let compression = parse_compression(compression).map_err(JsError::new)?;
+        Ok(Decoder(StreamDecoder::new(seed, compression)))
+    }
+
+    /// Feed encoded input, returning the data of every completed frame
+    pub fn update(&mut self, chunk: &[u8]) -> Result<Vec<u8>, JsError> {
+        self.0.update(chunk).map_err(JsError::new)
+    }
+
+    /// Data of a final frame missing its delimiter; the decoder cannot be used afterwards
+    pub fn finish(self) -> Result<Vec<u8>, JsError> {
+        self.0.finish().map_err(JsError::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // JsError needs a JavaScript host, so natively only the success paths can run
+    #[test]
+    fn test_chunked_roundtrip() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let mut encoder = Encoder::new(b"wallet", "lz4").unwrap();
+        let mut encoded = Vec::new();
+        for chunk in data.chunks(10_000) {
+            encoded.extend(encoder.update(chunk));
+        }
+        encoded.extend(encoder.finish());
+
+        let mut decoder = Decoder::new(b"wallet", "lz4").unwrap();
+        let mut decoded = Vec::new();
+        for chunk in encoded.chunks(7_000) {
+            decoded.extend(decoder.update(chunk).unwrap());
+        }
+        decoded.extend(decoder.finish().unwrap());
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_matches_http_codec() {
+        let encoded = encode(b"payload", b"seed", "none").unwrap();
+        assert_eq!(encoded, gxcore_core::encode(b"payload", b"seed", CompressionAlgorithm::None));
+        assert_eq!(decode(&encoded, b"seed", "none").unwrap(), b"payload");
+        assert!(verify(&encoded, b"seed", "none"));
+        assert!(!verify(&encoded, b"other", "none"));
+        assert!(parse_compression("huffman").is_err());
+    }
+}
@@ -0,0 +1,219 @@
+/// The gxcore codec over the C API of gxcore-ffi, producing what `/v1/encode/raw` and the web
+/// WebAssembly build do.
+library;
+
+import 'dart:async';
+import 'dart:ffi';
+import 'dart:io';
+import 'dart:typed_data';
+
+import 'package:ffi/ffi.dart';
+
+/// Compressions the HTTP API accepts, by their `GX_COMPRESSION_*` id
+enum Compression {
+  none(0),
+  lz4(2),
+  brotli(3),
+  lz4Frame(4);
+
+  const Compression(this.id);
+
+  final int id;
+}
+
+/// A call failed with a `GxStatus` other than `GX_STATUS_OK`
+class GxcoreException implements Exception {
+  GxcoreException(this.status) : message = _statusMessage(status).toDartString();
+
+  /// `GxStatus` value: 1 null pointer, 2 unknown compression, 3 decoding failed, 4 internal
+  final int status;
+  final String message;
+
+  @override
+  String toString() => 'GxcoreException: $message';
+}
+
+/// Encode `data` in one call
+Uint8List encode(List<int> data, List<int> seed, {Compression compression = Compression.none}) =>
+    _oneShot(_encode, data, seed, compression);
+
+/// Decode output of [encode] or of the HTTP API, throwing [GxcoreException] on a wrong seed or
+/// corruption
+Uint8List decode(List<int> encoded, List<int> seed, {Compression compression = Compression.none}) =>
+    _oneShot(_decode, encoded, seed, compression);
+
+/// Whether `encoded` decodes with `seed`, as the WebAssembly `verify` reports
+bool verify(List<int> encoded, List<int> seed, {Compression compression = Compression.none}) {
+  try {
+    decode(encoded, seed, compression: compression);
+    return true;
+  } on GxcoreException {
+    return false;
+  }
+}
+
+/// Streaming encoder fed chunk by chunk, emitting newline-delimited frames as the CLI's
+/// `--stream` mode and the WebAssembly `Encoder` do
+class Encoder {
+  Encoder(List<int> seed, {Compression compression = Compression.none})
+      : _handle = _open(_encoderNew, seed, compression) {
+    _encoderFinalizer.attach(this, _handle!.cast(), detach: this);
+  }
+
+  Pointer<_Encoder>? _handle;
+
+  /// Feed input, returning the frames of every completed chunk, possibly none
+  Uint8List update(List<int> chunk) => _update(_encoderUpdate, _live(_handle), chunk);
+
+  /// Frame of the final partial chunk; the encoder cannot be used afterwards
+  Uint8List finish() {
+    final handle = _live(_handle);
+    _handle = null;
+    _encoderFinalizer.detach(this);
+    return _finish(_encoderFinish, handle);
+  }
+}
+
+/// Streaming decoder for the output of [Encoder] or the CLI's `--stream` mode
+class Decoder {
+  Decoder(List<int> seed, {Compression compression = Compression.none})
+      : _handle = _open(_decoderNew, seed, compression) {
+    _decoderFinalizer.attach(this, _handle!.cast(), detach: this);
+  }
+
+  Pointer<_Decoder>? _handle;
+
+  /// Feed encoded input, returning the data of every completed frame
+  Uint8List update(List<int> chunk) => _update(_decoderUpdate, _live(_handle), chunk);
+
+  /// Data of a final frame missing its delimiter; the decoder cannot be used afterwards
+  Uint8List finish() {
+    final handle = _live(_handle);
+    _handle = null;
+    _decoderFinalizer.detach(this);
+    return _finish(_decoderFinish, handle);
+  }
+}
+
+/// Encode a byte stream, such as a file's `openRead()`, without holding it all in memory
+StreamTransformer<List<int>, Uint8List> encoder(List<int> seed, {Compression compression = Compression.none}) =>
+    StreamTransformer.fromBind((input) async* {
+      final encoder = Encoder(seed, compression: compression);
+      await for (final chunk in input) {
+        final frames = encoder.update(chunk);
+        if (frames.isNotEmpty) yield frames;
+      }
+      yield encoder.finish();
+    });
+
+/// Decode a stream produced by [encoder]
+StreamTransformer<List<int>, Uint8List> decoder(List<int> seed, {Compression compression = Compression.none}) =>
+    StreamTransformer.fromBind((input) async* {
+      final decoder = Decoder(seed, compression: compression);
+      await for (final chunk in input) {
+        final data = decoder.update(chunk);
+        if (data.isNotEmpty) yield data;
+      }
+      final rest = decoder.finish();
+      if (rest.isNotEmpty) yield rest;
+    });
+
+final class _Buffer extends Struct {
+  external Pointer<Uint8> data;
+
+  @Size()
+  external int len;
+}
+
+final class _Encoder extends Opaque {}
+
+final class _Decoder extends Opaque {}
+
+typedef _CodecC = Int32 Function(Pointer<Uint8>, Size, Pointer<Uint8>, Size, Uint8, Pointer<_Buffer>);
+typedef _Codec = int Function(Pointer<Uint8>, int, Pointer<Uint8>, int, int, Pointer<_Buffer>);
+typedef _Open<T extends NativeType> = int Function(Pointer<Uint8>, int, int, Pointer<Pointer<T>>);
+typedef _Update<T extends NativeType> = int Function(Pointer<T>, Pointer<Uint8>, int, Pointer<_Buffer>);
+typedef _Finish<T extends NativeType> = int Function(Pointer<T>, Pointer<_Buffer>);
+
+/// Statically linked into iOS apps, bundled as a shared library elsewhere
+final DynamicLibrary _library = switch (Platform.operatingSystem) {
+  'ios' => DynamicLibrary.process(),
+  'macos' => DynamicLibrary.open('libgxcore_ffi.dylib'),
+  'windows' => DynamicLibrary.open('gxcore_ffi.dll'),
+  _ => DynamicLibrary.open('libgxcore_ffi.so'),
+};
+
+final _encode = _library.lookupFunction<_CodecC, _Codec>('gx_encode');
+final _decode = _library.lookupFunction<_CodecC, _Codec>('gx_decode');
+final _free = _library.lookupFunction<Void Function(_Buffer), void Function(_Buffer)>('gx_free');
+final _statusMessage =
+    _library.lookupFunction<Pointer<Utf8> Function(Int32), Pointer<Utf8> Function(int)>('gx_status_message');
+
+final _encoderNew = _library.lookupFunction<Int32 Function(Pointer<Uint8>, Size, Uint8, Pointer<Pointer<_Encoder>>),
+    _Open<_Encoder>>('gx_encoder_new');
+final _encoderUpdate = _library.lookupFunction<Int32 Function(Pointer<_Encoder>, Pointer<Uint8>, Size, Pointer<_Buffer>),
+    _Update<_Encoder>>('gx_encoder_update');
+final _encoderFinish = _library
+    .lookupFunction<Int32 Function(Pointer<_Encoder>, Pointer<_Buffer>), _Finish<_Encoder>>('gx_encoder_finish');
+final _encoderFinalizer = NativeFinalizer(_library.lookup<NativeFinalizerFunction>('gx_encoder_free'));
+
+final _decoderNew = _library.lookupFunction<Int32 Function(Pointer<Uint8>, Size, Uint8, Pointer<Pointer<_Decoder>>),
+    _Open<_Decoder>>('gx_decoder_new');
+final _decoderUpdate = _library.lookupFunction<Int32 Function(Pointer<_Decoder>, Pointer<Uint8>, Size, Pointer<_Buffer>),
+    _Update<_Decoder>>('gx_decoder_update');
+final _decoderFinish = _library
+    .lookupFunction<Int32 Function(Pointer<_Decoder>, Pointer<_Buffer>), _Finish<_Decoder>>('gx_decoder_finish');
+final _decoderFinalizer = NativeFinalizer(_library.lookup<NativeFinalizerFunction>('gx_decoder_free'));
+
+void _check(int status) {
+  if (status != 0) throw GxcoreException(status);
+}
+
+/// Copy `bytes` into memory owned by `arena`
+Pointer<Uint8> _copy(Arena arena, List<int> bytes) {
+  final pointer = arena<Uint8>(bytes.length);
+  pointer.asTypedList(bytes.length).setAll(0, bytes);
+  return pointer;
+}
+
+/// Copy out and release a buffer filled by the library
+Uint8List _take(Pointer<_Buffer> out) {
+  final buffer = out.ref;
+  final bytes = Uint8List.fromList(buffer.data.asTypedList(buffer.len));
+  _free(buffer);
+  return bytes;
+}
+
+/// Copy of the seed that is zeroed before `arena` frees it
+Pointer<Uint8> _seed(Arena arena, List<int> seed) {
+  final pointer = _copy(arena, seed);
+  arena.onReleaseAll(() => pointer.asTypedList(seed.length).fillRange(0, seed.length, 0));
+  return pointer;
+}
+
+Uint8List _oneShot(_Codec codec, List<int> data, List<int> seed, Compression compression) => using((arena) {
+      final out = arena<_Buffer>();
+      _check(codec(_copy(arena, data), data.length, _seed(arena, seed), seed.length, compression.id, out));
+      return _take(out);
+    });
+
+Pointer<T> _open<T extends NativeType>(_Open<T> open, List<int> seed, Compression compression) => using((arena) {
+      final out = arena<Pointer<T>>();
+      _check(open(_seed(arena, seed), seed.length, compression.id, out));
+      return out.value;
+    });
+
+Uint8List _update<T extends NativeType>(_Update<T> update, Pointer<T> handle, List<int> chunk) => using((arena) {
+      final out = arena<_Buffer>();
+      _check(update(handle, _copy(arena, chunk), chunk.length, out));
+      return _take(out);
+    });
+
+Uint8List _finish<T extends NativeType>(_Finish<T> finish, Pointer<T> handle) => using((arena) {
+      final out = arena<_Buffer>();
+      _check(finish(handle, out));
+      return _take(out);
+    });
+
+Pointer<T> _live<T extends NativeType>(Pointer<T>? handle) =>
+    handle ?? (throw StateError('finish() was already called'));
@@ -0,0 +1,126 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace Gxcore
+{
+    /// <summary>Compressions the HTTP API accepts, by their <c>GX_COMPRESSION_*</c> id</summary>
+    public enum Compression : byte
+    {
+        None = NativeMethods.GX_COMPRESSION_NONE,
+        Lz4 = NativeMethods.GX_COMPRESSION_LZ4,
+        Brotli = NativeMethods.GX_COMPRESSION_BROTLI,
+        Lz4Frame = NativeMethods.GX_COMPRESSION_LZ4_FRAME,
+    }
+
+    /// <summary>A call failed with a <c>GxStatus</c> other than <c>GX_STATUS_OK</c></summary>
+    public sealed class GxcoreException : Exception
+    {
+        internal unsafe GxcoreException(GxStatus status)
+            : base(Marshal.PtrToStringUTF8((IntPtr)NativeMethods.gx_status_message(status)))
+        {
+            Status = (int)status;
+        }
+
+        /// <summary><c>GxStatus</c> value: 1 null pointer, 2 unknown compression, 3 decoding failed, 4 internal</summary>
+        public int Status { get; }
+    }
+
+    /// <summary>
+    /// The gxcore codec over the C API of gxcore-ffi, producing what <c>/v1/encode/raw</c> and the
+    /// WebAssembly build do
+    /// </summary>
+    public static unsafe class Codec
+    {
+        /// <summary>Encode <paramref name="data"/> in one call</summary>
+        public static byte[] Encode(ReadOnlySpan<byte> data, ReadOnlySpan<byte> seed, Compression compression = Compression.None)
+        {
+            fixed (byte* d = data)
+            fixed (byte* s = seed)
+            {
+                GxBuffer buffer = default;
+                var status = NativeMethods.gx_encode(d, (nuint)data.Length, s, (nuint)seed.Length, (byte)compression, &buffer);
+                return Take(status, buffer);
+            }
+        }
+
+        /// <summary>
+        /// Decode output of <see cref="Encode"/> or of the HTTP API, throwing
+        /// <see cref="GxcoreException"/> on a wrong seed or corruption
+        /// </summary>
+        public static byte[] Decode(ReadOnlySpan<byte> encoded, ReadOnlySpan<byte> seed, Compression compression = Compression.None)
+        {
+            fixed (byte* e = encoded)
+            fixed (byte* s = seed)
+            {
+                GxBuffer buffer = default;
+                var status = NativeMethods.gx_decode(e, (nuint)encoded.Length, s, (nuint)seed.Length, (byte)compression, &buffer);
+                return Take(status, buffer);
+            }
+        }
+
+        /// <summary>
+        /// Encode into <paramref name="destination"/>, returning false without writing if it is too
+        /// small for the result
+        /// </summary>
+        public static bool TryEncode(ReadOnlySpan<byte> data, ReadOnlySpan<byte> seed, Span<byte> destination, out int written, Compression compression = Compression.None)
+        {
+            return TryCopy(Encode(data, seed, compression), destination, out written);
+        }
+
+        /// <summary>
+        /// Decode into <paramref name="destination"/>, returning false without writing if it is too
+        /// small for the result
+        /// </summary>
+        public static bool TryDecode(ReadOnlySpan<byte> encoded, ReadOnlySpan<byte> seed, Span<byte> destination, out int written, Compression compression = Compression.None)
+        {
+            return TryCopy(Decode(encoded, seed, compression), destination, out written);
+        }
+
+        /// <summary>Whether <paramref name="encoded"/> decodes with <paramref name="seed"/>, as the WebAssembly <c>verify</c> reports</summary>
+        public static bool Verify(ReadOnlySpan<byte> encoded, ReadOnlySpan<byte> seed, Compression compression = Compression.None)
+        {
+            try
+            {
+                Decode(encoded, seed, compression);
+                return true;
+            }
+            catch (GxcoreException)
+            {
+                return false;
+            }
+        }
+
+        internal static void Check(GxStatus status)
+        {
+            if (status != GxStatus.Ok)
+            {
+                throw new GxcoreException(status);
+            }
+        }
+
+        /// <summary>Copy a buffer the library returned into managed memory and release it</summary>
+        internal static byte[] Take(GxStatus status, GxBuffer buffer)
+        {
+            Check(status);
+            try
+            {
+                return new ReadOnlySpan<byte>(buffer.data, checked((int)buffer.len)).ToArray();
+            }
+            finally
+            {
+                NativeMethods.gx_free(buffer);
+            }
+        }
+
+        static bool TryCopy(byte[] result, Span<byte> destination, out int written)
+        {
+            if (!result.AsSpan().TryCopyTo(destination))
+            {
+                written = 0;
+                return false;
+            }
+            written = result.Length;
+            return true;
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// <auto-generated>
+// This code is generated by csbindgen.
+// DON'T CHANGE THIS DIRECTLY.
+// </auto-generated>
+#pragma warning disable CS8500
+#pragma warning disable CS8981
+using System;
+using System.Runtime.InteropServices;
+
+
+namespace Gxcore
+{
+    internal static unsafe partial class NativeMethods
+    {
+        const string __DllName = "gxcore_ffi";
+
+        internal const byte GX_COMPRESSION_NONE = 0;
+        internal const byte GX_COMPRESSION_LZ4 = 2;
+        internal const byte GX_COMPRESSION_BROTLI = 3;
+        internal const byte GX_COMPRESSION_LZ4_FRAME = 4;
+
+
+
+
+        /// <summary>
+        ///  Encode `len` bytes of `data` as `/v1/encode/raw` does, storing the result in `out`.
+        ///
+        ///  # Safety
+        ///  `data` and `seed` must be valid for reads of their lengths, or null with length zero, and
+        ///  `out` valid for writes. On success `out` must later be passed to `gx_free`.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_encode", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern GxStatus gx_encode(byte* data, nuint len, byte* seed, nuint seed_len, byte compression, GxBuffer* @out);
+
+        /// <summary>
+        ///  Decode output of `gx_encode` or of the HTTP API, storing the data in `out`.
+        ///
+        ///  # Safety
+        ///  As for `gx_encode`.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_decode", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern GxStatus gx_decode(byte* data, nuint len, byte* seed, nuint seed_len, byte compression, GxBuffer* @out);
+
+        /// <summary>
+        ///  Start encoding a stream, storing the encoder in `out`.
+        ///
+        ///  # Safety
+        ///  `seed` must be valid for reads of `seed_len` bytes, or null with `seed_len` zero, and `out`
+        ///  valid for writes. On success `out` must later be passed to `gx_encoder_finish` or
+        ///  `gx_encoder_free`.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_encoder_new", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern GxStatus gx_encoder_new(byte* seed, nuint seed_len, byte compression, GxEncoder** @out);
+
+        /// <summary>
+        ///  Feed `len` bytes of `data`, storing the frames of every completed chunk, possibly none, in `out`.
+        ///
+        ///  # Safety
+        ///  `encoder` must come from `gx_encoder_new` and not have been finished or freed, `data` be
+        ///  valid for reads of `len` bytes, or null with `len` zero, and `out` valid for writes.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_encoder_update", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern GxStatus gx_encoder_update(GxEncoder* encoder, byte* data, nuint len, GxBuffer* @out);
+
+        /// <summary>
+        ///  Store the frame of the final partial chunk in `out` and free the encoder, whatever the result.
+        ///
+        ///  # Safety
+        ///  As for `gx_encoder_update`; the encoder cannot be used afterwards.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_encoder_finish", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern GxStatus gx_encoder_finish(GxEncoder* encoder, GxBuffer* @out);
+
+        /// <summary>
+        ///  Abandon an encoder without finishing it; null is ignored.
+        ///
+        ///  # Safety
+        ///  `encoder` must come from `gx_encoder_new` and not have been finished or freed.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_encoder_free", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern void gx_encoder_free(GxEncoder* encoder);
+
+        /// <summary>
+        ///  Start decoding a stream, storing the decoder in `out`.
+        ///
+        ///  # Safety
+        ///  As for `gx_encoder_new`, with `gx_decoder_finish` or `gx_decoder_free` to release it.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_decoder_new", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern GxStatus gx_decoder_new(byte* seed, nuint seed_len, byte compression, GxDecoder** @out);
+
+        /// <summary>
+        ///  Feed encoded input, storing the data of every completed frame in `out`.
+        ///
+        ///  # Safety
+        ///  As for `gx_encoder_update`, with a decoder from `gx_decoder_new`.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_decoder_update", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern GxStatus gx_decoder_update(GxDecoder* decoder, byte* data, nuint len, GxBuffer* @out);
+
+        /// <summary>
+        ///  Store the data of a final frame missing its delimiter in `out` and free the decoder,
+        ///  whatever the result.
+        ///
+        ///  # Safety
+        ///  As for `gx_decoder_update`; the decoder cannot be used afterwards.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_decoder_finish", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern GxStatus gx_decoder_finish(GxDecoder* decoder, GxBuffer* @out);
+
+        /// <summary>
+        ///  Abandon a decoder without finishing it; null is ignored.
+        ///
+        ///  # Safety
+        ///  `decoder` must come from `gx_decoder_new` and not have been finished or freed.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_decoder_free", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern void gx_decoder_free(GxDecoder* decoder);
+
+        /// <summary>
+        ///  Release a buffer returned by `gx_encode`, `gx_decode` or a stream call; a null `data` is ignored.
+        ///
+        ///  # Safety
+        ///  `buffer` must come from this library unchanged and not have been freed already.
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_free", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern void gx_free(GxBuffer buffer);
+
+        /// <summary>
+        ///  Static, NUL-terminated description of a status
+        /// </summary>
+        [DllImport(__DllName, EntryPoint = "gx_status_message", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        internal static extern byte* gx_status_message(GxStatus status);
+
+
+    }
+
+    /// <summary>
+    ///  Bytes allocated by the library, owned by the caller until passed to `gx_free`
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct GxBuffer
+    {
+        public byte* data;
+        public nuint len;
+    }
+
+    /// <summary>
+    ///  Streaming encoder, emitting newline-delimited frames as the CLI's `--stream` mode does
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct GxEncoder
+    {
+    }
+
+    /// <summary>
+    ///  Streaming decoder for the output of a `GxEncoder` or the CLI's `--stream` mode
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct GxDecoder
+    {
+    }
+
+
+    /// <summary>
+    ///  Result of every call, zero on success
+    /// </summary>
+    internal enum GxStatus : uint
+    {
+        Ok = 0,
+        /// <summary>
+        ///  A required pointer was null, or an input pointer was null with a nonzero length
+        /// </summary>
+        NullPointer = 1,
+        /// <summary>
+        ///  The compression id is not one of the `GX_COMPRESSION_*` constants
+        /// </summary>
+        UnknownCompression = 2,
+        /// <summary>
+        ///  The input is corrupt or was encoded with another seed or compression
+        /// </summary>
+        DecodeFailed = 3,
+        /// <summary>
+        ///  The codec failed unexpectedly; nothing was written to the output
+        /// </summary>
+        Internal = 4,
+    }
+
+
+}
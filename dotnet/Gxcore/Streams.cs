@@ -0,0 +1,158 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace Gxcore
+{
+    /// <summary>Owns a <c>GxEncoder</c>, freed with <c>gx_encoder_free</c> unless finished</summary>
+    internal sealed unsafe class EncoderHandle : SafeHandle
+    {
+        public EncoderHandle() : base(IntPtr.Zero, ownsHandle: true) { }
+
+        public override bool IsInvalid => handle == IntPtr.Zero;
+
+        protected override bool ReleaseHandle()
+        {
+            NativeMethods.gx_encoder_free((GxEncoder*)handle);
+            return true;
+        }
+    }
+
+    /// <summary>Owns a <c>GxDecoder</c>, freed with <c>gx_decoder_free</c> unless finished</summary>
+    internal sealed unsafe class DecoderHandle : SafeHandle
+    {
+        public DecoderHandle() : base(IntPtr.Zero, ownsHandle: true) { }
+
+        public override bool IsInvalid => handle == IntPtr.Zero;
+
+        protected override bool ReleaseHandle()
+        {
+            NativeMethods.gx_decoder_free((GxDecoder*)handle);
+            return true;
+        }
+    }
+
+    /// <summary>
+    /// Streaming encoder fed chunk by chunk, emitting newline-delimited frames as the CLI's
+    /// <c>--stream</c> mode and the WebAssembly <c>Encoder</c> do. Not safe for concurrent use.
+    /// </summary>
+    public sealed unsafe class Encoder : IDisposable
+    {
+        readonly EncoderHandle _handle = new();
+
+        public Encoder(ReadOnlySpan<byte> seed, Compression compression = Compression.None)
+        {
+            GxEncoder* encoder = null;
+            fixed (byte* s = seed)
+            {
+                Codec.Check(NativeMethods.gx_encoder_new(s, (nuint)seed.Length, (byte)compression, &encoder));
+            }
+            Marshal.InitHandle(_handle, (IntPtr)encoder);
+        }
+
+        /// <summary>Feed input, returning the frames of every completed chunk, possibly none</summary>
+        public byte[] Update(ReadOnlySpan<byte> chunk)
+        {
+            bool added = false;
+            _handle.DangerousAddRef(ref added);
+            try
+            {
+                fixed (byte* c = chunk)
+                {
+                    GxBuffer buffer = default;
+                    var status = NativeMethods.gx_encoder_update((GxEncoder*)_handle.DangerousGetHandle(), c, (nuint)chunk.Length, &buffer);
+                    return Codec.Take(status, buffer);
+                }
+            }
+            finally
+            {
+                if (added) _handle.DangerousRelease();
+            }
+        }
+
+        /// <summary>Frame of the final partial chunk; the encoder cannot be used afterwards</summary>
+        public byte[] Finish()
+        {
+            bool added = false;
+            _handle.DangerousAddRef(ref added);
+            try
+            {
+                var encoder = (GxEncoder*)_handle.DangerousGetHandle();
+                // gx_encoder_finish frees the encoder whatever the result
+                _handle.SetHandleAsInvalid();
+                GxBuffer buffer = default;
+                return Codec.Take(NativeMethods.gx_encoder_finish(encoder, &buffer), buffer);
+            }
+            finally
+            {
+                if (added) _handle.DangerousRelease();
+            }
+        }
+
+        /// <summary>Abandon the encoder without finishing it</summary>
+        public void Dispose() => _handle.Dispose();
+    }
+
+    /// <summary>
+    /// Streaming decoder for the output of <see cref="Encoder"/> or the CLI's <c>--stream</c> mode.
+    /// Not safe for concurrent use.
+    /// </summary>
+    public sealed unsafe class Decoder : IDisposable
+    {
+        readonly DecoderHandle _handle = new();
+
+        public Decoder(ReadOnlySpan<byte> seed, Compression compression = Compression.None)
+        {
+            GxDecoder* decoder = null;
+            fixed (byte* s = seed)
+            {
+                Codec.Check(NativeMethods.gx_decoder_new(s, (nuint)seed.Length, (byte)compression, &decoder));
+            }
+            Marshal.InitHandle(_handle, (IntPtr)decoder);
+        }
+
+        /// <summary>
+        /// Feed encoded input, returning the data of every completed frame, and throwing
+        /// <see cref="GxcoreException"/> on a wrong seed or corruption
+        /// </summary>
+        public byte[] Update(ReadOnlySpan<byte> chunk)
+        {
+            bool added = false;
+            _handle.DangerousAddRef(ref added);
+            try
+            {
+                fixed (byte* c = chunk)
+                {
+                    GxBuffer buffer = default;
+                    var status = NativeMethods.gx_decoder_update((GxDecoder*)_handle.DangerousGetHandle(), c, (nuint)chunk.Length, &buffer);
+                    return Codec.Take(status, buffer);
+                }
+            }
+            finally
+            {
+                if (added) _handle.DangerousRelease();
+            }
+        }
+
+        /// <summary>Data of a final frame missing its delimiter; the decoder cannot be used afterwards</summary>
+        public byte[] Finish()
+        {
+            bool added = false;
+            _handle.DangerousAddRef(ref added);
+            try
+            {
+                var decoder = (GxDecoder*)_handle.DangerousGetHandle();
+                // gx_decoder_finish frees the decoder whatever the result
+                _handle.SetHandleAsInvalid();
+                GxBuffer buffer = default;
+                return Codec.Take(NativeMethods.gx_decoder_finish(decoder, &buffer), buffer);
+            }
+            finally
+            {
+                if (added) _handle.DangerousRelease();
+            }
+        }
+
+        /// <summary>Abandon the decoder without finishing it</summary>
+        public void Dispose() => _handle.Dispose();
+    }
+}
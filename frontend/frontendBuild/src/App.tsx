@@ -82,8 +82,14 @@ function App() {
     setOutput("Running benchmarks... This may take a few seconds ⏳")
 
     try {
-      const response = await fetch(`https://gxcore.onrender.com/benchmark`)
-      const result = await response.json()
+      // Benchmarks run as a background job: start one, then poll until it finishes
+      const jobs = `https://gxcore.onrender.com/v1/benchmark`
+      let job = await (await fetch(jobs, { method: 'POST' })).json()
+      while (job.status === 'running') {
+        await new Promise(resolve => setTimeout(resolve, 1000))
+        job = await (await fetch(`${jobs}/${job.id}`)).json()
+      }
+      const result = job.result || job
 
       if (result.results) {
         let outputText = `🏁 Benchmarks completed in ${result.total_time_ms.toFixed(2)}ms\n\n`
@@ -1,7 +1,7 @@
 #![no_main]
 
 use libfuzzer_sys::fuzz_target;
-use cyphersolbase::{encode, decode, CompressionAlgorithm};
+use gxcore_core::{encode, decode, CompressionAlgorithm};
 
 fuzz_target!(|data: &[u8]| {
     // Fuzz test for encode/decode with various inputs
@@ -17,5 +17,5 @@ fuzz_target!(|data: &[u8]| {
 
     // Test partial verification
     let encoded = encode(data, seed, CompressionAlgorithm::None);
-    let _ = cyphersolbase::partial_verify(&encoded);
+    let _ = gxcore_core::partial_verify(&encoded);
 });
@@ -0,0 +1,13 @@
+/* tslint:disable */
+/* eslint-disable */
+
+/* auto-generated by NAPI-RS */
+
+/** Encode `data` as `/v1/encode/raw` does, resolving off the main thread */
+export function encode(data: Buffer, seed: Buffer, compression?: string | undefined | null): Promise<Buffer>
+/** Decode output of `encode` or of the HTTP API, rejecting on a wrong seed or corruption */
+export function decode(data: Buffer, seed: Buffer, compression?: string | undefined | null): Promise<Buffer>
+/** `encode` on the calling thread, for small payloads where scheduling costs more than the work */
+export function encodeSync(data: Buffer, seed: Buffer, compression?: string | undefined | null): Buffer
+/** `decode` on the calling thread */
+export function decodeSync(data: Buffer, seed: Buffer, compression?: string | undefined | null): Buffer